@@ -0,0 +1,65 @@
+#!/bin/sh
+#
+# Regenerates the embedded SPDX license list (src/license/licenses.txt and
+# exceptions.txt) from the official licenses.json and exceptions.json of
+# https://github.com/spdx/license-list-data, and updates
+# LICENSE_LIST_VERSION in src/license/list.rs.
+#
+# Usage: scripts/update-license-list.sh <licenses.json> <exceptions.json>
+#
+# Requires jq. deprecated.txt maps each deprecated id to its replacement,
+# which the JSON does not carry, so it is only checked, not generated.
+#
+
+set -e
+
+if [ $# -ne 2 ]; then
+    echo "Usage: $0 <licenses.json> <exceptions.json>" >&2
+    exit 1
+fi
+LICENSES_JSON=$1
+EXCEPTIONS_JSON=$2
+
+REPO_ROOT=$(git rev-parse --show-toplevel)
+LICENSE_DIR="$REPO_ROOT/src/license"
+
+VERSION=$(jq -r '.licenseListVersion' "$LICENSES_JSON")
+EXCEPTIONS_VERSION=$(jq -r '.licenseListVersion' "$EXCEPTIONS_JSON")
+if [ "$VERSION" != "$EXCEPTIONS_VERSION" ]; then
+    echo "License list versions differ: $VERSION and $EXCEPTIONS_VERSION" >&2
+    exit 1
+fi
+
+{
+    echo "# License identifiers from the SPDX License List $VERSION (https://spdx.org/licenses/),"
+    echo "# generated from licenses.json by scripts/update-license-list.sh."
+    echo "# One identifier per line, in canonical case. Deprecated identifiers live in"
+    echo "# deprecated.txt."
+    jq -r '.licenses | map(select(.isDeprecatedLicenseId | not) | .licenseId)
+        | sort_by(ascii_downcase) | .[]' "$LICENSES_JSON"
+} > "$LICENSE_DIR/licenses.txt"
+
+{
+    echo "# License exception identifiers from the SPDX License List $VERSION"
+    echo "# (https://spdx.org/licenses/exceptions-index.html), generated from"
+    echo "# exceptions.json by scripts/update-license-list.sh. In canonical case."
+    jq -r '.exceptions | map(select(.isDeprecatedLicenseId | not) | .licenseExceptionId)
+        | sort_by(ascii_downcase) | .[]' "$EXCEPTIONS_JSON"
+} > "$LICENSE_DIR/exceptions.txt"
+
+sed -i.bak "s/^pub const LICENSE_LIST_VERSION: &str = \".*\";/pub const LICENSE_LIST_VERSION: \&str = \"$VERSION\";/" \
+    "$LICENSE_DIR/list.rs"
+rm -f "$LICENSE_DIR/list.rs.bak"
+
+# Every deprecated license id needs a replacement in deprecated.txt
+MISSING=$(jq -r '.licenses[] | select(.isDeprecatedLicenseId) | .licenseId' "$LICENSES_JSON" |
+    while read -r id; do
+        grep -q "^$id	" "$LICENSE_DIR/deprecated.txt" || echo "$id"
+    done)
+if [ -n "$MISSING" ]; then
+    echo "Deprecated ids without a replacement in deprecated.txt:" >&2
+    echo "$MISSING" >&2
+    exit 1
+fi
+
+echo "Updated the embedded license list to version $VERSION"
@@ -0,0 +1,566 @@
+//! CycloneDX BOM-Link resolution across documents.
+//!
+//! A BOM-Link refers to a whole BOM (`urn:cdx:<uuid>/<version>`) or to an
+//! element of it (`urn:cdx:<uuid>/<version>#<bom-ref>`, with the bom-ref
+//! percent-encoded). The `<serial>#<bom-ref>` refs the SPDX → CycloneDX
+//! converter writes into `affects` (`urn:uuid:<uuid>#<bom-ref>`) are read as
+//! links to any version of the BOM.
+//!
+//! A [`BomIndex`] holds the bom-refs of a set of loaded BOMs, so the links of
+//! a document can be checked against it, and rewritten into local refs when
+//! the BOMs they point at are merged into the document.
+
+use crate::validation::{ValidationIssue, ValidationReport};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+const CDX_PREFIX: &str = "urn:cdx:";
+const UUID_PREFIX: &str = "urn:uuid:";
+
+/// A parsed BOM-Link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BomLink {
+    /// Serial number of the target BOM (`urn:uuid:<uuid>`)
+    pub serial_number: String,
+    /// Version of the target BOM; `None` for the `<serial>#<bom-ref>` form
+    pub version: Option<u32>,
+    /// The element of the target BOM; `None` for a link to the whole BOM
+    pub bom_ref: Option<String>,
+}
+
+impl BomLink {
+    /// Parses a `urn:cdx:` BOM-Link or a `<serial>#<bom-ref>` reference
+    pub fn parse(reference: &str) -> Option<Self> {
+        if let Some(link) = reference.strip_prefix(CDX_PREFIX) {
+            let (document, fragment) = match link.split_once('#') {
+                Some((document, fragment)) => (document, Some(fragment)),
+                None => (link, None),
+            };
+            let (uuid, version) = document.split_once('/')?;
+            let version = version.parse().ok().filter(|v| *v > 0)?;
+            if uuid.is_empty() {
+                return None;
+            }
+            return Some(BomLink {
+                serial_number: format!("{}{}", UUID_PREFIX, uuid),
+                version: Some(version),
+                bom_ref: fragment.map(percent_decode),
+            });
+        }
+
+        if !reference.starts_with("urn:") {
+            return None;
+        }
+        let (serial_number, bom_ref) = reference.split_once('#')?;
+        Some(BomLink {
+            serial_number: serial_number.to_string(),
+            version: None,
+            bom_ref: Some(bom_ref.to_string()),
+        })
+    }
+
+    /// Link to a whole BOM
+    pub fn document(serial_number: &str, version: u32) -> Self {
+        BomLink {
+            serial_number: serial_number.to_string(),
+            version: Some(version),
+            bom_ref: None,
+        }
+    }
+}
+
+impl fmt::Display for BomLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some(version) => {
+                let uuid = self
+                    .serial_number
+                    .strip_prefix(UUID_PREFIX)
+                    .unwrap_or(&self.serial_number);
+                write!(f, "{}{}/{}", CDX_PREFIX, uuid, version)?;
+                if let Some(bom_ref) = &self.bom_ref {
+                    write!(f, "#{}", percent_encode(bom_ref))?;
+                }
+                Ok(())
+            }
+            None => write!(
+                f,
+                "{}#{}",
+                self.serial_number,
+                self.bom_ref.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// The bom-ref a reference points at, which may be in BOM-Link form
+pub fn local_ref(reference: &str) -> Cow<'_, str> {
+    match BomLink::parse(reference).and_then(|link| link.bom_ref) {
+        Some(bom_ref) => Cow::Owned(bom_ref),
+        None => Cow::Borrowed(reference),
+    }
+}
+
+/// A reference as the document with `serial_number` sees it: BOM-Links to
+/// the document itself become plain bom-refs, other refs are unchanged
+pub fn localize<'a>(reference: &'a str, serial_number: Option<&str>) -> Cow<'a, str> {
+    match BomLink::parse(reference) {
+        Some(BomLink {
+            serial_number: target,
+            bom_ref: Some(bom_ref),
+            ..
+        }) if Some(target.as_str()) == serial_number => Cow::Owned(bom_ref),
+        _ => Cow::Borrowed(reference),
+    }
+}
+
+/// Why a BOM-Link does not resolve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// No BOM with the serial number is loaded
+    UnknownBom,
+    /// The BOM is loaded, but not in the linked version
+    UnknownVersion { versions: Vec<u32> },
+    /// The BOM has no element with the bom-ref
+    UnknownRef { bom: String },
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::UnknownBom => write!(f, "the BOM is not loaded"),
+            LinkError::UnknownVersion { versions } => write!(
+                f,
+                "only version {} of the BOM is loaded",
+                versions
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            LinkError::UnknownRef { bom } => write!(f, "{} has no such bom-ref", bom),
+        }
+    }
+}
+
+/// A loaded BOM
+#[derive(Debug)]
+struct IndexedBom {
+    version: u32,
+    /// Where the BOM was loaded from
+    label: String,
+    bom_refs: HashSet<String>,
+}
+
+/// The bom-refs of a set of loaded BOMs, by serial number and version
+#[derive(Debug, Default)]
+pub struct BomIndex {
+    boms: HashMap<String, Vec<IndexedBom>>,
+}
+
+impl BomIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes a CycloneDX BOM. Returns false for a BOM without a serial
+    /// number, which cannot be linked to.
+    pub fn add(&mut self, bom: &Value, label: impl Into<String>) -> bool {
+        let Some(serial_number) = bom.get("serialNumber").and_then(|s| s.as_str()) else {
+            return false;
+        };
+        let mut bom_refs = HashSet::new();
+        collect_bom_refs(bom, &mut bom_refs);
+        self.boms
+            .entry(serial_number.to_string())
+            .or_default()
+            .push(IndexedBom {
+                version: bom.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32,
+                label: label.into(),
+                bom_refs,
+            });
+        true
+    }
+
+    /// Whether a BOM with the serial number is loaded
+    pub fn contains(&self, serial_number: &str) -> bool {
+        self.boms.contains_key(serial_number)
+    }
+
+    /// Resolves a link to the label of the loaded BOM it points into
+    pub fn resolve(&self, link: &BomLink) -> Result<&str, LinkError> {
+        let boms = self
+            .boms
+            .get(&link.serial_number)
+            .ok_or(LinkError::UnknownBom)?;
+        let candidates: Vec<&IndexedBom> = boms
+            .iter()
+            .filter(|bom| link.version.is_none_or(|v| v == bom.version))
+            .collect();
+        let Some(first) = candidates.first() else {
+            return Err(LinkError::UnknownVersion {
+                versions: boms.iter().map(|bom| bom.version).collect(),
+            });
+        };
+        let Some(bom_ref) = &link.bom_ref else {
+            return Ok(&first.label);
+        };
+        candidates
+            .iter()
+            .find(|bom| bom.bom_refs.contains(bom_ref))
+            .map(|bom| bom.label.as_str())
+            .ok_or_else(|| LinkError::UnknownRef {
+                bom: first.label.clone(),
+            })
+    }
+
+    /// Checks that the BOM-Links in `bom` point at loaded BOMs and at
+    /// elements that exist in them. Links to BOMs that are not loaded are
+    /// reported once per BOM, as they cannot be checked.
+    pub fn check(&self, bom: &Value, report: &mut ValidationReport) {
+        let mut unloaded: BTreeMap<String, (String, usize)> = BTreeMap::new();
+        for (location, reference) in references(bom) {
+            let Some(link) = BomLink::parse(reference) else {
+                continue;
+            };
+            match self.resolve(&link) {
+                Ok(_) => {}
+                Err(LinkError::UnknownBom) => {
+                    unloaded
+                        .entry(link.serial_number)
+                        .or_insert((location, 0))
+                        .1 += 1;
+                }
+                Err(error @ LinkError::UnknownVersion { .. }) => report.add_issue(
+                    ValidationIssue::warning(format!(
+                        "BOM-Link {} cannot be checked: {}",
+                        reference, error
+                    ))
+                    .with_location(location),
+                ),
+                Err(error @ LinkError::UnknownRef { .. }) => report.add_issue(
+                    ValidationIssue::error(format!(
+                        "BOM-Link {} points at nothing: {}",
+                        reference, error
+                    ))
+                    .with_location(location),
+                ),
+            }
+        }
+        for (serial_number, (location, count)) in unloaded {
+            report.add_issue(
+                ValidationIssue::info(format!(
+                    "{} BOM-Link(s) into {} cannot be checked: the BOM is not loaded",
+                    count, serial_number
+                ))
+                .with_location(location)
+                .with_suggestion("Pass the linked BOM with --linked-bom"),
+            );
+        }
+    }
+}
+
+/// Collects the bom-refs of the components, services and vulnerabilities
+/// of a BOM, including nested components and services
+pub fn collect_bom_refs(bom: &Value, bom_refs: &mut HashSet<String>) {
+    fn collect(element: &Value, bom_refs: &mut HashSet<String>) {
+        if let Some(bom_ref) = element.get("bom-ref").and_then(|r| r.as_str()) {
+            bom_refs.insert(bom_ref.to_string());
+        }
+        for key in ["components", "services"] {
+            for child in element
+                .get(key)
+                .and_then(|c| c.as_array())
+                .into_iter()
+                .flatten()
+            {
+                collect(child, bom_refs);
+            }
+        }
+    }
+
+    if let Some(subject) = bom.get("metadata").and_then(|m| m.get("component")) {
+        collect(subject, bom_refs);
+    }
+    for key in ["components", "services", "vulnerabilities"] {
+        for element in bom
+            .get(key)
+            .and_then(|e| e.as_array())
+            .into_iter()
+            .flatten()
+        {
+            collect(element, bom_refs);
+        }
+    }
+}
+
+/// JSON pointers (and display locations) of every ref in a BOM: dependency
+/// graph, `affects`, compositions and external reference URLs
+fn reference_pointers(bom: &Value) -> Vec<(String, String)> {
+    fn strings(value: Option<&Value>) -> usize {
+        value.and_then(|v| v.as_array()).map_or(0, |a| a.len())
+    }
+    fn external_references(
+        element: &Value,
+        location: &str,
+        pointer: &str,
+        out: &mut Vec<(String, String)>,
+    ) {
+        for j in 0..strings(element.get("externalReferences")) {
+            out.push((
+                format!("{}externalReferences[{}].url", location, j),
+                format!("{}/externalReferences/{}/url", pointer, j),
+            ));
+        }
+        for (i, child) in element
+            .get("components")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            external_references(
+                child,
+                &format!("{}components[{}].", location, i),
+                &format!("{}/components/{}", pointer, i),
+                out,
+            );
+        }
+    }
+
+    let mut out = Vec::new();
+    for (i, dependency) in bom
+        .get("dependencies")
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        out.push((
+            format!("dependencies[{}].ref", i),
+            format!("/dependencies/{}/ref", i),
+        ));
+        for key in ["dependsOn", "provides"] {
+            for j in 0..strings(dependency.get(key)) {
+                out.push((
+                    format!("dependencies[{}].{}[{}]", i, key, j),
+                    format!("/dependencies/{}/{}/{}", i, key, j),
+                ));
+            }
+        }
+    }
+    for (i, vulnerability) in bom
+        .get("vulnerabilities")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        for j in 0..strings(vulnerability.get("affects")) {
+            out.push((
+                format!("vulnerabilities[{}].affects[{}].ref", i, j),
+                format!("/vulnerabilities/{}/affects/{}/ref", i, j),
+            ));
+        }
+    }
+    for (i, composition) in bom
+        .get("compositions")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        for key in ["assemblies", "dependencies", "vulnerabilities"] {
+            for j in 0..strings(composition.get(key)) {
+                out.push((
+                    format!("compositions[{}].{}[{}]", i, key, j),
+                    format!("/compositions/{}/{}/{}", i, key, j),
+                ));
+            }
+        }
+    }
+    external_references(bom, "", "", &mut out);
+    if let Some(subject) = bom.get("metadata").and_then(|m| m.get("component")) {
+        external_references(
+            subject,
+            "metadata.component.",
+            "/metadata/component",
+            &mut out,
+        );
+    }
+    out
+}
+
+/// Every ref in a BOM with its location, whether it is a BOM-Link or not
+pub fn references(bom: &Value) -> Vec<(String, &str)> {
+    reference_pointers(bom)
+        .into_iter()
+        .filter_map(|(location, pointer)| {
+            let reference = bom.pointer(&pointer)?.as_str()?;
+            Some((location, reference))
+        })
+        .collect()
+}
+
+/// Replaces the refs of a BOM for which `rewrite` returns a new ref.
+/// Returns the number of refs replaced.
+pub fn rewrite_references(
+    bom: &mut Value,
+    mut rewrite: impl FnMut(&str) -> Option<String>,
+) -> usize {
+    let mut rewritten = 0;
+    for (_, pointer) in reference_pointers(bom) {
+        if let Some(reference) = bom.pointer_mut(&pointer)
+            && let Some(new_ref) = reference.as_str().and_then(&mut rewrite)
+        {
+            *reference = Value::String(new_ref);
+            rewritten += 1;
+        }
+    }
+    rewritten
+}
+
+/// Percent-encodes the characters a URN fragment cannot hold
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        let allowed = byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/?".contains(&byte);
+        if allowed {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = value
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SERIAL: &str = "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79";
+
+    #[test]
+    fn test_parse_bom_link() {
+        let link =
+            BomLink::parse("urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/2#pkg%3Anpm%2Fa%401%23x")
+                .unwrap();
+        assert_eq!(link.serial_number, SERIAL);
+        assert_eq!(link.version, Some(2));
+        assert_eq!(link.bom_ref.as_deref(), Some("pkg:npm/a@1#x"));
+        assert_eq!(
+            link.to_string(),
+            "urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/2#pkg:npm/a@1%23x"
+        );
+
+        let document = BomLink::parse("urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/1").unwrap();
+        assert_eq!(document, BomLink::document(SERIAL, 1));
+        assert!(BomLink::parse("urn:cdx:3e671687/0#a").is_none());
+        assert!(BomLink::parse("urn:cdx:3e671687#a").is_none());
+    }
+
+    #[test]
+    fn test_parse_serial_ref() {
+        let link = BomLink::parse(&format!("{}#pkg-a", SERIAL)).unwrap();
+        assert_eq!(link.serial_number, SERIAL);
+        assert_eq!(link.version, None);
+        assert_eq!(link.bom_ref.as_deref(), Some("pkg-a"));
+        assert_eq!(link.to_string(), format!("{}#pkg-a", SERIAL));
+        assert!(BomLink::parse(SERIAL).is_none());
+        assert!(BomLink::parse("pkg:npm/a@1#sub").is_none());
+    }
+
+    #[test]
+    fn test_local_ref() {
+        assert_eq!(local_ref("urn:uuid:1234#pkg-a"), "pkg-a");
+        assert_eq!(local_ref("pkg:npm/a@1#sub"), "pkg:npm/a@1#sub");
+        assert_eq!(local_ref("pkg-a"), "pkg-a");
+        assert_eq!(
+            localize("urn:uuid:1234#pkg-a", Some("urn:uuid:1234")),
+            "pkg-a"
+        );
+        assert_eq!(
+            localize("urn:uuid:5678#pkg-a", Some("urn:uuid:1234")),
+            "urn:uuid:5678#pkg-a"
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut index = BomIndex::new();
+        let bom = json!({
+            "serialNumber": SERIAL,
+            "version": 2,
+            "metadata": { "component": { "bom-ref": "app" } },
+            "components": [{ "bom-ref": "a", "components": [{ "bom-ref": "a-1" }] }]
+        });
+        assert!(index.add(&bom, "bom.json"));
+        assert!(!index.add(&json!({ "version": 1 }), "unlinked.json"));
+
+        let link = |r: &str| BomLink::parse(r).unwrap();
+        let cdx = "urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79";
+        assert_eq!(
+            index.resolve(&link(&format!("{}/2#a-1", cdx))),
+            Ok("bom.json")
+        );
+        assert_eq!(
+            index.resolve(&link(&format!("{}#app", SERIAL))),
+            Ok("bom.json")
+        );
+        assert_eq!(index.resolve(&link(&format!("{}/2", cdx))), Ok("bom.json"));
+        assert_eq!(
+            index.resolve(&link(&format!("{}/1#a", cdx))),
+            Err(LinkError::UnknownVersion { versions: vec![2] })
+        );
+        assert_eq!(
+            index.resolve(&link(&format!("{}/2#b", cdx))),
+            Err(LinkError::UnknownRef {
+                bom: "bom.json".to_string()
+            })
+        );
+        assert_eq!(
+            index.resolve(&link("urn:uuid:0000#a")),
+            Err(LinkError::UnknownBom)
+        );
+    }
+
+    #[test]
+    fn test_rewrite_references() {
+        let mut bom = json!({
+            "dependencies": [{ "ref": "app", "dependsOn": ["urn:uuid:1234#a", "b"] }],
+            "vulnerabilities": [{ "id": "CVE-1", "affects": [{ "ref": "urn:uuid:1234#a" }] }],
+            "externalReferences": [{ "type": "bom", "url": "urn:cdx:1234/1" }]
+        });
+        assert_eq!(references(&bom).len(), 5);
+        let rewritten = rewrite_references(&mut bom, |r| {
+            BomLink::parse(r).and_then(|link| link.bom_ref)
+        });
+        assert_eq!(rewritten, 2);
+        assert_eq!(bom["dependencies"][0]["dependsOn"], json!(["a", "b"]));
+        assert_eq!(bom["vulnerabilities"][0]["affects"][0]["ref"], "a");
+        assert_eq!(bom["externalReferences"][0]["url"], "urn:cdx:1234/1");
+    }
+}
@@ -3,12 +3,13 @@
 use std::str::FromStr;
 
 /// CycloneDX specification version
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum CdxVersion {
     V1_3,
     V1_4,
     V1_5,
-    V1_6,
+    #[default]
+    V1_6, // Keep 1.6 as default for compatibility
     V1_7,
 }
 
@@ -39,9 +40,3 @@ impl FromStr for CdxVersion {
         }
     }
 }
-
-impl Default for CdxVersion {
-    fn default() -> Self {
-        Self::V1_6 // Keep 1.6 as default for compatibility
-    }
-}
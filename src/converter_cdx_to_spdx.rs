@@ -5,30 +5,78 @@
 //! We stream the input CDX file once, writing `SpdxElement` objects to the
 //! main output writer and `SpdxRelationship` objects to a temporary file.
 //! Finally, we append the contents of the temp file to the main output.
+//!
+//! Three output layouts are supported: SPDX 2.3 JSON (`packages`, `files`,
+//! `relationships`), the legacy SPDX 3 simple JSON (`elements` +
+//! `relationships`) and SPDX 3.0.1 JSON-LD, where every element and
+//! relationship is streamed into a single `@graph` array.
 
 use crate::errors::ConverterError;
-use crate::models_cdx::{CdxComponent, CdxDependency, CdxVulnerability};
-use crate::models_spdx::{RelationshipType, SpdxElement, SpdxPackage, SpdxRelationship};
+use crate::fidelity::FidelityReport;
+use crate::formats::spdx::SpdxSerialization;
+use crate::id_map::{IdMap, sanitize_spdx_id};
+use crate::line_sort;
+use crate::models_cdx::{
+    CdxAnalysis, CdxBomMetadata, CdxComponent, CdxDependency, CdxMetadataOnly, CdxRating,
+    CdxVulnerability,
+};
+use crate::models_spdx::{
+    RelationshipType, SPDX_JSONLD_CONTEXT, SPDX_JSONLD_CREATION_INFO_ID, Spdx2File, Spdx2Package,
+    SpdxElement, SpdxExternalIdentifier, SpdxExtractedLicense, SpdxJsonLdAgent,
+    SpdxJsonLdCreationInfo, SpdxJsonLdDictionaryEntry, SpdxJsonLdDocument,
+    SpdxJsonLdLicenseExpression, SpdxJsonLdLicensingText, SpdxJsonLdPackage,
+    SpdxJsonLdRelationship, SpdxJsonLdVulnerability, SpdxPackage, SpdxRelationship,
+    SpdxVulnAssessment, cdx_hashes_to_spdx2, cdx_licenses_to_spdx,
+};
 use crate::progress::ProgressTracker;
+use crate::reproducible::OutputIdentity;
+use crate::spdx_version::SpdxVersion;
 
-use log::{debug, info};
-use serde::Deserializer;
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use serde::{Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
+/// Relationships held in memory at once when sorting them
+const SORT_CHUNK_LINES: usize = 100_000;
+
 /// Orchestrates the CDX -> SPDX conversion.
 ///
 /// Reads from `reader`, writes elements to `writer`, and writes relationships
-/// to a temporary file at `temp_path`.
+/// to a temporary file at `temp_path`. `version` selects SPDX 2.3 or 3.0.1
+/// output; for 3.0.1, `serialization` selects between the legacy simple
+/// layout and JSON-LD. Fields the chosen layout cannot represent are
+/// recorded in `fidelity`. `identity` supplies the document namespace and
+/// creation time; in reproducible mode relationships are also sorted.
+/// `ids` maps bom-refs to the SPDX IDs written.
+#[allow(clippy::too_many_arguments)]
 pub fn convert_cdx_to_spdx<R: Read>(
     reader: R,
     writer: &mut BufWriter<File>,
+    input_path: &Path,
     temp_path: &Path,
     progress: ProgressTracker,
+    version: SpdxVersion,
+    serialization: SpdxSerialization,
+    fidelity: FidelityReport,
+    identity: &OutputIdentity,
+    ids: &IdMap,
 ) -> Result<(), ConverterError> {
-    info!("Starting CDX -> SPDX conversion stream...");
+    let mut ctx = SpdxWriteContext::new(version, serialization);
+    ctx.fidelity = fidelity;
+    ctx.ids = ids.clone();
+    ctx.set_identity(identity.clone());
+    match ctx.layout() {
+        Layout::Spdx23 => info!("Starting CDX -> SPDX 2.3 conversion stream..."),
+        _ => info!(
+            "Starting CDX -> SPDX 3.0.1 conversion stream ({} serialization)...",
+            serialization.as_str()
+        ),
+    }
     debug!("Using temp file: {}", temp_path.display());
 
     // 1. Open the temporary file for relationships
@@ -36,88 +84,567 @@ pub fn convert_cdx_to_spdx<R: Read>(
         .map_err(|e| ConverterError::FileIO(format!("Failed to create temp file: {}", e)))?;
     let mut temp_writer = BufWriter::new(temp_file);
 
-    // 2. Write SPDX header
-    writer.write_all(b"{\n")?;
-    writer.write_all(b"  \"spdxVersion\": \"SPDX-3.0\",\n")?;
-    writer.write_all(b"  \"dataLicense\": \"CC0-1.0\",\n")?;
-    writer.write_all(b"  \"spdxId\": \"SPDXRef-DOCUMENT\",\n")?;
-    writer.write_all(b"  \"name\": \"Converted SBOM\",\n")?;
-    writer.write_all(
-        format!(
-            "  \"documentNamespace\": \"urn:uuid:{}\",\n",
-            uuid::Uuid::new_v4()
-        )
-        .as_bytes(),
-    )?;
-    writer.write_all(b"  \"creationInfo\": {\n")?;
-    writer.write_all(
-        format!(
-            "    \"created\": \"{}\",\n",
-            chrono::Utc::now().to_rfc3339()
-        )
-        .as_bytes(),
-    )?;
-    writer.write_all(b"    \"creators\": [\"Tool: sbom-converter\"]\n")?;
-    writer.write_all(b"  },\n")?;
-
-    // 3. Start elements array
-    writer.write_all(b"  \"elements\": [\n")?;
-    let mut first_element = true;
+    // SPDX 2.3 keeps files in their own array, so they get a second temp file
+    let files_temp_path = temp_path.with_extension("files.json");
+    if ctx.layout() == Layout::Spdx23 {
+        let files_temp = File::create(&files_temp_path).map_err(|e| {
+            ConverterError::FileIO(format!("Failed to create files temp file: {}", e))
+        })?;
+        ctx.files_writer = Some(BufWriter::new(files_temp));
+    }
 
-    // 4. Set up the streaming deserializer
+    // 2. Set up the streaming deserializer
     let mut deserializer = serde_json::Deserializer::from_reader(reader);
 
-    // 5. Run the streaming visitor
+    // 3. Run the streaming visitor. It writes the SPDX header and the BOM
+    //    subject as the root element once it has the `metadata`
     let visitor = CdxVisitor {
         writer,
         temp_writer: &mut temp_writer,
-        first_element: &mut first_element,
+        ctx: &mut ctx,
         progress: progress.clone(),
+        input_path,
+        started: false,
     };
     deserializer
         .deserialize_any(visitor)
         .map_err(|e| ConverterError::JsonParse(e.to_string()))?;
 
-    // 6. Close the "elements" array
-    writer.write_all(b"\n  ],\n")?;
+    // 4. Close the elements array (and append files for SPDX 2.3), then
+    //    start the "relationships" array (JSON-LD keeps everything in "@graph")
+    match ctx.layout() {
+        Layout::Spdx23 => {
+            if let Some(mut files_writer) = ctx.files_writer.take() {
+                files_writer.flush()?;
+            }
+            writer.write_all(b"\n  ],\n")?;
+            writer.write_all(b"  \"files\": [\n")?;
+            ctx.first_element = true;
+            append_temp_lines(writer, &mut ctx, &files_temp_path)?;
+            let _ = std::fs::remove_file(&files_temp_path);
 
-    // 7. Start the "relationships" array
-    writer.write_all(b"  \"relationships\": [\n")?;
+            writer.write_all(b"\n  ],\n")?;
+            writer.write_all(b"  \"relationships\": [\n")?;
+            ctx.first_element = true;
+        }
+        Layout::Simple => {
+            writer.write_all(b"\n  ],\n")?;
+            writer.write_all(b"  \"relationships\": [\n")?;
+            ctx.first_element = true;
+        }
+        Layout::JsonLd => {}
+    }
 
-    // 8. Flush and append temp file contents
+    // 5. Flush and append temp file contents
     temp_writer.flush()?;
     drop(temp_writer); // Close the temp file writer
 
     info!("Appending relationships from temp file...");
-    let temp_file = File::open(temp_path).map_err(|e| {
+    if ctx.identity.is_reproducible() {
+        sort_relationship_lines(temp_path)?;
+    }
+    append_temp_lines(writer, &mut ctx, temp_path)?;
+
+    // 6. Close the arrays and the main JSON object
+    match ctx.layout() {
+        Layout::Spdx23 => {
+            writer.write_all(b"\n  ],\n")?;
+            writer.write_all(b"  \"documentDescribes\": ")?;
+            // The BOM subject describes the document on its own
+            match &ctx.root_id {
+                Some(root_id) => serde_json::to_writer(&mut *writer, &[root_id])?,
+                None => serde_json::to_writer(&mut *writer, &ctx.described)?,
+            }
+            write_extracted_licenses(writer, &ctx)?;
+            writer.write_all(b"\n")?;
+            if ctx.skipped_vulnerabilities > 0 {
+                warn!(
+                    "SPDX 2.3 has no vulnerability elements; skipped {} vulnerabilities",
+                    ctx.skipped_vulnerabilities
+                );
+            }
+        }
+        Layout::Simple => {
+            writer.write_all(b"\n  ]")?;
+            write_extracted_licenses(writer, &ctx)?;
+            writer.write_all(b"\n")?;
+        }
+        Layout::JsonLd => {
+            // The SpdxDocument goes last so it can describe the whole graph
+            let document = SpdxJsonLdDocument {
+                element_type: "SpdxDocument".to_string(),
+                spdx_id: ctx.element_id("DOCUMENT"),
+                creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+                name: ctx.document.name(),
+                profile_conformance: vec![
+                    "core".to_string(),
+                    "software".to_string(),
+                    "security".to_string(),
+                    "simpleLicensing".to_string(),
+                ],
+                data_license: "https://spdx.org/licenses/CC0-1.0".to_string(),
+                root_element: ctx.root_id.iter().cloned().collect(),
+            };
+            ctx.write_element(writer, &document)?;
+            writer.write_all(b"\n  ]\n")?;
+        }
+    }
+    writer.write_all(b"}\n")?;
+
+    writer.flush()?;
+    info!("CDX -> SPDX conversion complete.");
+    Ok(())
+}
+
+/// Writes `hasExtractedLicensingInfos` (preceded by a comma) if any
+/// custom licenses were converted
+fn write_extracted_licenses<W: Write>(
+    writer: &mut BufWriter<W>,
+    ctx: &SpdxWriteContext,
+) -> Result<(), std::io::Error> {
+    if ctx.extracted_licenses.is_empty() {
+        return Ok(());
+    }
+    writer.write_all(b",\n  \"hasExtractedLicensingInfos\": [\n")?;
+    for (i, license) in ctx.extracted_licenses.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",\n")?;
+        }
+        writer.write_all(b"    ")?;
+        serde_json::to_writer(&mut *writer, license)?;
+    }
+    writer.write_all(b"\n  ]")?;
+    Ok(())
+}
+
+/// Appends the one-object-per-line contents of a temp file to the open array
+fn append_temp_lines<W: Write>(
+    writer: &mut BufWriter<W>,
+    ctx: &mut SpdxWriteContext,
+    path: &Path,
+) -> Result<(), ConverterError> {
+    let temp_file = File::open(path).map_err(|e| {
         ConverterError::FileIO(format!("Failed to open temp file for reading: {}", e))
     })?;
     let temp_reader = BufReader::new(temp_file);
 
-    let mut first_relationship = true;
     for line in temp_reader.lines() {
         let line = line?;
         if line.trim().is_empty() {
             continue; // Skip empty lines
         }
 
-        if !first_relationship {
-            writer.write_all(b",\n")?;
-        }
-        writer.write_all(b"    ")?;
+        ctx.write_separator(writer)?;
         writer.write_all(line.trim().as_bytes())?;
-        first_relationship = false;
     }
+    Ok(())
+}
 
-    // 9. Close the "relationships" array and the main JSON object
-    writer.write_all(b"\n  ]\n")?;
-    writer.write_all(b"}\n")?;
+/// Sorts the one-relationship-per-line temp file by source, type and
+/// target, so the relationship order does not depend on the input order.
+/// Sorted in chunks of `SORT_CHUNK_LINES` lines, so memory stays bounded.
+fn sort_relationship_lines(path: &Path) -> Result<(), ConverterError> {
+    line_sort::sort_lines(path, SORT_CHUNK_LINES, |line| {
+        let value: serde_json::Value = serde_json::from_str(line).map_err(|e| {
+            ConverterError::JsonParse(format!("Bad relationship in temp file: {}", e))
+        })?;
+        let text = |keys: [&str; 2]| {
+            keys.iter()
+                .find_map(|key| value.get(*key))
+                .map(|v| match v.as_str() {
+                    Some(s) => s.to_string(),
+                    None => v.to_string(),
+                })
+                .unwrap_or_default()
+        };
+        Ok((
+            text(["spdxElementId", "from"]),
+            text(["relationshipType", "relationshipType"]),
+            text(["relatedSpdxElement", "to"]),
+        ))
+    })
+}
+
+/// Writes the SPDX 2.3 document header up to the opening of `packages`
+fn write_spdx23_header<W: Write>(
+    writer: &mut BufWriter<W>,
+    ctx: &SpdxWriteContext,
+) -> Result<(), std::io::Error> {
+    writer.write_all(b"{\n")?;
+    writer.write_all(b"  \"spdxVersion\": \"SPDX-2.3\",\n")?;
+    writer.write_all(b"  \"dataLicense\": \"CC0-1.0\",\n")?;
+    writer.write_all(b"  \"SPDXID\": \"SPDXRef-DOCUMENT\",\n")?;
+    writer.write_all(b"  \"name\": ")?;
+    serde_json::to_writer(&mut *writer, &ctx.document.name())?;
+    writer.write_all(b",\n")?;
+    writer.write_all(format!("  \"documentNamespace\": \"{}\",\n", ctx.namespace).as_bytes())?;
+    writer.write_all(b"  \"creationInfo\": {\n")?;
+    writer.write_all(
+        format!(
+            "    \"created\": \"{}\",\n",
+            ctx.created().format("%Y-%m-%dT%H:%M:%SZ")
+        )
+        .as_bytes(),
+    )?;
+    writer.write_all(b"    \"creators\": ")?;
+    let tool = format!("sbom-converter-{}", env!("CARGO_PKG_VERSION"));
+    serde_json::to_writer(&mut *writer, &ctx.document.creators(&tool))?;
+    writer.write_all(b"\n")?;
+    writer.write_all(b"  },\n")?;
+    writer.write_all(b"  \"packages\": [\n")?;
+    Ok(())
+}
+
+/// Writes the legacy simple-JSON header up to the opening of `elements`
+fn write_simple_header<W: Write>(
+    writer: &mut BufWriter<W>,
+    ctx: &SpdxWriteContext,
+) -> Result<(), std::io::Error> {
+    writer.write_all(b"{\n")?;
+    writer.write_all(b"  \"spdxVersion\": \"SPDX-3.0\",\n")?;
+    writer.write_all(b"  \"dataLicense\": \"CC0-1.0\",\n")?;
+    writer.write_all(b"  \"spdxId\": \"SPDXRef-DOCUMENT\",\n")?;
+    writer.write_all(b"  \"name\": ")?;
+    serde_json::to_writer(&mut *writer, &ctx.document.name())?;
+    writer.write_all(b",\n")?;
+    writer.write_all(
+        format!(
+            "  \"documentNamespace\": \"urn:uuid:{}\",\n",
+            ctx.identity.uuid("document")
+        )
+        .as_bytes(),
+    )?;
+    writer.write_all(b"  \"creationInfo\": {\n")?;
+    writer
+        .write_all(format!("    \"created\": \"{}\",\n", ctx.created().to_rfc3339()).as_bytes())?;
+    writer.write_all(b"    \"creators\": ")?;
+    serde_json::to_writer(&mut *writer, &ctx.document.creators("sbom-converter"))?;
+    writer.write_all(b"\n")?;
+    writer.write_all(b"  },\n")?;
+    writer.write_all(b"  \"elements\": [\n")?;
+    Ok(())
+}
+
+/// Writes the JSON-LD header, the shared `CreationInfo` and the creating
+/// agents/tools, leaving `@graph` open for the streamed elements
+fn write_jsonld_header<W: Write>(
+    writer: &mut BufWriter<W>,
+    ctx: &mut SpdxWriteContext,
+) -> Result<(), std::io::Error> {
+    writer.write_all(b"{\n")?;
+    writer.write_all(format!("  \"@context\": \"{}\",\n", SPDX_JSONLD_CONTEXT).as_bytes())?;
+    writer.write_all(b"  \"@graph\": [\n")?;
+
+    // This tool, plus the authors and tools named in the source metadata
+    let mut agents = vec![SpdxJsonLdAgent {
+        element_type: "SoftwareAgent".to_string(),
+        spdx_id: ctx.element_id("Agent-sbom-converter"),
+        creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+        name: "sbom-converter".to_string(),
+    }];
+    let mut tools = vec![SpdxJsonLdAgent {
+        element_type: "Tool".to_string(),
+        spdx_id: ctx.element_id("Tool-sbom-converter"),
+        creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+        name: format!("sbom-converter {}", env!("CARGO_PKG_VERSION")),
+    }];
+    for (i, (name, _)) in ctx.document.authors.iter().enumerate() {
+        agents.push(SpdxJsonLdAgent {
+            element_type: "Person".to_string(),
+            spdx_id: ctx.element_id(&format!("Person-{}", i + 1)),
+            creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+            name: name.clone(),
+        });
+    }
+    for (i, name) in ctx.document.tools.iter().enumerate() {
+        tools.push(SpdxJsonLdAgent {
+            element_type: "Tool".to_string(),
+            spdx_id: ctx.element_id(&format!("Tool-{}", i + 1)),
+            creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+            name: name.clone(),
+        });
+    }
+
+    let creation_info = SpdxJsonLdCreationInfo {
+        element_type: "CreationInfo".to_string(),
+        id: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+        spec_version: "3.0.1".to_string(),
+        created: ctx.created().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        created_by: agents.iter().map(|a| a.spdx_id.clone()).collect(),
+        created_using: tools.iter().map(|t| t.spdx_id.clone()).collect(),
+    };
+    ctx.write_element(writer, &creation_info)?;
+
+    for agent in agents.iter().chain(&tools) {
+        ctx.write_element(writer, agent)?;
+    }
 
-    writer.flush()?;
-    info!("CDX -> SPDX conversion complete.");
     Ok(())
 }
 
+// =========================================================================
+// Write context
+// =========================================================================
+
+/// Concrete document layout being written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Spdx23,
+    Simple,
+    JsonLd,
+}
+
+/// State shared by the streaming handlers while writing one SPDX document
+#[derive(Debug)]
+pub struct SpdxWriteContext {
+    pub version: SpdxVersion,
+    pub serialization: SpdxSerialization,
+    /// Namespace used for `documentNamespace` and JSON-LD element IRIs
+    pub namespace: String,
+    first_element: bool,
+    relationship_count: usize,
+    /// License expression -> LicenseExpression element ID (JSON-LD only)
+    license_ids: HashMap<String, String>,
+    /// `LicenseRef-` id -> SimpleLicensingText element ID (JSON-LD only)
+    license_texts: HashMap<String, String>,
+    /// SPDX 2.3 and simple JSON: `hasExtractedLicensingInfos`
+    extracted_licenses: Vec<SpdxExtractedLicense>,
+    /// SPDX 2.3 only: temp file for the `files` array
+    files_writer: Option<BufWriter<File>>,
+    /// SPDX 2.3 only: packages listed in `documentDescribes`
+    described: Vec<String>,
+    skipped_vulnerabilities: usize,
+    /// Name and creation info from the source `metadata`
+    document: DocumentInfo,
+    /// SPDX ID of the BOM subject (`metadata.component`)
+    root_id: Option<String>,
+    /// bom-ref of the BOM subject, so a copy of it in `components` is not
+    /// written a second time
+    root_ref: Option<String>,
+    /// Whether the BOM subject had nested components of its own
+    root_nested: bool,
+    /// Collects the CDX data the layout has no place for
+    pub fidelity: FidelityReport,
+    /// Source of the document namespace and creation time
+    identity: OutputIdentity,
+    /// bom-ref -> SPDX ID of the components
+    pub ids: IdMap,
+    /// Sanitized vulnerability ids already written
+    vulnerability_keys: HashSet<String>,
+}
+
+/// Document-level fields taken from the CycloneDX `metadata`
+#[derive(Debug, Default)]
+struct DocumentInfo {
+    /// `name-version` of `metadata.component`
+    subject: Option<String>,
+    created: Option<DateTime<Utc>>,
+    /// Name and optional email of each BOM author
+    authors: Vec<(String, Option<String>)>,
+    /// `name-version` of each tool that created the BOM
+    tools: Vec<String>,
+}
+
+impl DocumentInfo {
+    fn from_metadata(metadata: &CdxBomMetadata, fidelity: &FidelityReport) -> Self {
+        fidelity.record_unmapped("metadata", metadata.extra.keys(), "");
+
+        let created = metadata.timestamp.as_deref().and_then(|timestamp| {
+            let parsed = DateTime::parse_from_rfc3339(timestamp).ok();
+            if parsed.is_none() {
+                warn!("Unparsable metadata.timestamp '{}', ignoring it", timestamp);
+                fidelity.record("metadata.timestamp", "");
+            }
+            parsed.map(|t| t.with_timezone(&Utc))
+        });
+
+        DocumentInfo {
+            subject: metadata.component.as_ref().map(|c| match &c.version {
+                Some(version) => format!("{}-{}", c.name, version),
+                None => c.name.clone(),
+            }),
+            created,
+            authors: metadata
+                .authors
+                .iter()
+                .flatten()
+                .filter_map(|a| Some((a.name.clone()?, a.email.clone())))
+                .collect(),
+            tools: metadata.tool_names(),
+        }
+    }
+
+    fn name(&self) -> String {
+        self.subject
+            .clone()
+            .unwrap_or_else(|| "Converted SBOM".to_string())
+    }
+
+    /// SPDX 2 style creators: the BOM authors and tools, then this tool
+    fn creators(&self, this_tool: &str) -> Vec<String> {
+        let authors = self.authors.iter().map(|(name, email)| match email {
+            Some(email) => format!("Person: {} ({})", name, email),
+            None => format!("Person: {}", name),
+        });
+        let tools = self.tools.iter().map(|tool| format!("Tool: {}", tool));
+        authors
+            .chain(tools)
+            .chain(std::iter::once(format!("Tool: {}", this_tool)))
+            .collect()
+    }
+}
+
+impl SpdxWriteContext {
+    pub fn new(version: SpdxVersion, serialization: SpdxSerialization) -> Self {
+        Self {
+            version,
+            serialization,
+            namespace: format!(
+                "https://spdx.org/spdxdocs/sbom-converter-{}",
+                uuid::Uuid::new_v4()
+            ),
+            first_element: true,
+            relationship_count: 0,
+            license_ids: HashMap::new(),
+            license_texts: HashMap::new(),
+            extracted_licenses: Vec::new(),
+            files_writer: None,
+            described: Vec::new(),
+            skipped_vulnerabilities: 0,
+            document: DocumentInfo::default(),
+            root_id: None,
+            root_ref: None,
+            root_nested: false,
+            fidelity: FidelityReport::new(),
+            identity: OutputIdentity::Random,
+            ids: IdMap::new(),
+            vulnerability_keys: HashSet::new(),
+        }
+    }
+
+    /// Derives the namespace (and later the creation time) from `identity`
+    pub fn set_identity(&mut self, identity: OutputIdentity) {
+        self.namespace = format!(
+            "https://spdx.org/spdxdocs/sbom-converter-{}",
+            identity.uuid("namespace")
+        );
+        self.identity = identity;
+    }
+
+    /// Creation time of the document
+    fn created(&self) -> DateTime<Utc> {
+        self.identity.created(self.document.created)
+    }
+
+    /// Collects the texts of a component's custom licenses (SPDX 2.3 and
+    /// simple JSON keep them in `hasExtractedLicensingInfos`)
+    fn add_extracted_licenses(&mut self, component: &CdxComponent) {
+        for license in cdx_licenses_to_spdx(component).extracted {
+            if !self
+                .extracted_licenses
+                .iter()
+                .any(|e| e.license_id == license.license_id)
+            {
+                self.extracted_licenses.push(license);
+            }
+        }
+    }
+
+    fn layout(&self) -> Layout {
+        match (self.version, self.serialization) {
+            (SpdxVersion::V2_3, _) => Layout::Spdx23,
+            (SpdxVersion::V3_0_1, SpdxSerialization::Simple) => Layout::Simple,
+            (SpdxVersion::V3_0_1, SpdxSerialization::JsonLd) => Layout::JsonLd,
+        }
+    }
+
+    /// Builds the identifier for a local element name.
+    ///
+    /// SPDX 2.3 and simple output use `SPDXRef-<name>`; JSON-LD requires an
+    /// IRI, so the same fragment is appended to the document namespace.
+    /// Characters SPDX IDs do not allow become `-`.
+    pub fn element_id(&self, local: &str) -> String {
+        let local = sanitize_spdx_id(local);
+        match self.layout() {
+            Layout::Spdx23 | Layout::Simple => format!("SPDXRef-{}", local),
+            Layout::JsonLd => format!("{}#SPDXRef-{}", self.namespace, local),
+        }
+    }
+
+    /// Builds the identifier of the element for a CycloneDX bom-ref, as
+    /// given by the ID map
+    pub fn component_id(&self, bom_ref: &str) -> String {
+        let local = self.ids.spdx_id(bom_ref);
+        match self.layout() {
+            Layout::Spdx23 | Layout::Simple => local,
+            Layout::JsonLd => format!("{}#{}", self.namespace, local),
+        }
+    }
+
+    /// The key the elements of a vulnerability are named by: its sanitized
+    /// id, with a `-2`, `-3`, ... suffix when an earlier vulnerability
+    /// already has it
+    fn vulnerability_key(&mut self, id: &str) -> String {
+        let sanitized = sanitize_spdx_id(id);
+        let key = (1..)
+            .map(|n| match n {
+                1 => sanitized.clone(),
+                n => format!("{}-{}", sanitized, n),
+            })
+            .find(|key| !self.vulnerability_keys.contains(key))
+            .unwrap_or(sanitized);
+        if key != id {
+            debug!("Vulnerability {} is written as {}", id, key);
+        }
+        self.vulnerability_keys.insert(key.clone());
+        key
+    }
+
+    fn next_relationship_id(&mut self) -> String {
+        self.relationship_count += 1;
+        self.element_id(&format!("Relationship-{}", self.relationship_count))
+    }
+
+    /// Writes the separator and indentation before the next array item
+    fn write_separator<W: Write>(&mut self, writer: &mut BufWriter<W>) -> std::io::Result<()> {
+        if !self.first_element {
+            writer.write_all(b",\n")?;
+        }
+        self.first_element = false;
+        writer.write_all(b"    ")
+    }
+
+    fn write_element<W: Write, T: Serialize>(
+        &mut self,
+        writer: &mut BufWriter<W>,
+        element: &T,
+    ) -> std::io::Result<()> {
+        self.write_separator(writer)?;
+        serde_json::to_writer(&mut *writer, element)?;
+        Ok(())
+    }
+
+    /// Writes a JSON-LD relationship as one line of the temp file
+    fn write_jsonld_relationship(
+        &mut self,
+        temp_writer: &mut BufWriter<File>,
+        from: String,
+        relationship_type: &str,
+        to: Vec<String>,
+    ) -> std::io::Result<()> {
+        let rel = SpdxJsonLdRelationship {
+            element_type: "Relationship".to_string(),
+            spdx_id: self.next_relationship_id(),
+            creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+            from,
+            relationship_type: relationship_type.to_string(),
+            to,
+        };
+        serde_json::to_writer(&mut *temp_writer, &rel)?;
+        temp_writer.write_all(b"\n")
+    }
+}
+
 // =========================================================================
 // Serde Visitor Implementation for streaming
 // =========================================================================
@@ -126,8 +653,40 @@ pub fn convert_cdx_to_spdx<R: Read>(
 struct CdxVisitor<'a, W: Write> {
     writer: &'a mut BufWriter<W>,
     temp_writer: &'a mut BufWriter<File>,
-    first_element: &'a mut bool,
+    ctx: &'a mut SpdxWriteContext,
     progress: ProgressTracker,
+    /// The input, re-read only if `metadata` follows the elements
+    input_path: &'a Path,
+    /// Whether the header and root element have been written
+    started: bool,
+}
+
+impl<'a, W: Write> CdxVisitor<'a, W> {
+    /// Writes the SPDX header, opening the elements array, and the BOM
+    /// subject as the root element
+    fn start_document(&mut self, metadata: CdxBomMetadata) -> Result<(), ConverterError> {
+        self.ctx.document = DocumentInfo::from_metadata(&metadata, &self.ctx.fidelity);
+        match self.ctx.layout() {
+            Layout::Spdx23 => write_spdx23_header(self.writer, self.ctx)?,
+            Layout::Simple => write_simple_header(self.writer, self.ctx)?,
+            Layout::JsonLd => write_jsonld_header(self.writer, self.ctx)?,
+        }
+        write_root_component(metadata, self.writer, self.temp_writer, self.ctx)?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Starts the document before the first element is written. `metadata`
+    /// normally comes first; if the elements precede it, the name and
+    /// creation info still belong in the header, so it is read up front.
+    fn ensure_started(&mut self) -> Result<(), ConverterError> {
+        if self.started {
+            return Ok(());
+        }
+        debug!("metadata follows the BOM content, reading it ahead");
+        let metadata = read_cdx_metadata(self.input_path)?;
+        self.start_document(metadata)
+    }
 }
 
 impl<'de, 'a, W: Write> serde::de::Visitor<'de> for CdxVisitor<'a, W> {
@@ -137,17 +696,30 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for CdxVisitor<'a, W> {
         formatter.write_str("a CycloneDX BOM JSON object")
     }
 
-    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    fn visit_map<M>(mut self, mut map: M) -> Result<Self::Value, M::Error>
     where
         M: serde::de::MapAccess<'de>,
     {
+        use serde::de::Error;
+
         while let Some(key) = map.next_key::<String>()? {
             match key.as_str() {
+                "metadata" if !self.started => {
+                    let metadata = map.next_value::<Option<CdxBomMetadata>>()?;
+                    self.start_document(metadata.unwrap_or_default())
+                        .map_err(Error::custom)?;
+                }
+                "metadata" => {
+                    // Already read ahead by `ensure_started`
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
                 "components" => {
                     // Stream components array
+                    self.ensure_started().map_err(Error::custom)?;
                     let component_visitor = ComponentArrayVisitor {
                         writer: self.writer,
-                        first_element: self.first_element,
+                        temp_writer: self.temp_writer,
+                        ctx: self.ctx,
                         progress: self.progress.clone(),
                     };
                     map.next_value_seed(component_visitor)?;
@@ -156,27 +728,38 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for CdxVisitor<'a, W> {
                     // Stream dependencies array
                     let dep_visitor = DependencyArrayVisitor {
                         temp_writer: self.temp_writer,
+                        ctx: self.ctx,
                         progress: self.progress.clone(),
                     };
                     map.next_value_seed(dep_visitor)?;
                 }
                 "vulnerabilities" => {
                     // Stream vulnerabilities array
+                    self.ensure_started().map_err(Error::custom)?;
                     let vuln_visitor = VulnerabilityArrayVisitor {
                         writer: self.writer,
                         temp_writer: self.temp_writer,
-                        first_element: self.first_element,
+                        ctx: self.ctx,
                         progress: self.progress.clone(),
                     };
                     map.next_value_seed(vuln_visitor)?;
                 }
+                "bomFormat" | "specVersion" | "serialNumber" | "version" | "$schema" => {
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
                 _ => {
-                    // Skip other keys
+                    // Everything else (services, formulation, ...) has no mapping
+                    self.ctx.fidelity.record(&format!("bom.{}", key), "");
                     map.next_value::<serde::de::IgnoredAny>()?;
                 }
             }
         }
 
+        // A BOM without `metadata` or elements still gets its header
+        if !self.started {
+            self.start_document(CdxBomMetadata::default())
+                .map_err(Error::custom)?;
+        }
         Ok(())
     }
 }
@@ -184,7 +767,8 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for CdxVisitor<'a, W> {
 /// Visitor for the components array
 struct ComponentArrayVisitor<'a, W: Write> {
     writer: &'a mut BufWriter<W>,
-    first_element: &'a mut bool,
+    temp_writer: &'a mut BufWriter<File>,
+    ctx: &'a mut SpdxWriteContext,
     progress: ProgressTracker,
 }
 
@@ -213,7 +797,7 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for ComponentArrayVisitor<'a, W>
         use serde::de::Error;
 
         while let Some(component) = seq.next_element::<CdxComponent>()? {
-            handle_cdx_component(component, self.writer, self.first_element)
+            handle_cdx_component(component, self.writer, self.temp_writer, self.ctx)
                 .map_err(Error::custom)?;
             self.progress.increment_element();
         }
@@ -224,6 +808,7 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for ComponentArrayVisitor<'a, W>
 /// Visitor for the dependencies array
 struct DependencyArrayVisitor<'a> {
     temp_writer: &'a mut BufWriter<File>,
+    ctx: &'a mut SpdxWriteContext,
     progress: ProgressTracker,
 }
 
@@ -252,7 +837,7 @@ impl<'de, 'a> serde::de::Visitor<'de> for DependencyArrayVisitor<'a> {
         use serde::de::Error;
 
         while let Some(dep) = seq.next_element::<CdxDependency>()? {
-            handle_cdx_dependency(dep, self.temp_writer).map_err(Error::custom)?;
+            handle_cdx_dependency(dep, self.temp_writer, self.ctx).map_err(Error::custom)?;
             self.progress.increment_relationship();
         }
         Ok(())
@@ -263,7 +848,7 @@ impl<'de, 'a> serde::de::Visitor<'de> for DependencyArrayVisitor<'a> {
 struct VulnerabilityArrayVisitor<'a, W: Write> {
     writer: &'a mut BufWriter<W>,
     temp_writer: &'a mut BufWriter<File>,
-    first_element: &'a mut bool,
+    ctx: &'a mut SpdxWriteContext,
     progress: ProgressTracker,
 }
 
@@ -292,7 +877,7 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for VulnerabilityArrayVisitor<'a
         use serde::de::Error;
 
         while let Some(vuln) = seq.next_element::<CdxVulnerability>()? {
-            handle_cdx_vulnerability(vuln, self.writer, self.temp_writer, self.first_element)
+            handle_cdx_vulnerability(vuln, self.writer, self.temp_writer, self.ctx)
                 .map_err(Error::custom)?;
             self.progress.increment_element();
         }
@@ -304,34 +889,394 @@ impl<'de, 'a, W: Write> serde::de::Visitor<'de> for VulnerabilityArrayVisitor<'a
 // Handler functions
 // =========================================================================
 
-/// Handles a single CDX component, converting and writing it
+/// Reads only the BOM `metadata`, skipping everything else
+fn read_cdx_metadata(input_path: &Path) -> Result<CdxBomMetadata, ConverterError> {
+    let file = File::open(input_path)
+        .map_err(|e| ConverterError::Io(e, "Failed to open input file".to_string()))?;
+    let bom: CdxMetadataOnly = serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| ConverterError::JsonParse(e.to_string()))?;
+    Ok(bom.metadata.unwrap_or_default())
+}
+
+/// Writes `metadata.component` as the document's root element, with a
+/// `DESCRIBES` relationship from the document
+fn write_root_component<W: Write>(
+    metadata: CdxBomMetadata,
+    writer: &mut BufWriter<W>,
+    temp_writer: &mut BufWriter<File>,
+    ctx: &mut SpdxWriteContext,
+) -> Result<(), std::io::Error> {
+    let Some(mut component) = metadata.component else {
+        if metadata.supplier.is_some() {
+            ctx.fidelity.record("metadata.supplier", "");
+        }
+        return Ok(());
+    };
+
+    // The BOM-level supplier is the supplier of the subject
+    match (&component.supplier, metadata.supplier) {
+        (None, supplier) => component.supplier = supplier,
+        (Some(_), Some(_)) => ctx.fidelity.record("metadata.supplier", ""),
+        (Some(_), None) => {}
+    }
+    if component.bom_ref.is_empty() {
+        component.bom_ref = "root".to_string();
+    }
+    ctx.root_ref = Some(component.bom_ref.clone());
+    ctx.root_nested = component.components.as_ref().is_some_and(|c| !c.is_empty());
+
+    let root_id = ctx.component_id(&component.bom_ref);
+    write_component_tree(component, writer, temp_writer, ctx, None)?;
+
+    let document_id = ctx.element_id("DOCUMENT");
+    if ctx.layout() == Layout::JsonLd {
+        ctx.write_jsonld_relationship(
+            temp_writer,
+            document_id,
+            "describes",
+            vec![root_id.clone()],
+        )?;
+    } else {
+        let rel = SpdxRelationship {
+            spdx_element_id: document_id,
+            relationship_type: RelationshipType::Describes,
+            related_spdx_element: root_id.clone(),
+        };
+        serde_json::to_writer(&mut *temp_writer, &rel)?;
+        temp_writer.write_all(b"\n")?;
+    }
+    ctx.root_id = Some(root_id);
+    Ok(())
+}
+
+/// Handles a single CDX component, converting and writing it.
+///
+/// Nested `components` (assemblies, container layers) are written as
+/// elements of their own, each with a `CONTAINS` relationship from its
+/// parent. A copy of `metadata.component` is not written again; its nested
+/// components go under the root element unless that has its own.
 pub fn handle_cdx_component<W: Write>(
+    mut component: CdxComponent,
+    writer: &mut BufWriter<W>,
+    temp_writer: &mut BufWriter<File>,
+    ctx: &mut SpdxWriteContext,
+) -> Result<(), std::io::Error> {
+    if ctx.root_ref.as_deref() != Some(component.bom_ref.as_str()) {
+        return write_component_tree(component, writer, temp_writer, ctx, None);
+    }
+
+    debug!(
+        "Skipping copy of metadata.component '{}' in components",
+        component.bom_ref
+    );
+    if ctx.root_nested {
+        return Ok(());
+    }
+    let root_id = ctx.component_id(&component.bom_ref);
+    for child in component.components.take().unwrap_or_default() {
+        write_component_tree(child, writer, temp_writer, ctx, Some(&root_id))?;
+    }
+    Ok(())
+}
+
+/// Writes `component` and, depth first, everything nested under it.
+///
+/// `parent_id` is the SPDX ID of the containing component, if any.
+fn write_component_tree<W: Write>(
+    mut component: CdxComponent,
+    writer: &mut BufWriter<W>,
+    temp_writer: &mut BufWriter<File>,
+    ctx: &mut SpdxWriteContext,
+    parent_id: Option<&str>,
+) -> Result<(), std::io::Error> {
+    let children = component.components.take().unwrap_or_default();
+    let spdx_id = ctx.component_id(&component.bom_ref);
+
+    write_component(component, writer, temp_writer, ctx, parent_id.is_none())?;
+
+    if let Some(parent_id) = parent_id {
+        write_contains_relationship(temp_writer, ctx, parent_id, &spdx_id)?;
+    }
+    for child in children {
+        write_component_tree(child, writer, temp_writer, ctx, Some(&spdx_id))?;
+    }
+    Ok(())
+}
+
+/// Writes the `CONTAINS` relationship from a parent to a nested component
+fn write_contains_relationship(
+    temp_writer: &mut BufWriter<File>,
+    ctx: &mut SpdxWriteContext,
+    parent_id: &str,
+    child_id: &str,
+) -> Result<(), std::io::Error> {
+    if ctx.layout() == Layout::JsonLd {
+        return ctx.write_jsonld_relationship(
+            temp_writer,
+            parent_id.to_string(),
+            "contains",
+            vec![child_id.to_string()],
+        );
+    }
+
+    let rel = SpdxRelationship {
+        spdx_element_id: parent_id.to_string(),
+        relationship_type: RelationshipType::Contains,
+        related_spdx_element: child_id.to_string(),
+    };
+    serde_json::to_writer(&mut *temp_writer, &rel)?;
+    temp_writer.write_all(b"\n")
+}
+
+/// Writes one component (without its nested components) as an SPDX element.
+///
+/// Only top-level packages are listed in SPDX 2.3 `documentDescribes`.
+fn write_component<W: Write>(
     component: CdxComponent,
     writer: &mut BufWriter<W>,
-    first_element: &mut bool,
+    temp_writer: &mut BufWriter<File>,
+    ctx: &mut SpdxWriteContext,
+    top_level: bool,
 ) -> Result<(), std::io::Error> {
-    let spdx_pkg = SpdxPackage::from_cdx_component(&component);
+    record_component_losses(&component, ctx);
 
-    if !*first_element {
-        writer.write_all(b",\n")?;
+    match ctx.layout() {
+        Layout::Spdx23 => {
+            ctx.add_extracted_licenses(&component);
+            let spdx_id = ctx.component_id(&component.bom_ref);
+            if component.component_type == "file" {
+                let file = Spdx2File::from_cdx_component(&component, spdx_id);
+                if let Some(files_writer) = ctx.files_writer.as_mut() {
+                    serde_json::to_writer(&mut *files_writer, &file)?;
+                    files_writer.write_all(b"\n")?;
+                }
+                return Ok(());
+            }
+            let package = Spdx2Package::from_cdx_component(&component, spdx_id.clone());
+            if top_level {
+                ctx.described.push(spdx_id);
+            }
+            return ctx.write_element(writer, &package);
+        }
+        Layout::Simple => {
+            ctx.add_extracted_licenses(&component);
+            let mut spdx_pkg = SpdxPackage::from_cdx_component(&component);
+            spdx_pkg.spdx_id = ctx.component_id(&component.bom_ref);
+            return ctx.write_element(writer, &spdx_pkg);
+        }
+        Layout::JsonLd => {}
+    }
+
+    let pkg_id = ctx.component_id(&component.bom_ref);
+    let spdx_pkg = SpdxJsonLdPackage::from_cdx_component(&component, pkg_id.clone());
+    ctx.write_element(writer, &spdx_pkg)?;
+
+    // Licenses are elements of their own in SPDX 3, linked by relationship
+    let licenses = cdx_licenses_to_spdx(&component);
+    for license in licenses.extracted {
+        if ctx.license_texts.contains_key(&license.license_id) {
+            continue;
+        }
+        let id = ctx.element_id(&format!("LicenseText-{}", ctx.license_texts.len() + 1));
+        let text = SpdxJsonLdLicensingText {
+            element_type: "simplelicensing_SimpleLicensingText".to_string(),
+            spdx_id: id.clone(),
+            creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+            name: license.name,
+            license_text: license.extracted_text,
+        };
+        ctx.write_element(writer, &text)?;
+        ctx.license_texts.insert(license.license_id, id);
+    }
+    let expressions = [
+        ("hasConcludedLicense", licenses.concluded),
+        ("hasDeclaredLicense", licenses.declared),
+    ];
+    for (relationship_type, expression) in expressions {
+        let Some(expression) = expression else {
+            continue;
+        };
+        let license_id = write_jsonld_license_expression(writer, ctx, expression)?;
+        ctx.write_jsonld_relationship(
+            temp_writer,
+            pkg_id.clone(),
+            relationship_type,
+            vec![license_id],
+        )?;
     }
-    *first_element = false;
 
-    writer.write_all(b"    ")?;
-    serde_json::to_writer(&mut *writer, &spdx_pkg)?;
     Ok(())
 }
 
+/// Returns the ID of the `LicenseExpression` element for `expression`,
+/// writing the element the first time the expression is seen
+fn write_jsonld_license_expression<W: Write>(
+    writer: &mut BufWriter<W>,
+    ctx: &mut SpdxWriteContext,
+    expression: String,
+) -> Result<String, std::io::Error> {
+    if let Some(id) = ctx.license_ids.get(&expression) {
+        return Ok(id.clone());
+    }
+
+    // Point the expression's LicenseRef- ids at their licensing texts
+    let custom_id_to_uri = expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|token| token.starts_with("LicenseRef-"))
+        .filter_map(|token| {
+            Some(SpdxJsonLdDictionaryEntry {
+                entry_type: "DictionaryEntry".to_string(),
+                key: token.to_string(),
+                value: Some(ctx.license_texts.get(token)?.clone()),
+            })
+        })
+        .collect();
+
+    let id = ctx.element_id(&format!("License-{}", ctx.license_ids.len() + 1));
+    let license = SpdxJsonLdLicenseExpression {
+        element_type: "simplelicensing_LicenseExpression".to_string(),
+        spdx_id: id.clone(),
+        creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+        license_expression: expression.clone(),
+        custom_id_to_uri,
+    };
+    ctx.write_element(writer, &license)?;
+    ctx.license_ids.insert(expression, id.clone());
+    Ok(id)
+}
+
+/// Records the component fields the current layout does not write
+fn record_component_losses(component: &CdxComponent, ctx: &SpdxWriteContext) {
+    let layout = ctx.layout();
+    let is_spdx2_file = layout == Layout::Spdx23 && component.component_type == "file";
+    let id = component.bom_ref.as_str();
+    let record = |field: &str| ctx.fidelity.record(&format!("component.{}", field), id);
+
+    // Fields no SPDX layout has a property for
+    let unmapped = [
+        ("mime-type", component.mime_type.is_some()),
+        ("manufacturer", component.manufacturer.is_some()),
+        ("publisher", component.publisher.is_some()),
+        ("group", component.group.is_some()),
+        ("versionRange", component.version_range.is_some()),
+        ("omniborId", component.omnibor_id.is_some()),
+        ("swhid", component.swhid.is_some()),
+        ("modified", component.modified.is_some()),
+        ("pedigree", component.pedigree.is_some()),
+        ("properties", component.properties.is_some()),
+        ("evidence", component.evidence.is_some()),
+        ("releaseNotes", component.release_notes.is_some()),
+        ("modelCard", component.model_card.is_some()),
+        ("data", component.data.is_some()),
+        ("cryptoProperties", component.crypto_properties.is_some()),
+        ("tags", component.tags.is_some()),
+        ("isExternal", component.is_external.is_some()),
+        ("signature", component.signature.is_some()),
+    ];
+    for (field, present) in unmapped {
+        if present {
+            record(field);
+        }
+    }
+
+    // Fields only some layouts carry
+    let has_authors = !component.author_names().is_empty();
+    let lost = [
+        (
+            "supplier",
+            component.supplier.is_some() && (layout == Layout::JsonLd || is_spdx2_file),
+        ),
+        (
+            "authors",
+            has_authors && (layout == Layout::JsonLd || is_spdx2_file),
+        ),
+        ("version", component.version.is_some() && is_spdx2_file),
+        (
+            "scope",
+            component.scope.is_some() && layout != Layout::Simple,
+        ),
+        ("cpe", component.cpe.is_some() && is_spdx2_file),
+        ("purl", component.purl.is_some() && is_spdx2_file),
+        (
+            "swid",
+            component.swid.is_some() && (layout != Layout::Spdx23 || is_spdx2_file),
+        ),
+        (
+            "externalReferences",
+            component.external_references.is_some() && is_spdx2_file,
+        ),
+    ];
+    for (field, is_lost) in lost {
+        if is_lost {
+            record(field);
+        }
+    }
+
+    // SPDX 2.3 files only have a concluded license
+    if is_spdx2_file && cdx_licenses_to_spdx(component).declared.is_some() {
+        record("licenses");
+    }
+    for license in component
+        .licenses
+        .iter()
+        .flatten()
+        .filter_map(|c| c.license.as_ref())
+    {
+        let lost = [
+            // Only plain text is carried into the SPDX license text
+            (
+                "licenses.text",
+                license.text.as_ref().is_some_and(|t| t.encoding.is_some()),
+            ),
+            // SPDX license ids imply their URL; JSON-LD texts have none
+            (
+                "licenses.url",
+                license.url.is_some() && (license.id.is_some() || layout == Layout::JsonLd),
+            ),
+            ("licenses.licensing", license.licensing.is_some()),
+            ("licenses.properties", license.properties.is_some()),
+        ];
+        for (field, is_lost) in lost {
+            if is_lost {
+                record(field);
+            }
+        }
+    }
+
+    // SPDX 2.3 checksums only cover a fixed set of algorithms
+    if layout == Layout::Spdx23
+        && let Some(hashes) = &component.hashes
+    {
+        for _ in cdx_hashes_to_spdx2(component).len()..hashes.len() {
+            record("hashes");
+        }
+    }
+}
+
 /// Handles a single CDX dependency, writing relationships to temp file
 pub fn handle_cdx_dependency(
     dep: CdxDependency,
     temp_writer: &mut BufWriter<File>,
+    ctx: &mut SpdxWriteContext,
 ) -> Result<(), std::io::Error> {
+    if ctx.layout() == Layout::JsonLd {
+        if dep.depends_on.is_empty() {
+            return Ok(());
+        }
+        let from = ctx.component_id(&dep.dep_ref);
+        let to = dep
+            .depends_on
+            .iter()
+            .map(|target_ref| ctx.component_id(target_ref))
+            .collect();
+        return ctx.write_jsonld_relationship(temp_writer, from, "dependsOn", to);
+    }
+
     for target_ref in dep.depends_on {
         let rel = SpdxRelationship {
-            spdx_element_id: format!("SPDXRef-{}", dep.dep_ref),
+            spdx_element_id: ctx.component_id(&dep.dep_ref),
             relationship_type: RelationshipType::DependsOn,
-            related_spdx_element: format!("SPDXRef-{}", target_ref),
+            related_spdx_element: ctx.component_id(&target_ref),
         };
 
         serde_json::to_writer(&mut *temp_writer, &rel)?;
@@ -340,45 +1285,328 @@ pub fn handle_cdx_dependency(
     Ok(())
 }
 
-/// Handles a single CDX vulnerability, converting and writing it
+/// Handles a single CDX vulnerability, converting and writing it.
+///
+/// SPDX 3 output carries the VEX `analysis` and CVSS `ratings` as security
+/// profile assessment relationships from the vulnerability to the affected
+/// elements; without an analysis the vulnerability plainly `affects` them.
 pub fn handle_cdx_vulnerability<W: Write>(
     vuln: CdxVulnerability,
     writer: &mut BufWriter<W>,
     temp_writer: &mut BufWriter<File>,
-    first_element: &mut bool,
+    ctx: &mut SpdxWriteContext,
 ) -> Result<(), std::io::Error> {
+    // SPDX 2.3 has no vulnerability element to map to
+    if ctx.layout() == Layout::Spdx23 {
+        debug!("Skipping vulnerability {} for SPDX 2.3 output", vuln.id);
+        ctx.skipped_vulnerabilities += 1;
+        ctx.fidelity.record("vulnerability", &vuln.id);
+        return Ok(());
+    }
+
+    ctx.fidelity
+        .record_unmapped("vulnerability", vuln.extra.keys(), &vuln.id);
+    if vuln.properties.is_some() {
+        ctx.fidelity.record("vulnerability.properties", &vuln.id);
+    }
+    if vuln.affects.iter().flatten().any(|a| a.versions.is_some()) {
+        ctx.fidelity
+            .record("vulnerability.affects.versions", &vuln.id);
+    }
+
     // 1. Write the Vulnerability as an SPDX Element
-    let vuln_spdx_id = format!("SPDXRef-Vulnerability-{}", vuln.id);
-    let element = SpdxElement {
-        spdx_id: vuln_spdx_id.clone(),
-        element_type: "SpdxVulnerability".to_string(),
-        name: vuln.id.clone(),
-        version_info: None,
-        purl: None,
-        license_concluded: None,
-    };
+    let key = ctx.vulnerability_key(&vuln.id);
+    let vuln_spdx_id = ctx.element_id(&format!("Vulnerability-{}", key));
+    match ctx.layout() {
+        Layout::Spdx23 => unreachable!("handled above"),
+        Layout::Simple => {
+            // Simple elements carry no external identifiers
+            if vuln.source.is_some() {
+                ctx.fidelity.record("vulnerability.source", &vuln.id);
+            }
+            if vuln.cwes.as_ref().is_some_and(|cwes| !cwes.is_empty()) {
+                ctx.fidelity.record("vulnerability.cwes", &vuln.id);
+            }
+            let element = SpdxElement {
+                spdx_id: vuln_spdx_id.clone(),
+                element_type: "SpdxVulnerability".to_string(),
+                name: vuln.id.clone(),
+                version_info: None,
+                purl: None,
+                license_concluded: None,
+            };
+            ctx.write_element(writer, &element)?;
+        }
+        Layout::JsonLd => {
+            let element = SpdxJsonLdVulnerability {
+                element_type: "security_Vulnerability".to_string(),
+                spdx_id: vuln_spdx_id.clone(),
+                creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+                name: vuln.id.clone(),
+                description: vuln.description.clone(),
+                external_identifier: vulnerability_identifiers(&vuln),
+            };
+            ctx.write_element(writer, &element)?;
+        }
+    }
 
-    if !*first_element {
-        writer.write_all(b",\n")?;
+    // 2. Assessments need something to assess
+    let affected: Vec<String> = vuln
+        .affects
+        .iter()
+        .flatten()
+        .map(|affected| ctx.component_id(&affected.bom_ref))
+        .collect();
+    let ratings = vuln.ratings.as_deref().unwrap_or_default();
+    if affected.is_empty() {
+        if vuln.analysis.is_some() {
+            ctx.fidelity.record("vulnerability.analysis", &vuln.id);
+        }
+        if !ratings.is_empty() {
+            ctx.fidelity.record("vulnerability.ratings", &vuln.id);
+        }
+        return Ok(());
     }
-    *first_element = false;
 
-    writer.write_all(b"    ")?;
-    serde_json::to_writer(&mut *writer, &element)?;
+    // 3. Write the VEX assessment, or the "AFFECTS" relationships when
+    //    there is no analysis, to the temp file with the other relationships
+    let vex = vuln
+        .analysis
+        .as_ref()
+        .and_then(|analysis| vex_assessment(&vuln, analysis, ctx));
+    match vex {
+        Some(mut assessment) => {
+            assessment.spdx_id = ctx.element_id(&format!("VexAssessment-{}", key));
+            assessment.from = vuln_spdx_id.clone();
+            assessment.to = affected.clone();
+            write_assessment(temp_writer, &assessment)?;
+        }
+        None if ctx.layout() == Layout::JsonLd => {
+            ctx.write_jsonld_relationship(
+                temp_writer,
+                vuln_spdx_id.clone(),
+                "affects",
+                affected.clone(),
+            )?;
+        }
+        None => {
+            for affected_element in &affected {
+                let rel = SpdxRelationship {
+                    spdx_element_id: vuln_spdx_id.clone(),
+                    relationship_type: RelationshipType::Affects,
+                    related_spdx_element: affected_element.clone(),
+                };
 
-    // 2. Write the "AFFECTS" relationships to the temp file
-    if let Some(affects) = vuln.affects {
-        for affected_component in affects {
-            let rel = SpdxRelationship {
-                spdx_element_id: vuln_spdx_id.clone(),
-                relationship_type: RelationshipType::Affects,
-                related_spdx_element: format!("SPDXRef-{}", affected_component.bom_ref),
-            };
+                serde_json::to_writer(&mut *temp_writer, &rel)?;
+                temp_writer.write_all(b"\n")?;
+            }
+        }
+    }
 
-            serde_json::to_writer(&mut *temp_writer, &rel)?;
-            temp_writer.write_all(b"\n")?;
+    // 4. Write one CVSS assessment per rating
+    for (i, rating) in ratings.iter().enumerate() {
+        let Some(mut assessment) = cvss_assessment(rating) else {
+            ctx.fidelity.record("vulnerability.ratings", &vuln.id);
+            continue;
+        };
+        if rating.source.is_some() {
+            ctx.fidelity
+                .record("vulnerability.ratings.source", &vuln.id);
+        }
+        if rating.justification.is_some() {
+            ctx.fidelity
+                .record("vulnerability.ratings.justification", &vuln.id);
+        }
+        assessment.spdx_id = ctx.element_id(&format!("CvssAssessment-{}-{}", key, i + 1));
+        assessment.from = vuln_spdx_id.clone();
+        assessment.to = affected.clone();
+        if ctx.layout() == Layout::JsonLd {
+            assessment.creation_info = Some(SPDX_JSONLD_CREATION_INFO_ID.to_string());
         }
+        write_assessment(temp_writer, &assessment)?;
     }
 
     Ok(())
 }
+
+/// Writes an assessment relationship as one line of the temp file
+fn write_assessment(
+    temp_writer: &mut BufWriter<File>,
+    assessment: &SpdxVulnAssessment,
+) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *temp_writer, assessment)?;
+    temp_writer.write_all(b"\n")
+}
+
+/// The vulnerability id (issued by the `source`) and its CWEs as SPDX
+/// external identifiers
+fn vulnerability_identifiers(vuln: &CdxVulnerability) -> Vec<SpdxExternalIdentifier> {
+    let id_type = if vuln.id.starts_with("CVE-") {
+        "cve"
+    } else {
+        "securityOther"
+    };
+    let mut identifiers = vec![SpdxExternalIdentifier {
+        id_type: "ExternalIdentifier".to_string(),
+        external_identifier_type: Some(id_type.to_string()),
+        identifier: Some(vuln.id.clone()),
+        issuing_authority: vuln.source.as_ref().map(|source| source.name.clone()),
+        identifier_locator: vuln
+            .source
+            .iter()
+            .filter_map(|source| source.url.clone())
+            .collect(),
+    }];
+    identifiers.extend(
+        vuln.cwes
+            .iter()
+            .flatten()
+            .map(|cwe| SpdxExternalIdentifier {
+                id_type: "ExternalIdentifier".to_string(),
+                external_identifier_type: Some("securityOther".to_string()),
+                identifier: Some(format!("CWE-{}", cwe)),
+                ..Default::default()
+            }),
+    );
+    identifiers
+}
+
+/// Maps a CDX VEX `analysis` to the matching SPDX VEX assessment, without
+/// its id and endpoints. `None` when the analysis has no usable state.
+fn vex_assessment(
+    vuln: &CdxVulnerability,
+    analysis: &CdxAnalysis,
+    ctx: &SpdxWriteContext,
+) -> Option<SpdxVulnAssessment> {
+    let state = analysis.state.as_deref()?;
+    let (element_type, relationship_type) = match state {
+        "exploitable" => ("security_VexAffectedVulnAssessmentRelationship", "affects"),
+        "in_triage" => (
+            "security_VexUnderInvestigationVulnAssessmentRelationship",
+            "underInvestigationFor",
+        ),
+        "not_affected" | "false_positive" => (
+            "security_VexNotAffectedVulnAssessmentRelationship",
+            "doesNotAffect",
+        ),
+        "resolved" | "resolved_with_pedigree" => {
+            ("security_VexFixedVulnAssessmentRelationship", "fixedIn")
+        }
+        _ => {
+            warn!(
+                "Unknown analysis state '{}' for vulnerability {}",
+                state, vuln.id
+            );
+            ctx.fidelity
+                .record("vulnerability.analysis.state", &vuln.id);
+            return None;
+        }
+    };
+    // SPDX only has the broader states for these
+    if matches!(state, "false_positive" | "resolved_with_pedigree") {
+        ctx.fidelity
+            .record("vulnerability.analysis.state", &vuln.id);
+    }
+
+    let mut assessment = SpdxVulnAssessment {
+        element_type: element_type.to_string(),
+        relationship_type: relationship_type.to_string(),
+        published_time: analysis.first_issued.clone(),
+        modified_time: analysis.last_updated.clone(),
+        ..Default::default()
+    };
+    if ctx.layout() == Layout::JsonLd {
+        assessment.creation_info = Some(SPDX_JSONLD_CREATION_INFO_ID.to_string());
+    }
+
+    let responses = analysis.response.join(", ");
+    match state {
+        "exploitable" => {
+            // The action statement is required; fall back to the responses
+            if analysis.detail.is_some() && !responses.is_empty() {
+                ctx.fidelity
+                    .record("vulnerability.analysis.response", &vuln.id);
+            }
+            assessment.action_statement = Some(
+                analysis
+                    .detail
+                    .clone()
+                    .or_else(|| (!responses.is_empty()).then_some(responses))
+                    .unwrap_or_else(|| "No action statement provided".to_string()),
+            );
+        }
+        "not_affected" | "false_positive" => {
+            assessment.justification_type = analysis
+                .justification
+                .as_deref()
+                .and_then(|justification| {
+                    let mapped = map_vex_justification(justification);
+                    if mapped.is_none() {
+                        ctx.fidelity
+                            .record("vulnerability.analysis.justification", &vuln.id);
+                    }
+                    mapped
+                })
+                .map(str::to_string);
+            assessment.impact_statement = analysis.detail.clone();
+        }
+        _ => assessment.status_notes = analysis.detail.clone(),
+    }
+
+    if state != "exploitable" && !analysis.response.is_empty() {
+        ctx.fidelity
+            .record("vulnerability.analysis.response", &vuln.id);
+    }
+    if !matches!(state, "not_affected" | "false_positive") && analysis.justification.is_some() {
+        ctx.fidelity
+            .record("vulnerability.analysis.justification", &vuln.id);
+    }
+
+    Some(assessment)
+}
+
+/// Maps a CDX impact analysis justification to an SPDX
+/// `security_VexJustificationType`
+fn map_vex_justification(justification: &str) -> Option<&'static str> {
+    match justification {
+        "code_not_present" => Some("vulnerableCodeNotPresent"),
+        "code_not_reachable" => Some("vulnerableCodeNotInExecutePath"),
+        "requires_configuration" | "requires_environment" => {
+            Some("vulnerableCodeCannotBeControlledByAdversary")
+        }
+        "requires_dependency" => Some("componentNotPresent"),
+        "protected_by_compiler"
+        | "protected_at_runtime"
+        | "protected_at_perimeter"
+        | "protected_by_mitigating_control" => Some("inlineMitigationsAlreadyExist"),
+        _ => None,
+    }
+}
+
+/// Maps a CVSS rating to a CVSS assessment, without its id and endpoints.
+/// `None` for other rating methods and ratings without a score or vector,
+/// which SPDX requires.
+fn cvss_assessment(rating: &CdxRating) -> Option<SpdxVulnAssessment> {
+    let method = rating.method.as_deref()?;
+    let element_type = match method {
+        "CVSSv2" => "security_CvssV2VulnAssessmentRelationship",
+        "CVSSv3" | "CVSSv31" => "security_CvssV3VulnAssessmentRelationship",
+        "CVSSv4" => "security_CvssV4VulnAssessmentRelationship",
+        _ => return None,
+    };
+    // CVSS v2 has no qualitative severity in SPDX
+    let severity = match rating.severity.as_deref() {
+        Some(severity @ ("critical" | "high" | "medium" | "low" | "none")) => Some(severity),
+        Some("info") => Some("none"),
+        _ => None,
+    }
+    .filter(|_| method != "CVSSv2");
+    Some(SpdxVulnAssessment {
+        element_type: element_type.to_string(),
+        relationship_type: "hasAssessmentFor".to_string(),
+        score: Some(rating.score?),
+        severity: severity.map(str::to_string),
+        vector_string: Some(rating.vector.clone()?),
+        ..Default::default()
+    })
+}
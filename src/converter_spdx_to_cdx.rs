@@ -1,43 +1,526 @@
 //! Implements Strategy 2: The "Multi-Pass Index" Method.
 //!
-//! Pass 1: Stream `relationships` array, build an in-memory index.
+//! Pass 1: Stream `relationships` array, build an index (in memory, or
+//!         spilled to disk past a threshold; see `relationship_index`).
 //! Pass 2: Stream `elements` array, writing `components` and `vulnerabilities`
 //!         one-by-one. Then, use the index to write `dependencies`.
+//!
+//! The same passes accept SPDX 3.0 simple JSON, SPDX 3.0.1 JSON-LD (`@graph`)
+//! and SPDX 2.3 JSON (`packages`, `files` and `relationships`).
 
+use crate::bom_link::BomLink;
 use crate::cdx_version::CdxVersion;
 use crate::errors::ConverterError;
+use crate::fidelity::FidelityReport;
+use crate::id_map::IdMap;
+use crate::integrity::Containers;
+use crate::license;
+use crate::line_sort;
 use crate::models_cdx as cdx;
 use crate::models_spdx as spdx;
 use crate::progress::ProgressTracker;
+use crate::reproducible::OutputIdentity;
 use log::{info, warn};
 use serde::Deserializer;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
-use std::path::Path;
-use uuid::Uuid;
-
-/// The in-memory index. Key is the source SPDX ID.
-pub type SpdxRelationshipIndex = HashMap<String, Vec<spdx::SpdxRelationshipMinimal>>;
-
-/// Vulnerability data extracted from Pass 3
-#[derive(Debug)]
-pub struct VulnerabilityData {
-    pub id: String,           // CVE ID
-    pub source_id: String,    // SPDX ID for the vulnerability
-    pub affects: Vec<String>, // SPDX IDs of affected components
-    pub state: String,        // "fixed", "not_affected", etc.
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub use crate::relationship_index::SpdxRelationshipIndex;
+
+/// License data collected in Pass 1, so Pass 2 can resolve the licenses of
+/// each element wherever in the document they are defined.
+#[derive(Debug, Default)]
+pub struct SpdxLicenseIndex {
+    /// JSON-LD `LicenseExpression` spdxId -> expression
+    expressions: HashMap<String, String>,
+    /// `LicenseRef-` id -> its text
+    extracted: HashMap<String, spdx::SpdxExtractedLicense>,
+    /// JSON-LD `SimpleLicensingText` spdxId -> its text, and the
+    /// `LicenseRef-` ids expressions map to it
+    texts: HashMap<String, spdx::SpdxExtractedLicense>,
+    custom_ids: HashMap<String, String>,
+    /// Whether the target CycloneDX version has `acknowledgement` (1.6+)
+    acknowledgement: bool,
+}
+
+impl SpdxLicenseIndex {
+    pub fn new(output_version: CdxVersion) -> Self {
+        Self {
+            acknowledgement: output_version >= CdxVersion::V1_6,
+            ..Default::default()
+        }
+    }
+
+    /// Records an SPDX 2.3 `hasExtractedLicensingInfos` entry
+    pub fn add_extracted(&mut self, license: spdx::SpdxExtractedLicense) {
+        self.extracted.insert(license.license_id.clone(), license);
+    }
+
+    /// Records a JSON-LD `LicenseExpression` or `SimpleLicensingText` element
+    pub fn add_jsonld_element(&mut self, element: &serde_json::Value) {
+        let text = |key: &str| {
+            element
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let Some(spdx_id) = text("spdxId") else {
+            return;
+        };
+        if let Some(expression) = text("simplelicensing_licenseExpression") {
+            let entries: Vec<spdx::SpdxJsonLdDictionaryEntry> = element
+                .get("simplelicensing_customIdToUri")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            for entry in entries {
+                if let Some(uri) = entry.value {
+                    self.custom_ids.insert(uri, entry.key);
+                }
+            }
+            self.expressions.insert(spdx_id, expression);
+        } else if let Some(license_text) = text("simplelicensing_licenseText") {
+            let license = spdx::SpdxExtractedLicense {
+                license_id: String::new(),
+                extracted_text: license_text,
+                name: text("name"),
+                see_alsos: Vec::new(),
+            };
+            self.texts.insert(spdx_id, license);
+        }
+    }
+
+    /// Links JSON-LD licensing texts to the `LicenseRef-` ids that use them
+    pub fn resolve(&mut self) {
+        for (uri, license_id) in self.custom_ids.drain() {
+            if let Some(mut license) = self.texts.remove(&uri) {
+                license.license_id = license_id.clone();
+                self.extracted.insert(license_id, license);
+            }
+        }
+    }
+
+    /// Expression of a JSON-LD `LicenseExpression` element
+    pub fn expression(&self, spdx_id: &str) -> Option<&str> {
+        self.expressions.get(spdx_id).map(String::as_str)
+    }
+
+    /// CycloneDX license choices for an element's concluded and declared
+    /// licenses.
+    ///
+    /// Targets without `acknowledgement` cannot tell the two apart, so they
+    /// get only the concluded license (or the declared one if that is all
+    /// there is). Valid expressions are written in canonical form.
+    pub fn to_cdx(
+        &self,
+        concluded: Option<&str>,
+        declared: Option<&str>,
+    ) -> Option<Vec<cdx::CdxLicenseChoice>> {
+        let concluded = spdx::spdx2_license_value(concluded).map(|e| license::canonical(&e));
+        let declared = spdx::spdx2_license_value(declared).map(|e| license::canonical(&e));
+
+        let choices: Vec<_> = if !self.acknowledgement {
+            concluded
+                .or(declared)
+                .map(|e| self.choice(e, None))
+                .into_iter()
+                .collect()
+        } else {
+            let both = concluded.is_some() && declared.is_some();
+            concluded
+                .map(|e| self.choice(e, both.then_some("concluded")))
+                .into_iter()
+                .chain(declared.map(|e| self.choice(e, Some("declared"))))
+                .collect()
+        };
+        if choices.is_empty() {
+            None
+        } else {
+            Some(choices)
+        }
+    }
+
+    /// A single custom license becomes a named license with its text;
+    /// anything else stays an expression
+    fn choice(&self, expression: String, acknowledgement: Option<&str>) -> cdx::CdxLicenseChoice {
+        let acknowledgement = acknowledgement.map(str::to_string);
+        match self.extracted.get(&expression) {
+            Some(license) => cdx::CdxLicenseChoice {
+                license: Some(cdx::CdxLicense {
+                    name: Some(license.name.clone().unwrap_or(expression)),
+                    acknowledgement,
+                    text: Some(license.extracted_text.as_str())
+                        .filter(|t| *t != "NOASSERTION")
+                        .map(|t| cdx::CdxAttachment {
+                            content: t.to_string(),
+                            ..Default::default()
+                        }),
+                    url: license.see_alsos.first().cloned(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            None => cdx::CdxLicenseChoice {
+                expression: Some(expression),
+                acknowledgement,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Vulnerability data collected in Pass 3: the vulnerabilities, their
+/// assessments and the versions of the assessed packages.
+#[derive(Debug, Default)]
+pub struct SpdxVulnerabilityIndex {
+    vulnerabilities: Vec<spdx::JsonLdVulnerability>,
+    /// Vulnerability SPDX ID -> assessments of it
+    assessments: HashMap<String, Vec<spdx::SpdxVulnAssessment>>,
+    /// Package SPDX ID -> version
+    versions: HashMap<String, String>,
+}
+
+impl SpdxVulnerabilityIndex {
+    /// Records a JSON-LD or simple JSON element if it is a vulnerability, an
+    /// assessment or a versioned package
+    pub fn add_element(&mut self, element: serde_json::Value) {
+        let Some(type_name) = element
+            .get("type")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)
+        else {
+            return;
+        };
+        match type_name.as_str() {
+            "security_Vulnerability" | "SpdxVulnerability" => {
+                match serde_json::from_value::<spdx::JsonLdVulnerability>(element) {
+                    Ok(vuln) => self.vulnerabilities.push(vuln),
+                    Err(e) => warn!("Skipping malformed vulnerability: {}", e),
+                }
+            }
+            "software_Package" | "SpdxPackage" => {
+                let text = |key: &str| element.get(key).and_then(|v| v.as_str());
+                if let (Some(spdx_id), Some(version)) = (
+                    text("spdxId"),
+                    text("software_packageVersion").or_else(|| text("versionInfo")),
+                ) {
+                    self.versions
+                        .insert(spdx_id.to_string(), version.to_string());
+                }
+            }
+            other if spdx::SpdxVulnAssessment::is_assessment_type(other) => {
+                match serde_json::from_value::<spdx::SpdxVulnAssessment>(element) {
+                    Ok(assessment) => self
+                        .assessments
+                        .entry(assessment.from.clone())
+                        .or_default()
+                        .push(assessment),
+                    Err(e) => warn!("Skipping malformed {}: {}", other, e),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Converts the collected vulnerabilities.
+    ///
+    /// CycloneDX has one analysis per vulnerability, so each VEX assessment
+    /// becomes a vulnerability entry of its own. Elements the vulnerability
+    /// plainly `affects` without a VEX assessment get one more entry without
+    /// an analysis.
+    pub fn to_cdx(
+        &self,
+        index: &SpdxRelationshipIndex,
+        serial_number: &str,
+        ids: &IdMap,
+        fidelity: &FidelityReport,
+    ) -> Result<Vec<cdx::CdxVulnerability>, ConverterError> {
+        let mut converted = Vec::new();
+        for vuln in &self.vulnerabilities {
+            let Some(id) = vuln.vulnerability_id() else {
+                warn!("Skipping vulnerability {} without an id", vuln.spdx_id);
+                fidelity.record("vulnerability", &vuln.spdx_id);
+                continue;
+            };
+            let assessments = self
+                .assessments
+                .get(&vuln.spdx_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            let mut ratings = Vec::new();
+            let mut properties = Vec::new();
+            for assessment in assessments.iter().filter(|a| !a.is_vex()) {
+                match assessment_rating(assessment) {
+                    Some(rating) => ratings.push(rating),
+                    None => match exploit_catalog_property(assessment) {
+                        Some(property) => properties.push(property),
+                        None => fidelity
+                            .record(&format!("vulnerability.{}", assessment.element_type), &id),
+                    },
+                }
+            }
+
+            let source = vuln.primary_identifier().and_then(|ext| {
+                Some(cdx::CdxVulnSource {
+                    name: ext.issuing_authority.clone()?,
+                    url: ext.identifier_locator.first().cloned(),
+                })
+            });
+            let cwes = vuln.cwes();
+            let entry = |analysis: Option<cdx::CdxAnalysis>, affects: Vec<cdx::CdxAffects>| {
+                cdx::CdxVulnerability {
+                    id: id.clone(),
+                    source: source.clone(),
+                    description: vuln.description.clone(),
+                    ratings: (!ratings.is_empty()).then(|| ratings.clone()),
+                    cwes: (!cwes.is_empty()).then(|| cwes.clone()),
+                    analysis,
+                    affects: (!affects.is_empty()).then_some(affects),
+                    properties: (!properties.is_empty()).then(|| properties.clone()),
+                    extra: HashMap::new(),
+                }
+            };
+
+            let mut assessed = HashSet::new();
+            for assessment in assessments.iter().filter(|a| a.is_vex()) {
+                let Some((analysis, status)) = vex_analysis(assessment) else {
+                    fidelity.record(&format!("vulnerability.{}", assessment.element_type), &id);
+                    continue;
+                };
+                let targets = assessment.assessed();
+                assessed.extend(targets.iter().cloned());
+                converted.push(entry(
+                    Some(analysis),
+                    self.affects(&targets, status, serial_number, ids),
+                ));
+            }
+
+            // Plain "affects" relationships not covered by an assessment
+            let plain: Vec<String> = index
+                .get(&vuln.spdx_id)?
+                .iter()
+                .filter(|rel| matches!(rel.relationship_type.as_str(), "AFFECTS" | "affects"))
+                .map(|rel| rel.related_spdx_element.clone())
+                .filter(|target| !assessed.contains(target))
+                .collect();
+            if !plain.is_empty() || assessed.is_empty() {
+                converted.push(entry(
+                    None,
+                    self.affects(&plain, "affected", serial_number, ids),
+                ));
+            }
+        }
+        Ok(converted)
+    }
+
+    /// `affects` entries for assessed elements, with the element version
+    /// where it is known
+    fn affects(
+        &self,
+        targets: &[String],
+        status: &str,
+        serial_number: &str,
+        ids: &IdMap,
+    ) -> Vec<cdx::CdxAffects> {
+        targets
+            .iter()
+            .map(|target| cdx::CdxAffects {
+                bom_ref: format!("{}#{}", serial_number, ids.bom_ref(target)),
+                versions: self.versions.get(target).map(|version| {
+                    vec![cdx::CdxAffectedVersion {
+                        version: Some(version.clone()),
+                        range: None,
+                        status: Some(status.to_string()),
+                    }]
+                }),
+            })
+            .collect()
+    }
+}
+
+/// Maps an SPDX VEX assessment to a CycloneDX analysis and the status of
+/// the affected versions
+fn vex_analysis(assessment: &spdx::SpdxVulnAssessment) -> Option<(cdx::CdxAnalysis, &'static str)> {
+    let mut analysis = cdx::CdxAnalysis {
+        first_issued: assessment.published_time.clone(),
+        last_updated: assessment.modified_time.clone(),
+        ..Default::default()
+    };
+    let status = match assessment.element_type.as_str() {
+        "security_VexAffectedVulnAssessmentRelationship" => {
+            analysis.state = Some("exploitable".to_string());
+            // An action statement made of response values is the response
+            let responses: Vec<String> = assessment
+                .action_statement
+                .iter()
+                .flat_map(|statement| statement.split(',').map(str::trim))
+                .map(str::to_string)
+                .collect();
+            if !responses.is_empty() && responses.iter().all(|r| is_vex_response(r)) {
+                analysis.response = responses;
+            } else {
+                analysis.detail = assessment.action_statement.clone();
+            }
+            "affected"
+        }
+        "security_VexNotAffectedVulnAssessmentRelationship" => {
+            analysis.state = Some("not_affected".to_string());
+            analysis.justification = assessment
+                .justification_type
+                .as_deref()
+                .and_then(map_spdx_justification)
+                .map(str::to_string);
+            analysis.detail = assessment.impact_statement.clone();
+            "unaffected"
+        }
+        "security_VexFixedVulnAssessmentRelationship" => {
+            analysis.state = Some("resolved".to_string());
+            "unaffected"
+        }
+        "security_VexUnderInvestigationVulnAssessmentRelationship" => {
+            analysis.state = Some("in_triage".to_string());
+            "unknown"
+        }
+        _ => return None,
+    };
+    if analysis.detail.is_none() {
+        analysis.detail = assessment.status_notes.clone();
+    }
+    Some((analysis, status))
+}
+
+/// Whether `response` is a CycloneDX `analysis.response` value
+fn is_vex_response(response: &str) -> bool {
+    matches!(
+        response,
+        "can_not_fix" | "will_not_fix" | "update" | "rollback" | "workaround_available"
+    )
+}
+
+/// Maps an SPDX `security_VexJustificationType` to a CycloneDX impact
+/// analysis justification
+fn map_spdx_justification(justification: &str) -> Option<&'static str> {
+    match justification {
+        "vulnerableCodeNotPresent" => Some("code_not_present"),
+        "vulnerableCodeNotInExecutePath" => Some("code_not_reachable"),
+        "vulnerableCodeCannotBeControlledByAdversary" => Some("requires_configuration"),
+        "componentNotPresent" => Some("requires_dependency"),
+        "inlineMitigationsAlreadyExist" => Some("protected_by_mitigating_control"),
+        _ => None,
+    }
+}
+
+/// Maps a CVSS, EPSS or SSVC assessment to a CycloneDX rating
+fn assessment_rating(assessment: &spdx::SpdxVulnAssessment) -> Option<cdx::CdxRating> {
+    let cvss = |method: &str| cdx::CdxRating {
+        score: assessment.score,
+        severity: assessment.severity.clone(),
+        method: Some(method.to_string()),
+        vector: assessment.vector_string.clone(),
+        ..Default::default()
+    };
+    match assessment.element_type.as_str() {
+        "security_CvssV2VulnAssessmentRelationship" => Some(cvss("CVSSv2")),
+        "security_CvssV3VulnAssessmentRelationship" => {
+            let v30 = assessment
+                .vector_string
+                .as_deref()
+                .is_some_and(|v| v.starts_with("CVSS:3.0/"));
+            Some(cvss(if v30 { "CVSSv3" } else { "CVSSv31" }))
+        }
+        "security_CvssV4VulnAssessmentRelationship" => Some(cvss("CVSSv4")),
+        // CycloneDX has no EPSS method: the probability is the score
+        "security_EpssVulnAssessmentRelationship" => Some(cdx::CdxRating {
+            source: Some(cdx::CdxVulnSource {
+                name: "EPSS".to_string(),
+                url: Some("https://www.first.org/epss/".to_string()),
+            }),
+            score: assessment.probability,
+            method: Some("other".to_string()),
+            justification: assessment
+                .percentile
+                .map(|percentile| format!("EPSS percentile {}", percentile)),
+            ..Default::default()
+        }),
+        "security_SsvcVulnAssessmentRelationship" => Some(cdx::CdxRating {
+            method: Some("SSVC".to_string()),
+            justification: assessment
+                .decision_type
+                .as_ref()
+                .map(|decision| format!("SSVC decision: {}", decision)),
+            ..Default::default()
+        }),
+        _ => None,
+    }
+}
+
+/// Maps an exploit catalog listing (e.g. CISA KEV) to a vulnerability
+/// property, as CycloneDX has no field for it
+fn exploit_catalog_property(assessment: &spdx::SpdxVulnAssessment) -> Option<cdx::CdxProperty> {
+    if assessment.element_type != "security_ExploitCatalogVulnAssessmentRelationship" {
+        return None;
+    }
+    let catalog = assessment.catalog_type.as_deref().unwrap_or("other");
+    let exploited = if assessment.exploited == Some(true) {
+        "exploited"
+    } else {
+        "listed"
+    };
+    Some(cdx::CdxProperty {
+        name: format!("spdx:exploitCatalog:{}", catalog),
+        value: Some(match &assessment.locator {
+            Some(locator) => format!("{} {}", exploited, locator),
+            None => exploited.to_string(),
+        }),
+    })
+}
+
+/// Default location of the split-off VEX document next to the output:
+/// `out.cdx.json` -> `out.vex.json`, `out.cdx.xml` -> `out.vex.xml`
+pub fn default_vex_path(output: &Path) -> PathBuf {
+    let extension = match output.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("xml") => "vex.xml",
+        _ => "vex.json",
+    };
+    output.with_extension("").with_extension(extension)
+}
+
+/// External reference type for a link to a VEX document
+fn vex_reference_type(output_version: CdxVersion) -> &'static str {
+    if output_version >= CdxVersion::V1_5 {
+        "exploitability-statement"
+    } else {
+        "bom"
+    }
 }
 
 /// Main function for SPDX -> CDX conversion.
+///
+/// SPDX data without a CycloneDX mapping is recorded in `fidelity`. With
+/// `vex_path`, the vulnerabilities go to a separate CycloneDX JSON VEX
+/// document there, and the two documents link to each other. `identity`
+/// supplies the serial numbers and timestamp of the written documents;
+/// `ids` maps SPDX IDs to the bom-refs written, and `containers` records
+/// which of them contains which, per `CONTAINS` relationships. With
+/// `spill_threshold`, the relationship index holds at most that many
+/// relationships in memory and spills the rest to disk.
+#[allow(clippy::too_many_arguments)]
 pub fn convert_spdx_to_cdx<R: Read, W: Write>(
     input_reader: BufReader<R>,
     mut output_writer: BufWriter<W>,
     input_path: &Path,
     progress: ProgressTracker,
     packages_only: bool,
-    split_vex: bool,
+    nest_components: bool,
+    vex_path: Option<&Path>,
     output_version: CdxVersion,
+    fidelity: FidelityReport,
+    identity: &OutputIdentity,
+    ids: &IdMap,
+    containers: &Containers,
+    spill_threshold: Option<usize>,
 ) -> Result<(), ConverterError> {
     // --- PASS 1: Build Index ---
     info!("[PASS 1/2] Building relationship index...");
@@ -45,11 +528,20 @@ pub fn convert_spdx_to_cdx<R: Read, W: Write>(
     let start_pass_1 = std::time::Instant::now();
 
     // We must consume the input_reader to build the index.
-    let index = pass_1_build_index(input_reader, progress.clone())?;
+    let mut licenses = SpdxLicenseIndex::new(output_version);
+    let mut created = None;
+    let index = pass_1_build_index(
+        input_reader,
+        &mut licenses,
+        &mut created,
+        spill_threshold,
+        progress.clone(),
+    )?;
 
     info!(
-        "[PASS 1/2] Index complete. Found relationships for {} elements. (Took {:.2?})",
+        "[PASS 1/2] Index complete. Found relationships for {} elements{}. (Took {:.2?})",
         index.len(),
+        if index.is_spilled() { " (on disk)" } else { "" },
         start_pass_1.elapsed()
     );
 
@@ -62,15 +554,28 @@ pub fn convert_spdx_to_cdx<R: Read, W: Write>(
         .map_err(|e| ConverterError::Io(e, "Failed to re-open input for Pass 2".to_string()))?;
     let input_reader_pass_2 = BufReader::new(input_file_pass_2);
 
-    // Pass 2 writes components and dependencies, returns serial_number for Pass 3
-    let serial_number = pass_2_convert_and_write(
+    // The split-off VEX document is a BOM of its own
+    let serial_number = identity.serial_number("bom");
+    let vex_serial_number = vex_path.map(|_| identity.serial_number("vex"));
+    let timestamp = identity.created_from(created.as_deref()).to_rfc3339();
+
+    // Pass 2 writes components and dependencies
+    pass_2_convert_and_write(
         input_reader_pass_2,
         &mut output_writer,
         &index,
+        &licenses,
         progress.clone(),
         packages_only,
-        split_vex,
+        nest_components,
+        &serial_number,
+        vex_serial_number.as_deref(),
+        &timestamp,
+        identity.is_reproducible(),
         output_version,
+        &fidelity,
+        ids,
+        containers,
     )?;
 
     info!(
@@ -86,18 +591,17 @@ pub fn convert_spdx_to_cdx<R: Read, W: Write>(
         .map_err(|e| ConverterError::Io(e, "Failed to re-open input for Pass 3".to_string()))?;
     let input_reader_pass_3 = BufReader::new(input_file_pass_3);
 
-    if split_vex {
+    if let (Some(vex_path), Some(vex_serial_number)) = (vex_path, &vex_serial_number) {
         // Close main BOM file without vulnerabilities
         output_writer.write_all(b"\n}\n")?;
         output_writer.flush()?;
 
         // Create separate VEX file
-        let vex_path = input_path.with_extension("").with_extension("vex.json");
         info!(
             "Writing vulnerabilities to separate VEX file: {:?}",
             vex_path
         );
-        let vex_file = File::create(&vex_path).map_err(|e| {
+        let vex_file = File::create(vex_path).map_err(|e| {
             ConverterError::Io(e, format!("Failed to create VEX file: {:?}", vex_path))
         })?;
         let mut vex_writer = BufWriter::new(vex_file);
@@ -105,18 +609,26 @@ pub fn convert_spdx_to_cdx<R: Read, W: Write>(
         pass_3_extract_vulnerabilities(
             input_reader_pass_3,
             &mut vex_writer,
+            &index,
             &serial_number,
-            true,
+            Some(vex_serial_number),
+            &timestamp,
             output_version,
+            &fidelity,
+            ids,
         )?;
     } else {
         // Write vulnerabilities to main file
         pass_3_extract_vulnerabilities(
             input_reader_pass_3,
             &mut output_writer,
+            &index,
             &serial_number,
-            false,
+            None,
+            &timestamp,
             output_version,
+            &fidelity,
+            ids,
         )?;
     }
 
@@ -129,36 +641,59 @@ pub fn convert_spdx_to_cdx<R: Read, W: Write>(
 }
 
 /// Pass 1: Streams the input file and builds the relationship index.
+/// Also picks up the creation time of the document into `created`.
 fn pass_1_build_index<R: Read>(
     input_reader: BufReader<R>,
+    licenses: &mut SpdxLicenseIndex,
+    created: &mut Option<String>,
+    spill_threshold: Option<usize>,
     progress: ProgressTracker,
 ) -> Result<SpdxRelationshipIndex, ConverterError> {
-    let mut index: SpdxRelationshipIndex = HashMap::new();
+    let mut index = match spill_threshold {
+        Some(threshold) => SpdxRelationshipIndex::with_spill_threshold(threshold),
+        None => SpdxRelationshipIndex::new(),
+    };
     let visitor = spdx::SpdxPass1Visitor {
         index: &mut index,
+        licenses: &mut *licenses,
+        created,
         progress: progress.clone(),
     };
     let mut deserializer = serde_json::Deserializer::from_reader(input_reader);
 
     // Drive the streaming visitor
     deserializer.deserialize_any(visitor)?;
+    licenses.resolve();
+    index.finish()?;
 
     Ok(index)
 }
 
 /// Pass 2: Streams the input file again, converts, and writes components/dependencies.
-/// Returns the serial number for use in Pass 3.
+///
+/// `vex_serial_number` is the serial number of the split-off VEX document,
+/// if any; the BOM links to it and is closed by the caller. Dependencies
+/// are written ordered by ref; with `sort_dependencies`, each `dependsOn`
+/// list is sorted and deduplicated as well.
+#[allow(clippy::too_many_arguments)]
 fn pass_2_convert_and_write<R: Read, W: Write>(
     input_reader: BufReader<R>,
     writer: &mut BufWriter<W>,
     index: &SpdxRelationshipIndex,
+    licenses: &SpdxLicenseIndex,
     progress: ProgressTracker,
     packages_only: bool,
-    split_vex: bool,
+    nest_components: bool,
+    serial_number: &str,
+    vex_serial_number: Option<&str>,
+    timestamp: &str,
+    sort_dependencies: bool,
     output_version: CdxVersion,
-) -> Result<String, ConverterError> {
+    fidelity: &FidelityReport,
+    ids: &IdMap,
+    containers: &Containers,
+) -> Result<(), ConverterError> {
     // --- Write CDX Header ---
-    let serial_number = format!("urn:uuid:{}", Uuid::new_v4());
     writer.write_all(b"{\n")?;
     writer.write_all(b"  \"bomFormat\": \"CycloneDX\",\n")?;
     writer
@@ -168,7 +703,7 @@ fn pass_2_convert_and_write<R: Read, W: Write>(
 
     // --- Write Metadata ---
     let metadata = cdx::CdxMetadata {
-        timestamp: chrono::Utc::now().to_rfc3339(),
+        timestamp: timestamp.to_string(),
         tools: Some(cdx::CdxTools {
             components: vec![cdx::CdxToolComponent {
                 component_type: "application".to_string(),
@@ -181,62 +716,128 @@ fn pass_2_convert_and_write<R: Read, W: Write>(
     serde_json::to_writer(&mut *writer, &metadata)?;
     writer.write_all(b",\n")?;
 
+    // --- Link the split-off VEX document ---
+    if let Some(vex_serial_number) = vex_serial_number {
+        let reference = cdx::CdxExternalReference {
+            ref_type: vex_reference_type(output_version).to_string(),
+            url: BomLink::document(vex_serial_number, 1).to_string(),
+            comment: Some("VEX document for this BOM".to_string()),
+            hashes: None,
+        };
+        writer.write_all(b"  \"externalReferences\": ")?;
+        serde_json::to_writer(&mut *writer, &[reference])?;
+        writer.write_all(b",\n")?;
+    }
+
     // --- Stream Elements (writing Components & Vulns) ---
     writer.write_all(b"  \"components\": [\n")?;
 
-    let first_component = true;
+    // Nesting holds the component trees back in memory, which a spilled
+    // index is meant to avoid, so the components stay flat
+    let nest_components = if nest_components && index.is_spilled() {
+        warn!("Relationship index spilled to disk; writing components flat instead of nested");
+        fidelity.record("option.nestComponents", "");
+        false
+    } else {
+        nest_components
+    };
+    let mut components = if nest_components {
+        ComponentOutput::nested(index, ids)?
+    } else {
+        ComponentOutput::flat()
+    };
     let first_vulnerability = true;
 
     let visitor = spdx::SpdxPass2Visitor {
         writer,
         index,
-        first_component,
+        licenses,
+        components: &mut components,
         first_vulnerability,
         progress: progress.clone(),
         packages_only,
+        fidelity: fidelity.clone(),
+        ids: ids.clone(),
     };
 
     let mut deserializer = serde_json::Deserializer::from_reader(input_reader);
     deserializer.deserialize_any(visitor)?;
 
+    // Write the component trees held back for nesting
+    components.finish(writer)?;
+
     // Close components array
     writer.write_all(b"\n  ],\n")?;
 
     // --- Write Dependencies (from Index) ---
     writer.write_all(b"  \"dependencies\": [\n")?;
-    let mut first_dep = true;
-    for (spdx_id, relationships) in index.iter() {
+    // A spilled index does not fit in memory, and neither would one entry
+    // per relationship
+    let mut dependencies = match index.spill_threshold().filter(|_| index.is_spilled()) {
+        Some(threshold) => {
+            containers.skip();
+            DependencyOutput::spilled(threshold)?
+        }
+        None => DependencyOutput::memory(),
+    };
+    // Map in SPDX ID order so the bom-refs do not depend on the order of
+    // the input, and write ordered by ref
+    index.scan(|spdx_id, relationships| -> Result<(), ConverterError> {
         let mut depends_on = Vec::new();
         for rel in relationships {
             // Support both simple JSON (DEPENDS_ON) and JSON-LD (dependsOn, contains) formats
             let rel_type = rel.relationship_type.as_str();
-            if rel_type == "DEPENDS_ON" || rel_type == "dependsOn" || rel_type == "contains" {
-                // Map SPDX ID to bom-ref using same extraction logic
-                let bom_ref = extract_bom_ref(&rel.related_spdx_element);
+            let is_dependency = matches!(
+                rel_type,
+                "DEPENDS_ON" | "CONTAINS" | "dependsOn" | "contains"
+            );
+            // SPDX 2.3 allows NOASSERTION/NONE as relationship targets
+            let is_placeholder =
+                matches!(rel.related_spdx_element.as_str(), "NOASSERTION" | "NONE");
+            // Licenses were resolved onto the components in Pass 2 and
+            // vulnerabilities are converted in Pass 3
+            let is_handled = matches!(
+                rel_type,
+                "hasConcludedLicense" | "hasDeclaredLicense" | "AFFECTS" | "affects"
+            );
+            if is_handled {
+                continue;
+            }
+            if is_dependency && !is_placeholder {
+                // Map SPDX ID to bom-ref through the shared ID map
+                let bom_ref = ids.bom_ref(&rel.related_spdx_element);
+                // Remembered for rewriting refs to dropped elements
+                if matches!(rel_type, "CONTAINS" | "contains") {
+                    containers.add(&bom_ref, &ids.bom_ref(spdx_id));
+                }
+                // Containment rebuilt as nesting is not also a dependency
+                if matches!(rel_type, "CONTAINS" | "contains")
+                    && components.is_nested(&ids.bom_ref(spdx_id), &bom_ref)
+                {
+                    continue;
+                }
                 depends_on.push(bom_ref);
+            } else if !is_dependency {
+                fidelity.record(&format!("relationship.{}", rel_type), spdx_id);
             }
         }
 
+        if sort_dependencies {
+            depends_on.sort();
+            depends_on.dedup();
+        }
         if !depends_on.is_empty() {
-            if !first_dep {
-                writer.write_all(b",\n")?;
-            }
-            first_dep = false;
-
-            // Map SPDX ID to bom-ref for the dependency ref
-            let dep_ref = extract_bom_ref(spdx_id);
-
-            let dep = cdx::CdxDependency {
-                dep_ref,
+            dependencies.add(cdx::CdxDependency {
+                dep_ref: ids.bom_ref(spdx_id),
                 depends_on,
-            };
-            writer.write_all(b"    ")?;
-            serde_json::to_writer(&mut *writer, &dep)?;
+            })?;
         }
-    }
+        Ok(())
+    })?;
+    dependencies.finish(writer)?;
 
     // Close dependencies array
-    if split_vex {
+    if vex_serial_number.is_some() {
         // No comma - we'll close the JSON right after this
         writer.write_all(b"\n  ]")?;
     } else {
@@ -246,30 +847,39 @@ fn pass_2_convert_and_write<R: Read, W: Write>(
 
     // Don't finalize JSON yet - Pass 3 may add vulnerabilities and close
     writer.flush()?;
-    Ok(serial_number) // Return serial_number for Pass 3
+    Ok(())
 }
 
-/// Pass 3: Extracts vulnerabilities and VEX assessments from JSON-LD @graph.
+/// Pass 3: Extracts vulnerabilities and their assessments from `@graph`
+/// (JSON-LD) or `elements` (simple JSON).
+///
+/// With `vex_serial_number`, writes a complete VEX document created at
+/// `timestamp` that links back to the BOM `serial_number`.
+#[allow(clippy::too_many_arguments)]
 fn pass_3_extract_vulnerabilities<R: Read, W: Write>(
     input_reader: BufReader<R>,
     writer: &mut BufWriter<W>,
+    index: &SpdxRelationshipIndex,
     serial_number: &str,
-    separate_file: bool,
+    vex_serial_number: Option<&str>,
+    timestamp: &str,
     output_version: CdxVersion,
+    fidelity: &FidelityReport,
+    ids: &IdMap,
 ) -> Result<(), ConverterError> {
-    if separate_file {
+    if let Some(vex_serial_number) = vex_serial_number {
         // Write complete VEX document structure
         writer.write_all(b"{\n")?;
         writer.write_all(b"  \"bomFormat\": \"CycloneDX\",\n")?;
         writer.write_all(
             format!("  \"specVersion\": \"{}\",\n", output_version.as_str()).as_bytes(),
         )?;
-        writer.write_all(format!("  \"serialNumber\": \"{}\",\n", serial_number).as_bytes())?;
+        writer.write_all(format!("  \"serialNumber\": \"{}\",\n", vex_serial_number).as_bytes())?;
         writer.write_all(b"  \"version\": 1,\n")?;
 
         // Add metadata
         let metadata = cdx::CdxMetadata {
-            timestamp: chrono::Utc::now().to_rfc3339(),
+            timestamp: timestamp.to_string(),
             tools: Some(cdx::CdxTools {
                 components: vec![cdx::CdxToolComponent {
                     component_type: "application".to_string(),
@@ -281,20 +891,38 @@ fn pass_3_extract_vulnerabilities<R: Read, W: Write>(
         writer.write_all(b"  \"metadata\": ")?;
         serde_json::to_writer(&mut *writer, &metadata)?;
         writer.write_all(b",\n")?;
-    }
 
-    writer.write_all(b"  \"vulnerabilities\": [\n")?;
+        // Link back to the BOM the statements are about
+        let reference = cdx::CdxExternalReference {
+            ref_type: "bom".to_string(),
+            url: BomLink::document(serial_number, 1).to_string(),
+            comment: Some("BOM the VEX statements apply to".to_string()),
+            hashes: None,
+        };
+        writer.write_all(b"  \"externalReferences\": ")?;
+        serde_json::to_writer(&mut *writer, &[reference])?;
+        writer.write_all(b",\n")?;
+    }
 
-    let first_vuln = true;
+    let mut vulnerabilities = SpdxVulnerabilityIndex::default();
     let visitor = spdx::SpdxPass3VulnVisitor {
-        writer,
-        serial_number: serial_number.to_string(),
-        first_vuln,
+        vulnerabilities: &mut vulnerabilities,
     };
-
     let mut deserializer = serde_json::Deserializer::from_reader(input_reader);
     deserializer.deserialize_any(visitor)?;
 
+    writer.write_all(b"  \"vulnerabilities\": [\n")?;
+    for (i, vuln) in vulnerabilities
+        .to_cdx(index, serial_number, ids, fidelity)?
+        .iter()
+        .enumerate()
+    {
+        if i > 0 {
+            writer.write_all(b",\n")?;
+        }
+        writer.write_all(b"    ")?;
+        serde_json::to_writer(&mut *writer, vuln)?;
+    }
     writer.write_all(b"\n  ]")?;
 
     // --- Finalize JSON ---
@@ -305,18 +933,21 @@ fn pass_3_extract_vulnerabilities<R: Read, W: Write>(
 
 /// This function is called *for each element* in the SPDX 'elements' array
 /// during Pass 2.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_spdx_element<W: Write>(
     element: spdx::SpdxElementMinimal,
     writer: &mut BufWriter<W>,
     _index: &SpdxRelationshipIndex,
-    first_component: &mut bool,
+    licenses: &SpdxLicenseIndex,
+    ids: &IdMap,
+    components: &mut ComponentOutput,
     _first_vulnerability: &mut bool,
 ) -> Result<(), std::io::Error> {
     match element.element_type.as_str() {
         // Support both simple JSON and JSON-LD type names
         "SpdxPackage" | "software_Package" | "SpdxFile" | "software_File" => {
             // Map SPDX ID to bom-ref
-            let bom_ref = extract_bom_ref(&element.spdx_id);
+            let bom_ref = ids.bom_ref(&element.spdx_id);
 
             // Extract CPE from externalIdentifier
             let cpe = element.external_identifier.as_ref().and_then(|ids| {
@@ -359,7 +990,7 @@ pub fn handle_spdx_element<W: Write>(
                         _ => "required".to_string(),
                     });
 
-            let component = cdx::CdxComponent {
+            let mut component = cdx::CdxComponent {
                 bom_ref,
                 component_type: if element.element_type == "SpdxPackage"
                     || element.element_type == "software_Package"
@@ -375,30 +1006,18 @@ pub fn handle_spdx_element<W: Write>(
                 purl: element.purl,
                 scope,
                 hashes,
-                licenses: element.license_concluded.map(|expr| {
-                    vec![cdx::CdxLicenseChoice {
-                        expression: Some(expr),
-                        license: None,
-                    }]
-                }),
-                extra: HashMap::new(), // We didn't deserialize any
+                licenses: licenses.to_cdx(
+                    element.license_concluded.as_deref(),
+                    element.license_declared.as_deref(),
+                ),
+                ..Default::default()
             };
+            element.details.apply_to(&mut component);
 
-            if !*first_component {
-                writer.write_all(b",\n")?;
-            }
-            *first_component = false;
-
-            // Serialize this one component straight to the output file
-            writer.write_all(b"    ")?;
-            serde_json::to_writer(&mut *writer, &component)?;
+            components.write(component, writer)?;
         }
         "SpdxVulnerability" | "security_Vulnerability" => {
-            // For now, we'll just skip them
-            warn!(
-                "Skipping vulnerability conversion (not implemented in this pass): {:?}",
-                element.name
-            );
+            // Converted with their assessments in Pass 3
         }
         _ => {
             // Other element type
@@ -409,45 +1028,77 @@ pub fn handle_spdx_element<W: Write>(
 }
 
 /// Extract a usable bom-ref from an SPDX ID (handles both simple format and JSON-LD URIs)
+///
+/// IRIs are named after their fragment, or their last path segment if they
+/// have none. The result only depends on `spdx_id`, so it is the same across
+/// runs and releases; [`IdMap::bom_ref`] resolves any collisions.
 pub fn extract_bom_ref(spdx_id: &str) -> String {
-    if spdx_id.starts_with("http://") || spdx_id.starts_with("https://") {
-        // JSON-LD URI format: use a hash of the full URI to ensure uniqueness
-        // This prevents collisions from URIs ending in common segments like "recipe"
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        spdx_id.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        // Also try to extract a meaningful name component
-        let name_part = spdx_id
-            .rsplit('/')
-            .next()
-            .filter(|s| !s.is_empty() && s.chars().any(|c| c.is_alphabetic()))
-            .unwrap_or("element");
-
-        format!("{}-{:x}", name_part, hash)
-    } else {
+    let is_url = spdx_id.starts_with("http://") || spdx_id.starts_with("https://");
+    let fragment = spdx_id.rsplit_once('#').map(|(_, f)| f);
+    if !is_url && fragment.is_none() {
         // Simple JSON format: remove SPDXRef- prefix
-        spdx_id
-            .strip_prefix("SPDXRef-")
-            .unwrap_or(spdx_id)
-            .to_string()
+        return sanitize_bom_ref(spdx_id.strip_prefix("SPDXRef-").unwrap_or(spdx_id));
     }
+
+    // JSON-LD URI format: use a hash of the full URI to ensure uniqueness
+    // This prevents collisions from URIs ending in common segments like "recipe"
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(spdx_id.as_bytes());
+    let hash: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+
+    // Also try to extract a meaningful name component
+    let local = fragment.unwrap_or_else(|| spdx_id.rsplit('/').next().unwrap_or(""));
+    let local = local.strip_prefix("SPDXRef-").unwrap_or(local);
+    let name_part = if local.chars().any(|c| c.is_alphabetic()) {
+        sanitize_bom_ref(local)
+    } else {
+        "element".to_string()
+    };
+
+    format!("{}-{}", name_part, hash)
+}
+
+/// Replaces the characters that are not safe in a bom-ref (and in the
+/// fragment of the BOM-Link that points at it) with `-`
+fn sanitize_bom_ref(local: &str) -> String {
+    local
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':' | '@' | '/') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
 }
 
 /// Handle JSON-LD element with full data extraction
 pub fn handle_jsonld_element<W: Write>(
     element: spdx::JsonLdElement,
     writer: &mut BufWriter<W>,
-    _index: &SpdxRelationshipIndex,
-    first_component: &mut bool,
+    index: &SpdxRelationshipIndex,
+    licenses: &SpdxLicenseIndex,
+    ids: &IdMap,
+    components: &mut ComponentOutput,
 ) -> Result<(), std::io::Error> {
     // Map SPDX ID to bom-ref
-    let bom_ref = extract_bom_ref(&element.spdx_id);
+    let bom_ref = ids.bom_ref(&element.spdx_id);
+
+    // Licenses are separate elements, linked by relationship
+    let relationships = index.get(&element.spdx_id)?;
+    let license = |relationship_type: &str| {
+        relationships.iter().find_map(|rel| {
+            (rel.relationship_type == relationship_type)
+                .then(|| licenses.expression(&rel.related_spdx_element))
+                .flatten()
+        })
+    };
+    let concluded = license("hasConcludedLicense");
+    let declared = license("hasDeclaredLicense");
 
-    let component = cdx::CdxComponent {
+    let mut component = cdx::CdxComponent {
         bom_ref,
         component_type: if element.element_type == "software_Package" {
             "library".to_string()
@@ -464,10 +1115,20 @@ pub fn handle_jsonld_element<W: Write>(
         purl: element.extract_purl(),
         scope: element.map_scope(),
         hashes: element.extract_hashes(),
-        licenses: None, // TODO: Extract from license relationships
-        extra: HashMap::new(),
+        licenses: licenses.to_cdx(concluded, declared),
+        ..Default::default()
     };
+    element.details.apply_to(&mut component);
+
+    components.write(component, writer)
+}
 
+/// Writes one converted component into the open `components` array
+pub fn write_component<W: Write>(
+    component: cdx::CdxComponent,
+    writer: &mut BufWriter<W>,
+    first_component: &mut bool,
+) -> Result<(), std::io::Error> {
     if !*first_component {
         writer.write_all(b",\n")?;
     }
@@ -479,3 +1140,258 @@ pub fn handle_jsonld_element<W: Write>(
 
     Ok(())
 }
+
+/// Where Pass 2 sends the `dependencies` entries, which are written ordered
+/// by ref.
+///
+/// The entries are collected in memory, unless the relationship index was
+/// spilled to disk: then they are written to a temp file, one per line,
+/// which [`line_sort`] sorts holding at most as many entries in memory as
+/// the index did.
+pub enum DependencyOutput {
+    Memory(Vec<cdx::CdxDependency>),
+    Spilled {
+        path: PathBuf,
+        temp_writer: BufWriter<File>,
+        chunk_lines: usize,
+    },
+}
+
+impl DependencyOutput {
+    /// Collects the entries in memory
+    pub fn memory() -> Self {
+        DependencyOutput::Memory(Vec::new())
+    }
+
+    /// Writes the entries to a temp file, sorted in chunks of `chunk_lines`
+    pub fn spilled(chunk_lines: usize) -> Result<Self, ConverterError> {
+        let path = std::env::temp_dir().join(format!(
+            "sbom-converter-dependencies-{}.jsonl",
+            uuid::Uuid::new_v4()
+        ));
+        let file = File::create(&path).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to create temp file {}", path.display()))
+        })?;
+        Ok(DependencyOutput::Spilled {
+            path,
+            temp_writer: BufWriter::new(file),
+            chunk_lines,
+        })
+    }
+
+    pub fn add(&mut self, dependency: cdx::CdxDependency) -> Result<(), ConverterError> {
+        match self {
+            DependencyOutput::Memory(dependencies) => dependencies.push(dependency),
+            DependencyOutput::Spilled { temp_writer, .. } => {
+                serde_json::to_writer(&mut *temp_writer, &dependency)?;
+                temp_writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of entries held in memory
+    pub fn buffered(&self) -> usize {
+        match self {
+            DependencyOutput::Memory(dependencies) => dependencies.len(),
+            DependencyOutput::Spilled { .. } => 0,
+        }
+    }
+
+    /// Writes the entries ordered by ref, separated by commas
+    pub fn finish<W: Write>(&mut self, writer: &mut BufWriter<W>) -> Result<(), ConverterError> {
+        match self {
+            DependencyOutput::Memory(dependencies) => {
+                dependencies.sort_by(|a, b| a.dep_ref.cmp(&b.dep_ref));
+                for (i, dep) in dependencies.iter().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b",\n")?;
+                    }
+                    writer.write_all(b"    ")?;
+                    serde_json::to_writer(&mut *writer, dep)?;
+                }
+            }
+            DependencyOutput::Spilled {
+                path,
+                temp_writer,
+                chunk_lines,
+            } => {
+                temp_writer.flush()?;
+                // Refs are unique, so this is the order of the in-memory sort
+                line_sort::sort_lines(path, *chunk_lines, |line| {
+                    serde_json::from_str::<cdx::CdxDependency>(line)
+                        .map(|dep| dep.dep_ref)
+                        .map_err(|e| {
+                            ConverterError::JsonParse(format!("Bad dependency in temp file: {}", e))
+                        })
+                })?;
+                let file = File::open(&*path).map_err(|e| {
+                    ConverterError::Io(e, format!("Failed to open {}", path.display()))
+                })?;
+                for (i, line) in BufReader::new(file).lines().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b",\n")?;
+                    }
+                    writer.write_all(b"    ")?;
+                    writer.write_all(line?.as_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DependencyOutput {
+    fn drop(&mut self) {
+        if let DependencyOutput::Spilled { path, .. } = self {
+            let _ = std::fs::remove_file(&*path);
+        }
+    }
+}
+
+/// Where Pass 2 sends converted components.
+///
+/// Components are streamed straight to the output, unless nesting was
+/// requested: then every component that contains, or is contained by,
+/// another one (per `CONTAINS` relationships) is held back until the input
+/// has been read, and written as a nested tree by [`ComponentOutput::finish`].
+pub struct ComponentOutput {
+    first_component: bool,
+    /// Child bom-ref -> parent bom-ref (empty unless nesting)
+    parents: HashMap<String, String>,
+    /// Bom-refs that contain at least one other element
+    containers: HashSet<String>,
+    /// Components held back for nesting, and the order they arrived in
+    pending: HashMap<String, cdx::CdxComponent>,
+    arrival: Vec<String>,
+    /// (parent, child) bom-ref pairs written as nested components
+    nested: HashSet<(String, String)>,
+}
+
+impl ComponentOutput {
+    /// Streams every component to the top-level `components` array
+    pub fn flat() -> Self {
+        Self {
+            first_component: true,
+            parents: HashMap::new(),
+            containers: HashSet::new(),
+            pending: HashMap::new(),
+            arrival: Vec::new(),
+            nested: HashSet::new(),
+        }
+    }
+
+    /// Rebuilds `components[].components` from the `CONTAINS` relationships
+    /// in `index`
+    pub fn nested(index: &SpdxRelationshipIndex, ids: &IdMap) -> Result<Self, ConverterError> {
+        let mut output = Self::flat();
+        // Map in SPDX ID order so the bom-refs do not depend on the input
+        index.scan(|spdx_id, relationships| -> Result<(), ConverterError> {
+            let parent = ids.bom_ref(spdx_id);
+            for rel in relationships {
+                let is_contains = matches!(rel.relationship_type.as_str(), "CONTAINS" | "contains");
+                let is_placeholder =
+                    matches!(rel.related_spdx_element.as_str(), "NOASSERTION" | "NONE");
+                if !is_contains || is_placeholder {
+                    continue;
+                }
+                let child = ids.bom_ref(&rel.related_spdx_element);
+                if child == parent {
+                    continue;
+                }
+                // A component can only be nested in one place; the index is
+                // unordered, so pick the parent deterministically
+                let current = output
+                    .parents
+                    .entry(child)
+                    .or_insert_with(|| parent.clone());
+                if parent < *current {
+                    *current = parent.clone();
+                }
+            }
+            Ok(())
+        })?;
+        output.containers = output.parents.values().cloned().collect();
+        Ok(output)
+    }
+
+    /// Writes `component` now, or holds it back if it belongs to a tree
+    pub fn write<W: Write>(
+        &mut self,
+        component: cdx::CdxComponent,
+        writer: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        let bom_ref = &component.bom_ref;
+        let in_tree = self.parents.contains_key(bom_ref) || self.containers.contains(bom_ref);
+        if !in_tree || self.pending.contains_key(bom_ref) {
+            return write_component(component, writer, &mut self.first_component);
+        }
+        self.arrival.push(bom_ref.clone());
+        self.pending.insert(bom_ref.clone(), component);
+        Ok(())
+    }
+
+    /// Writes the held-back components as nested trees.
+    ///
+    /// A component whose container was never converted (e.g. the SPDX
+    /// document itself, or a file skipped by `--packages-only`) stays at the
+    /// top level. Containment cycles are broken at the first component read.
+    pub fn finish<W: Write>(&mut self, writer: &mut BufWriter<W>) -> Result<(), std::io::Error> {
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+        for bom_ref in &self.arrival {
+            if let Some(parent) = self.parents.get(bom_ref)
+                && self.pending.contains_key(parent)
+            {
+                children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(bom_ref.clone());
+            }
+        }
+
+        let arrival = std::mem::take(&mut self.arrival);
+        let roots = arrival.iter().filter(|bom_ref| {
+            !self
+                .parents
+                .get(*bom_ref)
+                .is_some_and(|parent| self.pending.contains_key(parent))
+        });
+        // Cycles have no root, so whatever is left afterwards is written too
+        let roots: Vec<String> = roots.chain(arrival.iter()).cloned().collect();
+        for root in roots {
+            if let Some(component) = self.assemble(&root, &children) {
+                write_component(component, writer, &mut self.first_component)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes `bom_ref` out of the pending set with its subtree attached
+    fn assemble(
+        &mut self,
+        bom_ref: &str,
+        children: &HashMap<String, Vec<String>>,
+    ) -> Option<cdx::CdxComponent> {
+        let mut component = self.pending.remove(bom_ref)?;
+        let nested: Vec<cdx::CdxComponent> = children
+            .get(bom_ref)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| {
+                let nested = self.assemble(child, children)?;
+                self.nested.insert((bom_ref.to_string(), child.to_string()));
+                Some(nested)
+            })
+            .collect();
+        if !nested.is_empty() {
+            component.components = Some(nested);
+        }
+        Some(component)
+    }
+
+    /// Whether `child` was written nested under `parent`
+    pub fn is_nested(&self, parent: &str, child: &str) -> bool {
+        self.nested
+            .contains(&(parent.to_string(), child.to_string()))
+    }
+}
@@ -3,7 +3,10 @@
 //! Compares two SBOM files and generates a detailed report of differences.
 //! Supports both CycloneDX and SPDX formats.
 
+use crate::bom_link::localize;
 use crate::errors::ConverterError;
+use crate::formats::read_json_value;
+use crate::license;
 use crate::version_detection::{SbomFormat, detect_format};
 use colored::Colorize;
 use serde_json::{Value, json};
@@ -33,6 +36,10 @@ pub struct ComponentInfo {
     pub version: Option<String>,
     pub purl: Option<String>,
     pub component_type: Option<String>,
+    /// Normalized `ALGORITHM:value` digests, sorted
+    pub hashes: Vec<String>,
+    /// License ids, names or expressions, sorted
+    pub licenses: Vec<String>,
 }
 
 /// Component-level differences
@@ -317,24 +324,9 @@ pub fn diff_sboms(
     file1: impl AsRef<Path>,
     file2: impl AsRef<Path>,
 ) -> Result<DiffReport, ConverterError> {
-    // Read both files
-    let content1 = std::fs::read_to_string(file1.as_ref()).map_err(|e| {
-        ConverterError::Io(
-            e,
-            format!("Failed to read file1: {}", file1.as_ref().display()),
-        )
-    })?;
-    let content2 = std::fs::read_to_string(file2.as_ref()).map_err(|e| {
-        ConverterError::Io(
-            e,
-            format!("Failed to read file2: {}", file2.as_ref().display()),
-        )
-    })?;
-
-    let value1: Value = serde_json::from_str(&content1)
-        .map_err(|e| ConverterError::ParseError(format!("Invalid JSON in file1: {}", e)))?;
-    let value2: Value = serde_json::from_str(&content2)
-        .map_err(|e| ConverterError::ParseError(format!("Invalid JSON in file2: {}", e)))?;
+    // Read both files (JSON, CycloneDX XML or SPDX tag-value)
+    let value1 = read_json_value(file1.as_ref())?;
+    let value2 = read_json_value(file2.as_ref())?;
 
     // Detect formats
     let format1 = detect_format(&value1);
@@ -354,6 +346,32 @@ pub fn diff_sboms(
     }
 }
 
+/// Count the components, dependencies and vulnerabilities a diff compares
+pub fn count_items(value: &Value) -> usize {
+    match detect_format(value) {
+        SbomFormat::CycloneDx(_) => {
+            extract_cdx_components(value).len()
+                + extract_cdx_dependencies(value).len()
+                + extract_cdx_vulnerabilities(value).len()
+        }
+        SbomFormat::Spdx(_) => {
+            extract_spdx_packages(value).len()
+                + extract_spdx_relationships(value).len()
+                + extract_spdx_vulnerabilities(value).len()
+        }
+        SbomFormat::Unknown => 0,
+    }
+}
+
+/// The components of any supported SBOM, as the diff sees them
+pub fn extract_components(value: &Value) -> Vec<ComponentInfo> {
+    match detect_format(value) {
+        SbomFormat::CycloneDx(_) => extract_cdx_components(value),
+        SbomFormat::Spdx(_) => extract_spdx_packages(value),
+        SbomFormat::Unknown => Vec::new(),
+    }
+}
+
 /// Compare two CycloneDX SBOMs
 fn diff_cyclonedx(
     value1: &Value,
@@ -517,6 +535,13 @@ fn diff_spdx(
     report.added_dependencies = added_deps;
     report.removed_dependencies = removed_deps;
 
+    // Extract and compare vulnerabilities (SPDX 3.x only)
+    let vulns1 = extract_spdx_vulnerabilities(value1);
+    let vulns2 = extract_spdx_vulnerabilities(value2);
+    let (added_vulns, removed_vulns) = diff_vulnerabilities(&vulns1, &vulns2);
+    report.added_vulnerabilities = added_vulns;
+    report.removed_vulnerabilities = removed_vulns;
+
     // Compare metadata
     report.metadata_changes = compare_spdx_metadata(value1, value2);
 
@@ -525,9 +550,14 @@ fn diff_spdx(
 
 // Helper functions for extracting data from CycloneDX
 
+/// Collects the components of a BOM, including nested ones, depth first
 fn extract_cdx_components(value: &Value) -> Vec<ComponentInfo> {
     let mut components = Vec::new();
+    collect_cdx_components(value, &mut components);
+    components
+}
 
+fn collect_cdx_components(value: &Value, components: &mut Vec<ComponentInfo>) {
     if let Some(comps) = value.get("components").and_then(|c| c.as_array()) {
         for comp in comps {
             components.push(ComponentInfo {
@@ -548,15 +578,44 @@ fn extract_cdx_components(value: &Value) -> Vec<ComponentInfo> {
                     .get("type")
                     .and_then(|t| t.as_str())
                     .map(|s| s.to_string()),
+                hashes: collect_hashes(comp.get("hashes"), "alg", "content"),
+                licenses: extract_cdx_licenses(comp),
             });
+            collect_cdx_components(comp, components);
         }
     }
+}
 
-    components
+fn extract_cdx_licenses(comp: &Value) -> Vec<String> {
+    let mut licenses: Vec<String> = comp
+        .get("licenses")
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|choice| {
+            if let Some(expression) = choice
+                .get("expression")
+                .or_else(|| choice.get("license")?.get("id"))
+                .and_then(|e| e.as_str())
+            {
+                return Some(license::canonical(expression));
+            }
+            choice
+                .get("license")?
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+        })
+        .collect();
+    licenses.sort();
+    licenses.dedup();
+    licenses
 }
 
+/// BOM-Links to the BOM itself are compared as the plain bom-refs
 fn extract_cdx_dependencies(value: &Value) -> Vec<DependencyInfo> {
     let mut dependencies = Vec::new();
+    let serial_number = value.get("serialNumber").and_then(|s| s.as_str());
 
     if let Some(deps) = value.get("dependencies").and_then(|d| d.as_array()) {
         for dep in deps {
@@ -566,8 +625,8 @@ fn extract_cdx_dependencies(value: &Value) -> Vec<DependencyInfo> {
                 for target in depends_on {
                     if let Some(target_str) = target.as_str() {
                         dependencies.push(DependencyInfo {
-                            from: ref_id.to_string(),
-                            to: target_str.to_string(),
+                            from: localize(ref_id, serial_number).into_owned(),
+                            to: localize(target_str, serial_number).into_owned(),
                         });
                     }
                 }
@@ -580,6 +639,7 @@ fn extract_cdx_dependencies(value: &Value) -> Vec<DependencyInfo> {
 
 fn extract_cdx_vulnerabilities(value: &Value) -> Vec<VulnerabilityInfo> {
     let mut vulnerabilities = Vec::new();
+    let serial_number = value.get("serialNumber").and_then(|s| s.as_str());
 
     if let Some(vulns) = value.get("vulnerabilities").and_then(|v| v.as_array()) {
         for vuln in vulns {
@@ -600,7 +660,7 @@ fn extract_cdx_vulnerabilities(value: &Value) -> Vec<VulnerabilityInfo> {
                     .and_then(|arr| arr.first())
                     .and_then(|a| a.get("ref"))
                     .and_then(|r| r.as_str())
-                    .map(|s| s.to_string()),
+                    .map(|s| localize(s, serial_number).into_owned()),
             });
         }
     }
@@ -647,6 +707,7 @@ fn extract_spdx_packages(value: &Value) -> Vec<ComponentInfo> {
 
     // SPDX 3.x structure
     if let Some(graph) = value.get("@graph").and_then(|g| g.as_array()) {
+        let jsonld_licenses = extract_jsonld_licenses(graph);
         for element in graph {
             if let Some(elem_type) = element.get("type").and_then(|t| t.as_str())
                 && elem_type.contains("Package")
@@ -658,30 +719,184 @@ fn extract_spdx_packages(value: &Value) -> Vec<ComponentInfo> {
                         .unwrap_or("unknown")
                         .to_string(),
                     version: element
-                        .get("packageVersion")
+                        .get("software_packageVersion")
+                        .or_else(|| element.get("packageVersion"))
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string()),
                     purl: element
-                        .get("externalIdentifier")
-                        .and_then(|ei| ei.as_array())
-                        .and_then(|arr| {
-                            arr.iter().find(|e| {
-                                e.get("externalIdentifierType").and_then(|t| t.as_str())
-                                    == Some("purl")
-                            })
+                        .get("software_packageUrl")
+                        .and_then(|p| p.as_str())
+                        .or_else(|| {
+                            element
+                                .get("externalIdentifier")
+                                .and_then(|ei| ei.as_array())
+                                .and_then(|arr| {
+                                    arr.iter().find(|e| {
+                                        e.get("externalIdentifierType").and_then(|t| t.as_str())
+                                            == Some("purl")
+                                    })
+                                })
+                                .and_then(|e| e.get("identifier"))
+                                .and_then(|i| i.as_str())
                         })
-                        .and_then(|e| e.get("identifier"))
-                        .and_then(|i| i.as_str())
                         .map(|s| s.to_string()),
                     component_type: Some("package".to_string()),
+                    hashes: collect_hashes(element.get("verifiedUsing"), "algorithm", "hashValue"),
+                    licenses: element
+                        .get("spdxId")
+                        .and_then(|id| id.as_str())
+                        .and_then(|id| jsonld_licenses.get(id))
+                        .cloned()
+                        .unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    // SPDX 3.x simple JSON structure
+    if let Some(elements) = value.get("elements").and_then(|e| e.as_array()) {
+        for element in elements {
+            if element.get("type").and_then(|t| t.as_str()) == Some("SpdxPackage") {
+                components.push(ComponentInfo {
+                    name: element
+                        .get("name")
+                        .and_then(|n| n.as_str())
+                        .unwrap_or("unknown")
+                        .to_string(),
+                    version: element
+                        .get("versionInfo")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                    purl: element
+                        .get("purl")
+                        .and_then(|p| p.as_str())
+                        .map(|s| s.to_string()),
+                    component_type: Some("package".to_string()),
+                    hashes: collect_hashes(element.get("verifiedUsing"), "algorithm", "hashValue"),
+                    licenses: extract_spdx_licenses(element),
                 });
             }
         }
     }
 
+    // SPDX 2.x structure
+    if let Some(packages) = value.get("packages").and_then(|p| p.as_array()) {
+        for package in packages {
+            components.push(ComponentInfo {
+                name: package
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                version: package
+                    .get("versionInfo")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                purl: package
+                    .get("externalRefs")
+                    .and_then(|refs| refs.as_array())
+                    .and_then(|arr| {
+                        arr.iter().find(|r| {
+                            r.get("referenceType").and_then(|t| t.as_str()) == Some("purl")
+                        })
+                    })
+                    .and_then(|r| r.get("referenceLocator"))
+                    .and_then(|l| l.as_str())
+                    .map(|s| s.to_string()),
+                component_type: Some("package".to_string()),
+                hashes: collect_hashes(package.get("checksums"), "algorithm", "checksumValue"),
+                licenses: extract_spdx_licenses(package),
+            });
+        }
+    }
+
     components
 }
 
+fn extract_spdx_licenses(element: &Value) -> Vec<String> {
+    let mut licenses: Vec<String> = ["licenseConcluded", "licenseDeclared"]
+        .iter()
+        .filter_map(|key| element.get(*key).and_then(|l| l.as_str()))
+        .filter(|l| *l != "NOASSERTION" && *l != "NONE")
+        .map(license::canonical)
+        .collect();
+    licenses.sort();
+    licenses.dedup();
+    licenses
+}
+
+/// Element spdxId -> canonical expressions of its `hasConcludedLicense` and
+/// `hasDeclaredLicense` relationships, sorted
+fn extract_jsonld_licenses(graph: &[Value]) -> HashMap<String, Vec<String>> {
+    let expressions: HashMap<&str, &str> = graph
+        .iter()
+        .filter(|e| {
+            e.get("type").and_then(|t| t.as_str()) == Some("simplelicensing_LicenseExpression")
+        })
+        .filter_map(|e| {
+            Some((
+                e.get("spdxId")?.as_str()?,
+                e.get("simplelicensing_licenseExpression")?.as_str()?,
+            ))
+        })
+        .collect();
+
+    let mut licenses: HashMap<String, Vec<String>> = HashMap::new();
+    for rel in graph {
+        let rel_type = rel.get("relationshipType").and_then(|t| t.as_str());
+        if !matches!(rel_type, Some("hasConcludedLicense" | "hasDeclaredLicense")) {
+            continue;
+        }
+        let Some(from) = rel.get("from").and_then(|f| f.as_str()) else {
+            continue;
+        };
+        let targets = rel
+            .get("to")
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten();
+        for target in targets.filter_map(|t| t.as_str()) {
+            if let Some(expression) = expressions.get(target) {
+                licenses
+                    .entry(from.to_string())
+                    .or_default()
+                    .push(license::canonical(expression));
+            }
+        }
+    }
+    for expressions in licenses.values_mut() {
+        expressions.sort();
+        expressions.dedup();
+    }
+    licenses
+}
+
+fn extract_spdx_vulnerabilities(value: &Value) -> Vec<VulnerabilityInfo> {
+    let elements = value
+        .get("@graph")
+        .or_else(|| value.get("elements"))
+        .and_then(|e| e.as_array());
+
+    elements
+        .into_iter()
+        .flatten()
+        .filter(|e| {
+            e.get("type")
+                .and_then(|t| t.as_str())
+                .is_some_and(|t| t.contains("Vulnerability"))
+        })
+        .map(|e| VulnerabilityInfo {
+            id: e
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            source: None,
+            affected_component: None,
+        })
+        .collect()
+}
+
 fn extract_spdx_relationships(value: &Value) -> Vec<DependencyInfo> {
     let mut dependencies = Vec::new();
 
@@ -699,8 +914,8 @@ fn extract_spdx_relationships(value: &Value) -> Vec<DependencyInfo> {
                 for target in to {
                     if let Some(target_str) = target.as_str() {
                         dependencies.push(DependencyInfo {
-                            from: from.to_string(),
-                            to: target_str.to_string(),
+                            from: local_spdx_id(from).to_string(),
+                            to: local_spdx_id(target_str).to_string(),
                         });
                     }
                 }
@@ -708,6 +923,22 @@ fn extract_spdx_relationships(value: &Value) -> Vec<DependencyInfo> {
         }
     }
 
+    // SPDX 2.x structure
+    if let Some(relationships) = value.get("relationships").and_then(|r| r.as_array()) {
+        for rel in relationships {
+            if let (Some(from), Some("DEPENDS_ON"), Some(to)) = (
+                rel.get("spdxElementId").and_then(|f| f.as_str()),
+                rel.get("relationshipType").and_then(|r| r.as_str()),
+                rel.get("relatedSpdxElement").and_then(|t| t.as_str()),
+            ) {
+                dependencies.push(DependencyInfo {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                });
+            }
+        }
+    }
+
     dependencies
 }
 
@@ -743,6 +974,35 @@ fn compare_spdx_metadata(value1: &Value, value2: &Value) -> Vec<MetadataChange>
 
 // Helper functions
 
+/// Collects `ALGORITHM:value` digests, normalizing the algorithm spelling
+/// so `SHA-256` (CycloneDX), `SHA256` (SPDX 2) and `sha256` (SPDX 3) match
+fn collect_hashes(hashes: Option<&Value>, alg_key: &str, value_key: &str) -> Vec<String> {
+    let mut collected: Vec<String> = hashes
+        .and_then(|h| h.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|h| {
+            let alg = h.get(alg_key).and_then(|a| a.as_str())?;
+            let value = h.get(value_key).and_then(|v| v.as_str())?;
+            let alg: String = alg
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_ascii_uppercase();
+            Some(format!("{}:{}", alg, value.to_ascii_lowercase()))
+        })
+        .collect();
+    collected.sort();
+    collected.dedup();
+    collected
+}
+
+/// Drops the document namespace from an SPDX 3 JSON-LD IRI
+/// (`https://.../doc#SPDXRef-app` -> `SPDXRef-app`)
+fn local_spdx_id(id: &str) -> &str {
+    id.rsplit_once('#').map_or(id, |(_, local)| local)
+}
+
 fn component_key(comp: &ComponentInfo) -> String {
     if let Some(purl) = &comp.purl {
         purl.clone()
@@ -770,6 +1030,20 @@ fn compare_components(comp1: &ComponentInfo, comp2: &ComponentInfo) -> Vec<Strin
         ));
     }
 
+    for hash in comp1.hashes.iter().filter(|h| !comp2.hashes.contains(h)) {
+        changes.push(format!("hash removed: {}", hash));
+    }
+    for hash in comp2.hashes.iter().filter(|h| !comp1.hashes.contains(h)) {
+        changes.push(format!("hash added: {}", hash));
+    }
+
+    if comp1.licenses != comp2.licenses {
+        changes.push(format!(
+            "licenses: {:?} → {:?}",
+            comp1.licenses, comp2.licenses
+        ));
+    }
+
     changes
 }
 
@@ -845,6 +1119,8 @@ fn component_to_json(comp: &ComponentInfo) -> Value {
         "version": comp.version,
         "purl": comp.purl,
         "type": comp.component_type,
+        "hashes": comp.hashes,
+        "licenses": comp.licenses,
     })
 }
 
@@ -867,6 +1143,8 @@ mod tests {
             version: Some("1.0.0".to_string()),
             purl: Some("pkg:npm/test-lib@1.0.0".to_string()),
             component_type: Some("library".to_string()),
+            hashes: Vec::new(),
+            licenses: Vec::new(),
         };
         assert_eq!(component_key(&comp), "pkg:npm/test-lib@1.0.0");
     }
@@ -878,6 +1156,8 @@ mod tests {
             version: Some("1.0.0".to_string()),
             purl: None,
             component_type: Some("library".to_string()),
+            hashes: Vec::new(),
+            licenses: Vec::new(),
         };
         assert_eq!(component_key(&comp), "test-lib@1.0.0");
     }
@@ -889,7 +1169,78 @@ mod tests {
             version: None,
             purl: None,
             component_type: Some("library".to_string()),
+            hashes: Vec::new(),
+            licenses: Vec::new(),
         };
         assert_eq!(component_key(&comp), "test-lib");
     }
+
+    #[test]
+    fn test_collect_hashes_normalizes_algorithms() {
+        let cdx = json!([{ "alg": "SHA-256", "content": "ABC" }]);
+        let spdx3 = json!([{ "algorithm": "sha256", "hashValue": "abc" }]);
+        assert_eq!(
+            collect_hashes(Some(&cdx), "alg", "content"),
+            collect_hashes(Some(&spdx3), "algorithm", "hashValue")
+        );
+    }
+
+    #[test]
+    fn test_compare_components_hashes_and_licenses() {
+        let comp1 = ComponentInfo {
+            name: "test-lib".to_string(),
+            version: Some("1.0.0".to_string()),
+            purl: None,
+            component_type: Some("library".to_string()),
+            hashes: vec!["SHA256:abc".to_string()],
+            licenses: vec!["MIT".to_string()],
+        };
+        let comp2 = ComponentInfo {
+            hashes: Vec::new(),
+            licenses: Vec::new(),
+            ..comp1.clone()
+        };
+        let changes = compare_components(&comp1, &comp2);
+        assert_eq!(changes[0], "hash removed: SHA256:abc");
+        assert!(changes[1].starts_with("licenses:"));
+    }
+
+    #[test]
+    fn test_extract_licenses_canonical() {
+        let cdx = json!({
+            "licenses": [
+                { "expression": "gpl-2.0+ or mit" },
+                { "license": { "id": "apache-2.0" } },
+                { "license": { "name": "Acme EULA" } }
+            ]
+        });
+        assert_eq!(
+            extract_cdx_licenses(&cdx),
+            vec!["Acme EULA", "Apache-2.0", "GPL-2.0-or-later OR MIT"]
+        );
+
+        let spdx = json!({ "licenseConcluded": "GPL-2.0", "licenseDeclared": "GPL-2.0-only" });
+        assert_eq!(extract_spdx_licenses(&spdx), vec!["GPL-2.0-only"]);
+    }
+
+    #[test]
+    fn test_extract_jsonld_licenses() {
+        let graph = json!({
+            "@graph": [
+                { "type": "software_Package", "spdxId": "pkg", "name": "lib" },
+                {
+                    "type": "simplelicensing_LicenseExpression",
+                    "spdxId": "lic",
+                    "simplelicensing_licenseExpression": "mit"
+                },
+                {
+                    "type": "Relationship",
+                    "from": "pkg",
+                    "relationshipType": "hasDeclaredLicense",
+                    "to": ["lic"]
+                }
+            ]
+        });
+        assert_eq!(extract_spdx_packages(&graph)[0].licenses, vec!["MIT"]);
+    }
 }
@@ -46,6 +46,12 @@ pub enum ConverterError {
 
     #[error("Unsupported Format: {0}")]
     UnsupportedFormat(String),
+
+    #[error("Round-trip Fidelity Error: {0}")]
+    RoundtripLoss(String),
+
+    #[error("License Policy Violation: {0}")]
+    PolicyViolation(String),
 }
 
 // Implement From<io::Error> for easier error handling
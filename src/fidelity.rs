@@ -0,0 +1,229 @@
+//! Conversion fidelity reporting.
+//!
+//! Every conversion records the source fields, element types and
+//! relationship types that have no mapping in the target format. The
+//! report is cheap to clone (like `ProgressTracker`) so the streaming
+//! visitors can each hold a handle to the same collector.
+
+use crate::errors::ConverterError;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Number of example identifiers kept per dropped path
+const MAX_EXAMPLES: usize = 5;
+
+/// Keys that only carry JSON(-LD) structure, never data
+const STRUCTURAL_KEYS: &[&str] = &["@id", "@type", "creationInfo"];
+
+/// A source field (or element/relationship type) that was not converted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedField {
+    /// Where the data lived in the source, e.g. `component.pedigree`
+    pub path: String,
+    /// How many values were dropped
+    pub count: usize,
+    /// Up to five bom-refs / spdxIds of the affected objects
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+/// The JSON document written for `--fidelity-report`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FidelitySummary {
+    pub direction: String,
+    pub input: String,
+    pub output: String,
+    pub total_dropped: usize,
+    pub dropped: Vec<DroppedField>,
+}
+
+/// Collects everything a conversion could not carry across.
+#[derive(Clone, Debug, Default)]
+pub struct FidelityReport {
+    dropped: Arc<Mutex<BTreeMap<String, DroppedField>>>,
+}
+
+impl FidelityReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one dropped value at `path`, keeping `id` as an example.
+    ///
+    /// An empty `id` is counted but not listed (document-level fields).
+    pub fn record(&self, path: &str, id: &str) {
+        let mut dropped = self.dropped.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = dropped
+            .entry(path.to_string())
+            .or_insert_with(|| DroppedField {
+                path: path.to_string(),
+                count: 0,
+                examples: Vec::new(),
+            });
+        entry.count += 1;
+        if !id.is_empty()
+            && entry.examples.len() < MAX_EXAMPLES
+            && !entry.examples.iter().any(|e| e == id)
+        {
+            entry.examples.push(id.to_string());
+        }
+    }
+
+    /// Records `count` dropped values at `path` without examples
+    pub fn record_count(&self, path: &str, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let mut dropped = self.dropped.lock().unwrap_or_else(|e| e.into_inner());
+        dropped
+            .entry(path.to_string())
+            .or_insert_with(|| DroppedField {
+                path: path.to_string(),
+                count: 0,
+                examples: Vec::new(),
+            })
+            .count += count;
+    }
+
+    /// Records each key a reader collected into its catch-all `extra` map
+    pub fn record_unmapped<'a, I>(&self, scope: &str, keys: I, id: &str)
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        for key in keys {
+            if !STRUCTURAL_KEYS.contains(&key.as_str()) {
+                self.record(&format!("{}.{}", scope, key), id);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dropped
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+
+    /// All dropped paths, sorted by path
+    pub fn entries(&self) -> Vec<DroppedField> {
+        self.dropped
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Builds the serializable summary for one conversion run
+    pub fn summary(&self, direction: &str, input: &Path, output: &Path) -> FidelitySummary {
+        let dropped = self.entries();
+        FidelitySummary {
+            direction: direction.to_string(),
+            input: input.display().to_string(),
+            output: output.display().to_string(),
+            total_dropped: dropped.iter().map(|d| d.count).sum(),
+            dropped,
+        }
+    }
+
+    /// Logs one line per dropped path (visible with `--verbose`)
+    pub fn log(&self) {
+        let entries = self.entries();
+        if entries.is_empty() {
+            info!("Conversion fidelity: every source field was mapped");
+            return;
+        }
+        info!(
+            "Conversion fidelity: {} source path(s) had no mapping in the target:",
+            entries.len()
+        );
+        for entry in entries {
+            if entry.examples.is_empty() {
+                info!("  {} ({} dropped)", entry.path, entry.count);
+            } else {
+                info!(
+                    "  {} ({} dropped, e.g. {})",
+                    entry.path,
+                    entry.count,
+                    entry.examples.join(", ")
+                );
+            }
+        }
+    }
+
+    /// Writes the summary as pretty JSON to `path`
+    pub fn write_json(
+        &self,
+        path: &Path,
+        direction: &str,
+        input: &Path,
+        output: &Path,
+    ) -> Result<(), ConverterError> {
+        let summary = self.summary(direction, input, output);
+        let json = serde_json::to_string_pretty(&summary).map_err(|e| {
+            ConverterError::SerializationError(format!("Failed to write fidelity report: {}", e))
+        })?;
+        std::fs::write(path, json)
+            .map_err(|e| ConverterError::Io(e, "Failed to write fidelity report".to_string()))
+    }
+}
+
+/// Default report location next to the output: `out.spdx.json` ->
+/// `out.spdx.fidelity.json`
+pub fn default_report_path(output: &Path) -> PathBuf {
+    output.with_extension("fidelity.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_counts_and_examples() {
+        let report = FidelityReport::new();
+        for i in 0..7 {
+            report.record("component.pedigree", &format!("comp-{}", i));
+        }
+        report.record("component.pedigree", "comp-0");
+        report.record("bom.services", "");
+
+        let entries = report.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "bom.services");
+        assert!(entries[0].examples.is_empty());
+        assert_eq!(entries[1].count, 8);
+        assert_eq!(entries[1].examples.len(), MAX_EXAMPLES);
+    }
+
+    #[test]
+    fn test_record_unmapped_skips_structural_keys() {
+        let report = FidelityReport::new();
+        let keys = vec!["creationInfo".to_string(), "builtTime".to_string()];
+        report.record_unmapped("SpdxPackage", &keys, "SPDXRef-app");
+
+        let entries = report.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "SpdxPackage.builtTime");
+    }
+
+    #[test]
+    fn test_shared_between_clones() {
+        let report = FidelityReport::new();
+        report.clone().record_count("metadata.lifecycles", 2);
+        let summary = report.summary("cdx-to-cdx", Path::new("in.json"), Path::new("out.json"));
+        assert_eq!(summary.total_dropped, 2);
+    }
+
+    #[test]
+    fn test_default_report_path() {
+        assert_eq!(
+            default_report_path(Path::new("out/bom.spdx.json")),
+            PathBuf::from("out/bom.spdx.fidelity.json")
+        );
+    }
+}
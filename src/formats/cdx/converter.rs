@@ -1,6 +1,7 @@
 //! Conversion between CdxDocument (XML-friendly) and JSON format
 
 use super::document::*;
+use crate::models_cdx as model;
 use serde_json::{Value, json};
 
 /// Convert CdxDocument to JSON Value for JSON serialization
@@ -55,6 +56,10 @@ pub fn document_to_json(doc: &CdxDocument) -> Value {
             );
         }
 
+        if let Some(component) = &metadata.component {
+            meta["component"] = component_to_json(component);
+        }
+
         bom["metadata"] = meta;
     }
 
@@ -63,39 +68,432 @@ pub fn document_to_json(doc: &CdxDocument) -> Value {
         let comps = components
             .components
             .iter()
-            .map(|c| {
-                let mut comp = json!({
-                    "type": c.component_type,
-                    "name": c.name,
-                });
-
-                if let Some(bom_ref) = &c.bom_ref {
-                    comp["bom-ref"] = json!(bom_ref);
-                }
-                if let Some(version) = &c.version {
-                    comp["version"] = json!(version);
-                }
-                if let Some(description) = &c.description {
-                    comp["description"] = json!(description);
-                }
-                if let Some(purl) = &c.purl {
-                    comp["purl"] = json!(purl);
-                }
-
-                comp
-            })
+            .map(component_to_json)
             .collect::<Vec<_>>();
 
         bom["components"] = json!(comps);
     }
 
+    // Dependencies
+    if let Some(dependencies) = &doc.dependencies {
+        bom["dependencies"] = json!(
+            dependencies
+                .dependencies
+                .iter()
+                .map(|d| {
+                    let mut dep = json!({ "ref": d.dependency_ref });
+                    if !d.depends_on.is_empty() {
+                        dep["dependsOn"] = json!(
+                            d.depends_on
+                                .iter()
+                                .map(|o| o.dependency_ref.as_str())
+                                .collect::<Vec<_>>()
+                        );
+                    }
+                    dep
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    if let Some(refs) = external_references_from_xml(&doc.external_references) {
+        bom["externalReferences"] = json!(refs);
+    }
+
+    // Vulnerabilities
+    if let Some(vulnerabilities) = &doc.vulnerabilities {
+        bom["vulnerabilities"] = json!(
+            vulnerabilities
+                .vulnerabilities
+                .iter()
+                .map(|v| {
+                    let mut vulnerability = serde_json::to_value(model::CdxVulnerability::from(v))
+                        .unwrap_or(Value::Null);
+                    if let Some(bom_ref) = &v.bom_ref {
+                        vulnerability["bom-ref"] = json!(bom_ref);
+                    }
+                    vulnerability
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
     bom
 }
 
+/// Convert an XML component to its JSON form via the shared component model
+fn component_to_json(component: &CdxComponent) -> Value {
+    serde_json::to_value(model::CdxComponent::from(component)).unwrap_or(Value::Null)
+}
+
+/// Convert a JSON component to its XML form via the shared component model
+fn json_to_component(value: &Value) -> Option<CdxComponent> {
+    let component: model::CdxComponent = serde_json::from_value(value.clone()).ok()?;
+    Some(CdxComponent::from(&component))
+}
+
+fn hashes_to_xml(hashes: &Option<Vec<model::CdxHash>>) -> Option<CdxHashes> {
+    hashes.as_ref().map(|hashes| CdxHashes {
+        hashes: hashes
+            .iter()
+            .map(|h| CdxHash {
+                alg: h.alg.clone(),
+                content: h.content.clone(),
+            })
+            .collect(),
+    })
+}
+
+fn hashes_from_xml(hashes: &Option<CdxHashes>) -> Option<Vec<model::CdxHash>> {
+    hashes.as_ref().map(|hashes| {
+        hashes
+            .hashes
+            .iter()
+            .map(|h| model::CdxHash {
+                alg: h.alg.clone(),
+                content: h.content.clone(),
+            })
+            .collect()
+    })
+}
+
+fn external_references_to_xml(
+    refs: &Option<Vec<model::CdxExternalReference>>,
+) -> Option<CdxExternalReferences> {
+    refs.as_ref().map(|refs| CdxExternalReferences {
+        references: refs
+            .iter()
+            .map(|r| CdxExternalReference {
+                ref_type: r.ref_type.clone(),
+                url: r.url.clone(),
+                comment: r.comment.clone(),
+                hashes: hashes_to_xml(&r.hashes),
+            })
+            .collect(),
+    })
+}
+
+fn external_references_from_xml(
+    refs: &Option<CdxExternalReferences>,
+) -> Option<Vec<model::CdxExternalReference>> {
+    refs.as_ref().map(|refs| {
+        refs.references
+            .iter()
+            .map(|r| model::CdxExternalReference {
+                ref_type: r.ref_type.clone(),
+                url: r.url.clone(),
+                comment: r.comment.clone(),
+                hashes: hashes_from_xml(&r.hashes),
+            })
+            .collect()
+    })
+}
+
+fn properties_to_xml(properties: &Option<Vec<model::CdxProperty>>) -> Option<CdxProperties> {
+    properties.as_ref().map(|props| CdxProperties {
+        properties: props
+            .iter()
+            .map(|p| CdxProperty {
+                name: p.name.clone(),
+                value: p.value.clone(),
+            })
+            .collect(),
+    })
+}
+
+fn properties_from_xml(properties: &Option<CdxProperties>) -> Option<Vec<model::CdxProperty>> {
+    properties.as_ref().map(|props| {
+        props
+            .properties
+            .iter()
+            .map(|p| model::CdxProperty {
+                name: p.name.clone(),
+                value: p.value.clone(),
+            })
+            .collect()
+    })
+}
+
+impl From<&model::CdxComponent> for CdxComponent {
+    fn from(c: &model::CdxComponent) -> Self {
+        // The XML schema has a single expression or a list of licenses
+        let licenses = c.licenses.as_ref().map(|choices| CdxLicenses {
+            licenses: choices
+                .iter()
+                .filter_map(|choice| choice.license.as_ref())
+                .map(|l| CdxLicense {
+                    id: l.id.clone(),
+                    name: l.name.clone(),
+                    url: l.url.clone(),
+                })
+                .collect(),
+            expression: choices.iter().find_map(|choice| choice.expression.clone()),
+        });
+        let author = c.author.clone().or_else(|| {
+            let names = c.author_names();
+            (!names.is_empty()).then(|| names.join(", "))
+        });
+
+        Self {
+            component_type: c.component_type.clone(),
+            mime_type: c.mime_type.clone(),
+            bom_ref: (!c.bom_ref.is_empty()).then(|| c.bom_ref.clone()),
+            supplier: c.supplier.as_ref().map(|s| CdxOrganizationalEntity {
+                name: s.name.clone(),
+                url: s.url.clone(),
+                contact: s
+                    .contact
+                    .iter()
+                    .map(|contact| CdxOrganizationalContact {
+                        name: contact.name.clone(),
+                        email: contact.email.clone(),
+                        phone: contact.phone.clone(),
+                    })
+                    .collect(),
+            }),
+            author,
+            publisher: c.publisher.clone(),
+            group: c.group.clone(),
+            name: c.name.clone(),
+            version: c.version.clone(),
+            description: c.description.clone(),
+            scope: c.scope.clone(),
+            hashes: hashes_to_xml(&c.hashes),
+            licenses,
+            copyright: c.copyright.clone(),
+            cpe: c.cpe.clone(),
+            purl: c.purl.clone(),
+            swid: c.swid.as_ref().map(|swid| CdxSwid {
+                tag_id: swid.tag_id.clone(),
+                name: swid.name.clone(),
+                version: swid.version.clone(),
+                tag_version: swid.tag_version,
+                patch: swid.patch,
+                url: swid.url.clone(),
+            }),
+            external_references: external_references_to_xml(&c.external_references),
+            properties: properties_to_xml(&c.properties),
+            components: c.components.as_ref().map(|children| CdxComponents {
+                components: children.iter().map(CdxComponent::from).collect(),
+            }),
+        }
+    }
+}
+
+impl From<&CdxComponent> for model::CdxComponent {
+    fn from(c: &CdxComponent) -> Self {
+        let licenses = c.licenses.as_ref().map(|licenses| {
+            let mut choices: Vec<model::CdxLicenseChoice> = licenses
+                .licenses
+                .iter()
+                .map(|l| model::CdxLicenseChoice {
+                    license: Some(model::CdxLicense {
+                        id: l.id.clone(),
+                        name: l.name.clone(),
+                        url: l.url.clone(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .collect();
+            if let Some(expression) = &licenses.expression {
+                choices.push(model::CdxLicenseChoice {
+                    expression: Some(expression.clone()),
+                    ..Default::default()
+                });
+            }
+            choices
+        });
+
+        Self {
+            component_type: c.component_type.clone(),
+            mime_type: c.mime_type.clone(),
+            bom_ref: c.bom_ref.clone().unwrap_or_default(),
+            supplier: c.supplier.as_ref().map(|s| model::CdxOrganizationalEntity {
+                name: s.name.clone(),
+                url: s.url.clone(),
+                contact: s
+                    .contact
+                    .iter()
+                    .map(|contact| model::CdxOrganizationalContact {
+                        name: contact.name.clone(),
+                        email: contact.email.clone(),
+                        phone: contact.phone.clone(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            author: c.author.clone(),
+            publisher: c.publisher.clone(),
+            group: c.group.clone(),
+            name: c.name.clone(),
+            version: c.version.clone(),
+            description: c.description.clone(),
+            scope: c.scope.clone(),
+            hashes: hashes_from_xml(&c.hashes),
+            licenses,
+            copyright: c.copyright.clone(),
+            cpe: c.cpe.clone(),
+            purl: c.purl.clone(),
+            swid: c.swid.as_ref().map(|swid| model::CdxSwid {
+                tag_id: swid.tag_id.clone(),
+                name: swid.name.clone(),
+                version: swid.version.clone(),
+                tag_version: swid.tag_version,
+                patch: swid.patch,
+                url: swid.url.clone(),
+                ..Default::default()
+            }),
+            external_references: external_references_from_xml(&c.external_references),
+            properties: properties_from_xml(&c.properties),
+            components: c
+                .components
+                .as_ref()
+                .map(|children| children.components.iter().map(Self::from).collect()),
+            ..Default::default()
+        }
+    }
+}
+
+fn vuln_source_to_xml(source: &model::CdxVulnSource) -> CdxVulnSource {
+    CdxVulnSource {
+        name: Some(source.name.clone()),
+        url: source.url.clone(),
+    }
+}
+
+fn vuln_source_from_xml(source: &CdxVulnSource) -> model::CdxVulnSource {
+    model::CdxVulnSource {
+        name: source.name.clone().unwrap_or_default(),
+        url: source.url.clone(),
+    }
+}
+
+impl From<&model::CdxVulnerability> for CdxVulnerability {
+    fn from(v: &model::CdxVulnerability) -> Self {
+        Self {
+            bom_ref: None,
+            id: Some(v.id.clone()),
+            source: v.source.as_ref().map(vuln_source_to_xml),
+            ratings: v.ratings.as_ref().map(|ratings| CdxRatings {
+                ratings: ratings
+                    .iter()
+                    .map(|r| CdxRating {
+                        source: r.source.as_ref().map(vuln_source_to_xml),
+                        score: r.score,
+                        severity: r.severity.clone(),
+                        method: r.method.clone(),
+                        vector: r.vector.clone(),
+                        justification: r.justification.clone(),
+                    })
+                    .collect(),
+            }),
+            cwes: v.cwes.as_ref().map(|cwes| CdxCwes { cwes: cwes.clone() }),
+            description: v.description.clone(),
+            analysis: v.analysis.as_ref().map(|a| CdxAnalysis {
+                state: a.state.clone(),
+                justification: a.justification.clone(),
+                responses: (!a.response.is_empty()).then(|| CdxResponses {
+                    responses: a.response.clone(),
+                }),
+                detail: a.detail.clone(),
+                first_issued: a.first_issued.clone(),
+                last_updated: a.last_updated.clone(),
+            }),
+            affects: v.affects.as_ref().map(|affects| CdxAffects {
+                targets: affects
+                    .iter()
+                    .map(|target| CdxAffectsTarget {
+                        target_ref: target.bom_ref.clone(),
+                        versions: target
+                            .versions
+                            .as_ref()
+                            .map(|versions| CdxAffectedVersions {
+                                versions: versions
+                                    .iter()
+                                    .map(|version| CdxAffectedVersion {
+                                        version: version.version.clone(),
+                                        range: version.range.clone(),
+                                        status: version.status.clone(),
+                                    })
+                                    .collect(),
+                            }),
+                    })
+                    .collect(),
+            }),
+            properties: properties_to_xml(&v.properties),
+        }
+    }
+}
+
+impl From<&CdxVulnerability> for model::CdxVulnerability {
+    fn from(v: &CdxVulnerability) -> Self {
+        Self {
+            id: v.id.clone().unwrap_or_default(),
+            source: v.source.as_ref().map(vuln_source_from_xml),
+            description: v.description.clone(),
+            ratings: v.ratings.as_ref().map(|ratings| {
+                ratings
+                    .ratings
+                    .iter()
+                    .map(|r| model::CdxRating {
+                        source: r.source.as_ref().map(vuln_source_from_xml),
+                        score: r.score,
+                        severity: r.severity.clone(),
+                        method: r.method.clone(),
+                        vector: r.vector.clone(),
+                        justification: r.justification.clone(),
+                    })
+                    .collect()
+            }),
+            cwes: v.cwes.as_ref().map(|cwes| cwes.cwes.clone()),
+            analysis: v.analysis.as_ref().map(|a| model::CdxAnalysis {
+                state: a.state.clone(),
+                justification: a.justification.clone(),
+                response: a
+                    .responses
+                    .as_ref()
+                    .map(|r| r.responses.clone())
+                    .unwrap_or_default(),
+                detail: a.detail.clone(),
+                first_issued: a.first_issued.clone(),
+                last_updated: a.last_updated.clone(),
+            }),
+            affects: v.affects.as_ref().map(|affects| {
+                affects
+                    .targets
+                    .iter()
+                    .map(|target| model::CdxAffects {
+                        bom_ref: target.target_ref.clone(),
+                        versions: target.versions.as_ref().map(|versions| {
+                            versions
+                                .versions
+                                .iter()
+                                .map(|version| model::CdxAffectedVersion {
+                                    version: version.version.clone(),
+                                    range: version.range.clone(),
+                                    status: version.status.clone(),
+                                })
+                                .collect()
+                        }),
+                    })
+                    .collect()
+            }),
+            properties: properties_from_xml(&v.properties),
+            extra: Default::default(),
+        }
+    }
+}
+
 /// Convert JSON Value (standard CDX JSON) to CdxDocument for XML serialization
 pub fn json_to_document(value: &Value) -> Result<CdxDocument, String> {
+    let spec_version = value
+        .get("specVersion")
+        .and_then(|v| v.as_str())
+        .unwrap_or("1.6");
     let mut doc = CdxDocument {
-        xmlns: Some("http://cyclonedx.org/schema/bom/1.6".to_string()),
+        xmlns: Some(format!("http://cyclonedx.org/schema/bom/{}", spec_version)),
         version: value.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32,
         ..Default::default()
     };
@@ -149,6 +547,11 @@ pub fn json_to_document(value: &Value) -> Result<CdxDocument, String> {
             }
         }
 
+        meta.component = metadata
+            .get("component")
+            .and_then(json_to_component)
+            .map(Box::new);
+
         doc.metadata = Some(meta);
     }
 
@@ -156,33 +559,7 @@ pub fn json_to_document(value: &Value) -> Result<CdxDocument, String> {
     if let Some(components_array) = value.get("components").and_then(|v| v.as_array()) {
         let components: Vec<CdxComponent> = components_array
             .iter()
-            .filter_map(|c| {
-                let component_type = c.get("type").and_then(|v| v.as_str())?.to_string();
-                let name = c.get("name").and_then(|v| v.as_str())?.to_string();
-
-                Some(CdxComponent {
-                    component_type,
-                    bom_ref: c
-                        .get("bom-ref")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string()),
-                    name,
-                    version: c
-                        .get("version")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string()),
-                    description: c
-                        .get("description")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string()),
-                    purl: c
-                        .get("purl")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string()),
-                    hashes: None,   // TODO: Handle hashes if needed
-                    licenses: None, // TODO: Handle licenses if needed
-                })
-            })
+            .filter_map(json_to_component)
             .collect();
 
         if !components.is_empty() {
@@ -223,6 +600,35 @@ pub fn json_to_document(value: &Value) -> Result<CdxDocument, String> {
         }
     }
 
+    // External references of the BOM itself
+    if let Some(refs) = value.get("externalReferences") {
+        let refs: Option<Vec<model::CdxExternalReference>> =
+            serde_json::from_value(refs.clone()).ok();
+        doc.external_references = external_references_to_xml(&refs);
+    }
+
+    // Vulnerabilities
+    if let Some(vulns_array) = value.get("vulnerabilities").and_then(|v| v.as_array()) {
+        let vulnerabilities: Vec<CdxVulnerability> = vulns_array
+            .iter()
+            .filter_map(|v| {
+                let vulnerability: model::CdxVulnerability =
+                    serde_json::from_value(v.clone()).ok()?;
+                Some(CdxVulnerability {
+                    bom_ref: v
+                        .get("bom-ref")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                    ..CdxVulnerability::from(&vulnerability)
+                })
+            })
+            .collect();
+
+        if !vulnerabilities.is_empty() {
+            doc.vulnerabilities = Some(CdxVulnerabilities { vulnerabilities });
+        }
+    }
+
     Ok(doc)
 }
 
@@ -265,10 +671,53 @@ mod tests {
         assert_eq!(doc.version, 1);
         assert_eq!(doc.spec_version, Some("1.6".to_string()));
         assert_eq!(doc.serial_number, Some("urn:uuid:test-123".to_string()));
+        assert_eq!(
+            doc.xmlns,
+            Some("http://cyclonedx.org/schema/bom/1.6".to_string())
+        );
         assert!(doc.components.is_some());
 
         let components = doc.components.unwrap();
         assert_eq!(components.components.len(), 1);
         assert_eq!(components.components[0].name, "example");
     }
+
+    #[test]
+    fn test_vulnerability_xml_roundtrip() {
+        let json = json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "externalReferences": [
+                { "type": "bom", "url": "urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/1" }
+            ],
+            "vulnerabilities": [
+                {
+                    "bom-ref": "vuln-1",
+                    "id": "CVE-2021-44228",
+                    "source": { "name": "NVD" },
+                    "ratings": [{ "score": 10.0, "severity": "critical", "method": "CVSSv31" }],
+                    "cwes": [502],
+                    "analysis": {
+                        "state": "not_affected",
+                        "justification": "code_not_reachable",
+                        "response": ["will_not_fix", "update"]
+                    },
+                    "affects": [
+                        {
+                            "ref": "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79#log4j",
+                            "versions": [{ "version": "2.14.1", "status": "affected" }]
+                        }
+                    ]
+                }
+            ]
+        });
+
+        let mut xml = Vec::new();
+        crate::formats::cdx::xml::write(&mut xml, &json_to_document(&json).unwrap()).unwrap();
+        let doc = crate::formats::cdx::xml::parse(xml.as_slice()).unwrap();
+        let roundtrip = document_to_json(&doc);
+        assert_eq!(roundtrip["externalReferences"], json["externalReferences"]);
+        assert_eq!(roundtrip["vulnerabilities"], json["vulnerabilities"]);
+    }
 }
@@ -42,6 +42,10 @@ pub struct CdxDocument {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<CdxComponents>,
 
+    /// External references of the BOM itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_references: Option<CdxExternalReferences>,
+
     /// Dependencies
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<CdxDependencies>,
@@ -87,15 +91,31 @@ pub struct CdxComponents {
     pub components: Vec<CdxComponent>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Component element. Fields are declared in the order the XSD requires.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxComponent {
     #[serde(rename = "@type")]
     pub component_type: String,
 
+    #[serde(rename = "@mime-type", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+
     #[serde(rename = "@bom-ref", skip_serializing_if = "Option::is_none")]
     pub bom_ref: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<CdxOrganizationalEntity>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
     pub name: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -105,13 +125,59 @@ pub struct CdxComponent {
     pub description: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub purl: Option<String>,
+    pub scope: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hashes: Option<CdxHashes>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub licenses: Option<CdxLicenses>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swid: Option<CdxSwid>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_references: Option<CdxExternalReferences>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<CdxProperties>,
+
+    /// Nested components
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<CdxComponents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CdxOrganizationalEntity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub url: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contact: Vec<CdxOrganizationalContact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CdxOrganizationalContact {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,19 +195,83 @@ pub struct CdxHash {
     pub content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Either a list of licenses or a single SPDX expression
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CdxLicenses {
-    #[serde(rename = "license", default)]
+    #[serde(rename = "license", default, skip_serializing_if = "Vec::is_empty")]
     pub licenses: Vec<CdxLicense>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CdxLicense {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxSwid {
+    #[serde(rename = "@tagId")]
+    pub tag_id: String,
+
+    #[serde(rename = "@name")]
+    pub name: String,
+
+    #[serde(rename = "@version", skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(rename = "@tagVersion", skip_serializing_if = "Option::is_none")]
+    pub tag_version: Option<i64>,
+
+    #[serde(rename = "@patch", skip_serializing_if = "Option::is_none")]
+    pub patch: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxExternalReferences {
+    #[serde(rename = "reference", default)]
+    pub references: Vec<CdxExternalReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxExternalReference {
+    #[serde(rename = "@type")]
+    pub ref_type: String,
+
+    pub url: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashes: Option<CdxHashes>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxProperties {
+    #[serde(rename = "property", default)]
+    pub properties: Vec<CdxProperty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxProperty {
+    #[serde(rename = "@name")]
+    pub name: String,
+
+    #[serde(rename = "$text", default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,7 +302,8 @@ pub struct CdxVulnerabilities {
     pub vulnerabilities: Vec<CdxVulnerability>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Vulnerability element. Fields are declared in the order the XSD requires.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxVulnerability {
     #[serde(rename = "@bom-ref", skip_serializing_if = "Option::is_none")]
@@ -181,8 +312,129 @@ pub struct CdxVulnerability {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<CdxVulnSource>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratings: Option<CdxRatings>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwes: Option<CdxCwes>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analysis: Option<CdxAnalysis>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affects: Option<CdxAffects>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<CdxProperties>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxVulnSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxRatings {
+    #[serde(rename = "rating", default)]
+    pub ratings: Vec<CdxRating>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CdxRating {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<CdxVulnSource>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxCwes {
+    #[serde(rename = "cwe", default)]
+    pub cwes: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxAnalysis {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub responses: Option<CdxResponses>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_issued: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxResponses {
+    #[serde(rename = "response", default)]
+    pub responses: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxAffects {
+    #[serde(rename = "target", default)]
+    pub targets: Vec<CdxAffectsTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxAffectsTarget {
+    #[serde(rename = "ref")]
+    pub target_ref: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versions: Option<CdxAffectedVersions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxAffectedVersions {
+    #[serde(rename = "version", default)]
+    pub versions: Vec<CdxAffectedVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdxAffectedVersion {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
 }
 
 #[cfg(test)]
@@ -1,12 +1,13 @@
 //! Format detection and definition module.
 //!
 //! This module provides types and utilities for detecting and handling
-//! different SBOM file formats (JSON, XML, etc.).
+//! different SBOM file formats (JSON, XML, SPDX tag-value).
 
 pub mod cdx;
 pub mod spdx;
 
 use crate::errors::ConverterError;
+use std::io::BufReader;
 use std::path::Path;
 
 /// Supported SBOM formats
@@ -16,6 +17,8 @@ pub enum Format {
     Json,
     /// XML format
     Xml,
+    /// SPDX 2.x tag-value format (`.spdx`)
+    TagValue,
 }
 
 impl Format {
@@ -31,8 +34,9 @@ impl Format {
         match extension.to_lowercase().as_str() {
             "json" => Ok(Format::Json),
             "xml" => Ok(Format::Xml),
+            "spdx" => Ok(Format::TagValue),
             ext => Err(ConverterError::InvalidInput(format!(
-                "Unsupported file format: .{}. Supported formats: .json, .xml",
+                "Unsupported file format: .{}. Supported formats: .json, .xml, .spdx",
                 ext
             ))),
         }
@@ -56,8 +60,9 @@ impl Format {
         match trimmed[0] {
             b'{' | b'[' => Ok(Format::Json),
             b'<' => Ok(Format::Xml),
+            _ if looks_like_tag_value(&trimmed) => Ok(Format::TagValue),
             _ => Err(ConverterError::InvalidInput(
-                "Could not detect format from content. Expected JSON (starts with '{' or '[') or XML (starts with '<')".to_string()
+                "Could not detect format from content. Expected JSON (starts with '{' or '['), XML (starts with '<') or SPDX tag-value (`Tag: value` lines)".to_string()
             )),
         }
     }
@@ -67,6 +72,7 @@ impl Format {
         match self {
             Format::Json => "json",
             Format::Xml => "xml",
+            Format::TagValue => "spdx",
         }
     }
 
@@ -75,10 +81,45 @@ impl Format {
         match self {
             Format::Json => "application/json",
             Format::Xml => "application/xml",
+            Format::TagValue => "text/spdx",
         }
     }
 }
 
+/// True if the first non-comment line is a `Tag: value` pair
+fn looks_like_tag_value(content: &[u8]) -> bool {
+    String::from_utf8_lossy(content)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.split_once(':'))
+        .is_some_and(|(tag, _)| !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Reads an SBOM file into its JSON representation.
+///
+/// JSON is parsed as-is, CycloneDX XML and SPDX tag-value are converted to
+/// their JSON layouts. The format comes from the file extension, falling
+/// back to sniffing the content.
+pub fn read_json_value(path: &Path) -> Result<serde_json::Value, ConverterError> {
+    let content = std::fs::read(path)
+        .map_err(|e| ConverterError::Io(e, format!("Failed to read file: {}", path.display())))?;
+    let format = Format::from_extension(path)
+        .or_else(|_| Format::from_content(&content))
+        .unwrap_or(Format::Json);
+
+    match format {
+        Format::Json => serde_json::from_slice(&content).map_err(|e| {
+            ConverterError::ParseError(format!("Invalid JSON in {}: {}", path.display(), e))
+        }),
+        Format::Xml => {
+            let doc = cdx::xml::parse(BufReader::new(content.as_slice()))?;
+            Ok(cdx::converter::document_to_json(&doc))
+        }
+        Format::TagValue => spdx::tagvalue::parse(content.as_slice()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,6 +139,14 @@ mod tests {
             Format::from_extension(&PathBuf::from("TEST.JSON")).unwrap(),
             Format::Json
         );
+        assert_eq!(
+            Format::from_extension(&PathBuf::from("image.spdx")).unwrap(),
+            Format::TagValue
+        );
+        assert_eq!(
+            Format::from_extension(&PathBuf::from("image.spdx.json")).unwrap(),
+            Format::Json
+        );
         assert!(Format::from_extension(&PathBuf::from("test.txt")).is_err());
         assert!(Format::from_extension(&PathBuf::from("test")).is_err());
     }
@@ -118,6 +167,14 @@ mod tests {
             Format::from_content(b"  \n  {\"test\": true}").unwrap(),
             Format::Json
         );
+        assert_eq!(
+            Format::from_content(b"SPDXVersion: SPDX-2.3\nDataLicense: CC0-1.0").unwrap(),
+            Format::TagValue
+        );
+        assert_eq!(
+            Format::from_content(b"## Document Information\nSPDXVersion: SPDX-2.3").unwrap(),
+            Format::TagValue
+        );
         assert!(Format::from_content(b"").is_err());
         assert!(Format::from_content(b"invalid").is_err());
     }
@@ -126,11 +183,13 @@ mod tests {
     fn test_extension_method() {
         assert_eq!(Format::Json.extension(), "json");
         assert_eq!(Format::Xml.extension(), "xml");
+        assert_eq!(Format::TagValue.extension(), "spdx");
     }
 
     #[test]
     fn test_mime_type() {
         assert_eq!(Format::Json.mime_type(), "application/json");
         assert_eq!(Format::Xml.mime_type(), "application/xml");
+        assert_eq!(Format::TagValue.mime_type(), "text/spdx");
     }
 }
@@ -1,4 +1,62 @@
-//! SPDX format handlers (JSON and XML)
+//! SPDX format handlers (JSON, tag-value and XML)
 
 pub mod json;
+pub mod tagvalue;
 pub mod xml;
+
+use std::str::FromStr;
+
+/// JSON layout produced when writing SPDX 3.x
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpdxSerialization {
+    /// Flat `elements` + `relationships` layout (legacy, not consumable by SPDX 3 tools)
+    #[default]
+    Simple,
+    /// Conformant SPDX 3.0.1 JSON-LD (`@context` + `@graph`)
+    JsonLd,
+}
+
+impl SpdxSerialization {
+    /// Get the CLI name (e.g., "json-ld")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpdxSerialization::Simple => "simple",
+            SpdxSerialization::JsonLd => "json-ld",
+        }
+    }
+}
+
+impl FromStr for SpdxSerialization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simple" => Ok(SpdxSerialization::Simple),
+            "json-ld" | "jsonld" => Ok(SpdxSerialization::JsonLd),
+            _ => Err(format!("Invalid SPDX serialization: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialization_from_str() {
+        assert_eq!(
+            SpdxSerialization::from_str("json-ld"),
+            Ok(SpdxSerialization::JsonLd)
+        );
+        assert_eq!(
+            SpdxSerialization::from_str("JSONLD"),
+            Ok(SpdxSerialization::JsonLd)
+        );
+        assert_eq!(
+            SpdxSerialization::from_str("simple"),
+            Ok(SpdxSerialization::Simple)
+        );
+        assert!(SpdxSerialization::from_str("rdf").is_err());
+        assert_eq!(SpdxSerialization::default(), SpdxSerialization::Simple);
+    }
+}
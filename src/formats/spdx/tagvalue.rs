@@ -0,0 +1,1012 @@
+//! SPDX 2.x tag-value format handler
+//!
+//! Tag-value (`.spdx`) documents are a sequence of `Tag: value` lines, with
+//! free-form text wrapped in `<text>...</text>` that may span several lines.
+//! Sections are implicit: `PackageName` starts a package, `FileName` starts
+//! a file and so on, and every file listed after a package belongs to it.
+//!
+//! Documents are read into, and written from, the SPDX 2.3 JSON layout so
+//! the converters, `merge` and `diff` only ever deal with JSON. Unlike the
+//! JSON readers this is not streamed: the whole document is held in memory.
+
+use crate::errors::ConverterError;
+use log::warn;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Lines, Write};
+
+/// One `Tag: value` pair and the line it starts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagValuePair {
+    pub tag: String,
+    pub value: String,
+    pub line: usize,
+}
+
+/// Streaming, line-based lexer over a tag-value document.
+///
+/// Blank lines and `#` comments are skipped; `<text>` blocks are joined
+/// into a single value.
+pub struct TagValueReader<R: BufRead> {
+    lines: Lines<R>,
+    line_number: usize,
+}
+
+impl<R: BufRead> TagValueReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line_number: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> Option<Result<String, ConverterError>> {
+        let line = self.lines.next()?;
+        self.line_number += 1;
+        Some(line.map_err(|e| ConverterError::Io(e, "Failed to read tag-value line".to_string())))
+    }
+
+    fn next_pair(&mut self) -> Result<Option<TagValuePair>, ConverterError> {
+        while let Some(line) = self.next_line() {
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let start = self.line_number;
+            let (tag, value) = trimmed.split_once(':').ok_or_else(|| {
+                ConverterError::ParseError(format!(
+                    "Invalid tag-value line {}: expected 'Tag: value', found '{}'",
+                    start, trimmed
+                ))
+            })?;
+            let value = value.trim();
+            let value = match value.strip_prefix("<text>") {
+                Some(rest) => self.read_text(rest, start)?,
+                None => value.to_string(),
+            };
+
+            return Ok(Some(TagValuePair {
+                tag: tag.trim().to_string(),
+                value,
+                line: start,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Collects a `<text>` block, starting with the rest of its first line
+    fn read_text(&mut self, first: &str, start: usize) -> Result<String, ConverterError> {
+        if let Some(end) = first.find("</text>") {
+            return Ok(first[..end].to_string());
+        }
+
+        let mut text = first.to_string();
+        while let Some(line) = self.next_line() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            text.push('\n');
+            if let Some(end) = line.find("</text>") {
+                text.push_str(&line[..end]);
+                return Ok(text.trim_start_matches('\n').to_string());
+            }
+            text.push_str(line);
+        }
+
+        Err(ConverterError::ParseError(format!(
+            "Unterminated <text> block starting at tag-value line {}",
+            start
+        )))
+    }
+}
+
+impl<R: BufRead> Iterator for TagValueReader<R> {
+    type Item = Result<TagValuePair, ConverterError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_pair().transpose()
+    }
+}
+
+// =========================================================================
+// Reading
+// =========================================================================
+
+/// The section the most recent section-starting tag opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Document,
+    Package,
+    File,
+    Snippet,
+    License,
+}
+
+/// Accumulates tag-value pairs into the SPDX 2.3 JSON layout
+#[derive(Default)]
+struct DocumentBuilder {
+    doc: Map<String, Value>,
+    creation_info: Map<String, Value>,
+    packages: Vec<Map<String, Value>>,
+    files: Vec<Map<String, Value>>,
+    /// Index of the package each file was listed under
+    file_owners: Vec<Option<usize>>,
+    snippets: Vec<Map<String, Value>>,
+    licenses: Vec<Map<String, Value>>,
+    relationships: Vec<Map<String, Value>>,
+    /// Annotations and the `SPDXREF` they apply to
+    annotations: Vec<(Option<String>, Map<String, Value>)>,
+    section: Option<Section>,
+    unknown_tags: HashSet<String>,
+}
+
+impl DocumentBuilder {
+    fn current(&mut self) -> Option<&mut Map<String, Value>> {
+        match self.section.unwrap_or(Section::Document) {
+            Section::Document => Some(&mut self.doc),
+            Section::Package => self.packages.last_mut(),
+            Section::File => self.files.last_mut(),
+            Section::Snippet => self.snippets.last_mut(),
+            Section::License => self.licenses.last_mut(),
+        }
+    }
+
+    fn set_current(&mut self, key: &str, value: Value) {
+        if let Some(obj) = self.current() {
+            obj.insert(key.to_string(), value);
+        }
+    }
+
+    fn push_current(&mut self, key: &str, value: Value) {
+        if let Some(obj) = self.current() {
+            push(obj, key, value);
+        }
+    }
+
+    fn add(&mut self, pair: TagValuePair) -> Result<(), ConverterError> {
+        let TagValuePair { tag, value, line } = pair;
+        let text = Value::String(value.clone());
+
+        match tag.as_str() {
+            // --- Document creation information ---
+            "SPDXVersion" => {
+                self.doc.insert("spdxVersion".to_string(), text);
+            }
+            "DataLicense" => {
+                self.doc.insert("dataLicense".to_string(), text);
+            }
+            "DocumentName" => {
+                self.doc.insert("name".to_string(), text);
+            }
+            "DocumentNamespace" => {
+                self.doc.insert("documentNamespace".to_string(), text);
+            }
+            "DocumentComment" => {
+                self.doc.insert("comment".to_string(), text);
+            }
+            "ExternalDocumentRef" => {
+                let mut parts = value.splitn(3, char::is_whitespace);
+                let (Some(id), Some(uri), Some(checksum)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid_value(&tag, &value, line));
+                };
+                let mut ext_ref = Map::new();
+                ext_ref.insert("externalDocumentId".to_string(), id.into());
+                ext_ref.insert("spdxDocument".to_string(), uri.into());
+                ext_ref.insert(
+                    "checksum".to_string(),
+                    parse_checksum(checksum).ok_or_else(|| invalid_value(&tag, &value, line))?,
+                );
+                push(
+                    &mut self.doc,
+                    "externalDocumentRefs",
+                    Value::Object(ext_ref),
+                );
+            }
+            "LicenseListVersion" => {
+                self.creation_info
+                    .insert("licenseListVersion".to_string(), text);
+            }
+            "Creator" => push(&mut self.creation_info, "creators", text),
+            "Created" => {
+                self.creation_info.insert("created".to_string(), text);
+            }
+            "CreatorComment" => {
+                self.creation_info.insert("comment".to_string(), text);
+            }
+
+            // --- Section starts ---
+            "SPDXID" => self.set_current("SPDXID", text),
+            "PackageName" => {
+                self.section = Some(Section::Package);
+                self.packages.push(object_with("name", text));
+            }
+            "FileName" => {
+                self.section = Some(Section::File);
+                self.files.push(object_with("fileName", text));
+                self.file_owners.push(self.packages.len().checked_sub(1));
+            }
+            "SnippetSPDXID" => {
+                self.section = Some(Section::Snippet);
+                self.snippets.push(object_with("SPDXID", text));
+            }
+            "LicenseID" => {
+                self.section = Some(Section::License);
+                self.licenses.push(object_with("licenseId", text));
+            }
+
+            // --- Package information ---
+            "PackageVersion" => self.set_current("versionInfo", text),
+            "PackageFileName" => self.set_current("packageFileName", text),
+            "PackageSupplier" => self.set_current("supplier", text),
+            "PackageOriginator" => self.set_current("originator", text),
+            "PackageDownloadLocation" => self.set_current("downloadLocation", text),
+            "FilesAnalyzed" => {
+                let analyzed = value.eq_ignore_ascii_case("true");
+                self.set_current("filesAnalyzed", Value::Bool(analyzed));
+            }
+            "PackageVerificationCode" => {
+                let (code, excludes) = match value.split_once("(excludes:") {
+                    Some((code, rest)) => (code.trim(), Some(rest.trim_end_matches(')'))),
+                    None => (value.as_str(), None),
+                };
+                let mut verification = Map::new();
+                verification.insert("packageVerificationCodeValue".to_string(), code.into());
+                if let Some(excludes) = excludes {
+                    let files: Vec<Value> = excludes
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|f| !f.is_empty())
+                        .map(Value::from)
+                        .collect();
+                    verification.insert(
+                        "packageVerificationCodeExcludedFiles".to_string(),
+                        Value::Array(files),
+                    );
+                }
+                self.set_current("packageVerificationCode", Value::Object(verification));
+            }
+            "PackageChecksum" | "FileChecksum" => {
+                let checksum =
+                    parse_checksum(&value).ok_or_else(|| invalid_value(&tag, &value, line))?;
+                self.push_current("checksums", checksum);
+            }
+            "PackageHomePage" => self.set_current("homepage", text),
+            "PackageSourceInfo" => self.set_current("sourceInfo", text),
+            "PackageLicenseConcluded" => self.set_current("licenseConcluded", text),
+            "PackageLicenseInfoFromFiles" => self.push_current("licenseInfoFromFiles", text),
+            "PackageLicenseDeclared" => self.set_current("licenseDeclared", text),
+            "PackageLicenseComments" => self.set_current("licenseComments", text),
+            "PackageCopyrightText" => self.set_current("copyrightText", text),
+            "PackageSummary" => self.set_current("summary", text),
+            "PackageDescription" => self.set_current("description", text),
+            "PackageComment" => self.set_current("comment", text),
+            "PackageAttributionText" => self.push_current("attributionTexts", text),
+            "PrimaryPackagePurpose" => self.set_current("primaryPackagePurpose", text),
+            "ReleaseDate" => self.set_current("releaseDate", text),
+            "BuiltDate" => self.set_current("builtDate", text),
+            "ValidUntilDate" => self.set_current("validUntilDate", text),
+            "ExternalRef" => {
+                let mut parts = value.splitn(3, char::is_whitespace);
+                let (Some(category), Some(ref_type), Some(locator)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid_value(&tag, &value, line));
+                };
+                let mut ext_ref = Map::new();
+                ext_ref.insert("referenceCategory".to_string(), category.into());
+                ext_ref.insert("referenceType".to_string(), ref_type.into());
+                ext_ref.insert("referenceLocator".to_string(), locator.trim().into());
+                self.push_current("externalRefs", Value::Object(ext_ref));
+            }
+            "ExternalRefComment" => {
+                if let Some(Value::Array(refs)) =
+                    self.current().and_then(|p| p.get_mut("externalRefs"))
+                    && let Some(Value::Object(last)) = refs.last_mut()
+                {
+                    last.insert("comment".to_string(), text);
+                }
+            }
+
+            // --- File information ---
+            "FileType" => self.push_current("fileTypes", text),
+            "LicenseConcluded" => self.set_current("licenseConcluded", text),
+            "LicenseInfoInFile" => self.push_current("licenseInfoInFiles", text),
+            "LicenseComments" => self.set_current("licenseComments", text),
+            "FileCopyrightText" => self.set_current("copyrightText", text),
+            "FileComment" => self.set_current("comment", text),
+            "FileNotice" => self.set_current("noticeText", text),
+            "FileContributor" => self.push_current("fileContributors", text),
+            "FileAttributionText" => self.push_current("attributionTexts", text),
+
+            // --- Snippet information ---
+            "SnippetFromFileSPDXID" => self.set_current("snippetFromFile", text),
+            "SnippetByteRange" | "SnippetLineRange" => {
+                let (start, end) = value
+                    .split_once(':')
+                    .and_then(|(s, e)| {
+                        Some((s.trim().parse::<u64>().ok()?, e.trim().parse::<u64>().ok()?))
+                    })
+                    .ok_or_else(|| invalid_value(&tag, &value, line))?;
+                let key = if tag == "SnippetByteRange" {
+                    "offset"
+                } else {
+                    "lineNumber"
+                };
+                let mut range = Map::new();
+                range.insert(
+                    "startPointer".to_string(),
+                    object_with(key, start.into()).into(),
+                );
+                range.insert(
+                    "endPointer".to_string(),
+                    object_with(key, end.into()).into(),
+                );
+                self.push_current("ranges", Value::Object(range));
+            }
+            "SnippetLicenseConcluded" => self.set_current("licenseConcluded", text),
+            "LicenseInfoInSnippet" => self.push_current("licenseInfoInSnippets", text),
+            "SnippetLicenseComments" => self.set_current("licenseComments", text),
+            "SnippetCopyrightText" => self.set_current("copyrightText", text),
+            "SnippetComment" => self.set_current("comment", text),
+            "SnippetName" => self.set_current("name", text),
+            "SnippetAttributionText" => self.push_current("attributionTexts", text),
+
+            // --- Other licensing information ---
+            "ExtractedText" => self.set_current("extractedText", text),
+            "LicenseName" => self.set_current("name", text),
+            "LicenseCrossReference" => self.push_current("seeAlsos", text),
+            "LicenseComment" => self.set_current("comment", text),
+
+            // --- Relationships ---
+            "Relationship" => {
+                let mut parts = value.split_whitespace();
+                let (Some(from), Some(rel_type), Some(to)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid_value(&tag, &value, line));
+                };
+                let mut rel = Map::new();
+                rel.insert("spdxElementId".to_string(), from.into());
+                rel.insert("relationshipType".to_string(), rel_type.into());
+                rel.insert("relatedSpdxElement".to_string(), to.into());
+                self.relationships.push(rel);
+            }
+            "RelationshipComment" => {
+                if let Some(rel) = self.relationships.last_mut() {
+                    rel.insert("comment".to_string(), text);
+                }
+            }
+
+            // --- Annotations ---
+            "Annotator" => self
+                .annotations
+                .push((None, object_with("annotator", text))),
+            "AnnotationDate" | "AnnotationType" | "AnnotationComment" | "SPDXREF" => {
+                let Some((target, annotation)) = self.annotations.last_mut() else {
+                    return Err(invalid_value(&tag, &value, line));
+                };
+                match tag.as_str() {
+                    "AnnotationDate" => {
+                        annotation.insert("annotationDate".to_string(), text);
+                    }
+                    "AnnotationType" => {
+                        annotation.insert("annotationType".to_string(), text);
+                    }
+                    "AnnotationComment" => {
+                        annotation.insert("comment".to_string(), text);
+                    }
+                    _ => *target = Some(value),
+                }
+            }
+
+            _ => {
+                if self.unknown_tags.insert(tag.clone()) {
+                    warn!(
+                        "Ignoring unsupported tag-value tag '{}' (first seen on line {})",
+                        tag, line
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Value {
+        // Files listed under a package belong to it
+        for (file, owner) in self.files.iter().zip(&self.file_owners) {
+            if let (Some(owner), Some(file_id)) = (owner, file.get("SPDXID")) {
+                push(&mut self.packages[*owner], "hasFiles", file_id.clone());
+            }
+        }
+
+        // Snippet ranges point at the file the snippet is taken from
+        for snippet in &mut self.snippets {
+            let Some(from_file) = snippet.get("snippetFromFile").cloned() else {
+                continue;
+            };
+            if let Some(Value::Array(ranges)) = snippet.get_mut("ranges") {
+                for pointer in ranges.iter_mut().filter_map(Value::as_object_mut) {
+                    for end in pointer.values_mut().filter_map(Value::as_object_mut) {
+                        end.insert("reference".to_string(), from_file.clone());
+                    }
+                }
+            }
+        }
+
+        // Annotations live on the element they annotate
+        let document_id = self.doc.get("SPDXID").cloned();
+        for (target, annotation) in std::mem::take(&mut self.annotations) {
+            let target = target.map(Value::String);
+            let owner = self
+                .packages
+                .iter_mut()
+                .chain(self.files.iter_mut())
+                .chain(self.snippets.iter_mut())
+                .find(|e| target.is_some() && e.get("SPDXID") == target.as_ref());
+            match owner {
+                Some(element) => push(element, "annotations", Value::Object(annotation)),
+                None => {
+                    if target.is_some() && target != document_id {
+                        warn!(
+                            "Annotation target {:?} not found; attaching it to the document",
+                            target
+                        );
+                    }
+                    push(&mut self.doc, "annotations", Value::Object(annotation));
+                }
+            }
+        }
+
+        let mut doc = self.doc;
+        if !self.creation_info.is_empty() {
+            doc.insert(
+                "creationInfo".to_string(),
+                Value::Object(self.creation_info),
+            );
+        }
+        for (key, items) in [
+            ("packages", self.packages),
+            ("files", self.files),
+            ("snippets", self.snippets),
+            ("hasExtractedLicensingInfos", self.licenses),
+            ("relationships", self.relationships),
+        ] {
+            if !items.is_empty() {
+                let items = items.into_iter().map(Value::Object).collect();
+                doc.insert(key.to_string(), Value::Array(items));
+            }
+        }
+        Value::Object(doc)
+    }
+}
+
+fn object_with(key: &str, value: Value) -> Map<String, Value> {
+    let mut obj = Map::new();
+    obj.insert(key.to_string(), value);
+    obj
+}
+
+/// Appends `value` to the array at `key`, creating it if needed
+fn push(obj: &mut Map<String, Value>, key: &str, value: Value) {
+    match obj
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Array(Vec::new()))
+    {
+        Value::Array(items) => items.push(value),
+        other => *other = Value::Array(vec![value]),
+    }
+}
+
+/// Parses `SHA1: d6a7...` into an SPDX 2.3 JSON checksum object
+fn parse_checksum(value: &str) -> Option<Value> {
+    let (algorithm, checksum) = value.split_once(':')?;
+    let mut obj = Map::new();
+    obj.insert("algorithm".to_string(), algorithm.trim().into());
+    obj.insert("checksumValue".to_string(), checksum.trim().into());
+    Some(Value::Object(obj))
+}
+
+fn invalid_value(tag: &str, value: &str, line: usize) -> ConverterError {
+    ConverterError::ParseError(format!(
+        "Invalid value for tag-value tag '{}' on line {}: '{}'",
+        tag, line, value
+    ))
+}
+
+/// Parse an SPDX 2.x tag-value document into the SPDX 2.3 JSON layout
+pub fn parse<R: BufRead>(reader: R) -> Result<Value, ConverterError> {
+    let mut builder = DocumentBuilder::default();
+    for pair in TagValueReader::new(reader) {
+        builder.add(pair?)?;
+    }
+
+    if !builder.doc.contains_key("spdxVersion") {
+        return Err(ConverterError::ParseError(
+            "Not an SPDX tag-value document: missing SPDXVersion".to_string(),
+        ));
+    }
+
+    Ok(builder.finish())
+}
+
+// =========================================================================
+// Writing
+// =========================================================================
+
+/// Write an SPDX 2.x document (JSON layout) as tag-value.
+///
+/// Files owned by a package (`hasFiles`) are written directly after it;
+/// files no package claims are written before the first package so they
+/// are not read back as belonging to one.
+pub fn write<W: Write>(mut writer: W, doc: &Value) -> Result<(), ConverterError> {
+    let doc = doc.as_object().ok_or_else(|| {
+        ConverterError::SerializationError("SPDX document must be a JSON object".to_string())
+    })?;
+    let w = &mut writer;
+
+    // --- Document creation information ---
+    writeln!(w, "## Document Information")?;
+    write_field(w, "SPDXVersion", doc, "spdxVersion")?;
+    write_field(w, "DataLicense", doc, "dataLicense")?;
+    write_field(w, "SPDXID", doc, "SPDXID")?;
+    write_field(w, "DocumentName", doc, "name")?;
+    write_field(w, "DocumentNamespace", doc, "documentNamespace")?;
+    for ext_ref in array(doc, "externalDocumentRefs") {
+        let empty = Map::new();
+        let checksum = ext_ref
+            .get("checksum")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        write_tag(
+            w,
+            "ExternalDocumentRef",
+            &format!(
+                "{} {} {}: {}",
+                text_field(ext_ref, "externalDocumentId"),
+                text_field(ext_ref, "spdxDocument"),
+                text_field(checksum, "algorithm"),
+                text_field(checksum, "checksumValue")
+            ),
+        )?;
+    }
+    write_field(w, "DocumentComment", doc, "comment")?;
+
+    if let Some(creation_info) = doc.get("creationInfo").and_then(Value::as_object) {
+        writeln!(w, "\n## Creation Information")?;
+        write_field(w, "LicenseListVersion", creation_info, "licenseListVersion")?;
+        write_list(w, "Creator", creation_info, "creators")?;
+        write_field(w, "Created", creation_info, "created")?;
+        write_field(w, "CreatorComment", creation_info, "comment")?;
+    }
+    write_annotations(w, doc)?;
+
+    // --- Files and packages ---
+    let files: HashMap<&str, &Map<String, Value>> = array(doc, "files")
+        .filter_map(|f| Some((f.get("SPDXID")?.as_str()?, f)))
+        .collect();
+    let owned: HashSet<&str> = array(doc, "packages")
+        .flat_map(|p| strings(p, "hasFiles"))
+        .filter(|id| files.contains_key(id))
+        .collect();
+
+    for file in array(doc, "files") {
+        let id = file.get("SPDXID").and_then(Value::as_str);
+        if !id.is_some_and(|id| owned.contains(id)) {
+            write_file(w, file)?;
+        }
+    }
+
+    let mut written = HashSet::new();
+    for package in array(doc, "packages") {
+        write_package(w, package)?;
+        for id in strings(package, "hasFiles") {
+            if let Some(file) = files.get(id)
+                && written.insert(id)
+            {
+                write_file(w, file)?;
+            }
+        }
+    }
+
+    // --- Snippets ---
+    for snippet in array(doc, "snippets") {
+        writeln!(w, "\n## Snippet Information")?;
+        write_field(w, "SnippetSPDXID", snippet, "SPDXID")?;
+        write_field(w, "SnippetFromFileSPDXID", snippet, "snippetFromFile")?;
+        for range in array(snippet, "ranges") {
+            for (tag, key) in [
+                ("SnippetByteRange", "offset"),
+                ("SnippetLineRange", "lineNumber"),
+            ] {
+                let point = |end: &str| {
+                    range
+                        .get(end)
+                        .and_then(|p| p.get(key))
+                        .and_then(Value::as_u64)
+                };
+                if let (Some(start), Some(end)) = (point("startPointer"), point("endPointer")) {
+                    write_tag(w, tag, &format!("{}:{}", start, end))?;
+                }
+            }
+        }
+        write_field(w, "SnippetLicenseConcluded", snippet, "licenseConcluded")?;
+        write_list(w, "LicenseInfoInSnippet", snippet, "licenseInfoInSnippets")?;
+        write_field(w, "SnippetLicenseComments", snippet, "licenseComments")?;
+        write_field(w, "SnippetCopyrightText", snippet, "copyrightText")?;
+        write_field(w, "SnippetComment", snippet, "comment")?;
+        write_field(w, "SnippetName", snippet, "name")?;
+        write_list(w, "SnippetAttributionText", snippet, "attributionTexts")?;
+        write_annotations(w, snippet)?;
+    }
+
+    // --- Other licensing information ---
+    for license in array(doc, "hasExtractedLicensingInfos") {
+        writeln!(w, "\n## License Information")?;
+        write_field(w, "LicenseID", license, "licenseId")?;
+        write_field(w, "ExtractedText", license, "extractedText")?;
+        write_field(w, "LicenseName", license, "name")?;
+        write_list(w, "LicenseCrossReference", license, "seeAlsos")?;
+        write_field(w, "LicenseComment", license, "comment")?;
+    }
+
+    // --- Relationships ---
+    let document_id = doc
+        .get("SPDXID")
+        .and_then(Value::as_str)
+        .unwrap_or("SPDXRef-DOCUMENT");
+    let relationships: Vec<&Map<String, Value>> = array(doc, "relationships").collect();
+    let described: Vec<&str> = strings(doc, "documentDescribes")
+        .filter(|id| {
+            !relationships.iter().any(|r| {
+                r.get("spdxElementId").and_then(Value::as_str) == Some(document_id)
+                    && r.get("relationshipType").and_then(Value::as_str) == Some("DESCRIBES")
+                    && r.get("relatedSpdxElement").and_then(Value::as_str) == Some(id)
+            })
+        })
+        .collect();
+
+    if !relationships.is_empty() || !described.is_empty() {
+        writeln!(w, "\n## Relationships")?;
+    }
+    for id in described {
+        write_tag(
+            w,
+            "Relationship",
+            &format!("{} DESCRIBES {}", document_id, id),
+        )?;
+    }
+    for rel in relationships {
+        write_tag(
+            w,
+            "Relationship",
+            &format!(
+                "{} {} {}",
+                text_field(rel, "spdxElementId"),
+                text_field(rel, "relationshipType"),
+                text_field(rel, "relatedSpdxElement")
+            ),
+        )?;
+        write_field(w, "RelationshipComment", rel, "comment")?;
+    }
+
+    w.flush()?;
+    Ok(())
+}
+
+fn write_package<W: Write>(w: &mut W, package: &Map<String, Value>) -> std::io::Result<()> {
+    writeln!(w, "\n## Package Information")?;
+    write_field(w, "PackageName", package, "name")?;
+    write_field(w, "SPDXID", package, "SPDXID")?;
+    write_field(w, "PackageVersion", package, "versionInfo")?;
+    write_field(w, "PackageFileName", package, "packageFileName")?;
+    write_field(w, "PackageSupplier", package, "supplier")?;
+    write_field(w, "PackageOriginator", package, "originator")?;
+    write_field(w, "PackageDownloadLocation", package, "downloadLocation")?;
+    write_field(w, "FilesAnalyzed", package, "filesAnalyzed")?;
+    if let Some(code) = package.get("packageVerificationCode") {
+        let value = code
+            .get("packageVerificationCodeValue")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let excluded: Vec<&str> = code
+            .as_object()
+            .map(|code| strings(code, "packageVerificationCodeExcludedFiles").collect())
+            .unwrap_or_default();
+        if excluded.is_empty() {
+            write_tag(w, "PackageVerificationCode", value)?;
+        } else {
+            write_tag(
+                w,
+                "PackageVerificationCode",
+                &format!("{} (excludes: {})", value, excluded.join(", ")),
+            )?;
+        }
+    }
+    write_checksums(w, "PackageChecksum", package)?;
+    write_field(w, "PackageHomePage", package, "homepage")?;
+    write_field(w, "PackageSourceInfo", package, "sourceInfo")?;
+    write_field(w, "PackageLicenseConcluded", package, "licenseConcluded")?;
+    write_list(
+        w,
+        "PackageLicenseInfoFromFiles",
+        package,
+        "licenseInfoFromFiles",
+    )?;
+    write_field(w, "PackageLicenseDeclared", package, "licenseDeclared")?;
+    write_field(w, "PackageLicenseComments", package, "licenseComments")?;
+    write_field(w, "PackageCopyrightText", package, "copyrightText")?;
+    write_field(w, "PackageSummary", package, "summary")?;
+    write_field(w, "PackageDescription", package, "description")?;
+    write_field(w, "PackageComment", package, "comment")?;
+    for ext_ref in array(package, "externalRefs") {
+        write_tag(
+            w,
+            "ExternalRef",
+            &format!(
+                "{} {} {}",
+                text_field(ext_ref, "referenceCategory"),
+                text_field(ext_ref, "referenceType"),
+                text_field(ext_ref, "referenceLocator")
+            ),
+        )?;
+        write_field(w, "ExternalRefComment", ext_ref, "comment")?;
+    }
+    write_list(w, "PackageAttributionText", package, "attributionTexts")?;
+    write_field(w, "PrimaryPackagePurpose", package, "primaryPackagePurpose")?;
+    write_field(w, "ReleaseDate", package, "releaseDate")?;
+    write_field(w, "BuiltDate", package, "builtDate")?;
+    write_field(w, "ValidUntilDate", package, "validUntilDate")?;
+    write_annotations(w, package)
+}
+
+fn write_file<W: Write>(w: &mut W, file: &Map<String, Value>) -> std::io::Result<()> {
+    writeln!(w, "\n## File Information")?;
+    write_field(w, "FileName", file, "fileName")?;
+    write_field(w, "SPDXID", file, "SPDXID")?;
+    write_list(w, "FileType", file, "fileTypes")?;
+    write_checksums(w, "FileChecksum", file)?;
+    write_field(w, "LicenseConcluded", file, "licenseConcluded")?;
+    write_list(w, "LicenseInfoInFile", file, "licenseInfoInFiles")?;
+    write_field(w, "LicenseComments", file, "licenseComments")?;
+    write_field(w, "FileCopyrightText", file, "copyrightText")?;
+    write_field(w, "FileComment", file, "comment")?;
+    write_field(w, "FileNotice", file, "noticeText")?;
+    write_list(w, "FileContributor", file, "fileContributors")?;
+    write_list(w, "FileAttributionText", file, "attributionTexts")?;
+    write_annotations(w, file)
+}
+
+fn write_checksums<W: Write>(
+    w: &mut W,
+    tag: &str,
+    element: &Map<String, Value>,
+) -> std::io::Result<()> {
+    for checksum in array(element, "checksums") {
+        write_tag(
+            w,
+            tag,
+            &format!(
+                "{}: {}",
+                text_field(checksum, "algorithm"),
+                text_field(checksum, "checksumValue")
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the annotations of an element, each pointing back at it via `SPDXREF`
+fn write_annotations<W: Write>(w: &mut W, element: &Map<String, Value>) -> std::io::Result<()> {
+    for annotation in array(element, "annotations") {
+        write_field(w, "Annotator", annotation, "annotator")?;
+        write_field(w, "AnnotationDate", annotation, "annotationDate")?;
+        write_field(w, "AnnotationType", annotation, "annotationType")?;
+        write_field(w, "SPDXREF", element, "SPDXID")?;
+        write_field(w, "AnnotationComment", annotation, "comment")?;
+    }
+    Ok(())
+}
+
+/// Iterates the objects in the array at `key`, if any
+fn array<'a>(
+    obj: &'a Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = &'a Map<String, Value>> {
+    obj.get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+}
+
+/// Iterates the strings in the array at `key`, if any
+fn strings<'a>(obj: &'a Map<String, Value>, key: &str) -> impl Iterator<Item = &'a str> {
+    obj.get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+}
+
+/// The string at `key`, or an empty string
+fn text_field<'a>(obj: &'a Map<String, Value>, key: &str) -> &'a str {
+    obj.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Writes `Tag: value`, wrapping multi-line values in `<text>`
+fn write_tag<W: Write>(w: &mut W, tag: &str, value: &str) -> std::io::Result<()> {
+    if value.contains('\n') {
+        writeln!(w, "{}: <text>{}</text>", tag, value)
+    } else {
+        writeln!(w, "{}: {}", tag, value)
+    }
+}
+
+fn write_field<W: Write>(
+    w: &mut W,
+    tag: &str,
+    obj: &Map<String, Value>,
+    key: &str,
+) -> std::io::Result<()> {
+    match obj.get(key) {
+        Some(Value::String(s)) => write_tag(w, tag, s),
+        Some(Value::Bool(b)) => write_tag(w, tag, &b.to_string()),
+        Some(Value::Number(n)) => write_tag(w, tag, &n.to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn write_list<W: Write>(
+    w: &mut W,
+    tag: &str,
+    obj: &Map<String, Value>,
+    key: &str,
+) -> std::io::Result<()> {
+    for value in strings(obj, key) {
+        write_tag(w, tag, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    const SAMPLE: &str = "SPDXVersion: SPDX-2.3
+DataLicense: CC0-1.0
+SPDXID: SPDXRef-DOCUMENT
+DocumentName: core-image-minimal
+DocumentNamespace: http://spdx.org/spdxdocs/core-image-minimal-1234
+# comments are ignored
+Creator: Tool: yocto
+Created: 2025-01-01T00:00:00Z
+
+PackageName: busybox
+SPDXID: SPDXRef-Package-busybox
+PackageVersion: 1.36.1
+PackageDownloadLocation: NOASSERTION
+FilesAnalyzed: true
+PackageVerificationCode: d6a770ba38583ed4bb4525bd96e50461655d2758 (excludes: ./busybox.spdx)
+PackageChecksum: SHA256: 6a2bb7a1cd2e8d1e6e0d7b0c0a1b8e8b8e6c0b5a1c2d3e4f5a6b7c8d9e0f1a2b
+PackageLicenseConcluded: GPL-2.0-only
+PackageCopyrightText: <text>Copyright (C) Busybox authors
+Copyright (C) Erik Andersen</text>
+ExternalRef: SECURITY cpe23Type cpe:2.3:a:busybox:busybox:1.36.1:*:*:*:*:*:*:*
+
+FileName: ./bin/busybox
+SPDXID: SPDXRef-File-busybox
+FileChecksum: SHA1: d6a770ba38583ed4bb4525bd96e50461655d2758
+LicenseConcluded: GPL-2.0-only
+
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-busybox
+RelationshipComment: top-level package
+";
+
+    #[test]
+    fn test_reader_joins_text_blocks() {
+        let pairs: Vec<TagValuePair> = TagValueReader::new(Cursor::new(
+            "A: 1\n\n# skip\nB: <text>x\ny\n</text>\nC: <text>z</text>\n",
+        ))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[1].tag, "B");
+        assert_eq!(pairs[1].value, "x\ny\n");
+        assert_eq!(pairs[2].value, "z");
+        assert_eq!(pairs[2].line, 7);
+    }
+
+    #[test]
+    fn test_reader_rejects_unterminated_text() {
+        let result: Result<Vec<_>, _> =
+            TagValueReader::new(Cursor::new("A: <text>never closed\n")).collect();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let doc = parse(Cursor::new(SAMPLE)).unwrap();
+
+        assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+        assert_eq!(doc["SPDXID"], "SPDXRef-DOCUMENT");
+        assert_eq!(doc["name"], "core-image-minimal");
+        assert_eq!(doc["creationInfo"]["creators"], json!(["Tool: yocto"]));
+
+        let package = &doc["packages"][0];
+        assert_eq!(package["SPDXID"], "SPDXRef-Package-busybox");
+        assert_eq!(package["versionInfo"], "1.36.1");
+        assert_eq!(package["filesAnalyzed"], true);
+        assert_eq!(
+            package["packageVerificationCode"]["packageVerificationCodeExcludedFiles"],
+            json!(["./busybox.spdx"])
+        );
+        assert_eq!(package["checksums"][0]["algorithm"], "SHA256");
+        assert_eq!(
+            package["copyrightText"],
+            "Copyright (C) Busybox authors\nCopyright (C) Erik Andersen"
+        );
+        assert_eq!(package["externalRefs"][0]["referenceType"], "cpe23Type");
+        assert_eq!(package["hasFiles"], json!(["SPDXRef-File-busybox"]));
+
+        let file = &doc["files"][0];
+        assert_eq!(file["fileName"], "./bin/busybox");
+        assert_eq!(file["licenseConcluded"], "GPL-2.0-only");
+
+        let rel = &doc["relationships"][0];
+        assert_eq!(rel["relationshipType"], "DESCRIBES");
+        assert_eq!(rel["comment"], "top-level package");
+    }
+
+    #[test]
+    fn test_parse_requires_spdx_version() {
+        assert!(parse(Cursor::new("PackageName: foo\n")).is_err());
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let doc = parse(Cursor::new(SAMPLE)).unwrap();
+
+        let mut output = Vec::new();
+        write(&mut output, &doc).unwrap();
+        let text = String::from_utf8(output).unwrap();
+
+        assert!(text.contains("PackageName: busybox\n"));
+        assert!(text.contains("PackageCopyrightText: <text>Copyright (C) Busybox authors\n"));
+        assert!(text.contains("FileChecksum: SHA1: d6a770ba38583ed4bb4525bd96e50461655d2758\n"));
+        assert!(
+            text.contains("Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-busybox\n")
+        );
+
+        assert_eq!(parse(Cursor::new(text)).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_write_document_describes() {
+        let doc = json!({
+            "spdxVersion": "SPDX-2.3",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "documentDescribes": ["SPDXRef-app"],
+            "files": [{ "SPDXID": "SPDXRef-loose", "fileName": "./loose.txt" }],
+            "packages": [{ "SPDXID": "SPDXRef-app", "name": "app" }]
+        });
+
+        let mut output = Vec::new();
+        write(&mut output, &doc).unwrap();
+        let text = String::from_utf8(output).unwrap();
+
+        assert!(text.contains("Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-app\n"));
+        // Files without an owning package come first so they stay unowned
+        assert!(
+            text.find("FileName: ./loose.txt").unwrap() < text.find("PackageName: app").unwrap()
+        );
+        let reparsed = parse(Cursor::new(text)).unwrap();
+        assert!(reparsed["packages"][0].get("hasFiles").is_none());
+    }
+}
@@ -0,0 +1,427 @@
+//! Mapping between SPDX IDs and CycloneDX bom-refs.
+//!
+//! Every identifier a conversion carries across goes through an [`IdMap`].
+//! A new identifier is derived from the source one (see
+//! [`crate::converter_spdx_to_cdx::extract_bom_ref`] and [`derive_spdx_id`]);
+//! if that target is already taken by another source identifier, a `-2`,
+//! `-3`, ... suffix keeps it unique. SPDX IDs that look like the ones the
+//! writer generates itself ([`is_reserved_spdx_id`]) become
+//! `SPDXRef-Component-...` first. Suffixes are handed out in
+//! the order the identifiers appear in the input, so the same input always
+//! gets the same IDs. The map can be loaded from and written
+//! to an ID-map file, so later conversions (in either direction) and VEX
+//! documents keep using the identifiers of earlier runs.
+//!
+//! Like `FidelityReport`, the map is a cheap handle that the streaming
+//! visitors of one conversion share.
+
+use crate::converter_spdx_to_cdx::extract_bom_ref;
+use crate::errors::ConverterError;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Version of the ID-map file format
+const ID_MAP_VERSION: u32 = 1;
+
+/// One SPDX ID and the bom-ref it corresponds to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct IdMapEntry {
+    /// SPDX ID, or JSON-LD IRI, of the element
+    pub spdx_id: String,
+    #[serde(rename = "bom-ref")]
+    pub bom_ref: String,
+}
+
+/// The JSON document written for `--id-map`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdMapFile {
+    pub id_map_version: u32,
+    pub ids: Vec<IdMapEntry>,
+}
+
+#[derive(Debug, Default)]
+struct Ids {
+    /// SPDX ID -> bom-ref
+    bom_refs: HashMap<String, String>,
+    /// bom-ref -> SPDX ID
+    spdx_ids: HashMap<String, String>,
+    loaded: usize,
+    collisions: usize,
+}
+
+impl Ids {
+    fn insert(&mut self, spdx_id: String, bom_ref: String) {
+        self.bom_refs.insert(spdx_id.clone(), bom_ref.clone());
+        self.spdx_ids.insert(bom_ref, spdx_id);
+    }
+}
+
+/// Shared SPDX ID <-> bom-ref mapping of one conversion
+#[derive(Clone, Debug, Default)]
+pub struct IdMap {
+    ids: Arc<Mutex<Ids>>,
+}
+
+impl IdMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads an ID-map file; a file that does not exist yet gives an
+    /// empty map
+    pub fn load(path: &Path) -> Result<Self, ConverterError> {
+        let map = Self::new();
+        if !path.exists() {
+            return Ok(map);
+        }
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to read ID map {}", path.display()))
+        })?;
+        let file: IdMapFile = serde_json::from_str(&content).map_err(|e| {
+            ConverterError::InvalidInput(format!("Invalid ID map {}: {}", path.display(), e))
+        })?;
+        if file.id_map_version != ID_MAP_VERSION {
+            return Err(ConverterError::InvalidInput(format!(
+                "Unsupported ID map version {} in {}",
+                file.id_map_version,
+                path.display()
+            )));
+        }
+        {
+            let mut ids = map.ids.lock().unwrap_or_else(|e| e.into_inner());
+            ids.loaded = file.ids.len();
+            for entry in file.ids {
+                ids.insert(entry.spdx_id, entry.bom_ref);
+            }
+        }
+        info!("Loaded {} ID mapping(s) from {}", map.len(), path.display());
+        Ok(map)
+    }
+
+    /// The bom-ref for an SPDX ID (or JSON-LD IRI)
+    pub fn bom_ref(&self, spdx_id: &str) -> String {
+        let mut ids = self.ids.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bom_ref) = ids.bom_refs.get(spdx_id) {
+            return bom_ref.clone();
+        }
+        // A map written from SPDX 2.3 or simple JSON knows the bare
+        // SPDXRef- id of a JSON-LD element. The IRI is not added, so the
+        // bom-ref keeps its one entry, under the bare id.
+        if let Some((_, fragment)) = spdx_id.rsplit_once('#')
+            && let Some(bom_ref) = ids.bom_refs.get(fragment)
+            && ids.spdx_ids.get(bom_ref).map(String::as_str) == Some(fragment)
+        {
+            return bom_ref.clone();
+        }
+
+        let derived = extract_bom_ref(spdx_id);
+        let bom_ref = unique(derived.clone(), |id| ids.spdx_ids.contains_key(id));
+        if bom_ref != derived {
+            ids.collisions += 1;
+            warn!(
+                "bom-ref {} is taken; {} becomes {}",
+                derived, spdx_id, bom_ref
+            );
+        }
+        ids.insert(spdx_id.to_string(), bom_ref.clone());
+        bom_ref
+    }
+
+    /// The SPDX ID (`SPDXRef-...`, without any JSON-LD namespace) for a
+    /// bom-ref; always a valid SPDX idstring
+    pub fn spdx_id(&self, bom_ref: &str) -> String {
+        let mut ids = self.ids.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(spdx_id) = ids.spdx_ids.get(bom_ref) {
+            // A map written by a JSON-LD conversion holds IRIs
+            let local = spdx_id
+                .rsplit_once('#')
+                .map_or(spdx_id.as_str(), |(_, f)| f);
+            if is_valid_spdx_id(local) && !is_reserved_spdx_id(local) {
+                return local.to_string();
+            }
+            // Replaced by the derived ID below, so the map keeps one entry
+            // per bom-ref
+            let rejected = spdx_id.clone();
+            ids.bom_refs.remove(&rejected);
+        }
+
+        let derived = derive_spdx_id(bom_ref);
+        let candidate = if is_reserved_spdx_id(&derived) {
+            format!("SPDXRef-Component-{}", sanitize_spdx_id(bom_ref))
+        } else {
+            derived
+        };
+        let spdx_id = unique(candidate.clone(), |id| ids.bom_refs.contains_key(id));
+        if spdx_id != candidate {
+            ids.collisions += 1;
+            warn!(
+                "SPDX ID {} is taken; bom-ref {} becomes {}",
+                candidate, bom_ref, spdx_id
+            );
+        }
+        ids.insert(spdx_id.clone(), bom_ref.to_string());
+        spdx_id
+    }
+
+    /// Number of mappings
+    pub fn len(&self) -> usize {
+        self.ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .bom_refs
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of identifiers that needed a suffix to stay unique
+    pub fn collisions(&self) -> usize {
+        self.ids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .collisions
+    }
+
+    /// All mappings, sorted by SPDX ID
+    pub fn entries(&self) -> Vec<IdMapEntry> {
+        let ids = self.ids.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<IdMapEntry> = ids
+            .bom_refs
+            .iter()
+            .map(|(spdx_id, bom_ref)| IdMapEntry {
+                spdx_id: spdx_id.clone(),
+                bom_ref: bom_ref.clone(),
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    /// Writes the map as pretty JSON to `path`
+    pub fn write_json(&self, path: &Path) -> Result<(), ConverterError> {
+        let file = IdMapFile {
+            id_map_version: ID_MAP_VERSION,
+            ids: self.entries(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| {
+            ConverterError::SerializationError(format!("Failed to write ID map: {}", e))
+        })?;
+        std::fs::write(path, json)
+            .map_err(|e| ConverterError::Io(e, "Failed to write ID map".to_string()))?;
+
+        let ids = self.ids.lock().unwrap_or_else(|e| e.into_inner());
+        info!(
+            "ID map written to {} ({} mapping(s), {} new, {} collision(s))",
+            path.display(),
+            ids.bom_refs.len(),
+            ids.bom_refs.len().saturating_sub(ids.loaded),
+            ids.collisions
+        );
+        Ok(())
+    }
+}
+
+/// Prefixes of the SPDX IDs the CDX -> SPDX writer generates for elements
+/// that are not components
+const GENERATED_ID_PREFIXES: &[&str] = &[
+    "SPDXRef-Relationship-",
+    "SPDXRef-License-",
+    "SPDXRef-LicenseText-",
+    "SPDXRef-Person-",
+    "SPDXRef-Tool-",
+    "SPDXRef-Agent-",
+    "SPDXRef-Vulnerability-",
+    "SPDXRef-VexAssessment-",
+    "SPDXRef-CvssAssessment-",
+];
+
+/// The SPDX ID derived for a bom-ref that has none yet.
+///
+/// SPDX idstrings only allow letters, digits, `.` and `-` after
+/// `SPDXRef-`, so every other character (as in the `:`, `/`, `@`, `?` and
+/// `%` of purls) becomes `-`.
+pub fn derive_spdx_id(bom_ref: &str) -> String {
+    format!("SPDXRef-{}", sanitize_spdx_id(bom_ref))
+}
+
+/// Replaces the characters SPDX idstrings do not allow with `-`
+pub fn sanitize_spdx_id(local: &str) -> String {
+    if local.is_empty() {
+        return "empty".to_string();
+    }
+    local
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Whether `id` is a valid SPDX idstring (`SPDXRef-[A-Za-z0-9.-]+`)
+pub fn is_valid_spdx_id(id: &str) -> bool {
+    id.strip_prefix("SPDXRef-").is_some_and(|local| {
+        !local.is_empty()
+            && local
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    })
+}
+
+/// Whether `id` is `SPDXRef-DOCUMENT` or could be one of the IDs the
+/// writer generates, which components must not take
+pub fn is_reserved_spdx_id(id: &str) -> bool {
+    id == "SPDXRef-DOCUMENT" || GENERATED_ID_PREFIXES.iter().any(|p| id.starts_with(p))
+}
+
+/// `candidate`, or the first of `candidate-2`, `candidate-3`, ... that is
+/// not `taken`
+fn unique(candidate: String, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(&candidate) {
+        return candidate;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", candidate, n))
+        .find(|id| !taken(id))
+        .unwrap_or(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bom_refs_are_stable_and_unique() {
+        let ids = IdMap::new();
+        assert_eq!(ids.bom_ref("SPDXRef-lib"), "lib");
+        assert_eq!(ids.bom_ref("SPDXRef-lib"), "lib");
+        // Both map to "lib" once the prefix is stripped
+        assert_eq!(ids.bom_ref("lib"), "lib-2");
+        assert_eq!(ids.collisions(), 1);
+
+        // IRIs get a hash-based bom-ref that is the same in every run...
+        let iri = "https://example.com/doc#SPDXRef-lib";
+        assert_eq!(IdMap::new().bom_ref(iri), IdMap::new().bom_ref(iri));
+        assert_ne!(IdMap::new().bom_ref(iri), "lib");
+        // ...unless the map already knows their SPDXRef- fragment
+        assert_eq!(ids.bom_ref(iri), "lib");
+    }
+
+    #[test]
+    fn test_iri_bom_refs_use_the_fragment() {
+        let ids = IdMap::new();
+        let bom_ref = ids.bom_ref("https://x/doc#SPDXRef-pkg");
+        assert!(bom_ref.starts_with("pkg-"), "{}", bom_ref);
+        assert!(!bom_ref.contains('#'));
+
+        let bom_ref = ids.bom_ref("urn:uuid:1234#SPDXRef-a b?c");
+        assert!(bom_ref.starts_with("a-b-c-"), "{}", bom_ref);
+
+        // Without a fragment the last path segment names the element
+        let bom_ref = ids.bom_ref("https://x/spdx/recipe");
+        assert!(bom_ref.starts_with("recipe-"), "{}", bom_ref);
+    }
+
+    #[test]
+    fn test_spdx_ids_are_unique() {
+        let ids = IdMap::new();
+        assert_eq!(ids.spdx_id("app"), "SPDXRef-app");
+        assert_eq!(ids.bom_ref("SPDXRef-app"), "app");
+        assert_eq!(ids.bom_ref("SPDXRef-other"), "other");
+        assert_eq!(ids.spdx_id("other"), "SPDXRef-other");
+        assert_eq!(ids.collisions(), 0);
+    }
+
+    #[test]
+    fn test_spdx_ids_are_sanitized() {
+        let ids = IdMap::new();
+        assert_eq!(
+            ids.spdx_id("pkg:npm/%40scope/lib@1.0.0?arch=x64"),
+            "SPDXRef-pkg-npm--40scope-lib-1.0.0-arch-x64"
+        );
+        // Two bom-refs that only differ in illegal characters
+        assert_eq!(ids.spdx_id("pkg:npm/a@1"), "SPDXRef-pkg-npm-a-1");
+        assert_eq!(ids.spdx_id("pkg:npm/a/1"), "SPDXRef-pkg-npm-a-1-2");
+        assert_eq!(ids.spdx_id("pkg:npm/a@1"), "SPDXRef-pkg-npm-a-1");
+        assert_eq!(ids.collisions(), 1);
+        assert!(is_valid_spdx_id(&ids.spdx_id("")));
+    }
+
+    #[test]
+    fn test_rejected_loaded_spdx_id_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ids.json");
+        let file = IdMapFile {
+            id_map_version: ID_MAP_VERSION,
+            ids: vec![IdMapEntry {
+                spdx_id: "SPDXRef-Relationship-1".to_string(),
+                bom_ref: "lib".to_string(),
+            }],
+        };
+        std::fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+
+        let ids = IdMap::load(&path).unwrap();
+        assert_eq!(ids.spdx_id("lib"), "SPDXRef-lib");
+        assert_eq!(
+            ids.entries(),
+            vec![IdMapEntry {
+                spdx_id: "SPDXRef-lib".to_string(),
+                bom_ref: "lib".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reserved_spdx_ids() {
+        let ids = IdMap::new();
+        assert_eq!(ids.spdx_id("DOCUMENT"), "SPDXRef-Component-DOCUMENT");
+        assert_eq!(
+            ids.spdx_id("Relationship-1"),
+            "SPDXRef-Component-Relationship-1"
+        );
+        assert_eq!(ids.bom_ref("SPDXRef-Component-DOCUMENT"), "DOCUMENT");
+        // Moving off a reserved ID is not a collision
+        assert_eq!(ids.collisions(), 0);
+        assert!(!is_valid_spdx_id("SPDXRef-pkg:npm/a@1"));
+        assert!(!is_valid_spdx_id("https://example.com/doc#SPDXRef-a"));
+    }
+
+    #[test]
+    fn test_write_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ids.json");
+
+        let ids = IdMap::new();
+        ids.spdx_id("pkg:npm/lodash@4.17.21");
+        ids.bom_ref("SPDXRef-app");
+        ids.write_json(&path).unwrap();
+
+        let loaded = IdMap::load(&path).unwrap();
+        assert_eq!(loaded.entries(), ids.entries());
+        assert_eq!(
+            loaded.bom_ref("SPDXRef-pkg-npm-lodash-4.17.21"),
+            "pkg:npm/lodash@4.17.21"
+        );
+        // JSON-LD IRIs fall back to their SPDXRef- fragment, without a
+        // second entry for the bom-ref
+        assert_eq!(loaded.bom_ref("https://example.com/doc#SPDXRef-app"), "app");
+        assert_eq!(loaded.entries(), ids.entries());
+        assert_eq!(loaded.spdx_id("app"), "SPDXRef-app");
+
+        assert!(
+            IdMap::load(&dir.path().join("missing.json"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
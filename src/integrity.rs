@@ -0,0 +1,807 @@
+//! Referential integrity of converted documents.
+//!
+//! A conversion can write refs to elements it never writes: SPDX →
+//! CycloneDX `--packages-only` drops the files `CONTAINS` relationships
+//! still point at, relationships can target licenses or agents, and
+//! CycloneDX `dependencies` or `affects` can name bom-refs no component
+//! has. After the conversion, [`check_files`] looks at every ref in the
+//! dependencies, `affects`, relationships, VEX assessments and described
+//! elements of the output, and handles the ones that point at nothing
+//! according to a [`DanglingRefPolicy`].
+//!
+//! Refs into other documents (BOM-Links to other BOMs, SPDX external
+//! document refs and imported elements) are not checked. Every pruned or
+//! rewritten ref is recorded in the fidelity report under `dangling_ref.*`.
+//! When `rewrite` has no containers to go by (the relationship index was
+//! spilled), it prunes, and `dangling_ref.rewrite_skipped` counts the refs
+//! pruned that way.
+//!
+//! Unlike the conversion itself, the check does not stream: it loads the
+//! whole output (and VEX document) into memory, so with a policy set memory
+//! grows with the size of the output.
+
+use crate::bom_link::{self, BomLink};
+use crate::errors::ConverterError;
+use crate::fidelity::FidelityReport;
+use log::{debug, info, warn};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Number of dangling refs listed in the error of [`DanglingRefPolicy::Fail`]
+const MAX_LISTED: usize = 5;
+
+/// What to do with refs that point at elements the output does not have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DanglingRefPolicy {
+    /// Remove the refs, and relationships that lose an end
+    Prune,
+    /// Point the refs at the nearest written element that contains their
+    /// target (such as the package of a dropped file); prune the rest
+    Rewrite,
+    /// Fail the conversion
+    Fail,
+}
+
+impl DanglingRefPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DanglingRefPolicy::Prune => "prune",
+            DanglingRefPolicy::Rewrite => "rewrite",
+            DanglingRefPolicy::Fail => "fail",
+        }
+    }
+}
+
+/// The containing element of each element, by the refs the output uses.
+///
+/// Filled by the converter; like `FidelityReport`, a cheap handle.
+/// Conversions whose relationship index was spilled to disk [`skip`] it,
+/// as it would hold an entry per `CONTAINS` relationship.
+///
+/// [`skip`]: Containers::skip
+#[derive(Clone, Debug, Default)]
+pub struct Containers {
+    parents: Arc<Mutex<HashMap<String, String>>>,
+    skipped: Arc<AtomicBool>,
+}
+
+impl Containers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `parent` contains `child`; the first container
+    /// recorded for a child wins
+    pub fn add(&self, child: &str, parent: &str) {
+        if child == parent || self.is_skipped() {
+            return;
+        }
+        let mut parents = self.parents.lock().unwrap_or_else(|e| e.into_inner());
+        parents
+            .entry(child.to_string())
+            .or_insert_with(|| parent.to_string());
+    }
+
+    /// Stops recording containers and drops the ones recorded;
+    /// [`DanglingRefPolicy::Rewrite`] then prunes every dangling ref
+    pub fn skip(&self) {
+        self.skipped.store(true, Ordering::Relaxed);
+        self.parents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Number of elements with a recorded container
+    pub fn len(&self) -> usize {
+        self.parents.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The nearest container of `id` for which `exists` holds
+    fn resolve(&self, id: &str, exists: impl Fn(&str) -> bool) -> Option<String> {
+        let parents = self.parents.lock().unwrap_or_else(|e| e.into_inner());
+        let mut seen = HashSet::new();
+        let mut current = id;
+        while let Some(parent) = parents.get(current) {
+            if !seen.insert(parent.as_str()) {
+                return None;
+            }
+            if exists(parent) {
+                return Some(parent.clone());
+            }
+            current = parent;
+        }
+        None
+    }
+}
+
+/// What the check found and changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub dangling: usize,
+    pub pruned: usize,
+    pub rewritten: usize,
+    /// `rewrite` was asked for but pruned instead, as the containers were
+    /// not kept (spilled relationship index)
+    pub rewrite_skipped: bool,
+}
+
+impl IntegrityReport {
+    /// Number of refs pruned or rewritten
+    pub fn changes(&self) -> usize {
+        self.pruned + self.rewritten
+    }
+
+    pub fn log(&self) {
+        if self.dangling == 0 {
+            info!("Referential integrity: no dangling refs");
+        } else {
+            warn!(
+                "Referential integrity: {} dangling ref(s), {} pruned, {} rewritten",
+                self.dangling, self.pruned, self.rewritten
+            );
+            if self.rewrite_skipped && self.pruned > 0 {
+                warn!(
+                    "Containers were not kept for the spilled relationship index; {} ref(s) were pruned instead of rewritten",
+                    self.pruned
+                );
+            }
+        }
+    }
+}
+
+/// The elements the refs of a document may point at
+struct Targets {
+    /// Serial number of the BOM BOM-Links must point into to be checked;
+    /// `None` for SPDX documents
+    serial_number: Option<String>,
+    ids: HashSet<String>,
+}
+
+impl Targets {
+    /// The targets of a CycloneDX BOM: its components, services and
+    /// vulnerabilities
+    fn of_cdx(bom: &Value) -> Self {
+        let mut ids = HashSet::new();
+        bom_link::collect_bom_refs(bom, &mut ids);
+        Targets {
+            serial_number: Some(
+                bom.get("serialNumber")
+                    .and_then(|s| s.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            ids,
+        }
+    }
+
+    /// The targets of an SPDX document in any layout: the document and its
+    /// elements, and the elements it imports
+    fn of_spdx(doc: &Value) -> Self {
+        let mut ids = HashSet::new();
+        let mut add = |element: &Value| {
+            for key in ["SPDXID", "spdxId"] {
+                if let Some(id) = element.get(key).and_then(|i| i.as_str()) {
+                    ids.insert(id.to_string());
+                }
+            }
+            for import in array(element.get("import")) {
+                if let Some(id) = import.get("externalSpdxId").and_then(|i| i.as_str()) {
+                    ids.insert(id.to_string());
+                }
+            }
+        };
+        add(doc);
+        for key in ["packages", "files", "snippets", "elements", "@graph"] {
+            array(doc.get(key)).for_each(&mut add);
+        }
+        Targets {
+            serial_number: None,
+            ids,
+        }
+    }
+
+    /// The id a ref points at, or `None` for refs this document cannot
+    /// check
+    fn local(&self, reference: &str) -> Option<String> {
+        match &self.serial_number {
+            Some(serial_number) => match BomLink::parse(reference) {
+                Some(BomLink {
+                    serial_number: target,
+                    bom_ref: Some(bom_ref),
+                    ..
+                }) if &target == serial_number => Some(bom_ref),
+                Some(_) => None,
+                None => Some(reference.to_string()),
+            },
+            None => {
+                let placeholder = matches!(reference, "NONE" | "NOASSERTION");
+                let external = reference.starts_with("DocumentRef-");
+                (!placeholder && !external).then(|| reference.to_string())
+            }
+        }
+    }
+
+    /// `reference`, pointed at `id` instead, in the same form
+    fn relink(&self, reference: &str, id: String) -> String {
+        match BomLink::parse(reference) {
+            Some(link) if self.serial_number.is_some() => BomLink {
+                bom_ref: Some(id),
+                ..link
+            }
+            .to_string(),
+            _ => id,
+        }
+    }
+}
+
+/// What to do with one ref
+#[derive(Debug, PartialEq, Eq)]
+enum Fix {
+    Keep,
+    Replace(String),
+    Remove,
+}
+
+/// Applies the policy to the refs of one document
+struct Checker<'a> {
+    policy: DanglingRefPolicy,
+    containers: &'a Containers,
+    fidelity: &'a FidelityReport,
+    report: IntegrityReport,
+    /// `location: ref` of the dangling refs, for the error of `Fail`
+    dangling: Vec<String>,
+}
+
+impl Checker<'_> {
+    fn fix(&mut self, location: &str, reference: &str, targets: &Targets) -> Fix {
+        let Some(id) = targets.local(reference) else {
+            return Fix::Keep;
+        };
+        if targets.ids.contains(&id) {
+            return Fix::Keep;
+        }
+        self.report.dangling += 1;
+        debug!("Dangling ref {} at {}", reference, location);
+
+        match self.policy {
+            DanglingRefPolicy::Fail => {
+                self.dangling.push(format!("{}: {}", location, reference));
+                return Fix::Keep;
+            }
+            DanglingRefPolicy::Rewrite => {
+                if let Some(parent) = self
+                    .containers
+                    .resolve(&id, |parent| targets.ids.contains(parent))
+                {
+                    self.report.rewritten += 1;
+                    self.fidelity
+                        .record(&format!("dangling_ref.{}.rewritten", location), reference);
+                    return Fix::Replace(targets.relink(reference, parent));
+                }
+            }
+            DanglingRefPolicy::Prune => {}
+        }
+        self.report.pruned += 1;
+        self.fidelity
+            .record(&format!("dangling_ref.{}", location), reference);
+        Fix::Remove
+    }
+
+    /// Fixes the refs in the string array `items`, dropping duplicates
+    /// and `exclude` that rewriting produces
+    fn fix_list(
+        &mut self,
+        location: &str,
+        items: &mut Vec<Value>,
+        exclude: Option<&str>,
+        targets: &Targets,
+    ) {
+        let mut kept: Vec<Value> = Vec::with_capacity(items.len());
+        for item in items.drain(..) {
+            let Some(reference) = item.as_str() else {
+                kept.push(item);
+                continue;
+            };
+            match self.fix(location, reference, targets) {
+                Fix::Keep => kept.push(item),
+                Fix::Replace(new) => {
+                    let new = Value::String(new);
+                    if exclude != new.as_str() && !kept.contains(&new) {
+                        kept.push(new);
+                    }
+                }
+                Fix::Remove => {}
+            }
+        }
+        *items = kept;
+    }
+}
+
+/// Checks the dependencies and `affects` of a CycloneDX BOM
+fn check_cdx(bom: &mut Value, targets: &Targets, checker: &mut Checker) {
+    if let Some(dependencies) = bom.get_mut("dependencies").and_then(|d| d.as_array_mut()) {
+        // Rewritten refs can make two entries about the same element, which
+        // are merged
+        let mut kept: Vec<(Value, bool)> = Vec::with_capacity(dependencies.len());
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for mut dependency in dependencies.drain(..) {
+            let Some(dep_ref) = dependency
+                .get("ref")
+                .and_then(|r| r.as_str())
+                .map(str::to_string)
+            else {
+                kept.push((dependency, false));
+                continue;
+            };
+            let (dep_ref, rewritten) = match checker.fix("dependencies.ref", &dep_ref, targets) {
+                Fix::Keep => (dep_ref, false),
+                Fix::Replace(new) => {
+                    dependency["ref"] = Value::String(new.clone());
+                    (new, true)
+                }
+                Fix::Remove => continue,
+            };
+            // An entry whose refs are all gone would claim the element
+            // has no dependencies; the converters write no entry instead
+            let mut emptied = false;
+            for key in ["dependsOn", "provides"] {
+                let Some(items) = dependency.get_mut(key).and_then(|d| d.as_array_mut()) else {
+                    continue;
+                };
+                let was_empty = items.is_empty();
+                let location = format!("dependencies.{}", key);
+                checker.fix_list(&location, items, Some(&dep_ref), targets);
+                if items.is_empty() && !was_empty {
+                    emptied = true;
+                    if let Some(object) = dependency.as_object_mut() {
+                        object.remove(key);
+                    }
+                }
+            }
+            let has_refs =
+                dependency.get("dependsOn").is_some() || dependency.get("provides").is_some();
+            if emptied && !has_refs {
+                continue;
+            }
+
+            match positions.get(&dep_ref) {
+                Some(&i) if rewritten || kept[i].1 => {
+                    merge_dependency(&mut kept[i].0, dependency);
+                    kept[i].1 = true;
+                }
+                _ => {
+                    positions.insert(dep_ref, kept.len());
+                    kept.push((dependency, rewritten));
+                }
+            }
+        }
+        *dependencies = kept.into_iter().map(|(dependency, _)| dependency).collect();
+    }
+
+    for vulnerability in bom
+        .get_mut("vulnerabilities")
+        .and_then(|v| v.as_array_mut())
+        .into_iter()
+        .flatten()
+    {
+        let Some(affects) = vulnerability
+            .get_mut("affects")
+            .and_then(|a| a.as_array_mut())
+        else {
+            continue;
+        };
+        let mut kept: Vec<Value> = Vec::with_capacity(affects.len());
+        for mut affected in affects.drain(..) {
+            let Some(reference) = affected.get("ref").and_then(|r| r.as_str()) else {
+                kept.push(affected);
+                continue;
+            };
+            match checker.fix("vulnerabilities.affects", reference, targets) {
+                Fix::Keep => kept.push(affected),
+                Fix::Replace(new) => {
+                    if !kept.iter().any(|a| a["ref"] == new.as_str()) {
+                        affected["ref"] = Value::String(new);
+                        kept.push(affected);
+                    }
+                }
+                Fix::Remove => {}
+            }
+        }
+        if kept.is_empty() {
+            if let Some(object) = vulnerability.as_object_mut() {
+                object.remove("affects");
+            }
+        } else {
+            *affects = kept;
+        }
+    }
+}
+
+/// Adds the `dependsOn` and `provides` refs of `other` to `dependency`
+fn merge_dependency(dependency: &mut Value, other: Value) {
+    for key in ["dependsOn", "provides"] {
+        let Some(items) = other.get(key).and_then(|d| d.as_array()) else {
+            continue;
+        };
+        let target = dependency.as_object_mut().map(|object| {
+            object
+                .entry(key)
+                .or_insert_with(|| Value::Array(Vec::new()))
+        });
+        if let Some(Value::Array(existing)) = target {
+            for item in items {
+                if !existing.contains(item) && item != &dependency_ref(&other) {
+                    existing.push(item.clone());
+                }
+            }
+        }
+    }
+}
+
+fn dependency_ref(dependency: &Value) -> Value {
+    dependency.get("ref").cloned().unwrap_or_default()
+}
+
+/// Checks the relationships, described elements, VEX assessments and root
+/// elements of an SPDX document in any layout
+fn check_spdx(doc: &mut Value, targets: &Targets, checker: &mut Checker) {
+    // SPDX 2.3 and the simple layout
+    if let Some(relationships) = doc.get_mut("relationships").and_then(|r| r.as_array_mut()) {
+        relationships.retain_mut(|relationship| {
+            let mut rewritten = false;
+            for key in ["spdxElementId", "relatedSpdxElement"] {
+                let Some(reference) = relationship.get(key).and_then(|r| r.as_str()) else {
+                    continue;
+                };
+                match checker.fix(&format!("relationships.{}", key), reference, targets) {
+                    Fix::Keep => {}
+                    Fix::Replace(new) => {
+                        relationship[key] = Value::String(new);
+                        rewritten = true;
+                    }
+                    Fix::Remove => return false,
+                }
+            }
+            // A file rewritten to the package containing it
+            !(rewritten && relationship["spdxElementId"] == relationship["relatedSpdxElement"])
+        });
+    }
+    if let Some(described) = doc
+        .get_mut("documentDescribes")
+        .and_then(|d| d.as_array_mut())
+    {
+        checker.fix_list("documentDescribes", described, None, targets);
+    }
+
+    // SPDX 3 relationships and assessments are elements; the simple layout
+    // keeps assessments in `relationships`
+    for key in ["elements", "relationships", "@graph"] {
+        let Some(elements) = doc.get_mut(key).and_then(|e| e.as_array_mut()) else {
+            continue;
+        };
+        elements.retain_mut(|element| {
+            let from = match element.get("from").and_then(|f| f.as_str()) {
+                Some(reference) => {
+                    match checker.fix(&format!("{}.from", key), reference, targets) {
+                        Fix::Keep => Some(reference.to_string()),
+                        Fix::Replace(new) => {
+                            element["from"] = Value::String(new.clone());
+                            Some(new)
+                        }
+                        Fix::Remove => return false,
+                    }
+                }
+                None => None,
+            };
+            if let Some(to) = element.get_mut("to").and_then(|t| t.as_array_mut()) {
+                checker.fix_list(&format!("{}.to", key), to, from.as_deref(), targets);
+                if to.is_empty() {
+                    return false;
+                }
+            }
+            if let Some(roots) = element
+                .get_mut("rootElement")
+                .and_then(|r| r.as_array_mut())
+            {
+                checker.fix_list(&format!("{}.rootElement", key), roots, None, targets);
+            }
+            true
+        });
+    }
+    if let Some(roots) = doc.get_mut("rootElement").and_then(|r| r.as_array_mut()) {
+        checker.fix_list("rootElement", roots, None, targets);
+    }
+}
+
+/// Checks the refs of the converted document at `output` and, for a BOM
+/// with split-off vulnerabilities, of the VEX document at `vex` against
+/// the BOM. Documents whose refs were pruned or rewritten are written back
+/// in place. Each document is read into memory whole.
+///
+/// With [`DanglingRefPolicy::Fail`] nothing is written back; the caller
+/// removes the output.
+pub fn check_files(
+    output: &Path,
+    vex: Option<&Path>,
+    policy: DanglingRefPolicy,
+    containers: &Containers,
+    fidelity: &FidelityReport,
+) -> Result<IntegrityReport, ConverterError> {
+    info!("Checking referential integrity ({})...", policy.as_str());
+    let mut checker = Checker {
+        policy,
+        containers,
+        fidelity,
+        report: IntegrityReport {
+            rewrite_skipped: policy == DanglingRefPolicy::Rewrite && containers.is_skipped(),
+            ..IntegrityReport::default()
+        },
+        dangling: Vec::new(),
+    };
+
+    let mut document = crate::formats::read_json_value(output)?;
+    let is_cdx = document.get("bomFormat").and_then(|f| f.as_str()) == Some("CycloneDX");
+    let targets = if is_cdx {
+        Targets::of_cdx(&document)
+    } else {
+        Targets::of_spdx(&document)
+    };
+    if is_cdx {
+        check_cdx(&mut document, &targets, &mut checker);
+    } else {
+        check_spdx(&mut document, &targets, &mut checker);
+    }
+    if checker.report.changes() > 0 {
+        write_json(output, &document)?;
+    }
+
+    if let Some(vex) = vex.filter(|_| is_cdx) {
+        let mut vex_document = crate::formats::read_json_value(vex)?;
+        let changed = checker.report.changes();
+        check_cdx(&mut vex_document, &targets, &mut checker);
+        if checker.report.changes() > changed {
+            write_json(vex, &vex_document)?;
+        }
+    }
+
+    if !checker.dangling.is_empty() {
+        let mut listed = checker.dangling[..checker.dangling.len().min(MAX_LISTED)].join(", ");
+        if checker.dangling.len() > MAX_LISTED {
+            listed.push_str(", ...");
+        }
+        return Err(ConverterError::Validation(format!(
+            "{} dangling ref(s) in the converted output: {}",
+            checker.dangling.len(),
+            listed
+        )));
+    }
+    if checker.report.rewrite_skipped {
+        fidelity.record_count("dangling_ref.rewrite_skipped", checker.report.pruned);
+    }
+    checker.report.log();
+    Ok(checker.report)
+}
+
+fn array(value: Option<&Value>) -> impl Iterator<Item = &Value> {
+    value.and_then(|v| v.as_array()).into_iter().flatten()
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), ConverterError> {
+    let file = File::create(path).map_err(|e| {
+        ConverterError::Io(e, format!("Failed to rewrite output: {}", path.display()))
+    })?;
+    serde_json::to_writer_pretty(BufWriter::new(file), value).map_err(|e| {
+        ConverterError::SerializationError(format!("Failed to write JSON output: {}", e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SERIAL: &str = "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79";
+
+    fn checker<'a>(
+        policy: DanglingRefPolicy,
+        containers: &'a Containers,
+        fidelity: &'a FidelityReport,
+    ) -> Checker<'a> {
+        Checker {
+            policy,
+            containers,
+            fidelity,
+            report: IntegrityReport::default(),
+            dangling: Vec::new(),
+        }
+    }
+
+    fn get_test_bom() -> Value {
+        json!({
+            "bomFormat": "CycloneDX",
+            "serialNumber": SERIAL,
+            "components": [
+                { "bom-ref": "app" },
+                { "bom-ref": "lib" }
+            ],
+            "dependencies": [
+                { "ref": "app", "dependsOn": ["lib", "app-main.c"] },
+                { "ref": "app-main.c", "dependsOn": ["lib", "license-mit"] }
+            ],
+            "vulnerabilities": [
+                {
+                    "id": "CVE-1",
+                    "affects": [
+                        { "ref": format!("{}#app-main.c", SERIAL) },
+                        { "ref": "urn:uuid:00000000-0000-0000-0000-000000000000#other" }
+                    ]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_prune_cdx() {
+        let mut bom = get_test_bom();
+        let targets = Targets::of_cdx(&bom);
+        let (containers, fidelity) = (Containers::new(), FidelityReport::new());
+        let mut checker = checker(DanglingRefPolicy::Prune, &containers, &fidelity);
+        check_cdx(&mut bom, &targets, &mut checker);
+
+        assert_eq!(
+            bom["dependencies"],
+            json!([{ "ref": "app", "dependsOn": ["lib"] }])
+        );
+        // Links into other BOMs are not checked
+        assert_eq!(
+            bom["vulnerabilities"][0]["affects"],
+            json!([{ "ref": "urn:uuid:00000000-0000-0000-0000-000000000000#other" }])
+        );
+        assert_eq!(checker.report.dangling, 3);
+        assert_eq!(checker.report.pruned, 3);
+        assert!(!fidelity.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_cdx_to_container() {
+        let mut bom = get_test_bom();
+        let targets = Targets::of_cdx(&bom);
+        let (containers, fidelity) = (Containers::new(), FidelityReport::new());
+        containers.add("app-main.c", "app");
+        let mut checker = checker(DanglingRefPolicy::Rewrite, &containers, &fidelity);
+        check_cdx(&mut bom, &targets, &mut checker);
+
+        // The file's entry is merged into its package's, without the
+        // package depending on itself; the license has no container
+        assert_eq!(
+            bom["dependencies"],
+            json!([{ "ref": "app", "dependsOn": ["lib"] }])
+        );
+        assert_eq!(
+            bom["vulnerabilities"][0]["affects"][0]["ref"],
+            format!("{}#app", SERIAL)
+        );
+        assert_eq!(checker.report.rewritten, 3);
+        assert_eq!(checker.report.pruned, 1);
+    }
+
+    #[test]
+    fn test_rewrite_without_containers_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.cdx.json");
+        write_json(&output, &get_test_bom()).unwrap();
+        let (containers, fidelity) = (Containers::new(), FidelityReport::new());
+        containers.add("app-main.c", "app");
+        containers.skip();
+
+        let report = check_files(
+            &output,
+            None,
+            DanglingRefPolicy::Rewrite,
+            &containers,
+            &fidelity,
+        )
+        .unwrap();
+        assert!(report.rewrite_skipped);
+        assert_eq!((report.rewritten, report.pruned), (0, 3));
+        let skipped = fidelity
+            .entries()
+            .into_iter()
+            .find(|entry| entry.path == "dangling_ref.rewrite_skipped")
+            .unwrap();
+        assert_eq!(skipped.count, 3);
+    }
+
+    #[test]
+    fn test_fail_keeps_document() {
+        let mut bom = get_test_bom();
+        let original = bom.clone();
+        let targets = Targets::of_cdx(&bom);
+        let (containers, fidelity) = (Containers::new(), FidelityReport::new());
+        let mut checker = checker(DanglingRefPolicy::Fail, &containers, &fidelity);
+        check_cdx(&mut bom, &targets, &mut checker);
+        assert_eq!(bom, original);
+        // Including the refs inside the entry of the missing file
+        assert_eq!(checker.dangling.len(), 4);
+    }
+
+    #[test]
+    fn test_prune_spdx() {
+        let mut doc = json!({
+            "spdxVersion": "SPDX-2.3",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "packages": [{ "SPDXID": "SPDXRef-app" }],
+            "documentDescribes": ["SPDXRef-app", "SPDXRef-gone"],
+            "relationships": [
+                { "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-app" },
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-gone" },
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "NOASSERTION" },
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "DocumentRef-ext:SPDXRef-lib" }
+            ]
+        });
+        let targets = Targets::of_spdx(&doc);
+        let (containers, fidelity) = (Containers::new(), FidelityReport::new());
+        let mut checker = checker(DanglingRefPolicy::Prune, &containers, &fidelity);
+        check_spdx(&mut doc, &targets, &mut checker);
+
+        assert_eq!(doc["documentDescribes"], json!(["SPDXRef-app"]));
+        assert_eq!(doc["relationships"].as_array().unwrap().len(), 3);
+        assert_eq!(checker.report.pruned, 2);
+    }
+
+    #[test]
+    fn test_prune_spdx3_elements() {
+        let mut doc = json!({
+            "@graph": [
+                { "type": "software_Package", "spdxId": "https://x/doc#SPDXRef-app" },
+                {
+                    "type": "Relationship",
+                    "spdxId": "https://x/doc#SPDXRef-Relationship-1",
+                    "from": "https://x/doc#SPDXRef-app",
+                    "to": ["https://x/doc#SPDXRef-gone"]
+                },
+                {
+                    "type": "Relationship",
+                    "spdxId": "https://x/doc#SPDXRef-Relationship-2",
+                    "from": "https://x/doc#SPDXRef-gone",
+                    "to": ["https://x/doc#SPDXRef-app"]
+                }
+            ]
+        });
+        let targets = Targets::of_spdx(&doc);
+        let (containers, fidelity) = (Containers::new(), FidelityReport::new());
+        let mut checker = checker(DanglingRefPolicy::Prune, &containers, &fidelity);
+        check_spdx(&mut doc, &targets, &mut checker);
+
+        assert_eq!(doc["@graph"].as_array().unwrap().len(), 1);
+        assert_eq!(checker.report.pruned, 2);
+    }
+
+    #[test]
+    fn test_containers_resolve() {
+        let containers = Containers::new();
+        containers.add("file", "dir");
+        containers.add("dir", "pkg");
+        containers.add("file", "other");
+        containers.add("a", "b");
+        containers.add("b", "a");
+        let exists = |id: &str| id == "pkg";
+        assert_eq!(containers.resolve("file", exists), Some("pkg".to_string()));
+        assert_eq!(containers.resolve("a", exists), None);
+        assert_eq!(containers.resolve("pkg", exists), None);
+    }
+}
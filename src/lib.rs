@@ -2,27 +2,46 @@
 //!
 //! This crate contains the core logic for the bi-directional, streaming
 //! conversion between CycloneDX and SPDX formats.
+//!
+//! Only the cross-format directions (`cdx-to-spdx`, `spdx-to-cdx`) stream.
+//! Same-family re-encoding (`cdx-to-cdx`, `spdx-to-spdx`) loads the whole
+//! document into a `serde_json::Value`, so its memory use grows with the
+//! input size, like merge and diff.
 
 // Make modules public within the crate but not necessarily public API
+pub mod bom_link;
 pub mod cdx_version;
 pub mod converter_cdx_to_spdx;
 pub mod converter_spdx_to_cdx;
 pub mod diff;
 pub mod errors;
+pub mod fidelity;
 pub mod formats;
+pub mod id_map;
+pub mod integrity;
+pub mod license;
+pub mod line_sort;
 pub mod merge;
 pub mod models_cdx;
 pub mod models_spdx;
+pub mod policy;
 pub mod progress;
+pub mod relationship_index;
+pub mod reproducible;
+pub mod roundtrip;
 pub mod schema;
+pub mod spdx_version;
+pub mod transcode;
 pub mod validation;
 pub mod version_detection;
+pub mod vex;
 pub mod xml_validator;
 
 use cdx_version::CdxVersion;
 use clap::ValueEnum;
 use errors::ConverterError;
 use log::info;
+use spdx_version::SpdxVersion;
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
@@ -35,6 +54,38 @@ pub enum ConversionDirection {
     CdxToSpdx,
     /// Convert from SPDX 3.0.1 to CycloneDX 1.6
     SpdxToCdx,
+    /// Re-encode CycloneDX (JSON/XML, spec version up- or downgrade)
+    CdxToCdx,
+    /// Re-encode SPDX (simple JSON/JSON-LD, JSON/tag-value)
+    SpdxToSpdx,
+}
+
+impl ConversionDirection {
+    /// Whether the input is CycloneDX
+    pub fn reads_cdx(&self) -> bool {
+        matches!(
+            self,
+            ConversionDirection::CdxToSpdx | ConversionDirection::CdxToCdx
+        )
+    }
+
+    /// Whether the output is CycloneDX
+    pub fn writes_cdx(&self) -> bool {
+        matches!(
+            self,
+            ConversionDirection::SpdxToCdx | ConversionDirection::CdxToCdx
+        )
+    }
+
+    /// CLI name of the direction
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConversionDirection::CdxToSpdx => "cdx-to-spdx",
+            ConversionDirection::SpdxToCdx => "spdx-to-cdx",
+            ConversionDirection::CdxToCdx => "cdx-to-cdx",
+            ConversionDirection::SpdxToSpdx => "spdx-to-spdx",
+        }
+    }
 }
 
 /// Top-level configuration for a conversion run.
@@ -47,9 +98,33 @@ pub struct Config {
     pub output_format: Option<formats::Format>,
     pub validate: bool,
     pub split_vex: bool,
+    /// Where to write the split-off VEX document (default: next to the
+    /// output, `out.cdx.json` -> `out.vex.json`); `.xml` writes XML
+    pub vex_output: Option<PathBuf>,
     pub packages_only: bool,
+    /// Rebuild nested components from `CONTAINS` relationships (SPDX -> CDX)
+    pub nest_components: bool,
     pub skip_jsonld_validation: bool,
     pub output_version: CdxVersion,
+    /// SPDX output version; `None` means 3.0.1, or the input's version for
+    /// spdx-to-spdx
+    pub spdx_version: Option<SpdxVersion>,
+    pub spdx_serialization: formats::spdx::SpdxSerialization,
+    /// Where to write the conversion fidelity report (JSON), if anywhere
+    pub fidelity_report: Option<PathBuf>,
+    /// Derive serial numbers, namespaces and timestamps from the input
+    /// instead of randomness and the clock, and sort dependencies and
+    /// relationships, so the same input always converts to the same bytes
+    pub reproducible: bool,
+    /// ID-map file to take SPDX ID / bom-ref mappings from and write them
+    /// back to, so identifiers stay the same across runs
+    pub id_map: Option<PathBuf>,
+    /// What to do with refs to elements missing from the output; `None`
+    /// skips the integrity check
+    pub dangling_refs: Option<integrity::DanglingRefPolicy>,
+    /// Relationships the SPDX -> CDX index holds in memory before spilling
+    /// sorted runs to disk; `None` keeps the whole index in memory
+    pub spill_threshold: Option<usize>,
 }
 
 /// The main entry point for the conversion logic.
@@ -75,55 +150,106 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
     info!("  Output format: {:?}", output_format);
 
     // Check for unsupported format combinations
-    if input_format == formats::Format::Xml && config.direction == ConversionDirection::SpdxToCdx {
+    if input_format == formats::Format::Xml && !config.direction.reads_cdx() {
         return Err(ConverterError::UnsupportedFormat(
             "SPDX XML input is not supported (SPDX 3.0+ uses JSON-LD, not XML)".to_string(),
         ));
     }
 
-    if output_format == formats::Format::Xml && config.direction == ConversionDirection::CdxToSpdx {
+    if output_format == formats::Format::Xml && !config.direction.writes_cdx() {
         return Err(ConverterError::UnsupportedFormat(
             "SPDX XML output is not supported (SPDX 3.0+ uses JSON-LD, not XML)".to_string(),
         ));
     }
 
-    // --- 1. Validation (Optional) ---
-    if config.validate {
-        let schema_start = Instant::now();
-        info!("Running pre-validation...");
-        let schema_str = match config.direction {
-            ConversionDirection::CdxToSpdx => {
-                // We are reading CDX, so validate against CDX schema
-                include_str!("../schemas/cdx_1.6.schema.json")
-            }
-            ConversionDirection::SpdxToCdx => {
-                // We are reading SPDX, so validate against SPDX 3.0.1 schema
-                // Note: SPDX 3.0.1 schema is strict and may reject files that
-                // convert successfully. Use --validate flag judiciously.
-                include_str!("../schemas/spdx_3.0.1.schema.json")
-            }
-        };
+    if input_format == formats::Format::TagValue && config.direction.reads_cdx() {
+        return Err(ConverterError::UnsupportedFormat(
+            "Tag-value input is SPDX-only; use an spdx-to-* direction".to_string(),
+        ));
+    }
 
-        schema::validate_json_schema(
-            schema_str,
-            &config.input_file,
-            config.skip_jsonld_validation,
-        )?;
-        info!(
-            "Validation passed successfully. (Took {:.2?})",
-            schema_start.elapsed()
-        );
+    if output_format == formats::Format::TagValue && config.direction.writes_cdx() {
+        return Err(ConverterError::UnsupportedFormat(
+            "Tag-value output is SPDX-only; use a *-to-spdx direction".to_string(),
+        ));
+    }
+
+    // Tag-value only exists for SPDX 2.x, so it always implies 2.3
+    let spdx_version = if output_format == formats::Format::TagValue {
+        SpdxVersion::V2_3
     } else {
-        info!("Skipping pre-validation.");
+        config.spdx_version.unwrap_or_default()
+    };
+
+    if config.direction == ConversionDirection::CdxToSpdx
+        && spdx_version == SpdxVersion::V2_3
+        && config.spdx_serialization == formats::spdx::SpdxSerialization::JsonLd
+    {
+        return Err(ConverterError::Config(
+            "JSON-LD serialization requires SPDX 3.0.1 output".to_string(),
+        ));
     }
 
-    // --- 2. Handle Format Conversion ---
-    // If XML input, convert to JSON first (to temp file)
-    // If XML output needed, we'll convert from JSON at the end
+    // The split-off VEX document must not replace either document
+    let vex_output = if config.split_vex && config.direction == ConversionDirection::SpdxToCdx {
+        let path = config
+            .vex_output
+            .clone()
+            .unwrap_or_else(|| converter_spdx_to_cdx::default_vex_path(&config.output_file));
+        if path == config.output_file || path == config.input_file {
+            return Err(ConverterError::Config(format!(
+                "VEX output {} would overwrite the {} file; use --vex-output",
+                path.display(),
+                if path == config.output_file {
+                    "output"
+                } else {
+                    "input"
+                }
+            )));
+        }
+        let format = formats::Format::from_extension(&path).unwrap_or(formats::Format::Json);
+        if format == formats::Format::TagValue {
+            return Err(ConverterError::UnsupportedFormat(
+                "VEX output must be CycloneDX JSON or XML".to_string(),
+            ));
+        }
+        info!("  VEX output: {} ({:?})", path.display(), format);
+        Some((path, format))
+    } else {
+        None
+    };
+
+    // Serial numbers, namespaces and timestamps of the output
+    let identity = if config.reproducible {
+        let settings = format!(
+            "{} {} {} {} packages-only={} nest-components={} split-vex={}",
+            config.direction.as_str(),
+            config.output_version.as_str(),
+            spdx_version.as_str(),
+            config.spdx_serialization.as_str(),
+            config.packages_only,
+            config.nest_components,
+            vex_output.is_some(),
+        );
+        info!("  Reproducible output ({})", settings);
+        reproducible::OutputIdentity::reproducible(&config.input_file, &settings)?
+    } else {
+        reproducible::OutputIdentity::Random
+    };
+
+    // SPDX ID <-> bom-ref mappings, seeded from an earlier run
+    let ids = match &config.id_map {
+        Some(path) => id_map::IdMap::load(path)?,
+        None => id_map::IdMap::new(),
+    };
+
+    // --- 1. Handle Format Conversion ---
+    // If XML or tag-value input, convert to JSON first (to temp file)
+    // If XML or tag-value output needed, we'll convert from JSON at the end
     let working_input_path: PathBuf;
     let temp_input_file: Option<PathBuf>;
 
-    if input_format == formats::Format::Xml && config.direction == ConversionDirection::CdxToSpdx {
+    if input_format == formats::Format::Xml {
         info!("Converting XML input to JSON for processing...");
         let temp_dir = std::env::temp_dir();
         let temp_json = temp_dir.join(format!(
@@ -147,6 +273,25 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
             ConverterError::SerializationError(format!("Failed to write temp JSON: {}", e))
         })?;
 
+        working_input_path = temp_json.clone();
+        temp_input_file = Some(temp_json);
+    } else if input_format == formats::Format::TagValue {
+        info!("Converting tag-value input to JSON for processing...");
+        let temp_json = std::env::temp_dir().join(format!(
+            "sbom-converter-tv-input-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+
+        let tv_file = File::open(&config.input_file)
+            .map_err(|e| ConverterError::Io(e, "Failed to open tag-value input".to_string()))?;
+        let spdx_doc = formats::spdx::tagvalue::parse(BufReader::new(tv_file))?;
+
+        let json_file = File::create(&temp_json)
+            .map_err(|e| ConverterError::Io(e, "Failed to create temp JSON".to_string()))?;
+        serde_json::to_writer(BufWriter::new(json_file), &spdx_doc).map_err(|e| {
+            ConverterError::SerializationError(format!("Failed to write temp JSON: {}", e))
+        })?;
+
         working_input_path = temp_json.clone();
         temp_input_file = Some(temp_json);
     } else {
@@ -154,11 +299,46 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
         temp_input_file = None;
     }
 
+    // --- 2. Validation (Optional) ---
+    if config.validate {
+        let schema_start = Instant::now();
+        info!("Running pre-validation...");
+        match config.direction {
+            ConversionDirection::CdxToSpdx | ConversionDirection::CdxToCdx => {
+                // We are reading CDX, so validate against CDX schema
+                schema::validate_json_schema(
+                    include_str!("../schemas/cdx_1.6.schema.json"),
+                    &working_input_path,
+                    config.skip_jsonld_validation,
+                )?;
+            }
+            ConversionDirection::SpdxToCdx | ConversionDirection::SpdxToSpdx => {
+                // We are reading SPDX, so validate against the SPDX 2.3 or
+                // 3.0.1 schema. Note: the SPDX 3.0.1 schema is strict and may
+                // reject files that convert successfully. Use --validate
+                // flag judiciously.
+                schema::validate_spdx_json_schema(
+                    &working_input_path,
+                    config.skip_jsonld_validation,
+                )?;
+            }
+        }
+        info!(
+            "Validation passed successfully. (Took {:.2?})",
+            schema_start.elapsed()
+        );
+    } else {
+        info!("Skipping pre-validation.");
+    }
+
     let working_output_path: PathBuf;
     let temp_output_file: Option<PathBuf>;
 
-    if output_format == formats::Format::Xml {
-        info!("Will convert output to XML after processing...");
+    if output_format == formats::Format::Xml || output_format == formats::Format::TagValue {
+        info!(
+            "Will convert output to {:?} after processing...",
+            output_format
+        );
         let temp_dir = std::env::temp_dir();
         let temp_json = temp_dir.join(format!(
             "sbom-converter-xml-output-{}.json",
@@ -171,6 +351,18 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
         temp_output_file = None;
     }
 
+    // XML VEX output is converted from JSON at the end as well
+    let working_vex_path = vex_output.as_ref().map(|(path, format)| {
+        if *format == formats::Format::Xml {
+            std::env::temp_dir().join(format!(
+                "sbom-converter-vex-output-{}.json",
+                uuid::Uuid::new_v4()
+            ))
+        } else {
+            path.clone()
+        }
+    });
+
     // --- 3. File Handling ---
     let input_file = File::open(&working_input_path)
         .map_err(|e| ConverterError::Io(e, "Failed to open input file".to_string()))?;
@@ -186,6 +378,8 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
 
     // Create progress tracker (reports every 1000 elements)
     let progress = progress::ProgressTracker::new(1000);
+    let fidelity = fidelity::FidelityReport::new();
+    let containers = integrity::Containers::new();
 
     match config.direction {
         ConversionDirection::CdxToSpdx => {
@@ -198,8 +392,14 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
             converter_cdx_to_spdx::convert_cdx_to_spdx(
                 input_reader,
                 &mut output_writer,
+                &working_input_path,
                 &temp_file_path,
                 progress.clone(),
+                spdx_version,
+                config.spdx_serialization,
+                fidelity.clone(),
+                &identity,
+                &ids,
             )?;
 
             // Clean up temp file
@@ -216,39 +416,105 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
                 &working_input_path,
                 progress.clone(),
                 config.packages_only,
-                config.split_vex,
+                config.nest_components,
+                working_vex_path.as_deref(),
                 config.output_version,
+                fidelity.clone(),
+                &identity,
+                &ids,
+                &containers,
+                config.spill_threshold,
+            )?;
+        }
+        ConversionDirection::CdxToCdx => {
+            // Same-family conversion works on the whole document: memory
+            // grows with the input, unlike the streaming directions above
+            let bom: serde_json::Value = serde_json::from_reader(input_reader)
+                .map_err(|e| ConverterError::JsonParse(e.to_string()))?;
+            let bom = transcode::transcode_cdx(bom, config.output_version, &fidelity)?;
+            write_json(&mut output_writer, &bom)?;
+        }
+        ConversionDirection::SpdxToSpdx => {
+            let doc: serde_json::Value = serde_json::from_reader(input_reader)
+                .map_err(|e| ConverterError::JsonParse(e.to_string()))?;
+            let doc = transcode::transcode_spdx(
+                doc,
+                config.spdx_version,
+                config.spdx_serialization,
+                &identity,
+                &fidelity,
             )?;
+            if output_format == formats::Format::TagValue
+                && !doc
+                    .get("spdxVersion")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|v| v.starts_with("SPDX-2"))
+            {
+                return Err(ConverterError::UnsupportedFormat(
+                    "Tag-value output requires an SPDX 2.x input for spdx-to-spdx".to_string(),
+                ));
+            }
+            write_json(&mut output_writer, &doc)?;
         }
     }
 
     progress.finish();
 
+    if let Some(policy) = config.dangling_refs
+        && let Err(e) = integrity::check_files(
+            &working_output_path,
+            working_vex_path.as_deref(),
+            policy,
+            &containers,
+            &fidelity,
+        )
+    {
+        // Leave no output behind that has the refs the policy rejected
+        let _ = std::fs::remove_file(&working_output_path);
+        if let Some(vex) = &working_vex_path {
+            let _ = std::fs::remove_file(vex);
+        }
+        return Err(e);
+    }
+
+    fidelity.log();
+    if let Some(report_path) = &config.fidelity_report {
+        fidelity.write_json(
+            report_path,
+            config.direction.as_str(),
+            &config.input_file,
+            &config.output_file,
+        )?;
+        info!("Fidelity report written to {}", report_path.display());
+    }
+    if let Some(path) = &config.id_map {
+        ids.write_json(path)?;
+    }
+
     info!(
         "Streaming conversion finished. (Took {:.2?})",
         conversion_start.elapsed()
     );
 
-    // --- 5. Handle XML Output (Convert from temp JSON) ---
+    // --- 5. Handle XML / Tag-Value Output (Convert from temp JSON) ---
     if let Some(temp_output) = temp_output_file {
-        info!("Converting JSON output to XML...");
+        info!("Converting JSON output to {:?}...", output_format);
 
-        // Read the standard CDX JSON output
+        // Read the standard JSON output
         let json_content = std::fs::read_to_string(&temp_output)
             .map_err(|e| ConverterError::Io(e, "Failed to read temp JSON output".to_string()))?;
 
         let json_value: serde_json::Value = serde_json::from_str(&json_content)
             .map_err(|e| ConverterError::ParseError(format!("Failed to parse temp JSON: {}", e)))?;
 
-        // Convert standard CDX JSON to CdxDocument for XML serialization
-        let cdx_doc = formats::cdx::converter::json_to_document(&json_value).map_err(|e| {
-            ConverterError::ParseError(format!("Failed to convert JSON to document: {}", e))
-        })?;
-
-        // Write as XML to final output
-        let xml_file = File::create(&config.output_file)
-            .map_err(|e| ConverterError::Io(e, "Failed to create XML output file".to_string()))?;
-        formats::cdx::xml::write(xml_file, &cdx_doc)?;
+        if output_format == formats::Format::TagValue {
+            let tv_file = File::create(&config.output_file).map_err(|e| {
+                ConverterError::Io(e, "Failed to create tag-value output file".to_string())
+            })?;
+            formats::spdx::tagvalue::write(BufWriter::new(tv_file), &json_value)?;
+        } else {
+            write_cdx_xml(&config.output_file, &json_value)?;
+        }
 
         // Clean up temp file
         if temp_output.exists() {
@@ -256,7 +522,19 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
         }
     }
 
-    // --- 6. Clean up XML input temp file ---
+    if let (Some((vex_path, formats::Format::Xml)), Some(temp_vex)) =
+        (&vex_output, &working_vex_path)
+    {
+        info!("Converting VEX output to XML...");
+        let json_content = std::fs::read_to_string(temp_vex)
+            .map_err(|e| ConverterError::Io(e, "Failed to read temp VEX output".to_string()))?;
+        let json_value: serde_json::Value = serde_json::from_str(&json_content)
+            .map_err(|e| ConverterError::ParseError(format!("Failed to parse temp VEX: {}", e)))?;
+        write_cdx_xml(vex_path, &json_value)?;
+        let _ = std::fs::remove_file(temp_vex);
+    }
+
+    // --- 6. Clean up converted input temp file ---
     if let Some(temp_input) = temp_input_file
         && temp_input.exists()
     {
@@ -270,3 +548,30 @@ pub fn run(config: Config) -> Result<(), ConverterError> {
     info!("Total execution time: {:.2?}", start_time.elapsed());
     Ok(())
 }
+
+/// Writes a CycloneDX JSON document as XML
+fn write_cdx_xml(
+    path: &std::path::Path,
+    json_value: &serde_json::Value,
+) -> Result<(), ConverterError> {
+    // Convert standard CDX JSON to CdxDocument for XML serialization
+    let cdx_doc = formats::cdx::converter::json_to_document(json_value).map_err(|e| {
+        ConverterError::ParseError(format!("Failed to convert JSON to document: {}", e))
+    })?;
+
+    let xml_file = File::create(path)
+        .map_err(|e| ConverterError::Io(e, "Failed to create XML output file".to_string()))?;
+    formats::cdx::xml::write(xml_file, &cdx_doc)
+}
+
+/// Writes a fully built document as pretty JSON and flushes the writer
+fn write_json<W: std::io::Write>(
+    writer: &mut BufWriter<W>,
+    value: &serde_json::Value,
+) -> Result<(), ConverterError> {
+    serde_json::to_writer_pretty(&mut *writer, value).map_err(|e| {
+        ConverterError::SerializationError(format!("Failed to write JSON output: {}", e))
+    })?;
+    std::io::Write::flush(writer)
+        .map_err(|e| ConverterError::Io(e, "Failed to flush output".to_string()))
+}
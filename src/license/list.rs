@@ -0,0 +1,59 @@
+//! The embedded SPDX license list.
+//!
+//! Lookups are case-insensitive, as SPDX license ids are, and return the id
+//! in its canonical case. `licenses.txt` and `exceptions.txt` are generated
+//! by `scripts/update-license-list.sh`.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Version of the SPDX License List the embedded ids come from
+pub const LICENSE_LIST_VERSION: &str = "3.24";
+
+const LICENSES: &str = include_str!("licenses.txt");
+const EXCEPTIONS: &str = include_str!("exceptions.txt");
+const DEPRECATED: &str = include_str!("deprecated.txt");
+
+/// Non-comment, non-empty lines of an embedded list
+fn entries(data: &'static str) -> impl Iterator<Item = &'static str> {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn by_lowercase(data: &'static str) -> HashMap<String, &'static str> {
+    entries(data).map(|id| (id.to_lowercase(), id)).collect()
+}
+
+/// Canonical form of a current (non-deprecated) license id
+pub fn license(id: &str) -> Option<&'static str> {
+    static INDEX: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+    INDEX
+        .get_or_init(|| by_lowercase(LICENSES))
+        .get(&id.to_lowercase())
+        .copied()
+}
+
+/// Canonical form of a license exception id
+pub fn exception(id: &str) -> Option<&'static str> {
+    static INDEX: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+    INDEX
+        .get_or_init(|| by_lowercase(EXCEPTIONS))
+        .get(&id.to_lowercase())
+        .copied()
+}
+
+/// Canonical form of a deprecated license id and the expression replacing
+/// it. `id` may end in `+`.
+pub fn deprecated(id: &str) -> Option<(&'static str, &'static str)> {
+    static INDEX: OnceLock<HashMap<String, (&'static str, &'static str)>> = OnceLock::new();
+    INDEX
+        .get_or_init(|| {
+            entries(DEPRECATED)
+                .filter_map(|line| line.split_once('\t'))
+                .map(|(id, replacement)| (id.to_lowercase(), (id, replacement.trim())))
+                .collect()
+        })
+        .get(&id.to_lowercase())
+        .copied()
+}
@@ -0,0 +1,541 @@
+//! SPDX license expressions.
+//!
+//! Parses license expressions (`AND`, `OR`, `WITH`, parentheses, the `+`
+//! operator and `LicenseRef-` ids), checks them against the embedded SPDX
+//! license list and re-serialises them in a canonical form: operators in
+//! upper case, ids in their canonical case, deprecated ids replaced and
+//! only the parentheses precedence requires.
+
+pub mod list;
+
+use crate::errors::ConverterError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed SPDX license expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// A license from the SPDX license list (or an unknown id), with `+`
+    License {
+        id: String,
+        or_later: bool,
+    },
+    /// `LicenseRef-…` or `DocumentRef-…:LicenseRef-…`
+    LicenseRef(String),
+    /// `license WITH exception`
+    With {
+        license: Box<Expression>,
+        exception: String,
+    },
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+}
+
+/// A problem with an otherwise well-formed expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseIssue {
+    UnknownLicense(String),
+    UnknownException(String),
+    Deprecated { id: String, replacement: String },
+}
+
+impl fmt::Display for LicenseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseIssue::UnknownLicense(id) => write!(f, "Unknown SPDX license id '{}'", id),
+            LicenseIssue::UnknownException(id) => {
+                write!(f, "Unknown SPDX license exception '{}'", id)
+            }
+            LicenseIssue::Deprecated { id, .. } => {
+                write!(f, "Deprecated SPDX license id '{}'", id)
+            }
+        }
+    }
+}
+
+impl Expression {
+    /// Upgrades deprecated ids, puts ids in canonical case, flattens nested
+    /// `AND`/`OR` and drops repeated operands
+    pub fn normalize(self) -> Expression {
+        match self {
+            Expression::License { id, or_later } => normalize_license(id, or_later),
+            Expression::LicenseRef(id) => Expression::LicenseRef(id),
+            Expression::With { license, exception } => {
+                let license = match license.normalize() {
+                    // A deprecated id that already carried an exception
+                    Expression::With { license, .. } => license,
+                    license => Box::new(license),
+                };
+                let exception = list::exception(&exception)
+                    .map(str::to_string)
+                    .unwrap_or(exception);
+                Expression::With { license, exception }
+            }
+            Expression::And(terms) => combine(terms, Expression::And, |e| match e {
+                Expression::And(terms) => Ok(terms),
+                other => Err(other),
+            }),
+            Expression::Or(terms) => combine(terms, Expression::Or, |e| match e {
+                Expression::Or(terms) => Ok(terms),
+                other => Err(other),
+            }),
+        }
+    }
+
+    /// Unknown and deprecated ids in the expression
+    pub fn issues(&self) -> Vec<LicenseIssue> {
+        let mut issues = Vec::new();
+        self.collect_issues(&mut issues);
+        issues
+    }
+
+    fn collect_issues(&self, issues: &mut Vec<LicenseIssue>) {
+        match self {
+            Expression::License { id, or_later } => {
+                let plus = format!("{}+", id);
+                let deprecated = or_later
+                    .then(|| list::deprecated(&plus))
+                    .flatten()
+                    .or_else(|| list::deprecated(id));
+                if let Some((id, replacement)) = deprecated {
+                    issues.push(LicenseIssue::Deprecated {
+                        id: id.to_string(),
+                        replacement: replacement.to_string(),
+                    });
+                } else if list::license(id).is_none() {
+                    issues.push(LicenseIssue::UnknownLicense(id.clone()));
+                }
+            }
+            Expression::LicenseRef(_) => {}
+            Expression::With { license, exception } => {
+                license.collect_issues(issues);
+                if list::exception(exception).is_none() {
+                    issues.push(LicenseIssue::UnknownException(exception.clone()));
+                }
+            }
+            Expression::And(terms) | Expression::Or(terms) => {
+                for term in terms {
+                    term.collect_issues(issues);
+                }
+            }
+        }
+    }
+}
+
+fn normalize_license(id: String, or_later: bool) -> Expression {
+    let plus = format!("{}+", id);
+    if or_later && let Some((_, replacement)) = list::deprecated(&plus) {
+        return parse_replacement(replacement);
+    }
+    if let Some((_, replacement)) = list::deprecated(&id) {
+        let replacement = parse_replacement(replacement);
+        return match replacement {
+            Expression::License { id, .. } if or_later => Expression::License { id, or_later },
+            replacement => replacement,
+        };
+    }
+    let id = list::license(&id).map(str::to_string).unwrap_or(id);
+    Expression::License { id, or_later }
+}
+
+/// Replacements come from the embedded list, so they always parse
+fn parse_replacement(replacement: &str) -> Expression {
+    replacement.parse().unwrap_or_else(|_| Expression::License {
+        id: replacement.to_string(),
+        or_later: false,
+    })
+}
+
+/// Normalizes the operands of one `AND`/`OR`, inlining operands that use
+/// the same operator and dropping repeats
+fn combine(
+    terms: Vec<Expression>,
+    build: fn(Vec<Expression>) -> Expression,
+    split: fn(Expression) -> Result<Vec<Expression>, Expression>,
+) -> Expression {
+    let mut combined: Vec<Expression> = Vec::new();
+    for term in terms {
+        let flattened = match split(term.normalize()) {
+            Ok(inner) => inner,
+            Err(single) => vec![single],
+        };
+        for term in flattened {
+            if !combined.contains(&term) {
+                combined.push(term);
+            }
+        }
+    }
+    if combined.len() == 1 {
+        combined.pop().unwrap()
+    } else {
+        build(combined)
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::License { id, or_later } => {
+                write!(f, "{}{}", id, if *or_later { "+" } else { "" })
+            }
+            Expression::LicenseRef(id) => write!(f, "{}", id),
+            Expression::With { license, exception } => {
+                write!(f, "{} WITH {}", license, exception)
+            }
+            Expression::And(terms) => {
+                for (i, term) in terms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    if matches!(term, Expression::Or(_)) {
+                        write!(f, "({})", term)?;
+                    } else {
+                        write!(f, "{}", term)?;
+                    }
+                }
+                Ok(())
+            }
+            Expression::Or(terms) => {
+                for (i, term) in terms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " OR ")?;
+                    }
+                    write!(f, "{}", term)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ConverterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |detail: String| {
+            ConverterError::ParseError(format!("Invalid license expression '{}': {}", s, detail))
+        };
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            pos: 0,
+        };
+        let expression = parser.parse_or().map_err(error)?;
+        match parser.next() {
+            None => Ok(expression),
+            Some(token) => Err(error(format!("unexpected '{}'", token))),
+        }
+    }
+}
+
+/// Parses and normalizes `expression` and returns its canonical form
+pub fn normalize(expression: &str) -> Result<String, ConverterError> {
+    Ok(expression.parse::<Expression>()?.normalize().to_string())
+}
+
+/// Canonical form of `expression`, or the expression as given when it
+/// does not parse
+pub fn canonical(expression: &str) -> String {
+    normalize(expression).unwrap_or_else(|_| expression.trim().to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Word(word) => write!(f, "{}", word),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(begin) = start.take() {
+                tokens.push(Token::Word(&s[begin..i]));
+            }
+            match c {
+                '(' => tokens.push(Token::Open),
+                ')' => tokens.push(Token::Close),
+                _ => {}
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(Token::Word(&s[begin..]));
+    }
+    tokens
+}
+
+/// Recursive-descent parser; `WITH` binds tighter than `AND`, which binds
+/// tighter than `OR`
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the operator `op`
+    fn operator(&mut self, op: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(op) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, String> {
+        let mut terms = vec![self.parse_and()?];
+        while self.operator("OR") {
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Expression::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, String> {
+        let mut terms = vec![self.parse_with()?];
+        while self.operator("AND") {
+            terms.push(self.parse_with()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Expression::And(terms)
+        })
+    }
+
+    fn parse_with(&mut self) -> Result<Expression, String> {
+        let license = self.parse_primary()?;
+        if !self.operator("WITH") {
+            return Ok(license);
+        }
+        if !matches!(
+            license,
+            Expression::License { .. } | Expression::LicenseRef(_)
+        ) {
+            return Err("WITH must follow a single license id".to_string());
+        }
+        match self.next() {
+            Some(Token::Word(exception)) if !is_operator(exception) => {
+                check_idstring(exception)?;
+                Ok(Expression::With {
+                    license: Box::new(license),
+                    exception: exception.to_string(),
+                })
+            }
+            Some(token) => Err(format!("expected an exception id, found '{}'", token)),
+            None => Err("expected an exception id after WITH".to_string()),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expression = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expression),
+                    Some(token) => Err(format!("expected ')', found '{}'", token)),
+                    None => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Word(word)) if !is_operator(word) => parse_id(word),
+            Some(token) => Err(format!("expected a license id, found '{}'", token)),
+            None => Err("expected a license id".to_string()),
+        }
+    }
+}
+
+fn is_operator(word: &str) -> bool {
+    ["AND", "OR", "WITH"]
+        .iter()
+        .any(|op| word.eq_ignore_ascii_case(op))
+}
+
+fn parse_id(word: &str) -> Result<Expression, String> {
+    if let Some((document, license)) = word.split_once(':') {
+        if !starts_with_ignore_case(document, "DocumentRef-")
+            || !starts_with_ignore_case(license, "LicenseRef-")
+        {
+            return Err(format!(
+                "'{}' is not a DocumentRef-…:LicenseRef-… reference",
+                word
+            ));
+        }
+        check_idstring(&document["DocumentRef-".len()..])?;
+        check_idstring(&license["LicenseRef-".len()..])?;
+        return Ok(Expression::LicenseRef(word.to_string()));
+    }
+    if starts_with_ignore_case(word, "LicenseRef-") {
+        check_idstring(&word["LicenseRef-".len()..])?;
+        return Ok(Expression::LicenseRef(word.to_string()));
+    }
+    let (id, or_later) = match word.strip_suffix('+') {
+        Some(id) => (id, true),
+        None => (word, false),
+    };
+    check_idstring(id)?;
+    Ok(Expression::License {
+        id: id.to_string(),
+        or_later,
+    })
+}
+
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len()
+        && s.is_char_boundary(prefix.len())
+        && s[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// SPDX ids consist of letters, digits, `.` and `-`
+fn check_idstring(id: &str) -> Result<(), String> {
+    if !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a valid license id", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precedence() {
+        let expression: Expression =
+            "MIT OR Apache-2.0 AND GPL-2.0-only WITH Classpath-exception-2.0"
+                .parse()
+                .unwrap();
+        let Expression::Or(terms) = &expression else {
+            panic!("expected OR at the top, got {:?}", expression);
+        };
+        assert_eq!(terms.len(), 2);
+        assert!(
+            matches!(&terms[1], Expression::And(and) if matches!(and[1], Expression::With { .. }))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for invalid in [
+            "",
+            "MIT AND",
+            "(MIT",
+            "MIT)",
+            "AND MIT",
+            "MIT Apache-2.0",
+            "(MIT OR BSD-3-Clause) WITH LLVM-exception",
+            "MIT/X11",
+            "LicenseRef-",
+        ] {
+            assert!(invalid.parse::<Expression>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let cases = [
+            ("mit", "MIT"),
+            ("apache-2.0 or mit", "Apache-2.0 OR MIT"),
+            ("GPL-2.0", "GPL-2.0-only"),
+            ("GPL-2.0+", "GPL-2.0-or-later"),
+            ("LGPL-2.1 AND (MIT)", "LGPL-2.1-only AND MIT"),
+            (
+                "GPL-2.0-with-classpath-exception",
+                "GPL-2.0-only WITH Classpath-exception-2.0",
+            ),
+            (
+                "Apache-2.0 WITH llvm-exception",
+                "Apache-2.0 WITH LLVM-exception",
+            ),
+            ("MIT AND (ISC AND MIT)", "MIT AND ISC"),
+            ("(MIT OR ISC) AND Zlib", "(MIT OR ISC) AND Zlib"),
+            ("MIT OR (ISC AND Zlib)", "MIT OR ISC AND Zlib"),
+            ("EPL-1.0+", "EPL-1.0+"),
+            ("LicenseRef-acme", "LicenseRef-acme"),
+            (
+                "DocumentRef-other:LicenseRef-acme",
+                "DocumentRef-other:LicenseRef-acme",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_canonical_keeps_unparsable() {
+        assert_eq!(canonical(" MIT/X11 "), "MIT/X11");
+    }
+
+    #[test]
+    fn test_issues() {
+        let expression: Expression =
+            "GPL-2.0+ OR Foo-1.0 OR MIT WITH Bar-exception OR LicenseRef-x"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            expression.issues(),
+            vec![
+                LicenseIssue::Deprecated {
+                    id: "GPL-2.0+".to_string(),
+                    replacement: "GPL-2.0-or-later".to_string(),
+                },
+                LicenseIssue::UnknownLicense("Foo-1.0".to_string()),
+                LicenseIssue::UnknownException("Bar-exception".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_license_list_version() {
+        assert!(include_str!("licenses.txt").starts_with(&format!(
+            "# License identifiers from the SPDX License List {} ",
+            list::LICENSE_LIST_VERSION
+        )));
+
+        // Ids added in recent list versions
+        for id in [
+            "Elastic-2.0",
+            "Sendmail",
+            "Spencer-94",
+            "GFDL-1.3-no-invariants-or-later",
+            "Hippocratic-2.1",
+            "MIT-Khronos-old",
+        ] {
+            assert_eq!(list::license(&id.to_lowercase()), Some(id));
+        }
+        let expression: Expression = "Elastic-2.0 OR Sendmail".parse().unwrap();
+        assert!(expression.issues().is_empty());
+    }
+}
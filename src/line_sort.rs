@@ -0,0 +1,215 @@
+//! External sort of one-record-per-line temp files.
+//!
+//! Lines are read in chunks of a bounded size, each chunk is sorted in
+//! memory and written out as a run, and the runs are merged back into the
+//! file, at most `MAX_FAN_IN` at a time, like the runs of the relationship
+//! index. A file that fits in one chunk is sorted in memory.
+
+use crate::errors::ConverterError;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+
+/// Runs merged at once, which bounds the number of open files
+const MAX_FAN_IN: usize = 64;
+
+/// Sorts the non-empty lines of the file at `path` in place by `key`, then
+/// by the line itself, holding at most `chunk_lines` lines in memory
+pub fn sort_lines<K, F>(path: &Path, chunk_lines: usize, key: F) -> Result<(), ConverterError>
+where
+    K: Ord,
+    F: Fn(&str) -> Result<K, ConverterError>,
+{
+    let chunk_lines = chunk_lines.max(1);
+    let file = File::open(path).map_err(|e| {
+        ConverterError::Io(e, format!("Failed to open {} for sorting", path.display()))
+    })?;
+
+    let mut runs = Runs::new(path);
+    let mut chunk: Vec<(K, String)> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        chunk.push((key(&line)?, line));
+        if chunk.len() >= chunk_lines {
+            runs.write(&mut chunk)?;
+        }
+    }
+
+    if runs.paths.is_empty() {
+        chunk.sort();
+        return write_sorted(path, chunk.into_iter().map(|(_, line)| line));
+    }
+    if !chunk.is_empty() {
+        runs.write(&mut chunk)?;
+    }
+    while runs.paths.len() > MAX_FAN_IN {
+        let batch: Vec<PathBuf> = runs.paths.drain(..MAX_FAN_IN).collect();
+        let merged = runs.next_path();
+        runs.paths.push(merged.clone());
+        let result = merge(&batch, &merged, &key);
+        for run in &batch {
+            let _ = fs::remove_file(run);
+        }
+        result?;
+    }
+    merge(&runs.paths, path, &key)
+}
+
+/// The runs of one sort, removed when it ends
+struct Runs<'a> {
+    path: &'a Path,
+    paths: Vec<PathBuf>,
+    created: usize,
+}
+
+impl<'a> Runs<'a> {
+    fn new(path: &'a Path) -> Self {
+        Runs {
+            path,
+            paths: Vec::new(),
+            created: 0,
+        }
+    }
+
+    fn next_path(&mut self) -> PathBuf {
+        self.created += 1;
+        PathBuf::from(format!("{}.run-{}", self.path.display(), self.created))
+    }
+
+    /// Sorts `chunk` and writes it out as a new run, emptying it
+    fn write<K: Ord>(&mut self, chunk: &mut Vec<(K, String)>) -> Result<(), ConverterError> {
+        chunk.sort();
+        let path = self.next_path();
+        self.paths.push(path.clone());
+        write_sorted(&path, chunk.drain(..).map(|(_, line)| line))
+    }
+}
+
+impl Drop for Runs<'_> {
+    fn drop(&mut self) {
+        for run in &self.paths {
+            if run.as_path() != self.path {
+                let _ = fs::remove_file(run);
+            }
+        }
+    }
+}
+
+fn write_sorted(path: &Path, lines: impl Iterator<Item = String>) -> Result<(), ConverterError> {
+    let file = File::create(path)
+        .map_err(|e| ConverterError::Io(e, format!("Failed to write sorted {}", path.display())))?;
+    let mut writer = BufWriter::new(file);
+    for line in lines {
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Merges sorted runs into one sorted file at `path`
+fn merge<K, F>(runs: &[PathBuf], path: &Path, key: &F) -> Result<(), ConverterError>
+where
+    K: Ord,
+    F: Fn(&str) -> Result<K, ConverterError>,
+{
+    let mut readers: Vec<Lines<BufReader<File>>> = Vec::with_capacity(runs.len());
+    for run in runs {
+        let file = File::open(run).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to open sort run: {}", run.display()))
+        })?;
+        readers.push(BufReader::new(file).lines());
+    }
+
+    // The next line of each run, by its key and the run's position
+    let mut heads = BinaryHeap::new();
+    let mut advance = |i: usize, heads: &mut BinaryHeap<_>| -> Result<(), ConverterError> {
+        if let Some(line) = readers[i].next() {
+            let line = line?;
+            heads.push(Reverse((key(&line)?, line, i)));
+        }
+        Ok(())
+    };
+    for i in 0..runs.len() {
+        advance(i, &mut heads)?;
+    }
+
+    let file = File::create(path)
+        .map_err(|e| ConverterError::Io(e, format!("Failed to write sorted {}", path.display())))?;
+    let mut writer = BufWriter::new(file);
+    while let Some(Reverse((_, line, i))) = heads.pop() {
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        advance(i, &mut heads)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn number(line: &str) -> Result<u64, ConverterError> {
+        line.split(' ')
+            .next()
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| ConverterError::JsonParse(format!("bad line: {}", line)))
+    }
+
+    /// Lines "<n> <i>" with `n` cycling through a few values out of order
+    fn get_test_lines(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| format!("{} {}", (i * 7) % 13, i))
+            .collect()
+    }
+
+    fn sorted_with(chunk_lines: usize, lines: &[String]) -> Vec<String> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lines.jsonl");
+        fs::write(&path, lines.join("\n\n")).unwrap();
+        sort_lines(&path, chunk_lines, number).unwrap();
+
+        // Only the sorted file is left
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_runs_merge_to_in_memory_order() {
+        let lines = get_test_lines(1000);
+        let in_memory = sorted_with(usize::MAX, &lines);
+        assert_eq!(in_memory.len(), 1000);
+        assert!(
+            in_memory
+                .windows(2)
+                .all(|w| (number(&w[0]).unwrap(), &w[0]) <= (number(&w[1]).unwrap(), &w[1]))
+        );
+
+        // Two runs, and more runs than are merged at once
+        assert_eq!(sorted_with(600, &lines), in_memory);
+        assert_eq!(sorted_with(7, &lines), in_memory);
+    }
+
+    #[test]
+    fn test_bad_line_is_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lines.jsonl");
+        fs::write(&path, "2 a\n1 b\nnot a number\n0 c\n").unwrap();
+
+        let result = sort_lines(&path, 2, number);
+        assert!(matches!(result, Err(ConverterError::JsonParse(_))));
+        // The runs written so far are removed
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -2,10 +2,15 @@
 
 use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
+use sbom_converter::bom_link::BomIndex;
 use sbom_converter::cdx_version::CdxVersion;
 use sbom_converter::errors::ConverterError;
 use sbom_converter::formats::Format;
+use sbom_converter::formats::spdx::SpdxSerialization;
+use sbom_converter::integrity::DanglingRefPolicy;
+use sbom_converter::spdx_version::SpdxVersion;
 use sbom_converter::validation::{ValidationIssue, validate_cdx, validate_spdx};
+use sbom_converter::vex::{VexFormat, VexOptions};
 use sbom_converter::{Config, ConversionDirection};
 use std::fs;
 use std::path::PathBuf;
@@ -30,7 +35,7 @@ struct Cli {
     #[arg(
         long,
         value_enum,
-        help = "Input file format (autodetect if not specified)",
+        help = "Input file format (autodetect if not specified); tag-value input is parsed into memory, not streamed",
         global = true
     )]
     input_format: Option<CliFormat>,
@@ -56,6 +61,15 @@ struct Cli {
     )]
     split_vex: bool,
 
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "split_vex",
+        help = "Where to write the split VEX file, .json or .xml (default: <output stem>.vex.json)",
+        global = true
+    )]
+    vex_output: Option<PathBuf>,
+
     #[arg(
         long,
         help = "Only convert packages/libraries, skip individual files (SPDX→CDX only)",
@@ -63,6 +77,13 @@ struct Cli {
     )]
     packages_only: bool,
 
+    #[arg(
+        long,
+        help = "Rebuild nested components from CONTAINS relationships (SPDX→CDX only)",
+        global = true
+    )]
+    nest_components: bool,
+
     #[arg(
         long,
         help = "Skip JSON-LD structural validation (SPDX JSON-LD only)",
@@ -78,6 +99,64 @@ struct Cli {
         global = true
     )]
     output_version: CliCdxVersion,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "SPDX output version [default: 3.0.1; spdx-to-spdx keeps the input's version] (ignored for CycloneDX output)",
+        global = true
+    )]
+    spdx_version: Option<CliSpdxVersion>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "SPDX output serialization (ignored for CycloneDX output)",
+        default_value_t = CliSpdxSerialization::default(),
+        global = true
+    )]
+    spdx_serialization: CliSpdxSerialization,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        help = "Write a JSON report of fields dropped during conversion (default: <output>.fidelity.json)",
+        global = true
+    )]
+    fidelity_report: Option<Option<PathBuf>>,
+
+    #[arg(
+        long,
+        help = "Derive serial numbers, namespaces and timestamps from the input for byte-identical output",
+        global = true
+    )]
+    reproducible: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "SPDX ID / bom-ref map to reuse and update, so identifiers stay stable across runs",
+        global = true
+    )]
+    id_map: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        help = "Check the output for refs to missing elements and prune them, rewrite them to their container, or fail",
+        global = true
+    )]
+    dangling_refs: Option<CliDanglingRefPolicy>,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        help = "Spill the SPDX to CDX relationship index to disk once it holds this many relationships (--dangling-refs still loads the whole output into memory, and rewrite prunes instead)",
+        global = true
+    )]
+    spill_threshold: Option<usize>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -96,7 +175,7 @@ enum Command {
         #[arg(
             long,
             value_enum,
-            help = "Input file format (autodetect if not specified)"
+            help = "Input file format (autodetect if not specified); tag-value input is parsed into memory, not streamed"
         )]
         input_format: Option<CliFormat>,
 
@@ -135,6 +214,14 @@ enum Command {
 
         #[arg(long, help = "Show detected format and version")]
         show_version: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            num_args = 1..,
+            help = "CycloneDX BOMs that BOM-Links in the input point into"
+        )]
+        linked_bom: Vec<PathBuf>,
     },
 
     /// Merge multiple SBOM files into one
@@ -188,6 +275,141 @@ enum Command {
         #[arg(long, help = "Show only differences, hide common elements")]
         diff_only: bool,
     },
+
+    /// Convert to the other format family and back, reporting what was lost
+    Roundtrip {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "PERCENT",
+            help = "Fail if more than this percentage of components, dependencies and vulnerabilities is lost or changed",
+            default_value_t = 0.0
+        )]
+        max_loss: f64,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Output format for round-trip report",
+            default_value = "text"
+        )]
+        report_format: OutputFormat,
+
+        #[arg(long, value_name = "FILE", help = "Write round-trip report to file")]
+        output: Option<PathBuf>,
+    },
+
+    /// Check component licenses against a license policy
+    Policy {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Policy file (TOML with a .toml extension, JSON otherwise)"
+        )]
+        policy: PathBuf,
+
+        #[arg(long, help = "Also exit with non-zero code if licenses need review")]
+        fail_on_review: bool,
+
+        #[arg(long, help = "Disable colored output")]
+        no_color: bool,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Output format for policy report",
+            default_value = "text"
+        )]
+        report_format: OutputFormat,
+    },
+
+    /// Convert VEX documents and apply them onto SBOMs
+    Vex {
+        #[command(subcommand)]
+        command: VexCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum VexCommand {
+    /// Convert a VEX document between CycloneDX, OpenVEX and CSAF
+    Convert {
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+
+        #[arg(long, value_enum, help = "Output VEX format")]
+        to: CliVexFormat,
+
+        #[arg(
+            long,
+            help = "Author of the OpenVEX document or publisher of the CSAF document (defaults to the first BOM author)"
+        )]
+        author: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Publisher namespace of CSAF output (defaults to the BOM supplier or manufacturer URL)"
+        )]
+        publisher_namespace: Option<String>,
+    },
+
+    /// Apply VEX statements from one or more documents onto a CycloneDX SBOM
+    Apply {
+        #[arg(long, value_name = "FILE")]
+        sbom: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            required = true,
+            num_args = 1..,
+            help = "VEX documents: CycloneDX, SPDX 3, OpenVEX or CSAF (newer statements win)"
+        )]
+        vex: Vec<PathBuf>,
+
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+
+        #[arg(long, help = "Disable colored output")]
+        no_color: bool,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Output format for the apply report",
+            default_value = "text"
+        )]
+        report_format: OutputFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CliVexFormat {
+    #[value(name = "cyclonedx")]
+    CycloneDx,
+    #[value(name = "openvex")]
+    OpenVex,
+    #[value(name = "csaf")]
+    Csaf,
+}
+
+impl From<CliVexFormat> for VexFormat {
+    fn from(cli: CliVexFormat) -> Self {
+        match cli {
+            CliVexFormat::CycloneDx => VexFormat::CycloneDx,
+            CliVexFormat::OpenVex => VexFormat::OpenVex,
+            CliVexFormat::Csaf => VexFormat::Csaf,
+        }
+    }
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -196,6 +418,9 @@ enum CliFormat {
     Json,
     #[value(name = "xml")]
     Xml,
+    /// SPDX 2.x tag-value, parsed into memory as a whole document
+    #[value(name = "tag-value")]
+    TagValue,
     #[value(name = "cdx")]
     Cdx,
     #[value(name = "spdx")]
@@ -213,7 +438,7 @@ enum OutputFormat {
 }
 
 /// CLI wrapper for CycloneDX version
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
 enum CliCdxVersion {
     #[value(name = "1.3")]
     V1_3,
@@ -221,6 +446,7 @@ enum CliCdxVersion {
     V1_4,
     #[value(name = "1.5")]
     V1_5,
+    #[default]
     #[value(name = "1.6")]
     V1_6,
     #[value(name = "1.7")]
@@ -239,20 +465,79 @@ impl From<CliCdxVersion> for CdxVersion {
     }
 }
 
-impl Default for CliCdxVersion {
-    fn default() -> Self {
-        Self::V1_6
+/// CLI wrapper for SPDX version
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum CliSpdxVersion {
+    #[value(name = "2.3")]
+    V2_3,
+    #[default]
+    #[value(name = "3.0.1")]
+    V3_0_1,
+}
+
+impl From<CliSpdxVersion> for SpdxVersion {
+    fn from(cli: CliSpdxVersion) -> Self {
+        match cli {
+            CliSpdxVersion::V2_3 => SpdxVersion::V2_3,
+            CliSpdxVersion::V3_0_1 => SpdxVersion::V3_0_1,
+        }
+    }
+}
+
+/// CLI wrapper for SPDX output serialization
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum CliSpdxSerialization {
+    /// Legacy flat `elements`/`relationships` JSON
+    #[default]
+    #[value(name = "simple")]
+    Simple,
+    /// SPDX 3.0.1 JSON-LD (`@context` + `@graph`)
+    #[value(name = "json-ld")]
+    JsonLd,
+}
+
+impl From<CliSpdxSerialization> for SpdxSerialization {
+    fn from(cli: CliSpdxSerialization) -> Self {
+        match cli {
+            CliSpdxSerialization::Simple => SpdxSerialization::Simple,
+            CliSpdxSerialization::JsonLd => SpdxSerialization::JsonLd,
+        }
+    }
+}
+
+/// CLI wrapper for the dangling-ref policy
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CliDanglingRefPolicy {
+    /// Remove dangling refs
+    Prune,
+    /// Point dangling refs at their containing element, else remove them
+    Rewrite,
+    /// Fail the conversion
+    Fail,
+}
+
+impl From<CliDanglingRefPolicy> for DanglingRefPolicy {
+    fn from(cli: CliDanglingRefPolicy) -> Self {
+        match cli {
+            CliDanglingRefPolicy::Prune => DanglingRefPolicy::Prune,
+            CliDanglingRefPolicy::Rewrite => DanglingRefPolicy::Rewrite,
+            CliDanglingRefPolicy::Fail => DanglingRefPolicy::Fail,
+        }
     }
 }
 
 #[derive(Debug, Clone, ValueEnum)]
 enum CliDirection {
+    /// CycloneDX to SPDX (streaming)
     #[value(name = "cdx-to-spdx")]
     CdxToSpdx,
+    /// SPDX to CycloneDX (streaming)
     #[value(name = "spdx-to-cdx")]
     SpdxToCdx,
+    /// Re-encode CycloneDX (loads the whole document into memory)
     #[value(name = "cdx-to-cdx")]
     CdxToCdx,
+    /// Re-encode SPDX (loads the whole document into memory)
     #[value(name = "spdx-to-spdx")]
     SpdxToSpdx,
 }
@@ -296,6 +581,7 @@ fn setup_logging(verbose: bool) {
 /// ## Reference
 /// - CycloneDX CLI validates JSON with JSON Schema and XML with XSD schemas
 /// - See: https://github.com/CycloneDX/cyclonedx-dotnet-library/blob/main/src/CycloneDX.Core/Xml/Validator.cs
+#[allow(clippy::too_many_arguments)]
 fn run_validate(
     input: PathBuf,
     format: Option<CliFormat>,
@@ -304,6 +590,7 @@ fn run_validate(
     output_format: OutputFormat,
     schema: bool,
     show_version: bool,
+    linked_boms: Vec<PathBuf>,
 ) -> Result<(), ConverterError> {
     use sbom_converter::formats::Format;
     use sbom_converter::version_detection::{detect_format, format_description};
@@ -339,6 +626,11 @@ fn run_validate(
             serde_json::from_str(&content)
                 .map_err(|e| ConverterError::ParseError(format!("Invalid JSON: {}", e)))?
         }
+        Format::TagValue => {
+            // For tag-value files: parse into the SPDX 2.3 JSON layout
+            sbom_converter::formats::spdx::tagvalue::parse(content.as_bytes())
+                .map_err(|e| ConverterError::ParseError(format!("Invalid tag-value: {}", e)))?
+        }
     };
 
     // Detect format and version from the JSON representation
@@ -371,7 +663,7 @@ fn run_validate(
         match fmt {
             CliFormat::Cdx => "cdx",
             CliFormat::Spdx => "spdx",
-            CliFormat::Json | CliFormat::Xml | CliFormat::Autodetect => {
+            CliFormat::Json | CliFormat::Xml | CliFormat::TagValue | CliFormat::Autodetect => {
                 // Auto-detect from content
                 if value.get("bomFormat").is_some() {
                     "cdx"
@@ -404,6 +696,29 @@ fn run_validate(
         validate_spdx(&value)
     };
 
+    // Check BOM-Links into the input itself and the linked BOMs
+    if detected_format == "cdx" {
+        let mut index = BomIndex::new();
+        index.add(&value, input.display().to_string());
+        for path in &linked_boms {
+            let bom = sbom_converter::formats::read_json_value(path)?;
+            if !index.add(&bom, path.display().to_string()) {
+                report.add_issue(
+                    ValidationIssue::warning(format!(
+                        "{} has no serialNumber, so nothing can link to it",
+                        path.display()
+                    ))
+                    .with_location(path.display().to_string()),
+                );
+            }
+        }
+        index.check(&value, &mut report);
+    } else if !linked_boms.is_empty() {
+        report.add_issue(ValidationIssue::info(
+            "--linked-bom only applies to CycloneDX input",
+        ));
+    }
+
     report.file_path = Some(input.display().to_string());
 
     // Run schema validation if requested
@@ -413,8 +728,8 @@ fn run_validate(
     //       XML files receive structural validation only (parsing + model validation)
     if schema {
         match input_format {
-            Format::Json => {
-                // JSON schema validation
+            Format::Json | Format::TagValue => {
+                // JSON schema validation (tag-value is checked in its JSON layout)
                 if let Some(schema_file) = detected.schema_file() {
                     let schema_path = std::path::PathBuf::from("schemas").join(schema_file);
                     if schema_path.exists() {
@@ -555,21 +870,31 @@ fn run_convert(
     _verbose: bool,
     validate: bool,
     split_vex: bool,
+    vex_output: Option<PathBuf>,
     packages_only: bool,
+    nest_components: bool,
     skip_jsonld_validation: bool,
     output_version: CliCdxVersion,
+    spdx_version: Option<CliSpdxVersion>,
+    spdx_serialization: CliSpdxSerialization,
+    fidelity_report: Option<Option<PathBuf>>,
+    reproducible: bool,
+    id_map: Option<PathBuf>,
+    dangling_refs: Option<CliDanglingRefPolicy>,
+    spill_threshold: Option<usize>,
 ) -> Result<(), ConverterError> {
     let direction = match direction {
         CliDirection::CdxToSpdx => ConversionDirection::CdxToSpdx,
         CliDirection::SpdxToCdx => ConversionDirection::SpdxToCdx,
-        CliDirection::CdxToCdx => ConversionDirection::CdxToSpdx, // Dummy for format conversion
-        CliDirection::SpdxToSpdx => ConversionDirection::SpdxToCdx, // Dummy for format conversion
+        CliDirection::CdxToCdx => ConversionDirection::CdxToCdx,
+        CliDirection::SpdxToSpdx => ConversionDirection::SpdxToSpdx,
     };
 
     // Convert CLI format options to internal Format type
     let input_format = input_format.map(|f| match f {
         CliFormat::Json => Format::Json,
         CliFormat::Xml => Format::Xml,
+        CliFormat::TagValue => Format::TagValue,
         CliFormat::Cdx | CliFormat::Spdx | CliFormat::Autodetect => {
             // Autodetect from file extension
             Format::from_extension(&input).unwrap_or(Format::Json)
@@ -579,12 +904,17 @@ fn run_convert(
     let output_format = output_format.map(|f| match f {
         CliFormat::Json => Format::Json,
         CliFormat::Xml => Format::Xml,
+        CliFormat::TagValue => Format::TagValue,
         CliFormat::Cdx | CliFormat::Spdx | CliFormat::Autodetect => {
             // Autodetect from file extension
             Format::from_extension(&output).unwrap_or(Format::Json)
         }
     });
 
+    // A bare --fidelity-report puts the report next to the output
+    let fidelity_report = fidelity_report
+        .map(|path| path.unwrap_or_else(|| sbom_converter::fidelity::default_report_path(&output)));
+
     let config = Config {
         input_file: input,
         output_file: output,
@@ -593,9 +923,18 @@ fn run_convert(
         output_format,
         validate,
         split_vex,
+        vex_output,
         packages_only,
+        nest_components,
         skip_jsonld_validation,
         output_version: output_version.into(), // Convert CLI version to library version
+        spdx_version: spdx_version.map(Into::into),
+        spdx_serialization: spdx_serialization.into(),
+        fidelity_report,
+        reproducible,
+        id_map,
+        dangling_refs: dangling_refs.map(Into::into),
+        spill_threshold,
     };
 
     sbom_converter::run(config)
@@ -621,12 +960,7 @@ fn run_merge(
         .unwrap_or_default();
 
     // Detect format from first input file
-    let first_file_content = std::fs::read_to_string(&inputs[0]).map_err(|e| {
-        ConverterError::Io(e, format!("Failed to read file: {}", inputs[0].display()))
-    })?;
-
-    let first_value: serde_json::Value = serde_json::from_str(&first_file_content)
-        .map_err(|e| ConverterError::ParseError(format!("Invalid JSON: {}", e)))?;
+    let first_value = sbom_converter::formats::read_json_value(&inputs[0])?;
 
     let detected_format = detect_format(&first_value);
     println!("  Detected format: {}", detected_format.description());
@@ -653,6 +987,7 @@ fn run_merge(
         Some(fmt) => match fmt {
             CliFormat::Json => Format::Json,
             CliFormat::Xml => Format::Xml,
+            CliFormat::TagValue => Format::TagValue,
             _ => Format::Json,
         },
         None => Format::from_extension(&output).unwrap_or(Format::Json),
@@ -692,6 +1027,22 @@ fn run_merge(
                 }
             }
         }
+        Format::TagValue => {
+            let is_spdx2 = merged_bom
+                .get("spdxVersion")
+                .and_then(|v| v.as_str())
+                .is_some_and(|v| v.starts_with("SPDX-2"));
+            if !is_spdx2 {
+                return Err(ConverterError::UnsupportedFormat(
+                    "Tag-value output is only supported when merging SPDX 2.x documents"
+                        .to_string(),
+                ));
+            }
+            sbom_converter::formats::spdx::tagvalue::write(
+                std::io::BufWriter::new(output_file),
+                &merged_bom,
+            )?;
+        }
     }
 
     println!("✓ Successfully merged {} files", inputs.len());
@@ -737,6 +1088,143 @@ fn run_diff(
     Ok(())
 }
 
+fn run_roundtrip(
+    input: PathBuf,
+    max_loss: f64,
+    report_format: OutputFormat,
+    output: Option<PathBuf>,
+    options: sbom_converter::roundtrip::RoundtripOptions,
+) -> Result<(), ConverterError> {
+    use sbom_converter::roundtrip::roundtrip;
+
+    println!("🔁 Round-tripping SBOM file...");
+    println!("  Input: {}", input.display());
+
+    let report = roundtrip(&input, options)?;
+
+    let output_content = match report_format {
+        OutputFormat::Text => report.format_text(),
+        OutputFormat::Json => report.format_json()?,
+    };
+
+    match output {
+        Some(output_path) => {
+            std::fs::write(&output_path, output_content)
+                .map_err(|e| ConverterError::Io(e, "Failed to write output file".to_string()))?;
+            println!("✓ Round-trip report written to: {}", output_path.display());
+        }
+        None => {
+            println!("\n{}", output_content);
+        }
+    }
+
+    if report.loss_percent() > max_loss {
+        return Err(ConverterError::RoundtripLoss(format!(
+            "{} of {} items ({:.2}%) were lost or changed, above the {}% threshold",
+            report.lost_items(),
+            report.source_items,
+            report.loss_percent(),
+            max_loss
+        )));
+    }
+
+    Ok(())
+}
+
+fn run_policy(
+    input: PathBuf,
+    policy: PathBuf,
+    fail_on_review: bool,
+    no_color: bool,
+    report_format: OutputFormat,
+) -> Result<(), ConverterError> {
+    use sbom_converter::policy::Policy;
+
+    let report = Policy::load(&policy)?.check_file(&input)?;
+
+    match report_format {
+        OutputFormat::Json => {
+            let json = report.to_json().map_err(|e| {
+                ConverterError::SerializationError(format!("Failed to serialize report: {}", e))
+            })?;
+            println!("{}", json);
+        }
+        OutputFormat::Text => {
+            if no_color {
+                report.print_plain();
+            } else {
+                report.print_colored();
+            }
+        }
+    }
+
+    if report.has_errors() || (fail_on_review && report.warning_count() > 0) {
+        return Err(ConverterError::PolicyViolation(format!(
+            "{} denied, {} needing review",
+            report.error_count(),
+            report.warning_count()
+        )));
+    }
+
+    Ok(())
+}
+
+fn run_vex(
+    command: VexCommand,
+    cdx_version: CdxVersion,
+    id_map: Option<PathBuf>,
+) -> Result<(), ConverterError> {
+    match command {
+        VexCommand::Convert {
+            input,
+            output,
+            to,
+            author,
+            publisher_namespace,
+        } => {
+            let options = VexOptions {
+                author,
+                namespace: publisher_namespace,
+                cdx_version,
+            };
+            sbom_converter::vex::convert_file(&input, &output, to.into(), &options)
+        }
+        VexCommand::Apply {
+            sbom,
+            vex,
+            output,
+            no_color,
+            report_format,
+        } => {
+            let ids = match &id_map {
+                Some(path) => sbom_converter::id_map::IdMap::load(path)?,
+                None => sbom_converter::id_map::IdMap::new(),
+            };
+            let report = sbom_converter::vex::apply_files(&sbom, &vex, &output, &ids)?;
+
+            match report_format {
+                OutputFormat::Json => {
+                    let json = report.to_json().map_err(|e| {
+                        ConverterError::SerializationError(format!(
+                            "Failed to serialize report: {}",
+                            e
+                        ))
+                    })?;
+                    println!("{}", json);
+                }
+                OutputFormat::Text => {
+                    if no_color {
+                        report.print_plain();
+                    } else {
+                        report.print_colored();
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
 fn run_app() -> Result<(), ConverterError> {
     let cli = Cli::parse();
 
@@ -758,9 +1246,18 @@ fn run_app() -> Result<(), ConverterError> {
             cli.verbose,
             cli.validate,
             cli.split_vex,
+            cli.vex_output,
             cli.packages_only,
+            cli.nest_components,
             cli.skip_jsonld_validation,
             cli.output_version,
+            cli.spdx_version,
+            cli.spdx_serialization,
+            cli.fidelity_report,
+            cli.reproducible,
+            cli.id_map,
+            cli.dangling_refs,
+            cli.spill_threshold,
         ),
         Some(Command::Validate {
             input,
@@ -770,6 +1267,7 @@ fn run_app() -> Result<(), ConverterError> {
             report_format,
             schema,
             show_version,
+            linked_bom,
         }) => run_validate(
             input,
             format,
@@ -778,6 +1276,7 @@ fn run_app() -> Result<(), ConverterError> {
             report_format,
             schema,
             show_version,
+            linked_bom,
         ),
         Some(Command::Merge {
             inputs,
@@ -793,6 +1292,30 @@ fn run_app() -> Result<(), ConverterError> {
             output,
             diff_only,
         }) => run_diff(file1, file2, report_format, output, diff_only),
+        Some(Command::Roundtrip {
+            input,
+            max_loss,
+            report_format,
+            output,
+        }) => run_roundtrip(
+            input,
+            max_loss,
+            report_format,
+            output,
+            sbom_converter::roundtrip::RoundtripOptions {
+                cdx_version: cli.output_version.into(),
+                spdx_version: cli.spdx_version.unwrap_or_default().into(),
+                spdx_serialization: cli.spdx_serialization.into(),
+            },
+        ),
+        Some(Command::Policy {
+            input,
+            policy,
+            fail_on_review,
+            no_color,
+            report_format,
+        }) => run_policy(input, policy, fail_on_review, no_color, report_format),
+        Some(Command::Vex { command }) => run_vex(command, cli.output_version.into(), cli.id_map),
         None => {
             // Legacy mode: no subcommand, use old flags
             if let (Some(input), Some(output), Some(direction)) =
@@ -807,9 +1330,18 @@ fn run_app() -> Result<(), ConverterError> {
                     cli.verbose,
                     cli.validate,
                     cli.split_vex,
+                    cli.vex_output,
                     cli.packages_only,
+                    cli.nest_components,
                     cli.skip_jsonld_validation,
                     cli.output_version,
+                    cli.spdx_version,
+                    cli.spdx_serialization,
+                    cli.fidelity_report,
+                    cli.reproducible,
+                    cli.id_map,
+                    cli.dangling_refs,
+                    cli.spill_threshold,
                 )
             } else {
                 eprintln!("{}", "Error: Missing required arguments".red().bold());
@@ -3,28 +3,25 @@
 //! Merges multiple SBOM files into a single consolidated SBOM.
 //! Supports both CycloneDX and SPDX formats.
 
+use crate::bom_link::{BomIndex, BomLink, rewrite_references};
 use crate::errors::ConverterError;
+use crate::formats::read_json_value;
+use log::info;
 use serde_json::{Value, json};
 use std::collections::{HashMap, HashSet};
-use std::fs;
 use std::path::Path;
 use std::str::FromStr;
 
 /// Deduplication strategy for merging
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DedupStrategy {
     /// Keep the first occurrence of a duplicate component
+    #[default]
     First,
     /// Keep the latest (last) occurrence of a duplicate component
     Latest,
 }
 
-impl Default for DedupStrategy {
-    fn default() -> Self {
-        Self::First
-    }
-}
-
 impl FromStr for DedupStrategy {
     type Err = String;
 
@@ -38,6 +35,9 @@ impl FromStr for DedupStrategy {
 }
 
 /// Merge multiple CycloneDX SBOM files
+///
+/// BOM-Links from one input into another become local refs, since the
+/// linked components are part of the merged BOM.
 pub fn merge_cyclonedx_files(
     input_paths: &[impl AsRef<Path>],
     dedup_strategy: DedupStrategy,
@@ -45,6 +45,9 @@ pub fn merge_cyclonedx_files(
     let mut merged_components: HashMap<String, Value> = HashMap::new();
     let mut merged_dependencies: HashMap<String, HashSet<String>> = HashMap::new();
     let mut merged_vulnerabilities: Vec<Value> = Vec::new();
+    // The inputs, and the component key of each (serial, bom-ref)
+    let mut inputs = BomIndex::new();
+    let mut component_keys: HashMap<(String, String), String> = HashMap::new();
 
     let mut metadata: Option<Value> = None;
     let mut serial_number: Option<String> = None;
@@ -52,20 +55,7 @@ pub fn merge_cyclonedx_files(
 
     // Read and merge each input file
     for (idx, input_path) in input_paths.iter().enumerate() {
-        let content = fs::read_to_string(input_path.as_ref()).map_err(|e| {
-            ConverterError::Io(
-                e,
-                format!("Failed to read file: {}", input_path.as_ref().display()),
-            )
-        })?;
-
-        let bom: Value = serde_json::from_str(&content).map_err(|e| {
-            ConverterError::ParseError(format!(
-                "Invalid JSON in {}: {}",
-                input_path.as_ref().display(),
-                e
-            ))
-        })?;
+        let bom = read_json_value(input_path.as_ref())?;
 
         // Validate it's a CycloneDX BOM
         if bom.get("bomFormat").and_then(|v| v.as_str()) != Some("CycloneDX") {
@@ -75,6 +65,9 @@ pub fn merge_cyclonedx_files(
             )));
         }
 
+        inputs.add(&bom, input_path.as_ref().display().to_string());
+        let input_serial = bom.get("serialNumber").and_then(|v| v.as_str());
+
         // Use metadata from first file
         if idx == 0 {
             metadata = bom.get("metadata").cloned();
@@ -91,6 +84,12 @@ pub fn merge_cyclonedx_files(
         if let Some(components) = bom.get("components").and_then(|v| v.as_array()) {
             for component in components {
                 let key = get_component_key(component);
+                if let (Some(serial), Some(bom_ref)) = (
+                    input_serial,
+                    component.get("bom-ref").and_then(|v| v.as_str()),
+                ) {
+                    component_keys.insert((serial.to_string(), bom_ref.to_string()), key.clone());
+                }
                 match dedup_strategy {
                     DedupStrategy::First => {
                         merged_components
@@ -150,11 +149,46 @@ pub fn merge_cyclonedx_files(
     }
 
     // Convert components HashMap to array
+    let components_by_key: HashMap<String, String> = merged_components
+        .iter()
+        .filter_map(|(key, component)| {
+            let bom_ref = component.get("bom-ref")?.as_str()?;
+            Some((key.clone(), bom_ref.to_string()))
+        })
+        .collect();
     let components: Vec<Value> = merged_components.into_values().collect();
     merged_bom["components"] = json!(components);
 
-    // Convert dependencies HashMap to array
-    let dependencies: Vec<Value> = merged_dependencies
+    // Links into the inputs point at the component kept for the linked one
+    let to_local = |reference: &str| {
+        let link = BomLink::parse(reference)?;
+        inputs.resolve(&link).ok()?;
+        let bom_ref = link.bom_ref?;
+        let kept = component_keys
+            .get(&(link.serial_number, bom_ref.clone()))
+            .and_then(|key| components_by_key.get(key))
+            .cloned();
+        Some(kept.unwrap_or(bom_ref))
+    };
+    let mut rewritten = 0;
+    let mut local = |reference: String| match to_local(&reference) {
+        Some(local) => {
+            rewritten += 1;
+            local
+        }
+        None => reference,
+    };
+
+    // Convert dependencies HashMap to array, merging linked refs with the
+    // local ones
+    let mut local_dependencies: HashMap<String, HashSet<String>> = HashMap::new();
+    for (ref_id, depends_on) in merged_dependencies {
+        local_dependencies
+            .entry(local(ref_id))
+            .or_default()
+            .extend(depends_on.into_iter().map(&mut local));
+    }
+    let dependencies: Vec<Value> = local_dependencies
         .into_iter()
         .map(|(ref_id, depends_on)| {
             let depends_on_vec: Vec<String> = depends_on.into_iter().collect();
@@ -171,16 +205,28 @@ pub fn merge_cyclonedx_files(
         merged_bom["vulnerabilities"] = json!(merged_vulnerabilities);
     }
 
+    rewritten += rewrite_references(&mut merged_bom, to_local);
+    if rewritten > 0 {
+        info!("Rewrote {} BOM-Links into local refs", rewritten);
+    }
+
     Ok(merged_bom)
 }
 
 /// Merge multiple SPDX SBOM files
+///
+/// SPDX 2.x inputs (JSON or tag-value) are merged into an SPDX 2.x document
+/// with `packages` and `files`; SPDX 3 inputs into the simple `elements`
+/// layout.
 pub fn merge_spdx_files(
     input_paths: &[impl AsRef<Path>],
     dedup_strategy: DedupStrategy,
 ) -> Result<Value, ConverterError> {
     let mut merged_elements: HashMap<String, Value> = HashMap::new();
+    let mut merged_packages: HashMap<String, Value> = HashMap::new();
+    let mut merged_files: HashMap<String, Value> = HashMap::new();
     let mut merged_relationships: Vec<Value> = Vec::new();
+    let mut document_describes: Vec<Value> = Vec::new();
 
     let mut creation_info: Option<Value> = None;
     let mut document_namespace: Option<String> = None;
@@ -188,20 +234,7 @@ pub fn merge_spdx_files(
 
     // Read and merge each input file
     for (idx, input_path) in input_paths.iter().enumerate() {
-        let content = fs::read_to_string(input_path.as_ref()).map_err(|e| {
-            ConverterError::Io(
-                e,
-                format!("Failed to read file: {}", input_path.as_ref().display()),
-            )
-        })?;
-
-        let doc: Value = serde_json::from_str(&content).map_err(|e| {
-            ConverterError::ParseError(format!(
-                "Invalid JSON in {}: {}",
-                input_path.as_ref().display(),
-                e
-            ))
-        })?;
+        let doc = read_json_value(input_path.as_ref())?;
 
         // Check if it's SPDX (either simple JSON or JSON-LD)
         let is_spdx = doc.get("spdxVersion").is_some()
@@ -247,6 +280,36 @@ pub fn merge_spdx_files(
             }
         }
 
+        // Merge packages and files (for SPDX 2.x)
+        for (key, merged) in [
+            ("packages", &mut merged_packages),
+            ("files", &mut merged_files),
+        ] {
+            for element in doc
+                .get(key)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let key = get_spdx_element_key(element);
+                match dedup_strategy {
+                    DedupStrategy::First => {
+                        merged.entry(key).or_insert_with(|| element.clone());
+                    }
+                    DedupStrategy::Latest => {
+                        merged.insert(key, element.clone());
+                    }
+                }
+            }
+        }
+        if let Some(describes) = doc.get("documentDescribes").and_then(|v| v.as_array()) {
+            for id in describes {
+                if !document_describes.contains(id) {
+                    document_describes.push(id.clone());
+                }
+            }
+        }
+
         // Merge @graph (for SPDX JSON-LD)
         if let Some(graph) = doc.get("@graph").and_then(|v| v.as_array()) {
             for element in graph {
@@ -276,10 +339,16 @@ pub fn merge_spdx_files(
         }
     }
 
-    // Build merged SPDX document (simple JSON format)
+    // Build merged SPDX document (SPDX 2.x or simple JSON format)
+    let is_spdx2 = spdx_version.starts_with("SPDX-2");
     let mut merged_doc = json!({
         "spdxVersion": spdx_version,
     });
+    if is_spdx2 {
+        merged_doc["dataLicense"] = json!("CC0-1.0");
+        merged_doc["SPDXID"] = json!("SPDXRef-DOCUMENT");
+        merged_doc["name"] = json!("Merged SBOM");
+    }
 
     if let Some(ns) = document_namespace {
         merged_doc["documentNamespace"] = json!(ns);
@@ -291,9 +360,21 @@ pub fn merge_spdx_files(
         merged_doc["creationInfo"] = info;
     }
 
-    // Convert elements HashMap to array
-    let elements: Vec<Value> = merged_elements.into_values().collect();
-    merged_doc["elements"] = json!(elements);
+    if is_spdx2 {
+        let packages: Vec<Value> = merged_packages.into_values().collect();
+        merged_doc["packages"] = json!(packages);
+        if !merged_files.is_empty() {
+            let files: Vec<Value> = merged_files.into_values().collect();
+            merged_doc["files"] = json!(files);
+        }
+        if !document_describes.is_empty() {
+            merged_doc["documentDescribes"] = json!(document_describes);
+        }
+    } else {
+        // Convert elements HashMap to array
+        let elements: Vec<Value> = merged_elements.into_values().collect();
+        merged_doc["elements"] = json!(elements);
+    }
 
     // Add relationships if any
     if !merged_relationships.is_empty() {
@@ -335,8 +416,12 @@ fn get_spdx_element_key(element: &Value) -> String {
         return id.to_string();
     }
 
-    // Try spdxId
-    if let Some(spdx_id) = element.get("spdxId").and_then(|v| v.as_str()) {
+    // Try spdxId (SPDX 3) or SPDXID (SPDX 2.x)
+    if let Some(spdx_id) = element
+        .get("spdxId")
+        .or_else(|| element.get("SPDXID"))
+        .and_then(|v| v.as_str())
+    {
         return spdx_id.to_string();
     }
 
@@ -357,135 +442,13 @@ fn get_spdx_element_key(element: &Value) -> String {
 pub fn value_to_cdx_document(
     value: &Value,
 ) -> Result<crate::formats::cdx::CdxDocument, ConverterError> {
-    use crate::formats::cdx::document::{
-        CdxComponent, CdxComponents, CdxDependencies, CdxDependency, CdxDocument,
-        CdxVulnerabilities, CdxVulnerability,
-    };
-
-    // The JSON structure uses "type" but XML struct uses "@type"
-    // We need to transform the JSON to match XML expectations
-    let mut xml_value = value.clone();
-
-    // Transform components: "type" -> "@type", add "@" prefix to attributes
-    if let Some(components) = xml_value
-        .get_mut("components")
-        .and_then(|c| c.as_array_mut())
-    {
-        for component in components {
-            if let Some(obj) = component.as_object_mut() {
-                // Rename "type" to "@type"
-                if let Some(type_val) = obj.remove("type") {
-                    obj.insert("@type".to_string(), type_val);
-                }
-                // Rename "bom-ref" to "@bom-ref"
-                if let Some(bom_ref) = obj.remove("bom-ref") {
-                    obj.insert("@bom-ref".to_string(), bom_ref);
-                }
-            }
-        }
-    }
-
-    // Transform dependencies structure for XML
-    if let Some(deps) = xml_value
-        .get_mut("dependencies")
-        .and_then(|d| d.as_array_mut())
-    {
-        for dep in deps {
-            if let Some(obj) = dep.as_object_mut() {
-                // Rename "ref" to "@ref"
-                if let Some(ref_val) = obj.remove("ref") {
-                    obj.insert("@ref".to_string(), ref_val);
-                }
-                // Transform dependsOn array to proper structure
-                if let Some(depends_on) = obj.get_mut("dependsOn")
-                    && let Some(arr) = depends_on.as_array()
-                {
-                    let deps_vec: Vec<Value> = arr.iter().map(|d| json!({"@ref": d})).collect();
-                    *depends_on = json!({"dependency": deps_vec});
-                }
-            }
-        }
-    }
-
-    // Build CdxDocument manually
-    let json_bom = xml_value.as_object().ok_or_else(|| {
-        ConverterError::SerializationError("BOM is not a valid JSON object".to_string())
-    })?;
-
-    let mut doc = CdxDocument {
-        xmlns: Some("http://cyclonedx.org/schema/bom/1.6".to_string()),
-        bom_format: Some("CycloneDX".to_string()),
-        spec_version: json_bom
-            .get("specVersion")
-            .and_then(|v| v.as_str())
-            .map(String::from),
-        version: json_bom
-            .get("version")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(1) as u32,
-        serial_number: json_bom
-            .get("serialNumber")
-            .and_then(|v| v.as_str())
-            .map(String::from),
-        metadata: None,
-        components: None,
-        dependencies: None,
-        vulnerabilities: None,
-    };
-
-    // Handle components
-    if let Some(components_array) = json_bom.get("components").and_then(|c| c.as_array()) {
-        let components: Result<Vec<CdxComponent>, _> = components_array
-            .iter()
-            .map(|c| serde_json::from_value(c.clone()))
-            .collect();
-
-        doc.components = Some(CdxComponents {
-            components: components.map_err(|e| {
-                ConverterError::SerializationError(format!("Failed to parse components: {}", e))
-            })?,
-        });
-    }
-
-    // Handle dependencies
-    if let Some(deps_array) = json_bom.get("dependencies").and_then(|d| d.as_array()) {
-        let dependencies: Result<Vec<CdxDependency>, _> = deps_array
-            .iter()
-            .map(|d| serde_json::from_value(d.clone()))
-            .collect();
-
-        doc.dependencies = Some(CdxDependencies {
-            dependencies: dependencies.map_err(|e| {
-                ConverterError::SerializationError(format!("Failed to parse dependencies: {}", e))
-            })?,
-        });
-    }
-
-    // Handle vulnerabilities
-    if let Some(vulns_array) = json_bom.get("vulnerabilities").and_then(|v| v.as_array()) {
-        let vulnerabilities: Result<Vec<CdxVulnerability>, _> = vulns_array
-            .iter()
-            .map(|v| serde_json::from_value(v.clone()))
-            .collect();
-
-        doc.vulnerabilities = Some(CdxVulnerabilities {
-            vulnerabilities: vulnerabilities.map_err(|e| {
-                ConverterError::SerializationError(format!(
-                    "Failed to parse vulnerabilities: {}",
-                    e
-                ))
-            })?,
-        });
-    }
-
-    // Handle metadata
-    if let Some(metadata_val) = json_bom.get("metadata") {
-        doc.metadata = Some(serde_json::from_value(metadata_val.clone()).map_err(|e| {
-            ConverterError::SerializationError(format!("Failed to parse metadata: {}", e))
-        })?);
+    if !value.is_object() {
+        return Err(ConverterError::SerializationError(
+            "BOM is not a valid JSON object".to_string(),
+        ));
     }
-
-    Ok(doc)
+    crate::formats::cdx::converter::json_to_document(value)
+        .map_err(ConverterError::SerializationError)
 }
 
 #[cfg(test)]
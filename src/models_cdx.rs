@@ -1,10 +1,13 @@
-//! Contains *minimal* data structures for streaming CycloneDX 1.6 JSON.
+//! Data structures for streaming CycloneDX JSON.
 //!
-//! We only define the fields we actually need for the conversion,
-//! allowing `serde` to efficiently skip all other fields.
+//! The top-level BOM, dependency and vulnerability structs are *minimal*:
+//! we only define the fields we need, allowing `serde` to efficiently skip
+//! all others. Components use the complete CycloneDX 1.7 model, since they
+//! are what every conversion path has to carry across.
 
 use serde::de::{self, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
@@ -51,59 +54,376 @@ pub struct CdxToolComponent {
     pub bom_ref: String,
 }
 
-/// Enhanced CycloneDX Component with full field support
-#[derive(Deserialize, Serialize, Debug)]
+/// Just the `metadata` of a BOM; every other key is skipped unparsed
+#[derive(Deserialize, Debug, Default)]
+pub struct CdxMetadataOnly {
+    #[serde(default)]
+    pub metadata: Option<CdxBomMetadata>,
+}
+
+/// CycloneDX `metadata` as read by the CDX -> SPDX converter
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxBomMetadata {
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// Legacy array (1.4) or `{components, services}` object (1.5+)
+    #[serde(default)]
+    pub tools: Option<Value>,
+    #[serde(default)]
+    pub authors: Option<Vec<CdxOrganizationalContact>>,
+    /// The subject of the BOM
+    #[serde(default)]
+    pub component: Option<CdxComponent>,
+    #[serde(default)]
+    pub supplier: Option<CdxOrganizationalEntity>,
+    /// Everything else (lifecycles, manufacturer, licenses, properties, ...)
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl CdxBomMetadata {
+    /// `name-version` of every tool, in either `tools` layout
+    pub fn tool_names(&self) -> Vec<String> {
+        let tools: Vec<&Value> = match &self.tools {
+            Some(Value::Array(tools)) => tools.iter().collect(),
+            Some(Value::Object(tools)) => ["components", "services"]
+                .iter()
+                .filter_map(|key| tools.get(*key).and_then(|t| t.as_array()))
+                .flatten()
+                .collect(),
+            _ => Vec::new(),
+        };
+        tools
+            .into_iter()
+            .filter_map(|tool| {
+                let name = tool.get("name").and_then(|n| n.as_str())?;
+                Some(match tool.get("version").and_then(|v| v.as_str()) {
+                    Some(version) => format!("{}-{}", name, version),
+                    None => name.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Complete CycloneDX 1.7 component, including nested `components`.
+///
+/// This is the component model shared by the streaming converters, merge
+/// and the XML path. Every field of the 1.7 schema is kept; the large,
+/// deeply nested blocks that SPDX has no equivalent for (model cards,
+/// crypto properties, ...) are carried as raw JSON so they survive a
+/// CycloneDX round trip unchanged.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxComponent {
-    #[serde(rename = "bom-ref")]
-    pub bom_ref: String,
     #[serde(rename = "type")]
     pub component_type: String, // "library", "file", etc.
+    #[serde(rename = "mime-type", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "String::is_empty")]
+    pub bom_ref: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<CdxOrganizationalEntity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<CdxOrganizationalEntity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<CdxOrganizationalContact>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>, // Deprecated in 1.6 in favour of `authors`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
     pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_range: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cpe: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub purl: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>, // "required", "excluded", etc.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hashes: Option<Vec<CdxHash>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub licenses: Option<Vec<CdxLicenseChoice>>,
-    // We use IgnoredAny to quickly skip over fields we don't need during deserialization
-    // Skip it during serialization
-    #[serde(flatten, skip_serializing)]
-    pub extra: HashMap<String, IgnoredAny>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub omnibor_id: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swhid: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swid: Option<CdxSwid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pedigree: Option<CdxPedigree>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_references: Option<Vec<CdxExternalReference>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<CdxProperty>>,
+    /// Sub-components (assemblies, container layers, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<CdxComponent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<CdxEvidence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_notes: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_card: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crypto_properties: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_external: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Value>,
+}
+
+impl CdxComponent {
+    /// Names of the component's authors, from `authors` or the legacy `author`
+    pub fn author_names(&self) -> Vec<String> {
+        match &self.authors {
+            Some(authors) if !authors.is_empty() => {
+                authors.iter().filter_map(|a| a.name.clone()).collect()
+            }
+            _ => self.author.iter().cloned().collect(),
+        }
+    }
+
+    /// URL of the first external reference of the given type
+    pub fn external_reference_url(&self, ref_type: &str) -> Option<&str> {
+        self.external_references
+            .as_ref()?
+            .iter()
+            .find(|r| r.ref_type == ref_type)
+            .map(|r| r.url.as_str())
+    }
+}
+
+/// Organization (supplier, manufacturer, provider)
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxOrganizationalEntity {
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub url: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contact: Vec<CdxOrganizationalContact>,
+}
+
+/// Individual contact (author, organization contact)
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxOrganizationalContact {
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
 }
 
 /// Hash algorithm and value
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxHash {
     pub alg: String,     // "SHA-256", "SHA-1", "MD5", etc.
     pub content: String, // hex-encoded hash value
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+/// Either an SPDX license expression or a single license
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxLicenseChoice {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expression: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub license: Option<CdxLicense>,
+    /// Expression choices only: "declared" or "concluded" (1.6+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledgement: Option<String>,
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxLicense {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledgement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<CdxAttachment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licensing: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<CdxProperty>>,
+}
+
+/// Inline text or base64 content (license text, SWID tag, ...)
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxAttachment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    pub content: String,
+}
+
+/// SWID tag identity
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxSwid {
+    pub tag_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_version: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<CdxAttachment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Component pedigree (ancestors, variants, patches, ...)
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxPedigree {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ancestors: Option<Vec<CdxComponent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub descendants: Option<Vec<CdxComponent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variants: Option<Vec<CdxComponent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commits: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patches: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// Link to an external resource (website, VCS, advisories, ...)
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxExternalReference {
+    #[serde(rename = "type")]
+    pub ref_type: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hashes: Option<Vec<CdxHash>>,
+}
+
+/// External reference types defined by CycloneDX 1.7
+const EXTERNAL_REFERENCE_TYPES: &[&str] = &[
+    "vcs",
+    "issue-tracker",
+    "website",
+    "advisories",
+    "bom",
+    "mailing-list",
+    "social",
+    "chat",
+    "documentation",
+    "support",
+    "source-distribution",
+    "distribution",
+    "distribution-intake",
+    "license",
+    "build-meta",
+    "build-system",
+    "release-notes",
+    "security-contact",
+    "model-card",
+    "log",
+    "configuration",
+    "evidence",
+    "formulation",
+    "attestation",
+    "threat-model",
+    "adversary-model",
+    "risk-assessment",
+    "vulnerability-assertion",
+    "exploitability-statement",
+    "pentest-report",
+    "static-analysis-report",
+    "dynamic-analysis-report",
+    "runtime-analysis-report",
+    "component-analysis-report",
+    "maturity-report",
+    "certification-report",
+    "codified-infrastructure",
+    "quality-metrics",
+    "poam",
+    "electronic-signature",
+    "digital-signature",
+    "rfc-9116",
+    "other",
+];
+
+/// Whether `ref_type` is a CycloneDX external reference type
+pub fn is_external_reference_type(ref_type: &str) -> bool {
+    EXTERNAL_REFERENCE_TYPES.contains(&ref_type)
+}
+
+/// Name/value property
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CdxProperty {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Evidence collected for a component
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CdxEvidence {
+    /// Object before 1.6, array from 1.6
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurrences: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callstack: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<Vec<CdxLicenseChoice>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<Vec<CdxCopyright>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CdxCopyright {
+    pub text: String,
 }
 
 /// A minimal representation of a CycloneDX Dependency.
@@ -117,7 +437,7 @@ pub struct CdxDependency {
 }
 
 /// Complete CycloneDX Vulnerability structure with VEX support
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxVulnerability {
     pub id: String, // e.g., CVE-2021-44228
@@ -126,25 +446,53 @@ pub struct CdxVulnerability {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratings: Option<Vec<CdxRating>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwes: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub analysis: Option<CdxAnalysis>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub affects: Option<Vec<CdxAffects>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<CdxProperty>>,
     #[serde(flatten, skip_serializing)]
     pub extra: HashMap<String, IgnoredAny>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct CdxVulnSource {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
 }
 
+/// Severity rating of a vulnerability
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct CdxRating {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<CdxVulnSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>, // "critical", "high", ..., "none", "unknown"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>, // "CVSSv31", "CVSSv4", "OWASP", ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+}
+
 /// VEX analysis information
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CdxAnalysis {
-    pub state: String, // "resolved", "not_affected", "in_triage", etc.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>, // "resolved", "not_affected", "in_triage", etc.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>, // "code_not_present", "requires_configuration", etc.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response: Vec<String>, // "update", "will_not_fix", etc.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub detail: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -153,10 +501,23 @@ pub struct CdxAnalysis {
     pub last_updated: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct CdxAffects {
     #[serde(rename = "ref")]
     pub bom_ref: String, // URN reference to affected component
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions: Option<Vec<CdxAffectedVersion>>,
+}
+
+/// Version of an affected component and whether it is affected
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CdxAffectedVersion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>, // "affected", "unaffected", "unknown"
 }
 
 // --- Streaming Visitor Logic ---
@@ -166,7 +527,7 @@ pub struct CdxStreamingVisitor<'a, W: std::io::Write> {
     // This visitor holds the state for our "Temp File" strategy.
     pub writer: &'a mut BufWriter<W>,
     pub temp_writer: &'a mut BufWriter<File>,
-    pub first_element: bool,
+    pub ctx: &'a mut crate::converter_cdx_to_spdx::SpdxWriteContext,
 }
 
 impl<'de, 'a, W: std::io::Write> Visitor<'de> for CdxStreamingVisitor<'a, W> {
@@ -238,7 +599,8 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for CdxComponentStreamVisitor<
             crate::converter_cdx_to_spdx::handle_cdx_component(
                 component,
                 self.state.writer,
-                &mut self.state.first_element,
+                self.state.temp_writer,
+                self.state.ctx,
             )
             .map_err(de::Error::custom)?;
         }
@@ -281,7 +643,7 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for CdxVulnerabilityStreamVisi
                 vuln,
                 self.state.writer,
                 self.state.temp_writer,
-                &mut self.state.first_element,
+                self.state.ctx,
             )
             .map_err(de::Error::custom)?;
         }
@@ -320,8 +682,12 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for CdxDependencyStreamVisitor
     {
         while let Some(dep) = seq.next_element::<CdxDependency>()? {
             // This is where we call the conversion logic
-            crate::converter_cdx_to_spdx::handle_cdx_dependency(dep, self.state.temp_writer)
-                .map_err(de::Error::custom)?;
+            crate::converter_cdx_to_spdx::handle_cdx_dependency(
+                dep,
+                self.state.temp_writer,
+                self.state.ctx,
+            )
+            .map_err(de::Error::custom)?;
         }
         Ok(())
     }
@@ -6,12 +6,12 @@ use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqA
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
-use std::io::{BufWriter, Write};
+use std::io::BufWriter;
 
 // --- Minimal Deserialization Structs (for Pass 1 & 2) ---
 
 /// Minimal struct for Pass 1 (Indexing) - Simple JSON format
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SpdxRelationshipMinimal {
     pub spdx_element_id: String,
@@ -19,6 +19,30 @@ pub struct SpdxRelationshipMinimal {
     pub related_spdx_element: String,
 }
 
+impl SpdxRelationshipMinimal {
+    /// Rewrites SPDX 2.3 inverse relationship types into their forward form,
+    /// e.g. `A DEPENDENCY_OF B` becomes `B DEPENDS_ON A`, so the index is
+    /// always keyed by the depending/containing element.
+    pub fn normalized(self) -> Self {
+        let forward = match self.relationship_type.as_str() {
+            "DEPENDENCY_OF"
+            | "RUNTIME_DEPENDENCY_OF"
+            | "BUILD_DEPENDENCY_OF"
+            | "DEV_DEPENDENCY_OF"
+            | "OPTIONAL_DEPENDENCY_OF"
+            | "PROVIDED_DEPENDENCY_OF"
+            | "TEST_DEPENDENCY_OF" => "DEPENDS_ON",
+            "CONTAINED_BY" => "CONTAINS",
+            _ => return self,
+        };
+        Self {
+            spdx_element_id: self.related_spdx_element,
+            relationship_type: forward.to_string(),
+            related_spdx_element: self.spdx_element_id,
+        }
+    }
+}
+
 /// Minimal struct for JSON-LD Relationship format
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -50,11 +74,15 @@ pub struct SpdxElementMinimal {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license_concluded: Option<String>, // SPDX expression
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_declared: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub external_identifier: Option<Vec<SpdxExternalIdentifier>>, // For CPE
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verified_using: Option<Vec<SpdxHash>>, // For hashes
     #[serde(skip_serializing_if = "Option::is_none")]
     pub software_primary_purpose: Option<String>, // For scope
+    #[serde(flatten)]
+    pub details: SpdxPackageDetails,
     // We use IgnoredAny to quickly skip over fields we don't need
     #[serde(flatten)]
     pub extra: HashMap<String, IgnoredAny>,
@@ -124,31 +152,133 @@ pub struct JsonLdElement {
     pub spdx_id: String, // Full URI in JSON-LD
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "software_packageVersion",
+        alias = "softwarePackageVersion",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub software_package_version: Option<String>, // JSON-LD uses different field name
+    #[serde(
+        rename = "software_packageUrl",
+        alias = "softwarePackageUrl",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub software_package_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "software_primaryPurpose",
+        alias = "softwarePrimaryPurpose",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub software_primary_purpose: Option<String>, // "install", "source", etc.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_identifier: Option<Vec<SpdxExternalIdentifier>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verified_using: Option<Vec<SpdxHash>>,
+    #[serde(flatten)]
+    pub details: SpdxPackageDetails,
     // We use IgnoredAny to quickly skip over fields we don't need
     #[serde(flatten)]
     pub extra: HashMap<String, IgnoredAny>,
 }
 
-/// External identifier (CPE, PURL, etc.)
+/// Package provenance fields shared by the simple and JSON-LD layouts.
+///
+/// The simple layout uses the plain names; JSON-LD reads the `software_`
+/// prefixed properties through the aliases. Supplier and originator are
+/// agent strings (`Organization: name`) and only exist in the simple layout.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxPackageDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub originator: Option<String>,
+    #[serde(
+        default,
+        alias = "software_copyrightText",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub copyright_text: Option<String>,
+    #[serde(
+        default,
+        alias = "software_homePage",
+        alias = "homePage",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub homepage: Option<String>,
+    #[serde(
+        default,
+        alias = "software_downloadLocation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub download_location: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_ref: Vec<SpdxExternalRef>,
+}
+
+impl SpdxPackageDetails {
+    /// Collects the provenance fields of a CDX component
+    pub fn from_cdx_component(comp: &crate::models_cdx::CdxComponent) -> Self {
+        Self {
+            supplier: cdx_supplier_agent(comp),
+            originator: cdx_originator_agent(comp),
+            copyright_text: comp.copyright.clone(),
+            homepage: comp.external_reference_url("website").map(str::to_string),
+            download_location: comp
+                .external_reference_url("distribution")
+                .map(str::to_string),
+            external_ref: cdx_to_spdx3_external_refs(comp),
+        }
+    }
+
+    /// Copies the provenance fields onto a CDX component
+    pub fn apply_to(&self, comp: &mut crate::models_cdx::CdxComponent) {
+        comp.supplier = self.supplier.as_deref().and_then(cdx_supplier_from_agent);
+        comp.author = self.originator.as_deref().and_then(spdx_agent_name);
+        comp.copyright = spdx2_license_value(self.copyright_text.as_deref());
+
+        let mut refs = Vec::new();
+        push_url_reference(&mut refs, "website", self.homepage.as_deref());
+        push_url_reference(&mut refs, "distribution", self.download_location.as_deref());
+        refs.extend(
+            self.external_ref
+                .iter()
+                .filter_map(spdx3_external_ref_to_cdx),
+        );
+        comp.external_references = if refs.is_empty() { None } else { Some(refs) };
+    }
+}
+
+/// SPDX 3 `ExternalRef` (VCS, issue tracker, advisories, ...)
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct SpdxExternalRef {
+    #[serde(rename = "type")]
+    pub ref_type: String, // "ExternalRef"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_ref_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locator: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// External identifier (CPE, PURL, etc.)
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct SpdxExternalIdentifier {
     #[serde(rename = "type")]
     pub id_type: String,
     pub external_identifier_type: Option<String>, // "cpe23", "purl", etc.
     pub identifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuing_authority: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identifier_locator: Vec<String>,
 }
 
 /// Hash information from SPDX
@@ -161,7 +291,8 @@ pub struct SpdxHash {
     pub hash_value: Option<String>,
 }
 
-/// Vulnerability data from JSON-LD
+/// Vulnerability element (`security_Vulnerability` in JSON-LD,
+/// `SpdxVulnerability` in simple JSON)
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct JsonLdVulnerability {
@@ -169,57 +300,60 @@ pub struct JsonLdVulnerability {
     pub vuln_type: String,
     #[serde(rename = "spdxId")]
     pub spdx_id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
     pub external_identifier: Option<Vec<SpdxExternalIdentifier>>,
 }
 
 impl JsonLdVulnerability {
-    /// Extract CVE ID from spdxId URL or external identifiers
-    pub fn extract_cve_id(&self) -> Option<String> {
-        // Try external_identifier first
-        if let Some(cve) = self.external_identifier.as_ref().and_then(|ids| {
-            ids.iter()
-                .find(|id| id.external_identifier_type.as_deref() == Some("cve"))
-                .and_then(|id| id.identifier.clone())
+    /// The identifier of the vulnerability: its CVE id, its name, another
+    /// security identifier or the end of a `.../vulnerability/<id>` spdxId
+    pub fn vulnerability_id(&self) -> Option<String> {
+        if let Some(cve) = self.identifier_of_type("cve") {
+            return Some(cve.clone());
+        }
+        if let Some(name) = &self.name {
+            return Some(name.clone());
+        }
+        if let Some(other) = self.identifiers().find(|id| {
+            id.external_identifier_type.as_deref() == Some("securityOther")
+                && id
+                    .identifier
+                    .as_ref()
+                    .is_some_and(|i| !i.starts_with("CWE-"))
         }) {
-            return Some(cve);
+            return other.identifier.clone();
         }
 
         // Fall back to extracting from spdxId URL (e.g., .../vulnerability/CVE-2025-11081)
-        if let Some(cve_part) = self.spdx_id.split("/vulnerability/").nth(1) {
-            return Some(cve_part.to_string());
-        }
+        self.spdx_id
+            .split("/vulnerability/")
+            .nth(1)
+            .map(|id| id.to_string())
+    }
 
-        None
+    /// The external identifier carrying the vulnerability id
+    pub fn primary_identifier(&self) -> Option<&SpdxExternalIdentifier> {
+        let id = self.vulnerability_id()?;
+        self.identifiers()
+            .find(|ext| ext.identifier.as_deref() == Some(id.as_str()))
     }
-}
 
-/// VEX assessment relationship from JSON-LD
-#[derive(Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct JsonLdVexRelationship {
-    #[serde(rename = "type")]
-    pub relationship_type: String, // "security_VexNotAffectedVulnAssessmentRelationship", etc.
-    #[serde(rename = "spdxId")]
-    pub spdx_id: String,
-    pub from: String, // vulnerability ID
-    #[serde(rename = "relationshipType")]
-    pub relationship_type_enum: String, // "doesNotAffect", "fixedIn"
-    pub to: Vec<String>, // affected component IDs
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub security_impact_statement: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub security_vex_version: Option<String>,
-}
+    /// CWE numbers from `CWE-<n>` identifiers
+    pub fn cwes(&self) -> Vec<u32> {
+        self.identifiers()
+            .filter_map(|id| id.identifier.as_deref()?.strip_prefix("CWE-")?.parse().ok())
+            .collect()
+    }
 
-impl JsonLdVexRelationship {
-    /// Map SPDX VEX state to CycloneDX analysis state
-    pub fn map_state(&self) -> String {
-        match self.relationship_type.as_str() {
-            "security_VexNotAffectedVulnAssessmentRelationship" => "not_affected",
-            "security_VexFixedVulnAssessmentRelationship" => "resolved",
-            _ => "in_triage",
-        }
-        .to_string()
+    fn identifiers(&self) -> impl Iterator<Item = &SpdxExternalIdentifier> {
+        self.external_identifier.iter().flatten()
+    }
+
+    fn identifier_of_type(&self, id_type: &str) -> Option<&String> {
+        self.identifiers()
+            .find(|id| id.external_identifier_type.as_deref() == Some(id_type))
+            .and_then(|id| id.identifier.as_ref())
     }
 }
 
@@ -232,11 +366,14 @@ impl JsonLdElement {
             name: self.name.clone(),
             version_info: self.software_package_version.clone(),
             summary: self.summary.clone().or_else(|| self.description.clone()),
-            purl: None,              // Would need to extract from externalIdentifier
-            license_concluded: None, // Would need to extract from relationships
+            purl: self.extract_purl(),
+            // Licenses are separate elements, resolved through relationships
+            license_concluded: None,
+            license_declared: None,
             external_identifier: self.external_identifier.clone(),
             verified_using: self.verified_using.clone(),
             software_primary_purpose: self.software_primary_purpose.clone(),
+            details: self.details.clone(),
             extra: HashMap::new(),
         }
     }
@@ -250,12 +387,20 @@ impl JsonLdElement {
             .and_then(|id| id.identifier.clone())
     }
 
-    /// Extract PURL from external identifiers
+    /// Extract PURL from `software_packageUrl` or external identifiers
     pub fn extract_purl(&self) -> Option<String> {
+        if let Some(purl) = &self.software_package_url {
+            return Some(purl.clone());
+        }
         self.external_identifier
             .as_ref()?
             .iter()
-            .find(|id| id.external_identifier_type.as_deref() == Some("purl"))
+            .find(|id| {
+                matches!(
+                    id.external_identifier_type.as_deref(),
+                    Some("purl") | Some("packageUrl")
+                )
+            })
             .and_then(|id| id.identifier.clone())
     }
 
@@ -294,6 +439,656 @@ impl JsonLdElement {
     }
 }
 
+// --- SPDX 2.3 Structs (read in Pass 2, written by CDX -> SPDX 2.3) ---
+
+/// SPDX 2.3 package from the top-level `packages` array
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Spdx2Package {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_info: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<String>, // "Organization: name"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub originator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_analyzed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_package_purpose: Option<String>, // "LIBRARY", "APPLICATION", etc.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_concluded: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_declared: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checksums: Vec<Spdx2Checksum>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_refs: Vec<Spdx2ExternalRef>,
+    // We use IgnoredAny to quickly skip over fields we don't need
+    // Skip it during serialization
+    #[serde(flatten, skip_serializing)]
+    pub extra: HashMap<String, IgnoredAny>,
+}
+
+/// SPDX 2.3 file from the top-level `files` array
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Spdx2File {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub file_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_concluded: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright_text: Option<String>,
+    // Required by the SPDX 2.3 schema, so always written
+    #[serde(default)]
+    pub checksums: Vec<Spdx2Checksum>,
+    #[serde(flatten, skip_serializing)]
+    pub extra: HashMap<String, IgnoredAny>,
+}
+
+/// SPDX 2.3 checksum (`algorithm` is e.g. "SHA256", "SHA3-256", "BLAKE2b-256")
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Spdx2Checksum {
+    pub algorithm: String,
+    pub checksum_value: String,
+}
+
+/// SPDX 2.3 package external reference (purl, cpe23Type, ...)
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Spdx2ExternalRef {
+    pub reference_category: String, // "PACKAGE-MANAGER", "SECURITY", ...
+    pub reference_type: String,
+    pub reference_locator: String,
+}
+
+impl Spdx2Package {
+    /// Convert this package into a CycloneDX component
+    pub fn to_cdx_component(
+        &self,
+        bom_ref: String,
+        licenses: &crate::converter_spdx_to_cdx::SpdxLicenseIndex,
+    ) -> crate::models_cdx::CdxComponent {
+        let find_ref = |ref_type: &str| {
+            self.external_refs
+                .iter()
+                .find(|r| r.reference_type == ref_type)
+                .map(|r| r.reference_locator.clone())
+        };
+
+        let mut refs = Vec::new();
+        push_url_reference(&mut refs, "website", self.homepage.as_deref());
+        push_url_reference(&mut refs, "distribution", self.download_location.as_deref());
+        refs.extend(
+            self.external_refs
+                .iter()
+                .filter_map(spdx2_external_ref_to_cdx),
+        );
+
+        let swid = self
+            .external_refs
+            .iter()
+            .find(|r| r.reference_type == "swid")
+            .map(|r| crate::models_cdx::CdxSwid {
+                tag_id: r.reference_locator.clone(),
+                name: self.name.clone(),
+                ..Default::default()
+            });
+
+        crate::models_cdx::CdxComponent {
+            bom_ref,
+            component_type: map_spdx2_purpose(self.primary_package_purpose.as_deref()).to_string(),
+            supplier: self.supplier.as_deref().and_then(cdx_supplier_from_agent),
+            author: self.originator.as_deref().and_then(spdx_agent_name),
+            name: self.name.clone(),
+            version: self.version_info.clone().filter(|v| !is_spdx2_no_value(v)),
+            description: self.description.clone().or_else(|| self.summary.clone()),
+            hashes: spdx2_checksums_to_cdx(&self.checksums),
+            licenses: licenses.to_cdx(
+                self.license_concluded.as_deref(),
+                self.license_declared.as_deref(),
+            ),
+            copyright: spdx2_license_value(self.copyright_text.as_deref()),
+            cpe: find_ref("cpe23Type").or_else(|| find_ref("cpe22Type")),
+            purl: find_ref("purl"),
+            swid,
+            external_references: if refs.is_empty() { None } else { Some(refs) },
+            ..Default::default()
+        }
+    }
+}
+
+impl Spdx2File {
+    /// Convert this file into a CycloneDX component of type `file`
+    pub fn to_cdx_component(
+        &self,
+        bom_ref: String,
+        licenses: &crate::converter_spdx_to_cdx::SpdxLicenseIndex,
+    ) -> crate::models_cdx::CdxComponent {
+        crate::models_cdx::CdxComponent {
+            bom_ref,
+            component_type: "file".to_string(),
+            name: self.file_name.clone(),
+            description: self.comment.clone(),
+            hashes: spdx2_checksums_to_cdx(&self.checksums),
+            licenses: licenses.to_cdx(self.license_concluded.as_deref(), None),
+            copyright: spdx2_license_value(self.copyright_text.as_deref()),
+            ..Default::default()
+        }
+    }
+}
+
+impl Spdx2Package {
+    /// Build an SPDX 2.3 package from a CDX component
+    pub fn from_cdx_component(comp: &crate::models_cdx::CdxComponent, spdx_id: String) -> Self {
+        let mut external_refs = Vec::new();
+        if let Some(purl) = &comp.purl {
+            external_refs.push(Spdx2ExternalRef {
+                reference_category: "PACKAGE-MANAGER".to_string(),
+                reference_type: "purl".to_string(),
+                reference_locator: purl.clone(),
+            });
+        }
+        if let Some(cpe) = &comp.cpe {
+            external_refs.push(Spdx2ExternalRef {
+                reference_category: "SECURITY".to_string(),
+                reference_type: "cpe23Type".to_string(),
+                reference_locator: cpe.clone(),
+            });
+        }
+        if let Some(swid) = &comp.swid {
+            external_refs.push(Spdx2ExternalRef {
+                reference_category: "SECURITY".to_string(),
+                reference_type: "swid".to_string(),
+                reference_locator: swid.tag_id.clone(),
+            });
+        }
+        external_refs.extend(cdx_to_spdx2_external_refs(comp));
+        let licenses = cdx_licenses_to_spdx(comp);
+
+        Self {
+            spdx_id,
+            name: comp.name.clone(),
+            version_info: comp.version.clone(),
+            supplier: cdx_supplier_agent(comp),
+            originator: cdx_originator_agent(comp),
+            download_location: Some(
+                comp.external_reference_url("distribution")
+                    .unwrap_or("NOASSERTION")
+                    .to_string(),
+            ),
+            files_analyzed: Some(false),
+            homepage: comp.external_reference_url("website").map(str::to_string),
+            description: comp.description.clone(),
+            summary: None,
+            primary_package_purpose: Some(
+                map_cdx_type_to_spdx2_purpose(&comp.component_type).to_string(),
+            ),
+            license_concluded: licenses.concluded,
+            license_declared: licenses.declared,
+            copyright_text: comp.copyright.clone(),
+            checksums: cdx_hashes_to_spdx2(comp),
+            external_refs,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl Spdx2File {
+    /// Build an SPDX 2.3 file from a CDX component of type `file`
+    pub fn from_cdx_component(comp: &crate::models_cdx::CdxComponent, spdx_id: String) -> Self {
+        Self {
+            spdx_id,
+            file_name: comp.name.clone(),
+            comment: comp.description.clone(),
+            // SPDX 2.3 files have no declared license
+            license_concluded: cdx_licenses_to_spdx(comp).concluded,
+            copyright_text: comp.copyright.clone(),
+            checksums: cdx_hashes_to_spdx2(comp),
+            extra: HashMap::new(),
+        }
+    }
+}
+
+/// Map a CycloneDX component type to an SPDX 2.3 `primaryPackagePurpose`
+pub fn map_cdx_type_to_spdx2_purpose(component_type: &str) -> &'static str {
+    match component_type {
+        "application" => "APPLICATION",
+        "framework" => "FRAMEWORK",
+        "library" => "LIBRARY",
+        "container" => "CONTAINER",
+        "operating-system" => "OPERATING_SYSTEM",
+        "device" => "DEVICE",
+        "firmware" => "FIRMWARE",
+        "file" => "FILE",
+        _ => "OTHER",
+    }
+}
+
+/// SPDX 2.3 `hasExtractedLicensingInfos` entry: the text behind a
+/// `LicenseRef-` id
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxExtractedLicense {
+    pub license_id: String,
+    pub extracted_text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub see_alsos: Vec<String>,
+}
+
+/// The licenses of a CDX component as SPDX expressions
+#[derive(Debug, Default, PartialEq)]
+pub struct SpdxLicenses {
+    pub concluded: Option<String>,
+    pub declared: Option<String>,
+    /// Named (non-SPDX) licenses, as `LicenseRef-` ids with their text
+    pub extracted: Vec<SpdxExtractedLicense>,
+}
+
+/// Maps every license choice of a CDX component to SPDX.
+///
+/// Choices acknowledged as `declared` make up the declared license; the
+/// rest (`concluded` or unspecified) the concluded one. Several choices on
+/// one side are combined with `AND`. Licenses known only by name become
+/// `LicenseRef-` ids, with their text in `extracted`.
+pub fn cdx_licenses_to_spdx(comp: &crate::models_cdx::CdxComponent) -> SpdxLicenses {
+    let mut concluded = Vec::new();
+    let mut declared = Vec::new();
+    let mut extracted: Vec<SpdxExtractedLicense> = Vec::new();
+
+    for choice in comp.licenses.iter().flatten() {
+        let (term, acknowledgement) = if let Some(expression) = &choice.expression {
+            (expression.clone(), choice.acknowledgement.as_deref())
+        } else if let Some(license) = &choice.license {
+            let term = match (&license.id, &license.name) {
+                (Some(id), _) => id.clone(),
+                (None, Some(name)) => {
+                    let license_id = license_ref_id(name);
+                    if !extracted.iter().any(|e| e.license_id == license_id) {
+                        extracted.push(SpdxExtractedLicense {
+                            license_id: license_id.clone(),
+                            extracted_text: license
+                                .text
+                                .as_ref()
+                                .filter(|t| t.encoding.is_none())
+                                .map(|t| t.content.clone())
+                                .unwrap_or_else(|| "NOASSERTION".to_string()),
+                            name: Some(name.clone()),
+                            see_alsos: license.url.iter().cloned().collect(),
+                        });
+                    }
+                    license_id
+                }
+                (None, None) => continue,
+            };
+            (term, license.acknowledgement.as_deref())
+        } else {
+            continue;
+        };
+
+        match acknowledgement {
+            Some("declared") => declared.push(term),
+            _ => concluded.push(term),
+        }
+    }
+
+    SpdxLicenses {
+        concluded: combine_license_terms(concluded),
+        declared: combine_license_terms(declared),
+        extracted,
+    }
+}
+
+/// `LicenseRef-` id for a license known only by name
+pub fn license_ref_id(name: &str) -> String {
+    if name.starts_with("LicenseRef-") {
+        return name.to_string();
+    }
+    let id: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("LicenseRef-{}", id)
+}
+
+/// Joins license terms with `AND` and normalizes the result. Terms that are
+/// not valid SPDX expressions are kept as given, parenthesized if compound.
+fn combine_license_terms(mut terms: Vec<String>) -> Option<String> {
+    let mut seen = std::collections::HashSet::new();
+    terms.retain(|t| seen.insert(t.clone()));
+    if terms.is_empty() {
+        return None;
+    }
+    let parsed: Result<Vec<crate::license::Expression>, _> =
+        terms.iter().map(|t| t.parse()).collect();
+    match parsed {
+        Ok(expressions) => Some(
+            crate::license::Expression::And(expressions)
+                .normalize()
+                .to_string(),
+        ),
+        Err(e) => {
+            log::warn!("{}", e);
+            Some(
+                terms
+                    .iter()
+                    .map(|t| {
+                        if t.contains(char::is_whitespace) && terms.len() > 1 {
+                            format!("({})", t)
+                        } else {
+                            t.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" AND "),
+            )
+        }
+    }
+}
+
+/// Map CycloneDX hashes to SPDX 2.3 checksums, dropping algorithms
+/// SPDX 2.3 has no equivalent for
+pub fn cdx_hashes_to_spdx2(comp: &crate::models_cdx::CdxComponent) -> Vec<Spdx2Checksum> {
+    comp.hashes
+        .iter()
+        .flatten()
+        .filter_map(|h| {
+            let algorithm = match h.alg.to_uppercase().as_str() {
+                "MD5" => "MD5",
+                "SHA-1" => "SHA1",
+                "SHA-256" => "SHA256",
+                "SHA-384" => "SHA384",
+                "SHA-512" => "SHA512",
+                "SHA3-256" => "SHA3-256",
+                "SHA3-384" => "SHA3-384",
+                "SHA3-512" => "SHA3-512",
+                "BLAKE2B-256" => "BLAKE2b-256",
+                "BLAKE2B-384" => "BLAKE2b-384",
+                "BLAKE2B-512" => "BLAKE2b-512",
+                "BLAKE3" => "BLAKE3",
+                _ => return None,
+            };
+            Some(Spdx2Checksum {
+                algorithm: algorithm.to_string(),
+                checksum_value: h.content.clone(),
+            })
+        })
+        .collect()
+}
+
+/// `NOASSERTION` and `NONE` carry no information for CycloneDX
+fn is_spdx2_no_value(value: &str) -> bool {
+    value == "NOASSERTION" || value == "NONE"
+}
+
+pub fn spdx2_license_value(value: Option<&str>) -> Option<String> {
+    value
+        .filter(|v| !v.is_empty() && !is_spdx2_no_value(v))
+        .map(str::to_string)
+}
+
+/// Map an SPDX 2.3 `primaryPackagePurpose` to a CycloneDX component type
+pub fn map_spdx2_purpose(purpose: Option<&str>) -> &'static str {
+    match purpose {
+        Some("APPLICATION") => "application",
+        Some("FRAMEWORK") => "framework",
+        Some("CONTAINER") => "container",
+        Some("OPERATING-SYSTEM") | Some("OPERATING_SYSTEM") => "operating-system",
+        Some("DEVICE") => "device",
+        Some("FIRMWARE") => "firmware",
+        Some("FILE") => "file",
+        _ => "library",
+    }
+}
+
+/// Map SPDX 2.3 checksums to CycloneDX hashes, dropping algorithms
+/// CycloneDX has no equivalent for (SHA224, MD2, ADLER32, ...)
+pub fn spdx2_checksums_to_cdx(
+    checksums: &[Spdx2Checksum],
+) -> Option<Vec<crate::models_cdx::CdxHash>> {
+    let hashes: Vec<_> = checksums
+        .iter()
+        .filter_map(|c| {
+            let alg = match c.algorithm.to_uppercase().as_str() {
+                "MD5" => "MD5",
+                "SHA1" => "SHA-1",
+                "SHA256" => "SHA-256",
+                "SHA384" => "SHA-384",
+                "SHA512" => "SHA-512",
+                "SHA3-256" => "SHA3-256",
+                "SHA3-384" => "SHA3-384",
+                "SHA3-512" => "SHA3-512",
+                "BLAKE2B-256" => "BLAKE2b-256",
+                "BLAKE2B-384" => "BLAKE2b-384",
+                "BLAKE2B-512" => "BLAKE2b-512",
+                "BLAKE3" => "BLAKE3",
+                _ => return None,
+            };
+            Some(crate::models_cdx::CdxHash {
+                alg: alg.to_string(),
+                content: c.checksum_value.clone(),
+            })
+        })
+        .collect();
+    if hashes.is_empty() {
+        None
+    } else {
+        Some(hashes)
+    }
+}
+
+/// Formats the CDX supplier as an SPDX agent (`Organization: name`)
+pub fn cdx_supplier_agent(comp: &crate::models_cdx::CdxComponent) -> Option<String> {
+    let name = comp.supplier.as_ref()?.name.as_ref()?;
+    Some(format!("Organization: {}", name))
+}
+
+/// Formats the CDX author(s) as an SPDX agent (`Person: name`)
+pub fn cdx_originator_agent(comp: &crate::models_cdx::CdxComponent) -> Option<String> {
+    let names = comp.author_names();
+    if names.is_empty() {
+        return None;
+    }
+    Some(format!("Person: {}", names.join(", ")))
+}
+
+/// Name part of an SPDX agent string, without the `Person:`/`Organization:`
+/// prefix or the trailing `(email)`
+pub fn spdx_agent_name(agent: &str) -> Option<String> {
+    if is_spdx2_no_value(agent) {
+        return None;
+    }
+    let name = agent.split_once(':').map(|(_, rest)| rest).unwrap_or(agent);
+    let name = match name.rfind('(') {
+        Some(pos) if name.trim_end().ends_with(')') => &name[..pos],
+        _ => name,
+    };
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Builds a CDX supplier from an SPDX agent string
+pub fn cdx_supplier_from_agent(agent: &str) -> Option<crate::models_cdx::CdxOrganizationalEntity> {
+    Some(crate::models_cdx::CdxOrganizationalEntity {
+        name: Some(spdx_agent_name(agent)?),
+        ..Default::default()
+    })
+}
+
+/// Appends a CDX external reference for a URL-valued SPDX field
+fn push_url_reference(
+    refs: &mut Vec<crate::models_cdx::CdxExternalReference>,
+    ref_type: &str,
+    url: Option<&str>,
+) {
+    if let Some(url) = url.filter(|u| !u.is_empty() && !is_spdx2_no_value(u)) {
+        refs.push(crate::models_cdx::CdxExternalReference {
+            ref_type: ref_type.to_string(),
+            url: url.to_string(),
+            comment: None,
+            hashes: None,
+        });
+    }
+}
+
+/// CDX external references carried in dedicated SPDX fields rather than
+/// in `externalRefs` / `externalRef`
+const DEDICATED_REFERENCE_TYPES: &[&str] = &["website", "distribution"];
+
+/// Map CDX external references to SPDX 2.3 `externalRefs`.
+///
+/// Advisories go into the SECURITY category; everything else is kept in
+/// OTHER with the CycloneDX type as the reference type.
+fn cdx_to_spdx2_external_refs(comp: &crate::models_cdx::CdxComponent) -> Vec<Spdx2ExternalRef> {
+    comp.external_references
+        .iter()
+        .flatten()
+        .filter(|r| !DEDICATED_REFERENCE_TYPES.contains(&r.ref_type.as_str()))
+        // SPDX 2.3 locators must not contain whitespace
+        .filter(|r| !r.url.is_empty() && !r.url.contains(char::is_whitespace))
+        .map(|r| {
+            let (category, ref_type) = match r.ref_type.as_str() {
+                "advisories" => ("SECURITY", "advisory"),
+                other => ("OTHER", other),
+            };
+            Spdx2ExternalRef {
+                reference_category: category.to_string(),
+                reference_type: ref_type.to_string(),
+                reference_locator: r.url.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Map an SPDX 2.3 `externalRefs` entry back to a CDX external reference
+/// (purl, CPE and SWID are handled separately)
+fn spdx2_external_ref_to_cdx(
+    r: &Spdx2ExternalRef,
+) -> Option<crate::models_cdx::CdxExternalReference> {
+    let (ref_type, comment) = match (r.reference_category.as_str(), r.reference_type.as_str()) {
+        ("SECURITY", "advisory") => ("advisories", None),
+        ("OTHER", t) if crate::models_cdx::is_external_reference_type(t) => (t, None),
+        ("OTHER", t) => ("other", Some(t.to_string())),
+        _ => return None,
+    };
+    Some(crate::models_cdx::CdxExternalReference {
+        ref_type: ref_type.to_string(),
+        url: r.reference_locator.clone(),
+        comment,
+        hashes: None,
+    })
+}
+
+/// CDX external reference types and their SPDX 3 `ExternalRefType`
+const SPDX3_EXTERNAL_REF_TYPES: &[(&str, &str)] = &[
+    ("vcs", "vcs"),
+    ("issue-tracker", "issueTracker"),
+    ("advisories", "securityAdvisory"),
+    ("mailing-list", "mailingList"),
+    ("social", "socialMedia"),
+    ("chat", "chat"),
+    ("documentation", "documentation"),
+    ("support", "support"),
+    ("license", "license"),
+    ("build-meta", "buildMeta"),
+    ("build-system", "buildSystem"),
+    ("release-notes", "releaseNotes"),
+    ("security-contact", "securityPolicy"),
+    ("threat-model", "securityThreatModel"),
+    ("adversary-model", "securityAdversaryModel"),
+    ("pentest-report", "securityPenTestReport"),
+    ("static-analysis-report", "staticAnalysisReport"),
+    ("dynamic-analysis-report", "dynamicAnalysisReport"),
+    ("runtime-analysis-report", "runtimeAnalysisReport"),
+    ("component-analysis-report", "componentAnalysisReport"),
+    ("certification-report", "certificationReport"),
+    ("risk-assessment", "riskAssessment"),
+    (
+        "exploitability-statement",
+        "vulnerabilityExploitabilityAssessment",
+    ),
+    ("source-distribution", "sourceArtifact"),
+];
+
+/// Map CDX external references to SPDX 3 `externalRef` entries.
+///
+/// Types without an SPDX 3 equivalent become `other`, with the CycloneDX
+/// type kept in the comment so the reverse mapping can restore it.
+fn cdx_to_spdx3_external_refs(comp: &crate::models_cdx::CdxComponent) -> Vec<SpdxExternalRef> {
+    comp.external_references
+        .iter()
+        .flatten()
+        .filter(|r| !DEDICATED_REFERENCE_TYPES.contains(&r.ref_type.as_str()))
+        .map(|r| {
+            let mapped = SPDX3_EXTERNAL_REF_TYPES
+                .iter()
+                .find(|(cdx, _)| *cdx == r.ref_type)
+                .map(|(_, spdx)| *spdx);
+            SpdxExternalRef {
+                ref_type: "ExternalRef".to_string(),
+                external_ref_type: Some(mapped.unwrap_or("other").to_string()),
+                locator: vec![r.url.clone()],
+                comment: match mapped {
+                    Some(_) => r.comment.clone(),
+                    None => Some(r.ref_type.clone()),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Map an SPDX 3 `externalRef` back to a CDX external reference
+fn spdx3_external_ref_to_cdx(
+    r: &SpdxExternalRef,
+) -> Option<crate::models_cdx::CdxExternalReference> {
+    let url = r.locator.first()?.clone();
+    let spdx_type = r.external_ref_type.as_deref().unwrap_or("other");
+    let mapped = SPDX3_EXTERNAL_REF_TYPES
+        .iter()
+        .find(|(_, spdx)| *spdx == spdx_type)
+        .map(|(cdx, _)| *cdx);
+
+    let (ref_type, comment) = match (mapped, r.comment.as_deref()) {
+        (Some(cdx), _) => (cdx.to_string(), r.comment.clone()),
+        (None, Some(c)) if crate::models_cdx::is_external_reference_type(c) => {
+            (c.to_string(), None)
+        }
+        (None, _) => ("other".to_string(), r.comment.clone()),
+    };
+    Some(crate::models_cdx::CdxExternalReference {
+        ref_type,
+        url,
+        comment,
+        hashes: None,
+    })
+}
+
 // --- Full Serialization Structs (for writing) ---
 
 /// Top-level SPDX Document (minimal)
@@ -350,11 +1145,15 @@ pub struct SpdxPackage {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub license_concluded: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_declared: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub external_identifier: Option<Vec<SpdxExternalIdentifier>>, // For CPE
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verified_using: Option<Vec<SpdxHash>>, // For hashes
     #[serde(skip_serializing_if = "Option::is_none")]
     pub software_primary_purpose: Option<String>, // For scope mapping
+    #[serde(flatten)]
+    pub details: SpdxPackageDetails,
 }
 
 impl SpdxPackage {
@@ -365,6 +1164,7 @@ impl SpdxPackage {
                 id_type: "ExternalIdentifier".to_string(),
                 external_identifier_type: Some("cpe23Type".to_string()),
                 identifier: Some(cpe.clone()),
+                ..Default::default()
             }]
         });
 
@@ -390,8 +1190,10 @@ impl SpdxPackage {
             .to_string()
         });
 
+        let licenses = cdx_licenses_to_spdx(comp);
+
         Self {
-            spdx_id: format!("SPDXRef-{}", comp.bom_ref),
+            spdx_id: crate::id_map::derive_spdx_id(&comp.bom_ref),
             element_type: if comp.component_type == "file" {
                 "SpdxFile".to_string()
             } else {
@@ -401,14 +1203,12 @@ impl SpdxPackage {
             version_info: comp.version.clone(),
             summary: comp.description.clone(),
             purl: comp.purl.clone(),
-            license_concluded: comp
-                .licenses
-                .as_ref()
-                .and_then(|lics| lics.first())
-                .and_then(|l| l.expression.clone()),
+            license_concluded: licenses.concluded,
+            license_declared: licenses.declared,
             external_identifier,
             verified_using,
             software_primary_purpose,
+            details: SpdxPackageDetails::from_cdx_component(comp),
         }
     }
 }
@@ -427,26 +1227,439 @@ pub struct SpdxElement {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub purl: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub license_concluded: Option<String>,
+    pub license_concluded: Option<String>,
+}
+
+/// Relationship type enum
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RelationshipType {
+    DependsOn,
+    Affects,
+    Contains,
+    Describes,
+}
+
+/// Represents an SPDX Relationship
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxRelationship {
+    #[serde(rename = "spdxElementId")]
+    pub spdx_element_id: String,
+    pub relationship_type: RelationshipType,
+    #[serde(rename = "relatedSpdxElement")]
+    pub related_spdx_element: String,
+}
+
+// --- SPDX 3.0.1 JSON-LD Serialization Structs (for writing) ---
+
+/// JSON-LD context for SPDX 3.0.1 documents
+pub const SPDX_JSONLD_CONTEXT: &str = "https://spdx.org/rdf/3.0.1/spdx-context.jsonld";
+
+/// Blank node identifier of the shared `CreationInfo`
+pub const SPDX_JSONLD_CREATION_INFO_ID: &str = "_:creationinfo";
+
+/// `CreationInfo` blank node referenced by every element in the graph
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdCreationInfo {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub spec_version: String,
+    pub created: String, // ISO 8601, second precision, UTC
+    pub created_by: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub created_using: Vec<String>,
+}
+
+/// Agent or Tool element (referenced from `CreationInfo`)
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdAgent {
+    #[serde(rename = "type")]
+    pub element_type: String, // "SoftwareAgent", "Tool", "Organization", ...
+    pub spdx_id: String,
+    pub creation_info: String,
+    pub name: String,
+}
+
+/// `software_Package` or `software_File` element
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdPackage {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    pub creation_info: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(
+        rename = "software_packageVersion",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub software_package_version: Option<String>,
+    #[serde(
+        rename = "software_packageUrl",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub software_package_url: Option<String>,
+    #[serde(
+        rename = "software_primaryPurpose",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub software_primary_purpose: Option<String>,
+    #[serde(
+        rename = "software_copyrightText",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub software_copyright_text: Option<String>,
+    #[serde(rename = "software_homePage", skip_serializing_if = "Option::is_none")]
+    pub software_home_page: Option<String>,
+    #[serde(
+        rename = "software_downloadLocation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub software_download_location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_identifier: Option<Vec<SpdxExternalIdentifier>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub external_ref: Vec<SpdxExternalRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_using: Option<Vec<SpdxHash>>,
+}
+
+impl SpdxJsonLdPackage {
+    /// Build a JSON-LD package (or file) element from a CDX component
+    pub fn from_cdx_component(comp: &crate::models_cdx::CdxComponent, spdx_id: String) -> Self {
+        let is_file = comp.component_type == "file";
+
+        let mut external_identifier = Vec::new();
+        if let Some(cpe) = &comp.cpe {
+            external_identifier.push(SpdxExternalIdentifier {
+                id_type: "ExternalIdentifier".to_string(),
+                external_identifier_type: Some("cpe23".to_string()),
+                identifier: Some(cpe.clone()),
+                ..Default::default()
+            });
+        }
+        // software_File has no packageUrl property, keep the purl as an identifier
+        if is_file && let Some(purl) = &comp.purl {
+            external_identifier.push(SpdxExternalIdentifier {
+                id_type: "ExternalIdentifier".to_string(),
+                external_identifier_type: Some("packageUrl".to_string()),
+                identifier: Some(purl.clone()),
+                ..Default::default()
+            });
+        }
+
+        let verified_using = comp.hashes.as_ref().map(|hashes| {
+            hashes
+                .iter()
+                .map(|h| SpdxHash {
+                    hash_type: "Hash".to_string(),
+                    algorithm: Some(map_hash_algorithm(&h.alg).to_string()),
+                    hash_value: Some(h.content.clone()),
+                })
+                .collect()
+        });
+
+        Self {
+            element_type: if is_file {
+                "software_File".to_string()
+            } else {
+                "software_Package".to_string()
+            },
+            spdx_id,
+            creation_info: SPDX_JSONLD_CREATION_INFO_ID.to_string(),
+            name: comp.name.clone(),
+            description: comp.description.clone(),
+            software_package_version: if is_file { None } else { comp.version.clone() },
+            software_package_url: if is_file { None } else { comp.purl.clone() },
+            software_primary_purpose: map_primary_purpose(&comp.component_type).map(str::to_string),
+            software_copyright_text: comp.copyright.clone(),
+            software_home_page: comp.external_reference_url("website").map(str::to_string),
+            software_download_location: if is_file {
+                None
+            } else {
+                comp.external_reference_url("distribution")
+                    .map(str::to_string)
+            },
+            external_identifier: if external_identifier.is_empty() {
+                None
+            } else {
+                Some(external_identifier)
+            },
+            external_ref: cdx_to_spdx3_external_refs(comp),
+            verified_using,
+        }
+    }
+}
+
+/// Map a CycloneDX component type to an SPDX 3 `software_primaryPurpose`
+pub fn map_primary_purpose(component_type: &str) -> Option<&'static str> {
+    match component_type {
+        "library" => Some("library"),
+        "application" => Some("application"),
+        "framework" => Some("framework"),
+        "container" => Some("container"),
+        "operating-system" => Some("operatingSystem"),
+        "device" => Some("device"),
+        "device-driver" => Some("deviceDriver"),
+        "firmware" => Some("firmware"),
+        "file" => Some("file"),
+        "platform" => Some("platform"),
+        "machine-learning-model" => Some("model"),
+        "data" => Some("data"),
+        _ => None,
+    }
+}
+
+/// Map a CycloneDX hash algorithm name to the SPDX 3 `HashAlgorithm` vocabulary
+pub fn map_hash_algorithm(alg: &str) -> &'static str {
+    match alg.to_uppercase().as_str() {
+        "MD5" => "md5",
+        "SHA-1" | "SHA1" => "sha1",
+        "SHA-256" | "SHA256" => "sha256",
+        "SHA-384" | "SHA384" => "sha384",
+        "SHA-512" | "SHA512" => "sha512",
+        "SHA3-256" => "sha3_256",
+        "SHA3-384" => "sha3_384",
+        "SHA3-512" => "sha3_512",
+        "BLAKE2B-256" => "blake2b256",
+        "BLAKE2B-384" => "blake2b384",
+        "BLAKE2B-512" => "blake2b512",
+        "BLAKE3" => "blake3",
+        _ => "other",
+    }
+}
+
+/// `security_Vulnerability` element
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdVulnerability {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    pub creation_info: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub external_identifier: Vec<SpdxExternalIdentifier>,
+}
+
+/// Security profile assessment of a vulnerability: one of the
+/// `security_Vex*VulnAssessmentRelationship` or
+/// `security_Cvss*VulnAssessmentRelationship` types. Relationship from the
+/// vulnerability to the assessed elements.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxVulnAssessment {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creation_info: Option<String>,
+    pub from: String,
+    pub relationship_type: String, // "affects", "doesNotAffect", "hasAssessmentFor", ...
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(
+        rename = "security_publishedTime",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub published_time: Option<String>,
+    #[serde(
+        rename = "security_modifiedTime",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub modified_time: Option<String>,
+    #[serde(
+        rename = "security_statusNotes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub status_notes: Option<String>,
+    /// VexAffected only (required there)
+    #[serde(
+        rename = "security_actionStatement",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub action_statement: Option<String>,
+    /// VexNotAffected only
+    #[serde(
+        rename = "security_justificationType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub justification_type: Option<String>,
+    /// VexNotAffected only
+    #[serde(
+        rename = "security_impactStatement",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub impact_statement: Option<String>,
+    /// CVSS assessments only
+    #[serde(
+        rename = "security_score",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub score: Option<f64>,
+    #[serde(
+        rename = "security_severity",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub severity: Option<String>,
+    #[serde(
+        rename = "security_vectorString",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub vector_string: Option<String>,
+    /// The more specific element assessed within the `to` elements
+    #[serde(
+        rename = "security_assessedElement",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub assessed_element: Option<String>,
+    /// EPSS assessments only
+    #[serde(
+        rename = "security_probability",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub probability: Option<f64>,
+    #[serde(
+        rename = "security_percentile",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub percentile: Option<f64>,
+    /// Exploit catalog assessments only
+    #[serde(
+        rename = "security_catalogType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub catalog_type: Option<String>,
+    #[serde(
+        rename = "security_exploited",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub exploited: Option<bool>,
+    #[serde(
+        rename = "security_locator",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub locator: Option<String>,
+    /// SSVC assessments only
+    #[serde(
+        rename = "security_decisionType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub decision_type: Option<String>,
+}
+
+impl SpdxVulnAssessment {
+    /// Whether `type_name` is one of the vulnerability assessment types
+    pub fn is_assessment_type(type_name: &str) -> bool {
+        type_name.starts_with("security_") && type_name.ends_with("VulnAssessmentRelationship")
+    }
+
+    /// Whether this is a VEX (rather than a scoring) assessment
+    pub fn is_vex(&self) -> bool {
+        self.element_type.starts_with("security_Vex")
+    }
+
+    /// The elements the assessment applies to
+    pub fn assessed(&self) -> Vec<String> {
+        match &self.assessed_element {
+            Some(element) => vec![element.clone()],
+            None => self.to.clone(),
+        }
+    }
+}
+
+/// `simplelicensing_LicenseExpression` element
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdLicenseExpression {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    pub creation_info: String,
+    #[serde(rename = "simplelicensing_licenseExpression")]
+    pub license_expression: String,
+    /// `LicenseRef-` ids used in the expression -> their licensing text
+    #[serde(
+        rename = "simplelicensing_customIdToUri",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub custom_id_to_uri: Vec<SpdxJsonLdDictionaryEntry>,
+}
+
+/// `DictionaryEntry` (key/value pair)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpdxJsonLdDictionaryEntry {
+    #[serde(rename = "type", default)]
+    pub entry_type: String,
+    pub key: String,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// `simplelicensing_SimpleLicensingText` element: the text of a custom license
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdLicensingText {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    pub creation_info: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "simplelicensing_licenseText")]
+    pub license_text: String,
 }
 
-/// Relationship type enum
+/// `Relationship` element (`from` one element `to` many)
 #[derive(Serialize, Debug)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum RelationshipType {
-    DependsOn,
-    Affects,
+#[serde(rename_all = "camelCase")]
+pub struct SpdxJsonLdRelationship {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    pub creation_info: String,
+    pub from: String,
+    pub relationship_type: String, // "dependsOn", "affects", "hasConcludedLicense", ...
+    pub to: Vec<String>,
 }
 
-/// Represents an SPDX Relationship
+/// `SpdxDocument` element describing the whole graph
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct SpdxRelationship {
-    #[serde(rename = "spdxElementId")]
-    pub spdx_element_id: String,
-    pub relationship_type: RelationshipType,
-    #[serde(rename = "relatedSpdxElement")]
-    pub related_spdx_element: String,
+pub struct SpdxJsonLdDocument {
+    #[serde(rename = "type")]
+    pub element_type: String,
+    pub spdx_id: String,
+    pub creation_info: String,
+    pub name: String,
+    pub profile_conformance: Vec<String>,
+    pub data_license: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub root_element: Vec<String>,
 }
 
 // --- Streaming Visitor Logic (for SPDX -> CDX) ---
@@ -454,6 +1667,9 @@ pub struct SpdxRelationship {
 /// Custom visitor for Pass 1 (Indexing Pass)
 pub struct SpdxPass1Visitor<'a> {
     pub index: &'a mut crate::converter_spdx_to_cdx::SpdxRelationshipIndex,
+    pub licenses: &'a mut crate::converter_spdx_to_cdx::SpdxLicenseIndex,
+    /// `creationInfo.created` of the document (first `CreationInfo` in JSON-LD)
+    pub created: &'a mut Option<String>,
     pub progress: crate::progress::ProgressTracker,
 }
 
@@ -484,9 +1700,23 @@ impl<'de, 'a> Visitor<'de> for SpdxPass1Visitor<'a> {
                     found_relationships = true;
                     map.next_value_seed(JsonLdGraphStreamVisitor {
                         index: self.index,
+                        licenses: self.licenses,
+                        created: self.created,
                         progress: self.progress.clone(),
                     })?;
                 }
+                "creationInfo" => {
+                    let creation_info = map.next_value::<serde_json::Value>()?;
+                    if let Some(created) = creation_info.get("created").and_then(|c| c.as_str()) {
+                        *self.created = Some(created.to_string());
+                    }
+                }
+                "hasExtractedLicensingInfos" => {
+                    // SPDX 2.3 (and simple JSON): texts of LicenseRef- ids
+                    for license in map.next_value::<Vec<SpdxExtractedLicense>>()? {
+                        self.licenses.add_extracted(license);
+                    }
+                }
                 _ => {
                     // Skip all other keys
                     let _ = map.next_value::<IgnoredAny>()?;
@@ -502,6 +1732,18 @@ impl<'de, 'a> Visitor<'de> for SpdxPass1Visitor<'a> {
     }
 }
 
+/// An entry of the 'relationships' array: a relationship, or in the SPDX 3
+/// simple layout also a vulnerability assessment (read in Pass 3)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxRelationshipEntry {
+    #[serde(rename = "type")]
+    element_type: Option<String>,
+    spdx_element_id: Option<String>,
+    relationship_type: String,
+    related_spdx_element: Option<String>,
+}
+
 /// Visitor for the 'relationships' array in Pass 1
 struct SpdxRelationshipStreamVisitor<'a> {
     index: &'a mut crate::converter_spdx_to_cdx::SpdxRelationshipIndex,
@@ -530,12 +1772,28 @@ impl<'de, 'a> Visitor<'de> for SpdxRelationshipStreamVisitor<'a> {
     where
         A: de::SeqAccess<'de>,
     {
-        while let Some(rel) = seq.next_element::<SpdxRelationshipMinimal>()? {
-            // Add this relationship to our in-memory index
-            self.index
-                .entry(rel.spdx_element_id.clone())
-                .or_default()
-                .push(rel);
+        while let Some(entry) = seq.next_element::<SpdxRelationshipEntry>()? {
+            let (Some(spdx_element_id), Some(related_spdx_element)) =
+                (entry.spdx_element_id, entry.related_spdx_element)
+            else {
+                match entry.element_type.as_deref() {
+                    Some(t) if SpdxVulnAssessment::is_assessment_type(t) => continue,
+                    _ => {
+                        return Err(de::Error::custom(
+                            "relationship without spdxElementId or relatedSpdxElement",
+                        ));
+                    }
+                }
+            };
+            // SPDX 2.3 also expresses edges from the target side
+            let rel = SpdxRelationshipMinimal {
+                spdx_element_id,
+                relationship_type: entry.relationship_type,
+                related_spdx_element,
+            }
+            .normalized();
+            // Add this relationship to our index
+            self.index.insert(rel).map_err(de::Error::custom)?;
             self.progress.increment_relationship();
         }
         Ok(())
@@ -545,6 +1803,8 @@ impl<'de, 'a> Visitor<'de> for SpdxRelationshipStreamVisitor<'a> {
 /// Visitor for the '@graph' array in JSON-LD format (used in both passes)
 struct JsonLdGraphStreamVisitor<'a> {
     index: &'a mut crate::converter_spdx_to_cdx::SpdxRelationshipIndex,
+    licenses: &'a mut crate::converter_spdx_to_cdx::SpdxLicenseIndex,
+    created: &'a mut Option<String>,
     progress: crate::progress::ProgressTracker,
 }
 
@@ -570,10 +1830,23 @@ impl<'de, 'a> Visitor<'de> for JsonLdGraphStreamVisitor<'a> {
     where
         A: de::SeqAccess<'de>,
     {
-        // In Pass 1, we only care about relationships
+        // In Pass 1, we only care about relationships and license elements
         // We need to deserialize as a generic Value to check the type
         while let Some(value) = seq.next_element::<serde_json::Value>()? {
-            if let Some(type_name) = value.get("type").and_then(|t| t.as_str())
+            let type_name = value.get("type").and_then(|t| t.as_str());
+            if matches!(
+                type_name,
+                Some("simplelicensing_LicenseExpression" | "simplelicensing_SimpleLicensingText")
+            ) {
+                self.licenses.add_jsonld_element(&value);
+            } else if type_name == Some("CreationInfo") {
+                if self.created.is_none() {
+                    *self.created = value
+                        .get("created")
+                        .and_then(|c| c.as_str())
+                        .map(str::to_string);
+                }
+            } else if let Some(type_name) = type_name
                 && (type_name == "Relationship" || type_name == "LifecycleScopedRelationship")
             {
                 // Parse as JSON-LD relationship
@@ -587,10 +1860,7 @@ impl<'de, 'a> Visitor<'de> for JsonLdGraphStreamVisitor<'a> {
                         relationship_type: rel.relationship_type.clone(),
                         related_spdx_element: target.clone(),
                     };
-                    self.index
-                        .entry(simple_rel.spdx_element_id.clone())
-                        .or_default()
-                        .push(simple_rel);
+                    self.index.insert(simple_rel).map_err(de::Error::custom)?;
                     self.progress.increment_relationship();
                 }
             }
@@ -603,12 +1873,32 @@ impl<'de, 'a> Visitor<'de> for JsonLdGraphStreamVisitor<'a> {
 pub struct SpdxPass2Visitor<'a, W: std::io::Write> {
     pub writer: &'a mut BufWriter<W>,
     pub index: &'a crate::converter_spdx_to_cdx::SpdxRelationshipIndex,
-    pub first_component: bool,
+    pub licenses: &'a crate::converter_spdx_to_cdx::SpdxLicenseIndex,
+    pub components: &'a mut crate::converter_spdx_to_cdx::ComponentOutput,
     pub first_vulnerability: bool,
     pub progress: crate::progress::ProgressTracker,
     pub packages_only: bool,
+    pub fidelity: crate::fidelity::FidelityReport,
+    pub ids: crate::id_map::IdMap,
 }
 
+/// Top-level SPDX keys that are consumed by one of the passes (or only
+/// describe the document's own serialization)
+const SPDX_DOCUMENT_KEYS: &[&str] = &[
+    "spdxVersion",
+    "SPDXID",
+    "spdxId",
+    "dataLicense",
+    "@context",
+    "elements",
+    "@graph",
+    "packages",
+    "files",
+    "relationships",
+    "documentDescribes",
+    "hasExtractedLicensingInfos",
+];
+
 impl<'de, 'a, W: std::io::Write> Visitor<'de> for SpdxPass2Visitor<'a, W> {
     type Value = ();
 
@@ -633,15 +1923,28 @@ impl<'de, 'a, W: std::io::Write> Visitor<'de> for SpdxPass2Visitor<'a, W> {
                     found_elements = true;
                     map.next_value_seed(JsonLdGraphPass2Visitor { state: &mut self })?;
                 }
+                "packages" => {
+                    // SPDX 2.3 format: Found packages array
+                    found_elements = true;
+                    map.next_value_seed(Spdx2PackageStreamVisitor { state: &mut self })?;
+                }
+                "files" if !self.packages_only => {
+                    // SPDX 2.3 format: Found files array
+                    found_elements = true;
+                    map.next_value_seed(Spdx2FileStreamVisitor { state: &mut self })?;
+                }
                 _ => {
                     // Skip all other keys
+                    if !SPDX_DOCUMENT_KEYS.contains(&key.as_str()) {
+                        self.fidelity.record(&format!("document.{}", key), "");
+                    }
                     let _ = map.next_value::<IgnoredAny>()?;
                 }
             }
         }
         if !found_elements {
             eprintln!(
-                "Warning: No elements found in SPDX file (looked for 'elements' or '@graph')"
+                "Warning: No elements found in SPDX file (looked for 'elements', '@graph' or 'packages')"
             );
         }
         Ok(())
@@ -678,12 +1981,29 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for SpdxElementStreamVisitor<'
         A: de::SeqAccess<'de>,
     {
         while let Some(element) = seq.next_element::<SpdxElementMinimal>()? {
+            let fidelity = &self.state.fidelity;
+            match element.element_type.as_str() {
+                "SpdxPackage" | "software_Package" | "SpdxFile" | "software_File" => {
+                    fidelity.record_unmapped(
+                        &element.element_type,
+                        element.extra.keys(),
+                        &element.spdx_id,
+                    );
+                }
+                // Converted in Pass 3
+                "SpdxVulnerability" => {}
+                other if SpdxVulnAssessment::is_assessment_type(other) => {}
+                other => fidelity.record(&format!("element.{}", other), &element.spdx_id),
+            }
+
             // This is where we call the conversion logic
             crate::converter_spdx_to_cdx::handle_spdx_element(
                 element,
                 self.state.writer,
                 self.state.index,
-                &mut self.state.first_component,
+                self.state.licenses,
+                &self.state.ids,
+                self.state.components,
                 &mut self.state.first_vulnerability,
             )
             .map_err(de::Error::custom)?;
@@ -693,6 +2013,98 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for SpdxElementStreamVisitor<'
     }
 }
 
+/// Visitor for the SPDX 2.3 'packages' array in Pass 2
+pub struct Spdx2PackageStreamVisitor<'a, 'b, W: std::io::Write> {
+    state: &'b mut SpdxPass2Visitor<'a, W>,
+}
+
+impl<'de, 'a, 'b, W: std::io::Write> de::DeserializeSeed<'de>
+    for Spdx2PackageStreamVisitor<'a, 'b, W>
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for Spdx2PackageStreamVisitor<'a, 'b, W> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of SPDX 2.3 packages")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        while let Some(package) = seq.next_element::<Spdx2Package>()? {
+            self.state
+                .fidelity
+                .record_unmapped("package", package.extra.keys(), &package.spdx_id);
+            let component = package.to_cdx_component(
+                self.state.ids.bom_ref(&package.spdx_id),
+                self.state.licenses,
+            );
+            self.state
+                .components
+                .write(component, self.state.writer)
+                .map_err(de::Error::custom)?;
+            self.state.progress.increment_element();
+        }
+        Ok(())
+    }
+}
+
+/// Visitor for the SPDX 2.3 'files' array in Pass 2
+pub struct Spdx2FileStreamVisitor<'a, 'b, W: std::io::Write> {
+    state: &'b mut SpdxPass2Visitor<'a, W>,
+}
+
+impl<'de, 'a, 'b, W: std::io::Write> de::DeserializeSeed<'de>
+    for Spdx2FileStreamVisitor<'a, 'b, W>
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for Spdx2FileStreamVisitor<'a, 'b, W> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of SPDX 2.3 files")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        while let Some(file) = seq.next_element::<Spdx2File>()? {
+            self.state
+                .fidelity
+                .record_unmapped("file", file.extra.keys(), &file.spdx_id);
+            let component =
+                file.to_cdx_component(self.state.ids.bom_ref(&file.spdx_id), self.state.licenses);
+            self.state
+                .components
+                .write(component, self.state.writer)
+                .map_err(de::Error::custom)?;
+            self.state.progress.increment_element();
+        }
+        Ok(())
+    }
+}
+
 /// Visitor for the '@graph' array in Pass 2 (Conversion)
 pub struct JsonLdGraphPass2Visitor<'a, 'b, W: std::io::Write> {
     state: &'b mut SpdxPass2Visitor<'a, W>,
@@ -735,19 +2147,31 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for JsonLdGraphPass2Visitor<'a
                     // Parse as JSON-LD element with full data
                     let element: JsonLdElement =
                         serde_json::from_value(value).map_err(de::Error::custom)?;
+                    self.state.fidelity.record_unmapped(
+                        &element.element_type,
+                        element.extra.keys(),
+                        &element.spdx_id,
+                    );
 
                     // Call enhanced handler with full element data
                     crate::converter_spdx_to_cdx::handle_jsonld_element(
                         element,
                         self.state.writer,
                         self.state.index,
-                        &mut self.state.first_component,
+                        self.state.licenses,
+                        &self.state.ids,
+                        self.state.components,
                     )
                     .map_err(de::Error::custom)?;
                     self.state.progress.increment_element();
                 } else if type_name == "security_Vulnerability" {
                     // Skip for now - will handle in Pass 3
                     self.state.progress.increment_element();
+                } else if !is_jsonld_handled_elsewhere(type_name) {
+                    let id = value.get("spdxId").and_then(|v| v.as_str()).unwrap_or("");
+                    self.state
+                        .fidelity
+                        .record(&format!("element.{}", type_name), id);
                 }
             }
         }
@@ -755,14 +2179,30 @@ impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for JsonLdGraphPass2Visitor<'a
     }
 }
 
-/// Custom visitor for Pass 3 (Vulnerability Extraction Pass)
-pub struct SpdxPass3VulnVisitor<'a, W: std::io::Write> {
-    pub writer: &'a mut BufWriter<W>,
-    pub serial_number: String,
-    pub first_vuln: bool,
+/// JSON-LD types consumed by Pass 1 / Pass 3, or that only describe the
+/// document itself (its creation info, creating tool and agent)
+fn is_jsonld_handled_elsewhere(type_name: &str) -> bool {
+    matches!(
+        type_name,
+        "Relationship"
+            | "LifecycleScopedRelationship"
+            | "CreationInfo"
+            | "SpdxDocument"
+            | "Tool"
+            | "SoftwareAgent"
+            | "simplelicensing_LicenseExpression"
+            | "simplelicensing_SimpleLicensingText"
+    ) || SpdxVulnAssessment::is_assessment_type(type_name)
+}
+
+/// Custom visitor for Pass 3 (Vulnerability Extraction Pass). Collects the
+/// vulnerabilities and their assessments from `@graph` (JSON-LD) or
+/// `elements` and `relationships` (simple JSON).
+pub struct SpdxPass3VulnVisitor<'a> {
+    pub vulnerabilities: &'a mut crate::converter_spdx_to_cdx::SpdxVulnerabilityIndex,
 }
 
-impl<'de, 'a, W: std::io::Write> Visitor<'de> for SpdxPass3VulnVisitor<'a, W> {
+impl<'de, 'a> Visitor<'de> for SpdxPass3VulnVisitor<'a> {
     type Value = ();
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -774,36 +2214,25 @@ impl<'de, 'a, W: std::io::Write> Visitor<'de> for SpdxPass3VulnVisitor<'a, W> {
         A: MapAccess<'de>,
     {
         while let Some(key) = map.next_key::<String>()? {
-            if key == "@graph" {
-                // This is JSON-LD format - process the graph
-                let state = JsonLdVulnState {
-                    writer: self.writer,
-                    serial_number: &self.serial_number,
-                    first_vuln: self.first_vuln,
-                };
-                map.next_value_seed(JsonLdGraphPass3Visitor { state })?;
+            if key == "@graph" || key == "elements" || key == "relationships" {
+                map.next_value_seed(VulnerabilityStreamVisitor {
+                    vulnerabilities: &mut *self.vulnerabilities,
+                })?;
             } else {
                 // Skip other fields
-                map.next_value::<serde_json::Value>()?;
+                map.next_value::<IgnoredAny>()?;
             }
         }
         Ok(())
     }
 }
 
-/// Helper struct to hold state during Pass 3 graph processing
-struct JsonLdVulnState<'a, 'b, W: std::io::Write> {
-    writer: &'a mut BufWriter<W>,
-    serial_number: &'b str,
-    first_vuln: bool,
-}
-
-/// Visitor for @graph array in Pass 3 - extracts vulnerabilities and VEX relationships
-struct JsonLdGraphPass3Visitor<'a, 'b, W: std::io::Write> {
-    state: JsonLdVulnState<'a, 'b, W>,
+/// Visitor for the `@graph`, `elements` or `relationships` array in Pass 3
+struct VulnerabilityStreamVisitor<'a> {
+    vulnerabilities: &'a mut crate::converter_spdx_to_cdx::SpdxVulnerabilityIndex,
 }
 
-impl<'de, 'a, 'b, W: std::io::Write> DeserializeSeed<'de> for JsonLdGraphPass3Visitor<'a, 'b, W> {
+impl<'de, 'a> DeserializeSeed<'de> for VulnerabilityStreamVisitor<'a> {
     type Value = ();
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
@@ -814,98 +2243,20 @@ impl<'de, 'a, 'b, W: std::io::Write> DeserializeSeed<'de> for JsonLdGraphPass3Vi
     }
 }
 
-impl<'de, 'a, 'b, W: std::io::Write> Visitor<'de> for JsonLdGraphPass3Visitor<'a, 'b, W> {
+impl<'de, 'a> Visitor<'de> for VulnerabilityStreamVisitor<'a> {
     type Value = ();
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("@graph array for vulnerability extraction")
+        formatter.write_str("an array of SPDX elements for vulnerability extraction")
     }
 
-    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
     {
-        // First pass: collect vulnerabilities
-        let mut vulnerabilities: Vec<JsonLdVulnerability> = Vec::new();
-        let mut vex_relationships: Vec<JsonLdVexRelationship> = Vec::new();
-
-        // We need to collect all data first, then write
-        // This requires deserializing the entire graph for this pass
         while let Some(element) = seq.next_element::<serde_json::Value>()? {
-            if let Some(type_name) = element.get("type").and_then(|t| t.as_str()) {
-                if type_name == "security_Vulnerability" {
-                    if let Ok(vuln) = serde_json::from_value::<JsonLdVulnerability>(element) {
-                        vulnerabilities.push(vuln);
-                    }
-                } else if type_name.starts_with("security_Vex")
-                    && let Ok(vex) = serde_json::from_value::<JsonLdVexRelationship>(element)
-                {
-                    vex_relationships.push(vex);
-                }
-            }
-        }
-
-        // Now write vulnerabilities with their VEX assessments
-        for vuln in vulnerabilities {
-            if let Some(cve_id) = vuln.extract_cve_id() {
-                // Find VEX relationships for this vulnerability
-                let affects: Vec<String> = vex_relationships
-                    .iter()
-                    .filter(|vex| vex.from == vuln.spdx_id)
-                    .flat_map(|vex| vex.to.iter())
-                    .map(|spdx_id| {
-                        let bom_ref = crate::converter_spdx_to_cdx::extract_bom_ref(spdx_id);
-                        format!("{}#{}", self.state.serial_number, bom_ref)
-                    })
-                    .collect();
-
-                // Determine VEX state
-                let state = vex_relationships
-                    .iter()
-                    .find(|vex| vex.from == vuln.spdx_id)
-                    .map(|vex| vex.map_state())
-                    .unwrap_or_else(|| "not_affected".to_string());
-
-                // Write vulnerability (even if no affects, for now)
-                if !self.state.first_vuln {
-                    self.state
-                        .writer
-                        .write_all(b",\n")
-                        .map_err(de::Error::custom)?;
-                }
-                self.state.first_vuln = false;
-
-                let cdx_vuln = crate::models_cdx::CdxVulnerability {
-                    id: cve_id.clone(),
-                    source: Some(crate::models_cdx::CdxVulnSource {
-                        name: "NVD".to_string(),
-                        url: Some(format!("https://nvd.nist.gov/vuln/detail/{}", cve_id)),
-                    }),
-                    description: None,
-                    analysis: Some(crate::models_cdx::CdxAnalysis {
-                        state,
-                        detail: None,
-                        first_issued: None,
-                        last_updated: None,
-                    }),
-                    affects: Some(
-                        affects
-                            .into_iter()
-                            .map(|ref_str| crate::models_cdx::CdxAffects { bom_ref: ref_str })
-                            .collect(),
-                    ),
-                    extra: HashMap::new(),
-                };
-
-                self.state
-                    .writer
-                    .write_all(b"    ")
-                    .map_err(de::Error::custom)?;
-                serde_json::to_writer(&mut *self.state.writer, &cdx_vuln)
-                    .map_err(de::Error::custom)?;
-            }
+            self.vulnerabilities.add_element(element);
         }
-
         Ok(())
     }
 }
@@ -925,13 +2276,16 @@ mod tests {
             summary: None,
             purl: None,
             license_concluded: None,
+            license_declared: None,
             external_identifier: Some(vec![SpdxExternalIdentifier {
                 id_type: "ExternalIdentifier".to_string(),
                 external_identifier_type: Some("cpe23".to_string()),
                 identifier: Some("cpe:2.3:a:vendor:product:1.0.0".to_string()),
+                ..Default::default()
             }]),
             verified_using: None,
             software_primary_purpose: None,
+            details: SpdxPackageDetails::default(),
             extra: HashMap::new(),
         };
 
@@ -949,6 +2303,7 @@ mod tests {
             id_type: "ExternalIdentifier".to_string(),
             external_identifier_type: Some("purl".to_string()),
             identifier: Some("pkg:maven/group/artifact".to_string()),
+            ..Default::default()
         }]);
         assert_eq!(pkg.extract_cpe(), None);
     }
@@ -963,13 +2318,16 @@ mod tests {
             summary: None,
             purl: None,
             license_concluded: None,
+            license_declared: None,
             external_identifier: Some(vec![SpdxExternalIdentifier {
                 id_type: "ExternalIdentifier".to_string(),
                 external_identifier_type: Some("purl".to_string()),
                 identifier: Some("pkg:maven/com.example/my-library@1.0.0".to_string()),
+                ..Default::default()
             }]),
             verified_using: None,
             software_primary_purpose: None,
+            details: SpdxPackageDetails::default(),
             extra: HashMap::new(),
         };
 
@@ -989,6 +2347,7 @@ mod tests {
             summary: None,
             purl: None,
             license_concluded: None,
+            license_declared: None,
             external_identifier: None,
             verified_using: Some(vec![
                 SpdxHash {
@@ -1003,6 +2362,7 @@ mod tests {
                 },
             ]),
             software_primary_purpose: None,
+            details: SpdxPackageDetails::default(),
             extra: HashMap::new(),
         };
 
@@ -1025,6 +2385,7 @@ mod tests {
             summary: None,
             purl: None,
             license_concluded: None,
+            license_declared: None,
             external_identifier: None,
             verified_using: Some(vec![SpdxHash {
                 hash_type: "Hash".to_string(),
@@ -1032,6 +2393,7 @@ mod tests {
                 hash_value: Some("abc123".to_string()),
             }]),
             software_primary_purpose: None,
+            details: SpdxPackageDetails::default(),
             extra: HashMap::new(),
         };
 
@@ -1049,9 +2411,11 @@ mod tests {
             summary: None,
             purl: None,
             license_concluded: None,
+            license_declared: None,
             external_identifier: None,
             verified_using: None,
             software_primary_purpose: Some("install".to_string()),
+            details: SpdxPackageDetails::default(),
             extra: HashMap::new(),
         };
 
@@ -1086,7 +2450,7 @@ mod tests {
             }]),
             scope: Some("required".to_string()),
             licenses: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let spdx_pkg = SpdxPackage::from_cdx_component(&cdx_comp);
@@ -1135,7 +2499,7 @@ mod tests {
             hashes: None,
             scope: None,
             licenses: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let spdx_pkg = SpdxPackage::from_cdx_component(&cdx_comp);
@@ -1164,7 +2528,7 @@ mod tests {
             hashes: None,
             scope: Some("required".to_string()),
             licenses: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let spdx_pkg = SpdxPackage::from_cdx_component(&cdx_comp);
@@ -1186,4 +2550,318 @@ mod tests {
         let spdx_pkg = SpdxPackage::from_cdx_component(&cdx_comp);
         assert_eq!(spdx_pkg.software_primary_purpose, Some("other".to_string()));
     }
+
+    #[test]
+    fn test_jsonld_package_from_cdx_component() {
+        let mut cdx_comp = CdxComponent {
+            component_type: "library".to_string(),
+            bom_ref: "pkg-123".to_string(),
+            name: "my-library".to_string(),
+            version: Some("2.0.0".to_string()),
+            purl: Some("pkg:npm/my-library@2.0.0".to_string()),
+            cpe: Some("cpe:2.3:a:vendor:my-library:2.0.0".to_string()),
+            description: None,
+            hashes: Some(vec![CdxHash {
+                alg: "SHA-256".to_string(),
+                content: "abcdef123456".to_string(),
+            }]),
+            scope: None,
+            licenses: None,
+            ..Default::default()
+        };
+
+        let pkg = SpdxJsonLdPackage::from_cdx_component(
+            &cdx_comp,
+            "https://example.com/doc#SPDXRef-pkg-123".to_string(),
+        );
+        assert_eq!(pkg.element_type, "software_Package");
+        assert_eq!(pkg.creation_info, SPDX_JSONLD_CREATION_INFO_ID);
+        assert_eq!(pkg.software_package_version, Some("2.0.0".to_string()));
+        assert_eq!(
+            pkg.software_package_url,
+            Some("pkg:npm/my-library@2.0.0".to_string())
+        );
+        assert_eq!(pkg.software_primary_purpose, Some("library".to_string()));
+        let ext_ids = pkg.external_identifier.unwrap();
+        assert_eq!(ext_ids.len(), 1);
+        assert_eq!(
+            ext_ids[0].external_identifier_type,
+            Some("cpe23".to_string())
+        );
+        let hashes = pkg.verified_using.unwrap();
+        assert_eq!(hashes[0].algorithm, Some("sha256".to_string()));
+
+        // Files carry no package version/url; the purl becomes an identifier
+        cdx_comp.component_type = "file".to_string();
+        let file = SpdxJsonLdPackage::from_cdx_component(
+            &cdx_comp,
+            "https://example.com/doc#SPDXRef-pkg-123".to_string(),
+        );
+        assert_eq!(file.element_type, "software_File");
+        assert!(file.software_package_version.is_none());
+        assert!(file.software_package_url.is_none());
+        let ext_ids = file.external_identifier.unwrap();
+        assert_eq!(ext_ids.len(), 2);
+        assert_eq!(
+            ext_ids[1].external_identifier_type,
+            Some("packageUrl".to_string())
+        );
+    }
+
+    #[test]
+    fn test_map_hash_algorithm() {
+        assert_eq!(map_hash_algorithm("SHA-256"), "sha256");
+        assert_eq!(map_hash_algorithm("sha-1"), "sha1");
+        assert_eq!(map_hash_algorithm("SHA3-512"), "sha3_512");
+        assert_eq!(map_hash_algorithm("BLAKE2b-256"), "blake2b256");
+        assert_eq!(map_hash_algorithm("MD5"), "md5");
+        assert_eq!(map_hash_algorithm("whirlpool"), "other");
+    }
+
+    #[test]
+    fn test_jsonld_element_spdx_field_names() {
+        let element: JsonLdElement = serde_json::from_value(serde_json::json!({
+            "type": "software_Package",
+            "spdxId": "https://example.com/doc#SPDXRef-pkg",
+            "name": "pkg",
+            "software_packageVersion": "1.2.3",
+            "software_packageUrl": "pkg:npm/pkg@1.2.3",
+            "software_primaryPurpose": "install"
+        }))
+        .unwrap();
+
+        assert_eq!(element.software_package_version, Some("1.2.3".to_string()));
+        assert_eq!(
+            element.extract_purl(),
+            Some("pkg:npm/pkg@1.2.3".to_string())
+        );
+        assert_eq!(element.map_scope(), Some("required".to_string()));
+    }
+
+    #[test]
+    fn test_spdx2_relationship_normalized() {
+        let rel = SpdxRelationshipMinimal {
+            spdx_element_id: "SPDXRef-lib".to_string(),
+            relationship_type: "RUNTIME_DEPENDENCY_OF".to_string(),
+            related_spdx_element: "SPDXRef-app".to_string(),
+        }
+        .normalized();
+        assert_eq!(rel.spdx_element_id, "SPDXRef-app");
+        assert_eq!(rel.relationship_type, "DEPENDS_ON");
+        assert_eq!(rel.related_spdx_element, "SPDXRef-lib");
+
+        // Forward relationships are left untouched
+        let rel = SpdxRelationshipMinimal {
+            spdx_element_id: "SPDXRef-app".to_string(),
+            relationship_type: "DEPENDS_ON".to_string(),
+            related_spdx_element: "SPDXRef-lib".to_string(),
+        }
+        .normalized();
+        assert_eq!(rel.spdx_element_id, "SPDXRef-app");
+    }
+
+    #[test]
+    fn test_spdx2_checksums_to_cdx() {
+        let checksums = vec![
+            Spdx2Checksum {
+                algorithm: "SHA256".to_string(),
+                checksum_value: "abc".to_string(),
+            },
+            Spdx2Checksum {
+                algorithm: "BLAKE2b-256".to_string(),
+                checksum_value: "def".to_string(),
+            },
+            Spdx2Checksum {
+                algorithm: "SHA224".to_string(),
+                checksum_value: "skipped".to_string(),
+            },
+        ];
+        let hashes = spdx2_checksums_to_cdx(&checksums).unwrap();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[0].alg, "SHA-256");
+        assert_eq!(hashes[1].alg, "BLAKE2b-256");
+
+        assert!(spdx2_checksums_to_cdx(&[]).is_none());
+    }
+
+    #[test]
+    fn test_spdx_agent_name() {
+        assert_eq!(
+            spdx_agent_name("Organization: ACME Corp (ops@acme.example)"),
+            Some("ACME Corp".to_string())
+        );
+        assert_eq!(
+            spdx_agent_name("Person: Jane Doe"),
+            Some("Jane Doe".to_string())
+        );
+        assert_eq!(spdx_agent_name("NOASSERTION"), None);
+    }
+
+    #[test]
+    fn test_package_details_round_trip() {
+        let cdx_comp = CdxComponent {
+            component_type: "library".to_string(),
+            name: "openssl".to_string(),
+            supplier: Some(crate::models_cdx::CdxOrganizationalEntity {
+                name: Some("OpenSSL Foundation".to_string()),
+                ..Default::default()
+            }),
+            author: Some("Jane Doe".to_string()),
+            copyright: Some("Copyright (c) OpenSSL".to_string()),
+            external_references: Some(vec![
+                crate::models_cdx::CdxExternalReference {
+                    ref_type: "website".to_string(),
+                    url: "https://www.openssl.org".to_string(),
+                    comment: None,
+                    hashes: None,
+                },
+                crate::models_cdx::CdxExternalReference {
+                    ref_type: "issue-tracker".to_string(),
+                    url: "https://github.com/openssl/openssl/issues".to_string(),
+                    comment: None,
+                    hashes: None,
+                },
+                crate::models_cdx::CdxExternalReference {
+                    ref_type: "model-card".to_string(),
+                    url: "https://example.com/card".to_string(),
+                    comment: None,
+                    hashes: None,
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let details = SpdxPackageDetails::from_cdx_component(&cdx_comp);
+        assert_eq!(
+            details.supplier.as_deref(),
+            Some("Organization: OpenSSL Foundation")
+        );
+        assert_eq!(details.originator.as_deref(), Some("Person: Jane Doe"));
+        assert_eq!(details.homepage.as_deref(), Some("https://www.openssl.org"));
+        assert_eq!(
+            details.external_ref[0].external_ref_type.as_deref(),
+            Some("issueTracker")
+        );
+        // No SPDX 3 equivalent: kept as "other" with the CDX type as comment
+        assert_eq!(
+            details.external_ref[1].external_ref_type.as_deref(),
+            Some("other")
+        );
+        assert_eq!(
+            details.external_ref[1].comment.as_deref(),
+            Some("model-card")
+        );
+
+        let mut restored = CdxComponent::default();
+        details.apply_to(&mut restored);
+        assert_eq!(restored.supplier, cdx_comp.supplier);
+        assert_eq!(restored.author, cdx_comp.author);
+        assert_eq!(restored.copyright, cdx_comp.copyright);
+        assert_eq!(restored.external_references, cdx_comp.external_references);
+    }
+
+    #[test]
+    fn test_cdx_licenses_to_spdx() {
+        use crate::models_cdx::{CdxAttachment, CdxLicense, CdxLicenseChoice};
+
+        let comp = CdxComponent {
+            licenses: Some(vec![
+                CdxLicenseChoice {
+                    expression: Some("Apache-2.0 OR MIT".to_string()),
+                    ..Default::default()
+                },
+                CdxLicenseChoice {
+                    license: Some(CdxLicense {
+                        id: Some("BSD-3-Clause".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                CdxLicenseChoice {
+                    license: Some(CdxLicense {
+                        name: Some("Acme EULA v2".to_string()),
+                        acknowledgement: Some("declared".to_string()),
+                        text: Some(CdxAttachment {
+                            content: "Terms".to_string(),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let licenses = cdx_licenses_to_spdx(&comp);
+        assert_eq!(
+            licenses.concluded.as_deref(),
+            Some("(Apache-2.0 OR MIT) AND BSD-3-Clause")
+        );
+        assert_eq!(
+            licenses.declared.as_deref(),
+            Some("LicenseRef-Acme-EULA-v2")
+        );
+        assert_eq!(licenses.extracted.len(), 1);
+        assert_eq!(licenses.extracted[0].extracted_text, "Terms");
+
+        assert_eq!(
+            cdx_licenses_to_spdx(&CdxComponent::default()),
+            SpdxLicenses::default()
+        );
+
+        // Deprecated ids are upgraded on the way
+        let comp = CdxComponent {
+            licenses: Some(vec![CdxLicenseChoice {
+                expression: Some("gpl-2.0+ and mit".to_string()),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(
+            cdx_licenses_to_spdx(&comp).concluded.as_deref(),
+            Some("GPL-2.0-or-later AND MIT")
+        );
+    }
+
+    #[test]
+    fn test_license_ref_id() {
+        assert_eq!(
+            license_ref_id("Acme EULA (v2)"),
+            "LicenseRef-Acme-EULA--v2-"
+        );
+        assert_eq!(license_ref_id("LicenseRef-custom"), "LicenseRef-custom");
+    }
+
+    #[test]
+    fn test_spdx2_external_refs_round_trip() {
+        let cdx_comp = CdxComponent {
+            component_type: "library".to_string(),
+            name: "lib".to_string(),
+            external_references: Some(vec![
+                crate::models_cdx::CdxExternalReference {
+                    ref_type: "vcs".to_string(),
+                    url: "https://github.com/example/lib".to_string(),
+                    comment: None,
+                    hashes: None,
+                },
+                crate::models_cdx::CdxExternalReference {
+                    ref_type: "advisories".to_string(),
+                    url: "https://example.com/advisories".to_string(),
+                    comment: None,
+                    hashes: None,
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let refs = cdx_to_spdx2_external_refs(&cdx_comp);
+        assert_eq!(refs[0].reference_category, "OTHER");
+        assert_eq!(refs[0].reference_type, "vcs");
+        assert_eq!(refs[1].reference_category, "SECURITY");
+        assert_eq!(refs[1].reference_type, "advisory");
+
+        let restored: Vec<_> = refs.iter().filter_map(spdx2_external_ref_to_cdx).collect();
+        assert_eq!(Some(restored), cdx_comp.external_references);
+    }
 }
@@ -0,0 +1,481 @@
+//! License policy evaluation.
+//!
+//! A policy lists allowed, denied and needs-review licenses, verdicts for
+//! whole expressions, and per-component exceptions keyed by purl patterns.
+//! Every license the diff extracts for a component is checked: `AND`
+//! requires every operand to pass, `OR` the best operand. The outcome is a
+//! [`ValidationReport`] with denied licenses as errors and licenses that
+//! need review as warnings.
+
+use crate::diff::{ComponentInfo, extract_components};
+use crate::errors::ConverterError;
+use crate::formats::read_json_value;
+use crate::license::{self, Expression};
+use crate::validation::{ValidationIssue, ValidationReport};
+use crate::version_detection::{SbomFormat, detect_format};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// What the policy says about a license. Ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Allow,
+    #[default]
+    Review,
+    Deny,
+}
+
+/// A verdict for one exact expression, e.g. `GPL-2.0-only WITH
+/// Classpath-exception-2.0`, overriding the verdicts of its parts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpressionRule {
+    pub expression: String,
+    pub verdict: Verdict,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Components whose purl matches `purl` (`*` matches anything; a pattern
+/// without `@` matches every version) are exempt from the policy, or only
+/// additionally allowed `licenses` when given
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyException {
+    pub purl: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A license policy, loaded from JSON or TOML
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    pub allowed: Vec<String>,
+    pub denied: Vec<String>,
+    pub needs_review: Vec<String>,
+    pub rules: Vec<ExpressionRule>,
+    pub exceptions: Vec<PolicyException>,
+    /// Verdict for licenses the policy does not mention
+    pub unknown: Verdict,
+    /// Verdict for components without any license
+    pub missing: Verdict,
+}
+
+impl Policy {
+    /// Loads a policy file; `.toml` files are TOML, everything else JSON
+    pub fn load(path: &Path) -> Result<Self, ConverterError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to read policy: {}", path.display()))
+        })?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let policy: Policy = if is_toml {
+            toml::from_str(&content).map_err(|e| {
+                ConverterError::Config(format!("Invalid policy {}: {}", path.display(), e))
+            })?
+        } else {
+            serde_json::from_str(&content).map_err(|e| {
+                ConverterError::Config(format!("Invalid policy {}: {}", path.display(), e))
+            })?
+        };
+
+        for rule in &policy.rules {
+            rule.expression.parse::<Expression>().map_err(|e| {
+                ConverterError::Config(format!("Invalid policy rule in {}: {}", path.display(), e))
+            })?;
+        }
+        Ok(policy)
+    }
+
+    /// Checks every component of the SBOM at `input` against the policy
+    pub fn check_file(&self, input: &Path) -> Result<ValidationReport, ConverterError> {
+        let value = read_json_value(input)?;
+        if detect_format(&value) == SbomFormat::Unknown {
+            return Err(ConverterError::InvalidInput(format!(
+                "Could not detect SBOM format of {}",
+                input.display()
+            )));
+        }
+        Ok(self.check(&extract_components(&value)).with_file(input))
+    }
+
+    /// Checks `components` against the policy
+    pub fn check(&self, components: &[ComponentInfo]) -> ValidationReport {
+        let matcher = Matcher::new(self);
+        let mut report = ValidationReport::new();
+
+        for component in components {
+            let label = component_label(component);
+            let exception = component
+                .purl
+                .as_deref()
+                .and_then(|purl| self.exceptions.iter().find(|e| purl_matches(&e.purl, purl)));
+
+            let extra_allowed: HashSet<String> = match exception {
+                Some(exception) if exception.licenses.is_none() => {
+                    let mut issue = ValidationIssue::info(format!(
+                        "Exempt from license policy by exception '{}'",
+                        exception.purl
+                    ))
+                    .with_location(&label);
+                    if let Some(reason) = &exception.reason {
+                        issue = issue.with_suggestion(reason);
+                    }
+                    report.add_issue(issue);
+                    continue;
+                }
+                Some(exception) => exception
+                    .licenses
+                    .iter()
+                    .flatten()
+                    .map(|l| license::canonical(l))
+                    .collect(),
+                None => HashSet::new(),
+            };
+
+            let (verdict, culprits) = if component.licenses.is_empty() {
+                (self.missing, vec!["no license".to_string()])
+            } else {
+                worst(
+                    component
+                        .licenses
+                        .iter()
+                        .map(|l| matcher.evaluate_str(l, &extra_allowed))
+                        .collect(),
+                )
+            };
+
+            let issue = match verdict {
+                Verdict::Allow => continue,
+                Verdict::Review => ValidationIssue::warning(format!(
+                    "License needs review: {}",
+                    culprits.join(", ")
+                ))
+                .with_suggestion(
+                    "Have the license reviewed, then add it to `allowed` or a policy exception",
+                ),
+                Verdict::Deny => {
+                    ValidationIssue::error(format!("Denied license: {}", culprits.join(", ")))
+                        .with_suggestion(
+                            "Replace the component or add a policy exception for its purl",
+                        )
+                }
+            };
+            report.add_issue(issue.with_location(&label));
+        }
+
+        report
+    }
+}
+
+/// The policy's lists in canonical form
+struct Matcher<'a> {
+    verdicts: HashMap<String, Verdict>,
+    rules: HashMap<String, &'a ExpressionRule>,
+    unknown: Verdict,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(policy: &'a Policy) -> Self {
+        // Later lists win, so a license both allowed and denied is denied
+        let verdicts = [
+            (&policy.allowed, Verdict::Allow),
+            (&policy.needs_review, Verdict::Review),
+            (&policy.denied, Verdict::Deny),
+        ]
+        .into_iter()
+        .flat_map(|(ids, verdict)| ids.iter().map(move |id| (license::canonical(id), verdict)))
+        .collect();
+        let rules = policy
+            .rules
+            .iter()
+            .map(|rule| (license::canonical(&rule.expression), rule))
+            .collect();
+        Self {
+            verdicts,
+            rules,
+            unknown: policy.unknown,
+        }
+    }
+
+    /// Verdict for one license string and the terms responsible for it
+    fn evaluate_str(&self, text: &str, extra_allowed: &HashSet<String>) -> (Verdict, Vec<String>) {
+        match text.parse::<Expression>() {
+            Ok(expression) => self.evaluate(&expression.normalize(), extra_allowed),
+            // Licenses known only by name are matched as written
+            Err(_) => self
+                .lookup(text, extra_allowed)
+                .unwrap_or_else(|| (self.unknown, vec![format!("{} (not in policy)", text)])),
+        }
+    }
+
+    fn evaluate(
+        &self,
+        expression: &Expression,
+        extra_allowed: &HashSet<String>,
+    ) -> (Verdict, Vec<String>) {
+        let text = expression.to_string();
+        if let Some(result) = self.lookup(&text, extra_allowed) {
+            return result;
+        }
+        match expression {
+            Expression::And(terms) => worst(
+                terms
+                    .iter()
+                    .map(|t| self.evaluate(t, extra_allowed))
+                    .collect(),
+            ),
+            Expression::Or(terms) => best(
+                terms
+                    .iter()
+                    .map(|t| self.evaluate(t, extra_allowed))
+                    .collect(),
+            ),
+            // An exception the policy does not mention leaves the license
+            Expression::With { license, .. } => self.evaluate(license, extra_allowed),
+            Expression::License { .. } | Expression::LicenseRef(_) => {
+                (self.unknown, vec![format!("{} (not in policy)", text)])
+            }
+        }
+    }
+
+    /// Verdict of a rule or list entry for exactly `text`
+    fn lookup(
+        &self,
+        text: &str,
+        extra_allowed: &HashSet<String>,
+    ) -> Option<(Verdict, Vec<String>)> {
+        if extra_allowed.contains(text) {
+            return Some((Verdict::Allow, Vec::new()));
+        }
+        if let Some(rule) = self.rules.get(text) {
+            let culprit = match &rule.reason {
+                Some(reason) => format!("{} ({})", text, reason),
+                None => text.to_string(),
+            };
+            return Some((rule.verdict, vec![culprit]));
+        }
+        self.verdicts
+            .get(text)
+            .map(|verdict| (*verdict, vec![text.to_string()]))
+    }
+}
+
+/// `AND`: the worst verdict, blamed on every operand that has it
+fn worst(results: Vec<(Verdict, Vec<String>)>) -> (Verdict, Vec<String>) {
+    let verdict = results.iter().map(|(v, _)| *v).max().unwrap_or_default();
+    collect(results, verdict)
+}
+
+/// `OR`: the best verdict; when that is not `allow`, every option is at
+/// fault
+fn best(results: Vec<(Verdict, Vec<String>)>) -> (Verdict, Vec<String>) {
+    let verdict = results.iter().map(|(v, _)| *v).min().unwrap_or_default();
+    collect(results, verdict)
+}
+
+fn collect(results: Vec<(Verdict, Vec<String>)>, verdict: Verdict) -> (Verdict, Vec<String>) {
+    if verdict == Verdict::Allow {
+        return (verdict, Vec::new());
+    }
+    let mut culprits: Vec<String> = Vec::new();
+    for culprit in results
+        .into_iter()
+        .filter(|(v, _)| *v == verdict)
+        .flat_map(|(_, c)| c)
+    {
+        if !culprits.contains(&culprit) {
+            culprits.push(culprit);
+        }
+    }
+    (verdict, culprits)
+}
+
+fn component_label(component: &ComponentInfo) -> String {
+    match (&component.purl, &component.version) {
+        (Some(purl), _) => purl.clone(),
+        (None, Some(version)) => format!("{}@{}", component.name, version),
+        (None, None) => component.name.clone(),
+    }
+}
+
+/// Matches a purl against a pattern; patterns without `@` ignore the
+/// version, qualifiers and subpath
+fn purl_matches(pattern: &str, purl: &str) -> bool {
+    if glob_match(pattern, purl) {
+        return true;
+    }
+    if pattern.contains('@') {
+        return false;
+    }
+    let base = purl.split(['?', '#']).next().unwrap_or(purl);
+    let name_start = base.rfind('/').map_or(0, |i| i + 1);
+    let base = match base[name_start..].find('@') {
+        Some(at) => &base[..name_start + at],
+        None => base,
+    };
+    glob_match(pattern, base)
+}
+
+/// Glob matching where `*` matches any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::Severity;
+
+    fn component(name: &str, purl: Option<&str>, licenses: &[&str]) -> ComponentInfo {
+        ComponentInfo {
+            name: name.to_string(),
+            version: Some("1.0".to_string()),
+            purl: purl.map(str::to_string),
+            component_type: Some("library".to_string()),
+            hashes: Vec::new(),
+            licenses: licenses.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    fn policy() -> Policy {
+        Policy {
+            allowed: vec!["MIT".into(), "Apache-2.0".into()],
+            denied: vec!["GPL-3.0-only".into()],
+            needs_review: vec!["LGPL-2.1-only".into()],
+            rules: vec![ExpressionRule {
+                expression: "GPL-2.0 WITH Classpath-exception-2.0".into(),
+                verdict: Verdict::Allow,
+                reason: None,
+            }],
+            exceptions: vec![PolicyException {
+                purl: "pkg:npm/internal-*".into(),
+                licenses: None,
+                reason: Some("First-party code".into()),
+            }],
+            unknown: Verdict::Review,
+            missing: Verdict::Deny,
+        }
+    }
+
+    #[test]
+    fn test_expression_operators() {
+        let policy = policy();
+        let check = |licenses: &[&str]| {
+            let report = policy.check(&[component("c", None, licenses)]);
+            report
+                .issues
+                .first()
+                .map(|i| (i.severity, i.message.clone()))
+        };
+
+        assert_eq!(check(&["MIT OR GPL-3.0-only"]), None);
+        assert_eq!(
+            check(&["MIT AND GPL-3.0-only"]),
+            Some((Severity::Error, "Denied license: GPL-3.0-only".to_string()))
+        );
+        assert_eq!(
+            check(&["MIT", "LGPL-2.1"]),
+            Some((
+                Severity::Warning,
+                "License needs review: LGPL-2.1-only".to_string()
+            ))
+        );
+        assert_eq!(
+            check(&["ISC"]),
+            Some((
+                Severity::Warning,
+                "License needs review: ISC (not in policy)".to_string()
+            ))
+        );
+        assert_eq!(check(&["gpl-2.0-only with classpath-exception-2.0"]), None);
+        assert_eq!(
+            check(&[]),
+            Some((Severity::Error, "Denied license: no license".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let mut policy = policy();
+        policy.exceptions.push(PolicyException {
+            purl: "pkg:maven/org.acme/widget".into(),
+            licenses: Some(vec!["GPL-3.0-only".into()]),
+            reason: None,
+        });
+
+        let report = policy.check(&[
+            component("a", Some("pkg:npm/internal-tool@2.0"), &["GPL-3.0-only"]),
+            component(
+                "b",
+                Some("pkg:maven/org.acme/widget@1.0?type=jar"),
+                &["GPL-3.0-only AND MIT"],
+            ),
+            component("c", Some("pkg:maven/org.acme/other@1.0"), &["GPL-3.0-only"]),
+        ]);
+        assert_eq!(report.info_count(), 1);
+        assert_eq!(report.error_count(), 1);
+        assert_eq!(
+            report.issues[1].location.as_deref(),
+            Some("pkg:maven/org.acme/other@1.0")
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("pkg:npm/*", "pkg:npm/lodash@4.17.21"));
+        assert!(glob_match("*lodash*", "pkg:npm/lodash@4.17.21"));
+        assert!(!glob_match("pkg:npm/lodash", "pkg:npm/lodash-es"));
+        assert!(purl_matches("pkg:npm/lodash", "pkg:npm/lodash@4.17.21"));
+        assert!(!purl_matches("pkg:npm/lodash@5*", "pkg:npm/lodash@4.17.21"));
+    }
+
+    #[test]
+    fn test_parse_toml_policy() {
+        let policy: Policy = toml::from_str(
+            r#"
+            allowed = ["MIT"]
+            denied = ["GPL-3.0-only"]
+            unknown = "deny"
+
+            [[rules]]
+            expression = "MIT OR GPL-3.0-only"
+            verdict = "review"
+
+            [[exceptions]]
+            purl = "pkg:npm/internal-*"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.unknown, Verdict::Deny);
+        assert_eq!(policy.missing, Verdict::Review);
+        assert_eq!(policy.rules[0].verdict, Verdict::Review);
+        assert_eq!(policy.exceptions[0].licenses, None);
+    }
+}
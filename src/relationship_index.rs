@@ -0,0 +1,488 @@
+//! The relationship index of SPDX -> CycloneDX conversions.
+//!
+//! Pass 1 indexes every relationship by its source SPDX ID; Pass 2 and 3
+//! look relationships up by element and walk the whole index in SPDX ID
+//! order to write `dependencies`. By default the index is a `HashMap`.
+//!
+//! With a spill threshold, the relationships held in memory are written
+//! out as a sorted run whenever there are that many of them. Finishing the
+//! index merges the runs into one file sorted by source SPDX ID, with a
+//! sparse in-memory index of key offsets for lookups. A lookup reads the
+//! whole block between two sparse entries and keeps it, so looking up
+//! elements in about the order of their SPDX IDs reads each block once.
+//! Relationships with the same source keep their input order, so the
+//! conversion output is the same either way.
+
+use crate::errors::ConverterError;
+use crate::models_spdx::SpdxRelationshipMinimal;
+use log::info;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Records between two entries of the sparse index of a merged file
+const SPARSE_INTERVAL: usize = 64;
+
+/// Runs merged at once, which bounds the number of open files
+const MAX_FAN_IN: usize = 64;
+
+/// Relationships by source SPDX ID, in memory or spilled to disk
+#[derive(Debug, Default)]
+pub struct SpdxRelationshipIndex {
+    memory: HashMap<String, Vec<SpdxRelationshipMinimal>>,
+    /// Relationships in `memory`
+    buffered: usize,
+    /// Relationships to hold in memory before spilling them to a run
+    spill_threshold: Option<usize>,
+    /// Sorted runs not yet merged
+    runs: Vec<PathBuf>,
+    merged: Option<MergedIndex>,
+}
+
+/// The merged runs: one relationship per line, sorted by source SPDX ID
+#[derive(Debug)]
+struct MergedIndex {
+    path: PathBuf,
+    /// Distinct source SPDX IDs
+    keys: usize,
+    /// First key of every few groups and the offset of its first record
+    sparse: Vec<(String, u64)>,
+    blocks: Mutex<BlockReader>,
+}
+
+/// Reads a merged index one sparse block at a time, keeping the last one
+#[derive(Debug)]
+struct BlockReader {
+    reader: BufReader<File>,
+    /// Position in `sparse` of the block held, and its relationships
+    cached: Option<(usize, Vec<SpdxRelationshipMinimal>)>,
+    /// Blocks read from disk
+    reads: usize,
+}
+
+impl BlockReader {
+    /// The relationships of the block starting at `sparse[block]`
+    fn read(
+        &mut self,
+        block: usize,
+        sparse: &[(String, u64)],
+    ) -> io::Result<&[SpdxRelationshipMinimal]> {
+        let rels = match self.cached.take() {
+            Some((cached, rels)) if cached == block => rels,
+            _ => {
+                let mut offset = sparse[block].1;
+                let end = sparse.get(block + 1).map(|(_, offset)| *offset);
+                self.reader.seek(SeekFrom::Start(offset))?;
+                let mut rels = Vec::new();
+                let mut line = String::new();
+                while end.is_none_or(|end| offset < end) {
+                    line.clear();
+                    let read = self.reader.read_line(&mut line)?;
+                    if read == 0 {
+                        break;
+                    }
+                    offset += read as u64;
+                    rels.push(decode(&line)?);
+                }
+                self.reads += 1;
+                rels
+            }
+        };
+        Ok(&self.cached.insert((block, rels)).1)
+    }
+}
+
+impl SpdxRelationshipIndex {
+    /// An index held in memory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An index that spills to disk once it holds `threshold` relationships
+    pub fn with_spill_threshold(threshold: usize) -> Self {
+        let mut index = Self::default();
+        index.spill_threshold = Some(threshold.max(1));
+        index
+    }
+
+    /// Adds a relationship under its source SPDX ID
+    pub fn insert(&mut self, rel: SpdxRelationshipMinimal) -> Result<(), ConverterError> {
+        self.memory
+            .entry(rel.spdx_element_id.clone())
+            .or_default()
+            .push(rel);
+        self.buffered += 1;
+        if self
+            .spill_threshold
+            .is_some_and(|threshold| self.buffered >= threshold)
+        {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Ends indexing: merges the spilled runs, if any, for lookups
+    pub fn finish(&mut self) -> Result<(), ConverterError> {
+        if self.runs.is_empty() {
+            return Ok(());
+        }
+        if !self.memory.is_empty() {
+            self.spill()?;
+        }
+        let count = self.runs.len();
+        // Merging the earliest runs first keeps equal keys in input order
+        while self.runs.len() > MAX_FAN_IN {
+            let batch: Vec<PathBuf> = self.runs.drain(..MAX_FAN_IN).collect();
+            let merged = merge_and_remove(&batch, temp_path("run"))?;
+            self.runs.insert(0, merged.path);
+        }
+        let runs = std::mem::take(&mut self.runs);
+        let merged = merge_and_remove(&runs, temp_path("index"))?;
+        info!(
+            "Merged {} relationship index runs into {}",
+            count,
+            merged.path.display()
+        );
+        self.merged = Some(merged);
+        Ok(())
+    }
+
+    /// Relationships held in memory before spilling, if limited
+    pub fn spill_threshold(&self) -> Option<usize> {
+        self.spill_threshold
+    }
+
+    /// Whether the index was spilled to disk
+    pub fn is_spilled(&self) -> bool {
+        self.merged.is_some() || !self.runs.is_empty()
+    }
+
+    /// Number of distinct source SPDX IDs
+    pub fn len(&self) -> usize {
+        match &self.merged {
+            Some(merged) => merged.keys,
+            None => self.memory.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The relationships of `spdx_id`, in input order
+    pub fn get(&self, spdx_id: &str) -> io::Result<Cow<'_, [SpdxRelationshipMinimal]>> {
+        let Some(merged) = &self.merged else {
+            return Ok(self
+                .memory
+                .get(spdx_id)
+                .map_or(Cow::Borrowed(&[]), |rels| Cow::Borrowed(rels.as_slice())));
+        };
+        let start = merged
+            .sparse
+            .partition_point(|(key, _)| key.as_str() <= spdx_id);
+        let Some(block) = start.checked_sub(1) else {
+            return Ok(Cow::Owned(Vec::new()));
+        };
+
+        let mut blocks = merged.blocks.lock().unwrap_or_else(|e| e.into_inner());
+        let rels = blocks.read(block, &merged.sparse)?;
+        // Blocks are sorted by key and always hold whole groups
+        let from = rels.partition_point(|rel| rel.spdx_element_id.as_str() < spdx_id);
+        let to = rels.partition_point(|rel| rel.spdx_element_id.as_str() <= spdx_id);
+        Ok(Cow::Owned(rels[from..to].to_vec()))
+    }
+
+    /// Calls `f` with every source SPDX ID, in order, and its
+    /// relationships, in input order
+    pub fn scan<E>(
+        &self,
+        mut f: impl FnMut(&str, &[SpdxRelationshipMinimal]) -> Result<(), E>,
+    ) -> Result<(), E>
+    where
+        E: From<io::Error>,
+    {
+        let Some(merged) = &self.merged else {
+            let mut spdx_ids: Vec<&String> = self.memory.keys().collect();
+            spdx_ids.sort();
+            for spdx_id in spdx_ids {
+                f(spdx_id, &self.memory[spdx_id])?;
+            }
+            return Ok(());
+        };
+
+        let reader = BufReader::new(File::open(&merged.path)?);
+        let mut group: Vec<SpdxRelationshipMinimal> = Vec::new();
+        for line in reader.lines() {
+            let rel = decode(&line?)?;
+            if group
+                .first()
+                .is_some_and(|first| first.spdx_element_id != rel.spdx_element_id)
+            {
+                f(&group[0].spdx_element_id, &group)?;
+                group.clear();
+            }
+            group.push(rel);
+        }
+        if let Some(first) = group.first() {
+            f(&first.spdx_element_id, &group)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the relationships in memory to a new sorted run
+    fn spill(&mut self) -> Result<(), ConverterError> {
+        let path = temp_path(&format!("run-{}", self.runs.len()));
+        let file = File::create(&path).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to create index run: {}", path.display()))
+        })?;
+        self.runs.push(path);
+
+        let mut groups: Vec<(String, Vec<SpdxRelationshipMinimal>)> = self.memory.drain().collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut writer = BufWriter::new(file);
+        for rel in groups.iter().flat_map(|(_, rels)| rels) {
+            encode(&mut writer, rel)?;
+        }
+        writer.flush()?;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+impl Drop for SpdxRelationshipIndex {
+    fn drop(&mut self) {
+        for run in &self.runs {
+            let _ = fs::remove_file(run);
+        }
+        if let Some(merged) = &self.merged {
+            let _ = fs::remove_file(&merged.path);
+        }
+    }
+}
+
+/// Merges sorted runs into one sorted file at `path`, and removes the runs
+fn merge_and_remove(runs: &[PathBuf], path: PathBuf) -> Result<MergedIndex, ConverterError> {
+    let merged = merge_runs(runs, &path);
+    for run in runs {
+        let _ = fs::remove_file(run);
+    }
+    if merged.is_err() {
+        let _ = fs::remove_file(&path);
+    }
+    merged
+}
+
+/// Merges sorted runs into one sorted file at `path`. Equal keys come out
+/// in run order, which is input order.
+fn merge_runs(runs: &[PathBuf], path: &Path) -> Result<MergedIndex, ConverterError> {
+    let mut readers = Vec::with_capacity(runs.len());
+    for run in runs {
+        let file = File::open(run).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to open index run: {}", run.display()))
+        })?;
+        readers.push(BufReader::new(file).lines());
+    }
+
+    // The next line of each run, by its key and the run's position
+    let mut heads = BinaryHeap::new();
+    let mut advance = |i: usize, heads: &mut BinaryHeap<_>| -> io::Result<()> {
+        if let Some(line) = readers[i].next() {
+            let line = line?;
+            let key = decode(&line)?.spdx_element_id;
+            heads.push(Reverse((key, i, line)));
+        }
+        Ok(())
+    };
+    for i in 0..runs.len() {
+        advance(i, &mut heads)?;
+    }
+
+    let file = File::create(path).map_err(|e| {
+        ConverterError::Io(e, format!("Failed to create index: {}", path.display()))
+    })?;
+    let mut writer = BufWriter::new(file);
+    let mut keys = 0;
+    let mut sparse = Vec::new();
+    let mut offset = 0u64;
+    let mut since_sparse = SPARSE_INTERVAL;
+    let mut previous: Option<String> = None;
+    while let Some(Reverse((key, i, line))) = heads.pop() {
+        if previous.as_deref() != Some(key.as_str()) {
+            keys += 1;
+            // Sparse entries start a group, so lookups never start midway
+            if since_sparse >= SPARSE_INTERVAL {
+                sparse.push((key.clone(), offset));
+                since_sparse = 0;
+            }
+            previous = Some(key);
+        }
+        since_sparse += 1;
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        offset += line.len() as u64 + 1;
+        advance(i, &mut heads)?;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    let blocks = Mutex::new(BlockReader {
+        reader: BufReader::new(File::open(path)?),
+        cached: None,
+        reads: 0,
+    });
+    Ok(MergedIndex {
+        path: path.to_path_buf(),
+        keys,
+        sparse,
+        blocks,
+    })
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "sbom-converter-relationships-{}-{}.jsonl",
+        uuid::Uuid::new_v4(),
+        name
+    ))
+}
+
+/// Writes one relationship as a line of JSON: `[source, type, target]`
+fn encode(writer: &mut impl Write, rel: &SpdxRelationshipMinimal) -> io::Result<()> {
+    serde_json::to_writer(
+        &mut *writer,
+        &(
+            &rel.spdx_element_id,
+            &rel.relationship_type,
+            &rel.related_spdx_element,
+        ),
+    )?;
+    writer.write_all(b"\n")
+}
+
+fn decode(line: &str) -> io::Result<SpdxRelationshipMinimal> {
+    let (spdx_element_id, relationship_type, related_spdx_element) =
+        serde_json::from_str::<(String, String, String)>(line)?;
+    Ok(SpdxRelationshipMinimal {
+        spdx_element_id,
+        relationship_type,
+        related_spdx_element,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rel(from: &str, relationship_type: &str, to: &str) -> SpdxRelationshipMinimal {
+        SpdxRelationshipMinimal {
+            spdx_element_id: from.to_string(),
+            relationship_type: relationship_type.to_string(),
+            related_spdx_element: to.to_string(),
+        }
+    }
+
+    /// Relationships of a few hundred elements, several per element, out
+    /// of order
+    fn get_test_relationships() -> Vec<SpdxRelationshipMinimal> {
+        let mut rels = Vec::new();
+        for i in 0..600 {
+            let from = format!("SPDXRef-{}", (i * 37) % 211);
+            rels.push(rel(&from, "DEPENDS_ON", &format!("SPDXRef-dep-{}", i)));
+            if i % 3 == 0 {
+                rels.push(rel(&from, "CONTAINS", &format!("SPDXRef-file-{}", i)));
+            }
+        }
+        rels
+    }
+
+    fn build(threshold: Option<usize>) -> SpdxRelationshipIndex {
+        let mut index = match threshold {
+            Some(threshold) => SpdxRelationshipIndex::with_spill_threshold(threshold),
+            None => SpdxRelationshipIndex::new(),
+        };
+        for rel in get_test_relationships() {
+            index.insert(rel).unwrap();
+        }
+        index.finish().unwrap();
+        index
+    }
+
+    fn scanned(index: &SpdxRelationshipIndex) -> Vec<(String, Vec<String>)> {
+        let mut groups = Vec::new();
+        index
+            .scan(|spdx_id, rels| -> io::Result<()> {
+                let targets = rels.iter().map(|r| r.related_spdx_element.clone());
+                groups.push((spdx_id.to_string(), targets.collect()));
+                Ok(())
+            })
+            .unwrap();
+        groups
+    }
+
+    #[test]
+    fn test_spilled_index_matches_memory() {
+        let memory = build(None);
+        assert!(!memory.is_spilled());
+
+        // One run per relationship exceeds the merge fan-in
+        for threshold in [1, 5, 100] {
+            let spilled = build(Some(threshold));
+            assert!(spilled.is_spilled());
+            assert_eq!(spilled.len(), memory.len());
+            assert_eq!(scanned(&spilled), scanned(&memory));
+
+            for spdx_id in [
+                "SPDXRef-0",
+                "SPDXRef-105",
+                "SPDXRef-210",
+                "SPDXRef-1000",
+                "A",
+            ] {
+                let expected = memory.get(spdx_id).unwrap();
+                let actual = spilled.get(spdx_id).unwrap();
+                let targets = |rels: &[SpdxRelationshipMinimal]| {
+                    rels.iter()
+                        .map(|r| (r.relationship_type.clone(), r.related_spdx_element.clone()))
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(targets(&actual), targets(&expected), "{}", spdx_id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookups_in_key_order_read_each_block_once() {
+        let memory = build(None);
+        let spilled = build(Some(5));
+        let mut spdx_ids: Vec<String> = scanned(&memory).into_iter().map(|(id, _)| id).collect();
+        spdx_ids.sort();
+        for spdx_id in &spdx_ids {
+            let expected = memory.get(spdx_id).unwrap();
+            let actual = spilled.get(spdx_id).unwrap();
+            let targets = |rels: &[SpdxRelationshipMinimal]| {
+                rels.iter()
+                    .map(|r| r.related_spdx_element.clone())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(targets(&actual), targets(&expected), "{}", spdx_id);
+        }
+
+        let merged = spilled.merged.as_ref().unwrap();
+        assert!(merged.sparse.len() > 1);
+        let reads = merged.blocks.lock().unwrap().reads;
+        assert_eq!(reads, merged.sparse.len());
+    }
+
+    #[test]
+    fn test_spilled_files_are_removed() {
+        let index = build(Some(50));
+        let path = index.merged.as_ref().unwrap().path.clone();
+        assert!(path.exists());
+        drop(index);
+        assert!(!path.exists());
+    }
+}
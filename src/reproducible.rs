@@ -0,0 +1,187 @@
+//! Identity of the documents a conversion writes.
+//!
+//! Converted documents get new serial numbers, document namespaces and a
+//! creation time. By default these are random UUIDs and the current time,
+//! so converting the same input twice gives different bytes. In
+//! reproducible mode they are derived instead:
+//!
+//! - identifiers are UUIDv5s of the SHA-256 of the input and the
+//!   conversion settings, one per purpose (`bom`, `vex`, `namespace`, ...);
+//! - the creation time is that of the source document; without one it is
+//!   `SOURCE_DATE_EPOCH` if set, else the Unix epoch, never the current
+//!   time.
+
+use crate::errors::ConverterError;
+use chrono::{DateTime, Utc};
+use log::warn;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Namespace of the UUIDv5s derived in reproducible mode
+const NAMESPACE: Uuid = Uuid::from_u128(0x6c1f_4e0a_5d2b_4f8e_9a3c_7b1d_2e4f_6a8b);
+
+/// How a conversion identifies and dates its output documents
+#[derive(Debug, Clone, Default)]
+pub enum OutputIdentity {
+    /// Random UUIDs; the source creation time, or the current time
+    #[default]
+    Random,
+    /// UUIDs and creation time derived from the input
+    Reproducible {
+        /// Hash of the input content and the conversion settings
+        seed: String,
+        /// Parsed `SOURCE_DATE_EPOCH`, if set
+        source_date_epoch: Option<DateTime<Utc>>,
+    },
+}
+
+impl OutputIdentity {
+    /// Derives the identity from the content of `input` and a description
+    /// of the conversion `settings`, and reads `SOURCE_DATE_EPOCH`
+    pub fn reproducible(input: &Path, settings: &str) -> Result<Self, ConverterError> {
+        let file = File::open(input).map_err(|e| {
+            ConverterError::Io(e, format!("Failed to read input: {}", input.display()))
+        })?;
+        let mut reader = BufReader::new(file);
+        let mut hasher = Sha256::new();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        hasher.update(b"\n");
+        hasher.update(settings.as_bytes());
+        let seed = format!("{:x}", hasher.finalize());
+
+        let source_date_epoch = match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(value) => Some(parse_source_date_epoch(&value)?),
+            Err(_) => None,
+        };
+        Ok(OutputIdentity::Reproducible {
+            seed,
+            source_date_epoch,
+        })
+    }
+
+    pub fn is_reproducible(&self) -> bool {
+        matches!(self, OutputIdentity::Reproducible { .. })
+    }
+
+    /// A UUID for `purpose`; the same purpose gives the same UUID for the
+    /// same input in reproducible mode
+    pub fn uuid(&self, purpose: &str) -> Uuid {
+        match self {
+            OutputIdentity::Random => Uuid::new_v4(),
+            OutputIdentity::Reproducible { seed, .. } => {
+                Uuid::new_v5(&NAMESPACE, format!("{}/{}", seed, purpose).as_bytes())
+            }
+        }
+    }
+
+    /// A `urn:uuid:` serial number for `purpose`
+    pub fn serial_number(&self, purpose: &str) -> String {
+        format!("urn:uuid:{}", self.uuid(purpose))
+    }
+
+    /// The time that stands in for "now" in the output
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            OutputIdentity::Random => Utc::now(),
+            OutputIdentity::Reproducible {
+                source_date_epoch, ..
+            } => source_date_epoch.unwrap_or_else(|| {
+                warn!("No source timestamp and no SOURCE_DATE_EPOCH; using the Unix epoch");
+                DateTime::UNIX_EPOCH
+            }),
+        }
+    }
+
+    /// Creation time of the output, given that of the source document
+    pub fn created(&self, source: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        source.unwrap_or_else(|| self.now())
+    }
+
+    /// [`Self::created`] for a source timestamp in RFC 3339 form;
+    /// unparsable timestamps count as missing
+    pub fn created_from(&self, source: Option<&str>) -> DateTime<Utc> {
+        self.created(source.and_then(|timestamp| {
+            DateTime::parse_from_rfc3339(timestamp)
+                .ok()
+                .map(|t| t.with_timezone(&Utc))
+        }))
+    }
+}
+
+/// Parses `SOURCE_DATE_EPOCH` (seconds since the Unix epoch)
+fn parse_source_date_epoch(value: &str) -> Result<DateTime<Utc>, ConverterError> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| {
+            ConverterError::Config(format!(
+                "SOURCE_DATE_EPOCH must be a number of seconds, got '{}'",
+                value
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reproducible(seed: &str) -> OutputIdentity {
+        OutputIdentity::Reproducible {
+            seed: seed.to_string(),
+            source_date_epoch: None,
+        }
+    }
+
+    #[test]
+    fn test_reproducible_uuids() {
+        let identity = reproducible("abc");
+        assert_eq!(identity.uuid("bom"), reproducible("abc").uuid("bom"));
+        assert_ne!(identity.uuid("bom"), identity.uuid("vex"));
+        assert_ne!(identity.uuid("bom"), reproducible("abd").uuid("bom"));
+        assert_eq!(identity.uuid("bom").get_version_num(), 5);
+        assert_ne!(
+            OutputIdentity::Random.uuid("bom"),
+            OutputIdentity::Random.uuid("bom")
+        );
+    }
+
+    #[test]
+    fn test_created() {
+        let source = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(OutputIdentity::Random.created(Some(source)), source);
+        assert_eq!(reproducible("abc").created(Some(source)), source);
+        assert_eq!(reproducible("abc").created(None), DateTime::UNIX_EPOCH);
+
+        let epoch = OutputIdentity::Reproducible {
+            seed: "abc".to_string(),
+            source_date_epoch: Some(parse_source_date_epoch("1700000000").unwrap()),
+        };
+        assert_eq!(epoch.created(Some(source)), source);
+        assert_eq!(
+            epoch.created(None).to_rfc3339(),
+            "2023-11-14T22:13:20+00:00"
+        );
+        assert_eq!(epoch.created_from(Some("not a date")), epoch.created(None));
+    }
+
+    #[test]
+    fn test_parse_source_date_epoch() {
+        assert!(parse_source_date_epoch("0").is_ok());
+        assert!(parse_source_date_epoch(" 1700000000\n").is_ok());
+        assert!(parse_source_date_epoch("yesterday").is_err());
+    }
+}
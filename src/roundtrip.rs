@@ -0,0 +1,225 @@
+//! Round-trip fidelity checking.
+//!
+//! Converts an SBOM to the other format family and back with [`crate::run`],
+//! then diffs the result against the original. Whatever the diff reports as
+//! removed or modified did not survive the round trip; the loss is expressed
+//! as a percentage of the components, dependencies and vulnerabilities in
+//! the original so it can be used as a CI gate.
+
+use crate::cdx_version::CdxVersion;
+use crate::diff::{DiffReport, count_items, diff_sboms};
+use crate::errors::ConverterError;
+use crate::formats::read_json_value;
+use crate::formats::spdx::SpdxSerialization;
+use crate::spdx_version::SpdxVersion;
+use crate::version_detection::{SbomFormat, detect_format};
+use crate::{Config, ConversionDirection};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Target versions for the intermediate document
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoundtripOptions {
+    /// CycloneDX version used when the input is SPDX
+    pub cdx_version: CdxVersion,
+    /// SPDX version used when the input is CycloneDX
+    pub spdx_version: SpdxVersion,
+    /// SPDX serialization used when the input is CycloneDX
+    pub spdx_serialization: SpdxSerialization,
+}
+
+/// The outcome of one round trip
+#[derive(Debug, Clone)]
+pub struct RoundtripReport {
+    pub forward: ConversionDirection,
+    pub backward: ConversionDirection,
+    /// Components, dependencies and vulnerabilities in the original
+    pub source_items: usize,
+    /// Original (file 1) compared with the round-tripped document (file 2)
+    pub diff: DiffReport,
+}
+
+impl RoundtripReport {
+    /// Items that were removed or changed by the round trip
+    pub fn lost_items(&self) -> usize {
+        self.diff.removed_components.len()
+            + self.diff.modified_components.len()
+            + self.diff.removed_dependencies.len()
+            + self.diff.removed_vulnerabilities.len()
+    }
+
+    /// Lost items as a percentage of the original's items
+    pub fn loss_percent(&self) -> f64 {
+        if self.source_items == 0 {
+            return 0.0;
+        }
+        (self.lost_items() as f64 * 100.0 / self.source_items as f64).min(100.0)
+    }
+
+    /// Format the round-trip report as human-readable text
+    pub fn format_text(&self) -> String {
+        let mut output = format!(
+            "Round trip: {} → {}\n",
+            self.forward.as_str(),
+            self.backward.as_str()
+        );
+        output.push_str(&format!(
+            "Lost or changed: {} of {} items ({:.2}%)\n\n",
+            self.lost_items(),
+            self.source_items,
+            self.loss_percent()
+        ));
+        output.push_str(&self.diff.format_text(true));
+        output
+    }
+
+    /// Format the round-trip report as JSON
+    pub fn format_json(&self) -> Result<String, ConverterError> {
+        let diff: serde_json::Value = serde_json::from_str(&self.diff.format_json()?)?;
+        let report = json!({
+            "forward": self.forward.as_str(),
+            "backward": self.backward.as_str(),
+            "source_items": self.source_items,
+            "lost_items": self.lost_items(),
+            "loss_percent": self.loss_percent(),
+            "diff": diff,
+        });
+        serde_json::to_string_pretty(&report).map_err(|e| {
+            ConverterError::SerializationError(format!("Failed to format JSON: {}", e))
+        })
+    }
+}
+
+/// Converts `input` to the other format family and back, then diffs the
+/// result against the original.
+///
+/// The back conversion targets the input's own version (and, for SPDX, its
+/// serialization) so only real losses show up in the diff.
+pub fn roundtrip(
+    input: &Path,
+    options: RoundtripOptions,
+) -> Result<RoundtripReport, ConverterError> {
+    let original = read_json_value(input)?;
+    let source_items = count_items(&original);
+
+    let intermediate = temp_path("intermediate");
+    let result = temp_path("result");
+
+    let (forward, backward) = match detect_format(&original) {
+        SbomFormat::CycloneDx(version) => {
+            let back_version = CdxVersion::from_str(&version).unwrap_or_default();
+            (
+                round_trip_config(
+                    input,
+                    &intermediate,
+                    ConversionDirection::CdxToSpdx,
+                    options,
+                ),
+                round_trip_config(
+                    &intermediate,
+                    &result,
+                    ConversionDirection::SpdxToCdx,
+                    RoundtripOptions {
+                        cdx_version: back_version,
+                        ..options
+                    },
+                ),
+            )
+        }
+        SbomFormat::Spdx(version) => {
+            let spdx_version = if version.starts_with('2') {
+                SpdxVersion::V2_3
+            } else {
+                SpdxVersion::V3_0_1
+            };
+            let spdx_serialization = if original.get("@graph").is_some() {
+                SpdxSerialization::JsonLd
+            } else {
+                SpdxSerialization::Simple
+            };
+            (
+                round_trip_config(
+                    input,
+                    &intermediate,
+                    ConversionDirection::SpdxToCdx,
+                    options,
+                ),
+                round_trip_config(
+                    &intermediate,
+                    &result,
+                    ConversionDirection::CdxToSpdx,
+                    RoundtripOptions {
+                        spdx_version,
+                        spdx_serialization,
+                        ..options
+                    },
+                ),
+            )
+        }
+        SbomFormat::Unknown => {
+            return Err(ConverterError::InvalidInput(format!(
+                "Could not detect SBOM format of {}",
+                input.display()
+            )));
+        }
+    };
+
+    let (forward_direction, backward_direction) =
+        (forward.direction.clone(), backward.direction.clone());
+    let diff = crate::run(forward)
+        .and_then(|()| crate::run(backward))
+        .and_then(|()| diff_sboms(input, &result));
+
+    for path in [&intermediate, &result] {
+        if path.exists() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    Ok(RoundtripReport {
+        forward: forward_direction,
+        backward: backward_direction,
+        source_items,
+        diff: diff?,
+    })
+}
+
+/// Builds the `run` configuration for one leg of the round trip
+fn round_trip_config(
+    input: &Path,
+    output: &Path,
+    direction: ConversionDirection,
+    options: RoundtripOptions,
+) -> Config {
+    Config {
+        input_file: input.to_path_buf(),
+        output_file: output.to_path_buf(),
+        direction,
+        input_format: None,
+        output_format: Some(crate::formats::Format::Json),
+        validate: false,
+        split_vex: false,
+        vex_output: None,
+        packages_only: false,
+        // Keeps CycloneDX assemblies intact on the way back
+        nest_components: true,
+        skip_jsonld_validation: true,
+        output_version: options.cdx_version,
+        spdx_version: Some(options.spdx_version),
+        spdx_serialization: options.spdx_serialization,
+        fidelity_report: None,
+        reproducible: false,
+        id_map: None,
+        dangling_refs: None,
+        spill_threshold: None,
+    }
+}
+
+fn temp_path(stage: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "sbom-converter-roundtrip-{}-{}.json",
+        stage,
+        uuid::Uuid::new_v4()
+    ))
+}
@@ -15,6 +15,7 @@
 //! - Conversion will still work correctly even with validation skipped
 
 use crate::errors::ConverterError;
+use crate::version_detection::{SbomFormat, detect_format};
 use jsonschema;
 use log::{info, warn};
 use serde_json::Value;
@@ -35,17 +36,42 @@ pub fn validate_json_schema(
     json_file_path: &Path,
     skip_jsonld_validation: bool,
 ) -> Result<(), ConverterError> {
-    info!("Loading schema...");
-    let schema_json: Value = serde_json::from_str(schema_str).map_err(ConverterError::Serde)?;
-    let compiled_schema = jsonschema::validator_for(&schema_json)
-        .map_err(|e| ConverterError::Validation(e.to_string()))?;
+    let instance = load_instance(json_file_path)?;
+    validate_instance(schema_str, &instance, skip_jsonld_validation)
+}
 
+/// Validates an SPDX JSON file, picking the bundled schema from its version.
+///
+/// SPDX 2.x documents (top-level `spdxVersion`) are checked against the
+/// SPDX 2.3 schema; everything else against the SPDX 3.0.1 schema.
+pub fn validate_spdx_json_schema(
+    json_file_path: &Path,
+    skip_jsonld_validation: bool,
+) -> Result<(), ConverterError> {
+    let instance = load_instance(json_file_path)?;
+    let schema_str = match detect_format(&instance) {
+        SbomFormat::Spdx(version) if version.starts_with("2.") => {
+            info!("Detected SPDX {} input, using the SPDX 2.3 schema", version);
+            include_str!("../schemas/spdx_2.3.schema.json")
+        }
+        _ => include_str!("../schemas/spdx_3.0.1.schema.json"),
+    };
+    validate_instance(schema_str, &instance, skip_jsonld_validation)
+}
+
+fn load_instance(json_file_path: &Path) -> Result<Value, ConverterError> {
     info!("Loading and parsing input file for validation...");
     let file = File::open(json_file_path)
         .map_err(|e| ConverterError::Io(e, "Failed to open input for validation".to_string()))?;
     let reader = BufReader::new(file);
-    let instance: Value = serde_json::from_reader(reader).map_err(ConverterError::Serde)?;
+    serde_json::from_reader(reader).map_err(ConverterError::Serde)
+}
 
+fn validate_instance(
+    schema_str: &str,
+    instance: &Value,
+    skip_jsonld_validation: bool,
+) -> Result<(), ConverterError> {
     // Check if this is JSON-LD format (has @context)
     if instance.get("@context").is_some() {
         if skip_jsonld_validation {
@@ -56,13 +82,18 @@ pub fn validate_json_schema(
             return Ok(());
         } else {
             info!("Detected JSON-LD format. Performing structural validation...");
-            return validate_jsonld_structure(&instance);
+            return validate_jsonld_structure(instance);
         }
     }
 
+    info!("Loading schema...");
+    let schema_json: Value = serde_json::from_str(schema_str).map_err(ConverterError::Serde)?;
+    let compiled_schema = jsonschema::validator_for(&schema_json)
+        .map_err(|e| ConverterError::Validation(e.to_string()))?;
+
     info!("Validating instance against schema...");
 
-    if compiled_schema.is_valid(&instance) {
+    if compiled_schema.is_valid(instance) {
         info!("Validation successful!");
         Ok(())
     } else {
@@ -0,0 +1,33 @@
+//! SPDX version types and utilities
+
+use std::str::FromStr;
+
+/// SPDX specification version for CDX -> SPDX output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum SpdxVersion {
+    V2_3,
+    #[default]
+    V3_0_1,
+}
+
+impl SpdxVersion {
+    /// Get the version string (e.g., "2.3")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpdxVersion::V2_3 => "2.3",
+            SpdxVersion::V3_0_1 => "3.0.1",
+        }
+    }
+}
+
+impl FromStr for SpdxVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("SPDX-").unwrap_or(s) {
+            "2.3" => Ok(SpdxVersion::V2_3),
+            "3.0" | "3.0.1" => Ok(SpdxVersion::V3_0_1),
+            _ => Err(format!("Invalid SPDX version: {}", s)),
+        }
+    }
+}
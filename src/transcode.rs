@@ -0,0 +1,1208 @@
+//! Same-family transcoding (CycloneDX -> CycloneDX, SPDX -> SPDX).
+//!
+//! Unlike the cross-format converters, these operate on a fully loaded
+//! `serde_json::Value`, like merge and diff. XML and tag-value are handled
+//! by the caller, so only the JSON shape has to change here:
+//!
+//! - CycloneDX: rewrite `specVersion` and drop or translate everything the
+//!   target version does not know about (e.g. `formulation` before 1.5,
+//!   `vulnerabilities` before 1.4, the object form of `metadata.tools`).
+//! - SPDX 3: switch between the simple (`elements` + `relationships`) and
+//!   JSON-LD (`@context` + `@graph`) layouts. SPDX 2.x documents keep their
+//!   version and are passed through unchanged.
+
+use crate::cdx_version::CdxVersion;
+use crate::errors::ConverterError;
+use crate::fidelity::FidelityReport;
+use crate::formats::spdx::SpdxSerialization;
+use crate::models_spdx::{SPDX_JSONLD_CONTEXT, SPDX_JSONLD_CREATION_INFO_ID, SpdxVulnAssessment};
+use crate::reproducible::OutputIdentity;
+use crate::spdx_version::SpdxVersion;
+use log::{info, warn};
+use serde_json::{Map, Value, json};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+// =========================================================================
+// CycloneDX
+// =========================================================================
+
+/// Top-level BOM fields and the first spec version that defines them
+const BOM_FIELDS: &[(&str, CdxVersion)] = &[
+    ("vulnerabilities", CdxVersion::V1_4),
+    ("signature", CdxVersion::V1_4),
+    ("annotations", CdxVersion::V1_5),
+    ("formulation", CdxVersion::V1_5),
+    ("properties", CdxVersion::V1_5),
+    ("definitions", CdxVersion::V1_6),
+    ("declarations", CdxVersion::V1_6),
+    ("citations", CdxVersion::V1_7),
+];
+
+/// `metadata` fields and the first spec version that defines them
+const METADATA_FIELDS: &[(&str, CdxVersion)] = &[
+    ("lifecycles", CdxVersion::V1_5),
+    ("manufacturer", CdxVersion::V1_6),
+    ("distributionConstraints", CdxVersion::V1_7),
+];
+
+/// Component fields and the first spec version that defines them
+const COMPONENT_FIELDS: &[(&str, CdxVersion)] = &[
+    ("releaseNotes", CdxVersion::V1_4),
+    ("signature", CdxVersion::V1_4),
+    ("modelCard", CdxVersion::V1_5),
+    ("data", CdxVersion::V1_5),
+    ("cryptoProperties", CdxVersion::V1_6),
+    ("manufacturer", CdxVersion::V1_6),
+    ("authors", CdxVersion::V1_6),
+    ("omniborId", CdxVersion::V1_6),
+    ("swhid", CdxVersion::V1_6),
+    ("tags", CdxVersion::V1_6),
+    ("isExternal", CdxVersion::V1_7),
+    ("versionRange", CdxVersion::V1_7),
+];
+
+/// Service fields and the first spec version that defines them
+const SERVICE_FIELDS: &[(&str, CdxVersion)] = &[
+    ("releaseNotes", CdxVersion::V1_4),
+    ("signature", CdxVersion::V1_4),
+    ("trustZone", CdxVersion::V1_5),
+    ("tags", CdxVersion::V1_6),
+];
+
+/// Vulnerability fields added after `vulnerabilities` itself (1.4)
+const VULNERABILITY_FIELDS: &[(&str, CdxVersion)] = &[
+    ("workaround", CdxVersion::V1_5),
+    ("proofOfConcept", CdxVersion::V1_5),
+    ("rejected", CdxVersion::V1_5),
+];
+
+/// License fields and the first spec version that defines them
+const LICENSE_FIELDS: &[(&str, CdxVersion)] = &[
+    ("bom-ref", CdxVersion::V1_5),
+    ("licensing", CdxVersion::V1_5),
+    ("properties", CdxVersion::V1_5),
+    ("acknowledgement", CdxVersion::V1_6),
+];
+
+/// Component types and the first spec version that defines them
+const COMPONENT_TYPES: &[(&str, CdxVersion)] = &[
+    ("platform", CdxVersion::V1_5),
+    ("device-driver", CdxVersion::V1_5),
+    ("machine-learning-model", CdxVersion::V1_5),
+    ("data", CdxVersion::V1_5),
+    ("cryptographic-asset", CdxVersion::V1_6),
+];
+
+/// External reference types added after 1.3
+const EXTERNAL_REFERENCE_TYPES: &[(&str, CdxVersion)] = &[
+    ("release-notes", CdxVersion::V1_4),
+    ("distribution-intake", CdxVersion::V1_5),
+    ("security-contact", CdxVersion::V1_5),
+    ("model-card", CdxVersion::V1_5),
+    ("log", CdxVersion::V1_5),
+    ("configuration", CdxVersion::V1_5),
+    ("evidence", CdxVersion::V1_5),
+    ("formulation", CdxVersion::V1_5),
+    ("attestation", CdxVersion::V1_5),
+    ("threat-model", CdxVersion::V1_5),
+    ("adversary-model", CdxVersion::V1_5),
+    ("risk-assessment", CdxVersion::V1_5),
+    ("vulnerability-assertion", CdxVersion::V1_5),
+    ("exploitability-statement", CdxVersion::V1_5),
+    ("pentest-report", CdxVersion::V1_5),
+    ("static-analysis-report", CdxVersion::V1_5),
+    ("dynamic-analysis-report", CdxVersion::V1_5),
+    ("runtime-analysis-report", CdxVersion::V1_5),
+    ("component-analysis-report", CdxVersion::V1_5),
+    ("maturity-report", CdxVersion::V1_5),
+    ("certification-report", CdxVersion::V1_5),
+    ("codified-infrastructure", CdxVersion::V1_5),
+    ("quality-metrics", CdxVersion::V1_5),
+    ("poam", CdxVersion::V1_5),
+    ("source-distribution", CdxVersion::V1_6),
+    ("electronic-signature", CdxVersion::V1_6),
+    ("digital-signature", CdxVersion::V1_6),
+    ("rfc-9116", CdxVersion::V1_6),
+];
+
+/// Rewrites a CycloneDX BOM for the `target` spec version.
+///
+/// Upgrades only change `specVersion`, as every older field is still valid.
+/// Downgrades remove fields the target does not define and translate the
+/// few that have an older equivalent. Each removal is counted, reported
+/// once as a warning and recorded in `fidelity`.
+pub fn transcode_cdx(
+    mut bom: Value,
+    target: CdxVersion,
+    fidelity: &FidelityReport,
+) -> Result<Value, ConverterError> {
+    let root = bom.as_object_mut().ok_or_else(|| {
+        ConverterError::InvalidInput("CycloneDX input must be a JSON object".to_string())
+    })?;
+    if root.get("bomFormat").and_then(Value::as_str) != Some("CycloneDX") {
+        return Err(ConverterError::InvalidInput(
+            "Input is not a CycloneDX document (missing bomFormat)".to_string(),
+        ));
+    }
+
+    let source = root
+        .get("specVersion")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string();
+    info!("Transcoding CycloneDX {} -> {}", source, target.as_str());
+
+    let mut dropped = Dropped::default();
+    downgrade_bom(root, target, &mut dropped);
+
+    root.insert("specVersion".to_string(), json!(target.as_str()));
+    if root.contains_key("$schema") {
+        root.insert(
+            "$schema".to_string(),
+            json!(format!(
+                "http://cyclonedx.org/schema/bom-{}.schema.json",
+                target.as_str()
+            )),
+        );
+    }
+
+    dropped.report(&format!("CycloneDX {}", target.as_str()), fidelity);
+    Ok(bom)
+}
+
+/// Counts fields removed (or translated) during a transcode
+#[derive(Debug, Default)]
+struct Dropped(BTreeMap<String, usize>);
+
+impl Dropped {
+    fn add(&mut self, path: &str) {
+        *self.0.entry(path.to_string()).or_insert(0) += 1;
+    }
+
+    fn report(&self, target: &str, fidelity: &FidelityReport) {
+        for (path, count) in &self.0 {
+            warn!(
+                "{} does not support {}; dropped {} value(s)",
+                target, path, count
+            );
+            fidelity.record_count(path, *count);
+        }
+    }
+}
+
+/// Removes the fields of `object` listed in `fields` that are newer than `target`
+fn strip_fields(
+    object: &mut Map<String, Value>,
+    fields: &[(&str, CdxVersion)],
+    target: CdxVersion,
+    path: &str,
+    dropped: &mut Dropped,
+) {
+    for (field, since) in fields {
+        if target < *since && object.remove(*field).is_some() {
+            dropped.add(&format!("{}.{}", path, field));
+        }
+    }
+}
+
+/// Returns true if `value` appears in `table` with a version newer than `target`
+fn is_newer(table: &[(&str, CdxVersion)], value: &str, target: CdxVersion) -> bool {
+    table
+        .iter()
+        .any(|(name, since)| *name == value && target < *since)
+}
+
+fn downgrade_bom(root: &mut Map<String, Value>, target: CdxVersion, dropped: &mut Dropped) {
+    strip_fields(root, BOM_FIELDS, target, "bom", dropped);
+
+    if let Some(metadata) = root.get_mut("metadata").and_then(Value::as_object_mut) {
+        strip_fields(metadata, METADATA_FIELDS, target, "metadata", dropped);
+        downgrade_tools(metadata, target, dropped);
+        if let Some(component) = metadata.get_mut("component").and_then(Value::as_object_mut) {
+            downgrade_component(component, target, dropped);
+        }
+        if let Some(licenses) = metadata.get_mut("licenses") {
+            downgrade_licenses(licenses, target, dropped);
+        }
+    }
+
+    if let Some(components) = root.get_mut("components").and_then(Value::as_array_mut) {
+        for component in components.iter_mut().filter_map(Value::as_object_mut) {
+            downgrade_component(component, target, dropped);
+        }
+    }
+
+    if let Some(services) = root.get_mut("services").and_then(Value::as_array_mut) {
+        for service in services.iter_mut().filter_map(Value::as_object_mut) {
+            downgrade_service(service, target, dropped);
+        }
+    }
+
+    if let Some(refs) = root.get_mut("externalReferences") {
+        downgrade_external_references(refs, target, dropped);
+    }
+
+    if target < CdxVersion::V1_6
+        && let Some(dependencies) = root.get_mut("dependencies").and_then(Value::as_array_mut)
+    {
+        for dependency in dependencies.iter_mut().filter_map(Value::as_object_mut) {
+            if dependency.remove("provides").is_some() {
+                dropped.add("dependencies.provides");
+            }
+        }
+    }
+
+    if let Some(vulnerabilities) = root
+        .get_mut("vulnerabilities")
+        .and_then(Value::as_array_mut)
+    {
+        for vulnerability in vulnerabilities.iter_mut().filter_map(Value::as_object_mut) {
+            strip_fields(
+                vulnerability,
+                VULNERABILITY_FIELDS,
+                target,
+                "vulnerabilities",
+                dropped,
+            );
+            if target < CdxVersion::V1_5
+                && let Some(analysis) = vulnerability
+                    .get_mut("analysis")
+                    .and_then(Value::as_object_mut)
+            {
+                for field in ["firstIssued", "lastUpdated"] {
+                    if analysis.remove(field).is_some() {
+                        dropped.add(&format!("vulnerabilities.analysis.{}", field));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Before 1.5, `metadata.tools` is a plain array of `{vendor, name, version}`
+fn downgrade_tools(metadata: &mut Map<String, Value>, target: CdxVersion, dropped: &mut Dropped) {
+    if target >= CdxVersion::V1_5 {
+        return;
+    }
+    let Some(Value::Object(tools)) = metadata.get("tools") else {
+        return;
+    };
+
+    let mut legacy = Vec::new();
+    for kind in ["components", "services"] {
+        for tool in tools
+            .get(kind)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let mut entry = Map::new();
+            let vendor = tool
+                .get("group")
+                .or_else(|| tool.pointer("/manufacturer/name"))
+                .or_else(|| tool.pointer("/supplier/name"))
+                .or_else(|| tool.pointer("/provider/name"));
+            if let Some(vendor) = vendor {
+                entry.insert("vendor".to_string(), vendor.clone());
+            }
+            for field in ["name", "version", "hashes", "externalReferences"] {
+                if let Some(value) = tool.get(field) {
+                    entry.insert(field.to_string(), value.clone());
+                }
+            }
+            legacy.push(Value::Object(entry));
+        }
+    }
+
+    dropped.add("metadata.tools (object form, converted to array)");
+    metadata.insert("tools".to_string(), Value::Array(legacy));
+}
+
+fn downgrade_component(
+    component: &mut Map<String, Value>,
+    target: CdxVersion,
+    dropped: &mut Dropped,
+) {
+    strip_fields(component, COMPONENT_FIELDS, target, "component", dropped);
+
+    if let Some(component_type) = component.get("type").and_then(Value::as_str)
+        && is_newer(COMPONENT_TYPES, component_type, target)
+    {
+        dropped.add(&format!(
+            "component type \"{}\" (now library)",
+            component_type
+        ));
+        component.insert("type".to_string(), json!("library"));
+    }
+
+    if let Some(evidence) = component.get_mut("evidence").and_then(Value::as_object_mut) {
+        downgrade_evidence(evidence, target, dropped);
+    }
+    if let Some(licenses) = component.get_mut("licenses") {
+        downgrade_licenses(licenses, target, dropped);
+    }
+    if let Some(refs) = component.get_mut("externalReferences") {
+        downgrade_external_references(refs, target, dropped);
+    }
+    if let Some(children) = component
+        .get_mut("components")
+        .and_then(Value::as_array_mut)
+    {
+        for child in children.iter_mut().filter_map(Value::as_object_mut) {
+            downgrade_component(child, target, dropped);
+        }
+    }
+}
+
+fn downgrade_service(service: &mut Map<String, Value>, target: CdxVersion, dropped: &mut Dropped) {
+    strip_fields(service, SERVICE_FIELDS, target, "service", dropped);
+    if let Some(licenses) = service.get_mut("licenses") {
+        downgrade_licenses(licenses, target, dropped);
+    }
+    if let Some(refs) = service.get_mut("externalReferences") {
+        downgrade_external_references(refs, target, dropped);
+    }
+    if let Some(children) = service.get_mut("services").and_then(Value::as_array_mut) {
+        for child in children.iter_mut().filter_map(Value::as_object_mut) {
+            downgrade_service(child, target, dropped);
+        }
+    }
+}
+
+/// Evidence gained identity/occurrences/callstack in 1.5, and `identity`
+/// became an array in 1.6
+fn downgrade_evidence(
+    evidence: &mut Map<String, Value>,
+    target: CdxVersion,
+    dropped: &mut Dropped,
+) {
+    if target < CdxVersion::V1_5 {
+        for field in ["identity", "occurrences", "callstack"] {
+            if evidence.remove(field).is_some() {
+                dropped.add(&format!("component.evidence.{}", field));
+            }
+        }
+    } else if target < CdxVersion::V1_6
+        && let Some(Value::Array(identities)) = evidence.get_mut("identity")
+    {
+        if identities.len() > 1 {
+            dropped.add("component.evidence.identity (all but the first)");
+        }
+        let first = identities.drain(..).next();
+        match first {
+            Some(identity) => {
+                evidence.insert("identity".to_string(), identity);
+            }
+            None => {
+                evidence.remove("identity");
+            }
+        }
+    }
+}
+
+fn downgrade_licenses(licenses: &mut Value, target: CdxVersion, dropped: &mut Dropped) {
+    for choice in licenses.as_array_mut().into_iter().flatten() {
+        if let Some(license) = choice.get_mut("license").and_then(Value::as_object_mut) {
+            strip_fields(license, LICENSE_FIELDS, target, "license", dropped);
+        }
+        // Expression choices carry the same newer fields
+        if let Some(choice) = choice.as_object_mut()
+            && choice.contains_key("expression")
+        {
+            strip_fields(choice, LICENSE_FIELDS, target, "license", dropped);
+        }
+    }
+}
+
+fn downgrade_external_references(refs: &mut Value, target: CdxVersion, dropped: &mut Dropped) {
+    for reference in refs.as_array_mut().into_iter().flatten() {
+        let Some(ref_type) = reference.get("type").and_then(Value::as_str) else {
+            continue;
+        };
+        if is_newer(EXTERNAL_REFERENCE_TYPES, ref_type, target) {
+            dropped.add(&format!(
+                "externalReferences type \"{}\" (now other)",
+                ref_type
+            ));
+            reference["type"] = json!("other");
+        }
+    }
+}
+
+// =========================================================================
+// SPDX
+// =========================================================================
+
+/// Rewrites an SPDX document in the requested SPDX 3 layout.
+///
+/// SPDX 3 documents are moved between the simple and JSON-LD layouts (a
+/// document already in the requested layout is returned as-is). SPDX 2.x
+/// documents have a single JSON layout and are returned unchanged; asking
+/// for another version or for JSON-LD is an `UnsupportedFormat` error, as
+/// versions are not converted. A namespace or creation time the source
+/// lacks comes from `identity`.
+/// Elements the target layout has no equivalent for are counted by type,
+/// reported once as a warning and recorded in `fidelity`.
+pub fn transcode_spdx(
+    doc: Value,
+    target_version: Option<SpdxVersion>,
+    serialization: SpdxSerialization,
+    identity: &OutputIdentity,
+    fidelity: &FidelityReport,
+) -> Result<Value, ConverterError> {
+    if doc.get("@graph").is_some() {
+        if target_version == Some(SpdxVersion::V2_3) {
+            return Err(ConverterError::UnsupportedFormat(
+                "spdx-to-spdx does not convert SPDX 3.0.1 input to SPDX 2.3".to_string(),
+            ));
+        }
+        return match serialization {
+            SpdxSerialization::JsonLd => Ok(doc),
+            SpdxSerialization::Simple => {
+                info!("Transcoding SPDX 3 JSON-LD -> simple JSON");
+                jsonld_to_simple(&doc, identity, fidelity)
+            }
+        };
+    }
+
+    let input_version = doc
+        .get("spdxVersion")
+        .and_then(Value::as_str)
+        .ok_or_else(|| {
+            ConverterError::InvalidInput(
+                "Input is not an SPDX document (missing spdxVersion or @graph)".to_string(),
+            )
+        })?;
+
+    if input_version.starts_with("SPDX-2") {
+        if target_version == Some(SpdxVersion::V3_0_1) {
+            return Err(ConverterError::UnsupportedFormat(format!(
+                "spdx-to-spdx does not convert {} input to SPDX 3.0.1",
+                input_version
+            )));
+        }
+        if serialization == SpdxSerialization::JsonLd {
+            return Err(ConverterError::UnsupportedFormat(format!(
+                "JSON-LD serialization requires SPDX 3 input; {} has no JSON-LD layout",
+                input_version
+            )));
+        }
+        info!(
+            "{} input keeps its version; re-serializing only",
+            input_version
+        );
+        return Ok(doc);
+    }
+
+    if target_version == Some(SpdxVersion::V2_3) {
+        return Err(ConverterError::UnsupportedFormat(format!(
+            "spdx-to-spdx does not convert {} input to SPDX 2.3",
+            input_version
+        )));
+    }
+
+    match serialization {
+        SpdxSerialization::Simple => Ok(doc),
+        SpdxSerialization::JsonLd => {
+            info!("Transcoding SPDX 3 simple JSON -> JSON-LD");
+            simple_to_jsonld(&doc, identity, fidelity)
+        }
+    }
+}
+
+/// Strips the namespace from a JSON-LD IRI, keeping the `SPDXRef-` fragment
+fn local_id(iri: &str) -> String {
+    match iri.rsplit_once('#') {
+        Some((_, fragment)) => fragment.to_string(),
+        None => iri.to_string(),
+    }
+}
+
+/// JSON-LD `type`, accepting the `@type` keyword form as well
+fn jsonld_type(element: &Value) -> &str {
+    element
+        .get("type")
+        .or_else(|| element.get("@type"))
+        .and_then(Value::as_str)
+        .unwrap_or("")
+}
+
+/// JSON-LD `spdxId`, accepting the `@id` keyword form as well
+fn jsonld_id(element: &Value) -> &str {
+    element
+        .get("spdxId")
+        .or_else(|| element.get("@id"))
+        .and_then(Value::as_str)
+        .unwrap_or("")
+}
+
+/// `dependsOn` -> `DEPENDS_ON`
+fn to_screaming_snake(relationship_type: &str) -> String {
+    let mut out = String::new();
+    for c in relationship_type.chars() {
+        if c.is_ascii_uppercase() && !out.is_empty() {
+            out.push('_');
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+/// `DEPENDS_ON` -> `dependsOn`
+fn to_camel(relationship_type: &str) -> String {
+    let mut out = String::new();
+    for (i, word) in relationship_type.split('_').enumerate() {
+        let lower = word.to_ascii_lowercase();
+        if i == 0 {
+            out.push_str(&lower);
+        } else {
+            let mut chars = lower.chars();
+            if let Some(first) = chars.next() {
+                out.push(first.to_ascii_uppercase());
+                out.extend(chars);
+            }
+        }
+    }
+    out
+}
+
+/// JSON-LD license relationship types and the simple JSON field for each
+const LICENSE_RELATIONSHIPS: &[(&str, &str)] = &[
+    ("hasConcludedLicense", "licenseConcluded"),
+    ("hasDeclaredLicense", "licenseDeclared"),
+];
+
+/// Copies the listed fields from `from` into `to` (renaming as it goes)
+fn copy_fields(from: &Value, to: &mut Map<String, Value>, fields: &[(&str, &str)]) {
+    for (source, dest) in fields {
+        if let Some(value) = from.get(*source) {
+            to.insert(dest.to_string(), value.clone());
+        }
+    }
+}
+
+fn jsonld_to_simple(
+    doc: &Value,
+    identity: &OutputIdentity,
+    fidelity: &FidelityReport,
+) -> Result<Value, ConverterError> {
+    let graph = doc
+        .get("@graph")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ConverterError::InvalidInput("@graph must be an array".to_string()))?;
+
+    // License expressions are separate elements in JSON-LD
+    let licenses: HashMap<&str, &str> = graph
+        .iter()
+        .filter(|e| jsonld_type(e) == "simplelicensing_LicenseExpression")
+        .filter_map(|e| {
+            Some((
+                jsonld_id(e),
+                e.get("simplelicensing_licenseExpression")?.as_str()?,
+            ))
+        })
+        .collect();
+
+    // Element -> license relationship type -> expression
+    let mut element_licenses: HashMap<(String, &str), String> = HashMap::new();
+    let mut relationships = Vec::new();
+    let mut document_name = None;
+    let mut created = None;
+    let mut dropped = Dropped::default();
+
+    for element in graph {
+        match jsonld_type(element) {
+            "CreationInfo" => {
+                created = element.get("created").and_then(Value::as_str);
+            }
+            "SpdxDocument" => {
+                document_name = element.get("name").and_then(Value::as_str);
+            }
+            _ if element.get("from").is_some() && element.get("to").is_some() => {
+                let from = element["from"].as_str().unwrap_or_default();
+                let relationship_type = element
+                    .get("relationshipType")
+                    .and_then(Value::as_str)
+                    .unwrap_or("other");
+                let targets = match &element["to"] {
+                    Value::Array(targets) => targets.iter().filter_map(Value::as_str).collect(),
+                    Value::String(target) => vec![target.as_str()],
+                    _ => Vec::new(),
+                };
+
+                // Assessments keep their fields in the `relationships` array
+                if SpdxVulnAssessment::is_assessment_type(jsonld_type(element)) {
+                    let mut assessment = element.clone();
+                    if let Some(object) = assessment.as_object_mut() {
+                        object.remove("creationInfo");
+                        object.insert("spdxId".to_string(), json!(local_id(jsonld_id(element))));
+                        object.insert("from".to_string(), json!(local_id(from)));
+                        let to: Vec<String> = targets.iter().map(|t| local_id(t)).collect();
+                        object.insert("to".to_string(), json!(to));
+                    }
+                    relationships.push(assessment);
+                    continue;
+                }
+
+                if let Some((license_type, _)) = LICENSE_RELATIONSHIPS
+                    .iter()
+                    .find(|(t, _)| *t == relationship_type)
+                {
+                    if let Some(expression) = targets.first().and_then(|t| licenses.get(t)) {
+                        element_licenses
+                            .insert((from.to_string(), *license_type), expression.to_string());
+                    }
+                    continue;
+                }
+                for target in targets {
+                    relationships.push(json!({
+                        "spdxElementId": local_id(from),
+                        "relationshipType": to_screaming_snake(relationship_type),
+                        "relatedSpdxElement": local_id(target),
+                    }));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut elements = Vec::new();
+    for element in graph {
+        let simple_type = match jsonld_type(element) {
+            "software_Package" => "SpdxPackage",
+            "software_File" => "SpdxFile",
+            "security_Vulnerability" => "SpdxVulnerability",
+            // Carried over as the document, its creation time and the
+            // license fields of the elements
+            "CreationInfo" | "SpdxDocument" | "simplelicensing_LicenseExpression" => continue,
+            _ if element.get("from").is_some() => continue,
+            // Including agents: the simple layout only names the converter
+            // as creator
+            other => {
+                dropped.add(&format!("element.{}", other));
+                continue;
+            }
+        };
+
+        let spdx_id = jsonld_id(element);
+        let mut out = Map::new();
+        out.insert("spdxId".to_string(), json!(local_id(spdx_id)));
+        out.insert("type".to_string(), json!(simple_type));
+        out.insert(
+            "name".to_string(),
+            element.get("name").cloned().unwrap_or(json!("")),
+        );
+        copy_fields(
+            element,
+            &mut out,
+            &[
+                ("software_packageVersion", "versionInfo"),
+                ("description", "summary"),
+                ("software_packageUrl", "purl"),
+            ],
+        );
+        for (relationship_type, field) in LICENSE_RELATIONSHIPS {
+            if let Some(expression) =
+                element_licenses.get(&(spdx_id.to_string(), *relationship_type))
+            {
+                out.insert(field.to_string(), json!(expression));
+            }
+        }
+        copy_fields(
+            element,
+            &mut out,
+            &[
+                ("externalIdentifier", "externalIdentifier"),
+                ("verifiedUsing", "verifiedUsing"),
+                ("software_primaryPurpose", "softwarePrimaryPurpose"),
+            ],
+        );
+        elements.push(Value::Object(out));
+    }
+
+    dropped.report("SPDX simple JSON", fidelity);
+
+    Ok(json!({
+        "spdxVersion": "SPDX-3.0",
+        "dataLicense": "CC0-1.0",
+        "spdxId": "SPDXRef-DOCUMENT",
+        "name": document_name.unwrap_or("Converted SBOM"),
+        "documentNamespace": format!("urn:uuid:{}", identity.uuid("document")),
+        "creationInfo": {
+            "created": created.map(str::to_string).unwrap_or_else(|| identity.now().to_rfc3339()),
+            "creators": ["Tool: sbom-converter"],
+        },
+        "elements": elements,
+        "relationships": relationships,
+    }))
+}
+
+fn simple_to_jsonld(
+    doc: &Value,
+    identity: &OutputIdentity,
+    fidelity: &FidelityReport,
+) -> Result<Value, ConverterError> {
+    let namespace = doc
+        .get("documentNamespace")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            format!(
+                "https://spdx.org/spdxdocs/sbom-converter-{}",
+                identity.uuid("namespace")
+            )
+        });
+    let iri = |local: &str| format!("{}#{}", namespace, local);
+
+    let created = doc
+        .pointer("/creationInfo/created")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| identity.now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    let agent_id = iri("SPDXRef-Agent-sbom-converter");
+    let tool_id = iri("SPDXRef-Tool-sbom-converter");
+
+    let mut graph = vec![
+        json!({
+            "type": "CreationInfo",
+            "@id": SPDX_JSONLD_CREATION_INFO_ID,
+            "specVersion": "3.0.1",
+            "created": created,
+            "createdBy": [agent_id],
+            "createdUsing": [tool_id],
+        }),
+        json!({
+            "type": "SoftwareAgent",
+            "spdxId": agent_id,
+            "creationInfo": SPDX_JSONLD_CREATION_INFO_ID,
+            "name": "sbom-converter",
+        }),
+        json!({
+            "type": "Tool",
+            "spdxId": tool_id,
+            "creationInfo": SPDX_JSONLD_CREATION_INFO_ID,
+            "name": format!("sbom-converter {}", env!("CARGO_PKG_VERSION")),
+        }),
+    ];
+
+    let mut relationships: Vec<(String, String, Vec<String>)> = Vec::new();
+    let mut license_ids: HashMap<String, String> = HashMap::new();
+    let mut dropped = Dropped::default();
+
+    for element in doc
+        .get("elements")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let element_type = element.get("type").and_then(Value::as_str).unwrap_or("");
+        let jsonld_type = match element_type {
+            "SpdxPackage" => "software_Package",
+            "SpdxFile" => "software_File",
+            "SpdxVulnerability" => "security_Vulnerability",
+            other => {
+                dropped.add(&format!("element.{}", other));
+                continue;
+            }
+        };
+        let spdx_id = iri(element.get("spdxId").and_then(Value::as_str).unwrap_or(""));
+
+        let mut out = Map::new();
+        out.insert("type".to_string(), json!(jsonld_type));
+        out.insert("spdxId".to_string(), json!(spdx_id));
+        out.insert(
+            "creationInfo".to_string(),
+            json!(SPDX_JSONLD_CREATION_INFO_ID),
+        );
+        out.insert(
+            "name".to_string(),
+            element.get("name").cloned().unwrap_or(json!("")),
+        );
+        copy_fields(element, &mut out, &[("summary", "description")]);
+        if jsonld_type == "software_Package" {
+            copy_fields(
+                element,
+                &mut out,
+                &[
+                    ("versionInfo", "software_packageVersion"),
+                    ("purl", "software_packageUrl"),
+                ],
+            );
+        }
+        copy_fields(
+            element,
+            &mut out,
+            &[
+                ("softwarePrimaryPurpose", "software_primaryPurpose"),
+                ("externalIdentifier", "externalIdentifier"),
+                ("verifiedUsing", "verifiedUsing"),
+            ],
+        );
+        if jsonld_type == "security_Vulnerability" && !out.contains_key("externalIdentifier") {
+            let name = element.get("name").and_then(Value::as_str).unwrap_or("");
+            let id_type = if name.starts_with("CVE-") {
+                "cve"
+            } else {
+                "securityOther"
+            };
+            out.insert(
+                "externalIdentifier".to_string(),
+                json!([{
+                    "type": "ExternalIdentifier",
+                    "externalIdentifierType": id_type,
+                    "identifier": name,
+                }]),
+            );
+        }
+        graph.push(Value::Object(out));
+
+        for (relationship_type, field) in LICENSE_RELATIONSHIPS {
+            let Some(expression) = element.get(*field).and_then(Value::as_str) else {
+                continue;
+            };
+            let license_id = match license_ids.get(expression) {
+                Some(id) => id.clone(),
+                None => {
+                    let id = iri(&format!("SPDXRef-License-{}", license_ids.len() + 1));
+                    graph.push(json!({
+                        "type": "simplelicensing_LicenseExpression",
+                        "spdxId": id,
+                        "creationInfo": SPDX_JSONLD_CREATION_INFO_ID,
+                        "simplelicensing_licenseExpression": expression,
+                    }));
+                    license_ids.insert(expression.to_string(), id.clone());
+                    id
+                }
+            };
+            relationships.push((
+                spdx_id.clone(),
+                relationship_type.to_string(),
+                vec![license_id],
+            ));
+        }
+    }
+
+    // Group the flat relationships by (from, type), as the converter does
+    let mut positions: HashMap<(String, String), usize> = relationships
+        .iter()
+        .enumerate()
+        .map(|(index, (from, relationship_type, _))| {
+            ((from.clone(), relationship_type.clone()), index)
+        })
+        .collect();
+    let mut assessments = Vec::new();
+    for rel in doc
+        .get("relationships")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let rel_type = rel.get("type").and_then(Value::as_str);
+        if rel_type.is_some_and(SpdxVulnAssessment::is_assessment_type) {
+            let mut assessment = rel.clone();
+            if let Some(object) = assessment.as_object_mut() {
+                let id = |key: &str| object.get(key).and_then(Value::as_str).map(&iri);
+                let (spdx_id, from) = (id("spdxId"), id("from"));
+                let to: Vec<String> = object
+                    .get("to")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(&iri)
+                    .collect();
+                object.insert("spdxId".to_string(), json!(spdx_id));
+                object.insert(
+                    "creationInfo".to_string(),
+                    json!(SPDX_JSONLD_CREATION_INFO_ID),
+                );
+                object.insert("from".to_string(), json!(from));
+                object.insert("to".to_string(), json!(to));
+            }
+            assessments.push(assessment);
+            continue;
+        }
+        let (Some(from), Some(relationship_type), Some(to)) = (
+            rel.get("spdxElementId").and_then(Value::as_str),
+            rel.get("relationshipType").and_then(Value::as_str),
+            rel.get("relatedSpdxElement").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let from = iri(from);
+        let relationship_type = to_camel(relationship_type);
+        match positions.entry((from, relationship_type)) {
+            Entry::Occupied(entry) => relationships[*entry.get()].2.push(iri(to)),
+            Entry::Vacant(entry) => {
+                let (from, relationship_type) = entry.key().clone();
+                entry.insert(relationships.len());
+                relationships.push((from, relationship_type, vec![iri(to)]));
+            }
+        }
+    }
+
+    for (index, (from, relationship_type, to)) in relationships.into_iter().enumerate() {
+        graph.push(json!({
+            "type": "Relationship",
+            "spdxId": iri(&format!("SPDXRef-Relationship-{}", index + 1)),
+            "creationInfo": SPDX_JSONLD_CREATION_INFO_ID,
+            "from": from,
+            "relationshipType": relationship_type,
+            "to": to,
+        }));
+    }
+    graph.extend(assessments);
+
+    graph.push(json!({
+        "type": "SpdxDocument",
+        "spdxId": iri("SPDXRef-DOCUMENT"),
+        "creationInfo": SPDX_JSONLD_CREATION_INFO_ID,
+        "name": doc.get("name").and_then(Value::as_str).unwrap_or("Converted SBOM"),
+        "profileConformance": ["core", "software", "security", "simpleLicensing"],
+        "dataLicense": "https://spdx.org/licenses/CC0-1.0",
+    }));
+
+    dropped.report("SPDX JSON-LD output", fidelity);
+
+    Ok(json!({
+        "@context": SPDX_JSONLD_CONTEXT,
+        "@graph": graph,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cdx_16() -> Value {
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "metadata": {
+                "lifecycles": [{ "phase": "build" }],
+                "tools": {
+                    "components": [
+                        { "type": "application", "group": "acme", "name": "scanner", "version": "2.0" }
+                    ]
+                }
+            },
+            "components": [
+                {
+                    "type": "machine-learning-model",
+                    "name": "model",
+                    "modelCard": {},
+                    "licenses": [{ "license": { "id": "MIT", "acknowledgement": "declared" } }],
+                    "components": [
+                        { "type": "library", "name": "child", "tags": ["x"] }
+                    ]
+                }
+            ],
+            "dependencies": [{ "ref": "model", "provides": ["x"] }],
+            "vulnerabilities": [{ "id": "CVE-2024-0001", "workaround": "none" }],
+            "formulation": []
+        })
+    }
+
+    #[test]
+    fn test_cdx_downgrade_to_1_4() {
+        let out = transcode_cdx(cdx_16(), CdxVersion::V1_4, &FidelityReport::new()).unwrap();
+        assert_eq!(out["specVersion"], "1.4");
+        assert!(out.get("formulation").is_none());
+        assert!(out["metadata"].get("lifecycles").is_none());
+        assert_eq!(
+            out["metadata"]["tools"],
+            json!([{ "vendor": "acme", "name": "scanner", "version": "2.0" }])
+        );
+
+        let model = &out["components"][0];
+        assert_eq!(model["type"], "library");
+        assert!(model.get("modelCard").is_none());
+        assert_eq!(model["licenses"][0]["license"], json!({ "id": "MIT" }));
+        assert!(model["components"][0].get("tags").is_none());
+
+        assert!(out["dependencies"][0].get("provides").is_none());
+        assert_eq!(out["vulnerabilities"][0], json!({ "id": "CVE-2024-0001" }));
+    }
+
+    #[test]
+    fn test_cdx_downgrade_to_1_3_drops_vulnerabilities() {
+        let out = transcode_cdx(cdx_16(), CdxVersion::V1_3, &FidelityReport::new()).unwrap();
+        assert_eq!(out["specVersion"], "1.3");
+        assert!(out.get("vulnerabilities").is_none());
+    }
+
+    #[test]
+    fn test_cdx_upgrade_keeps_fields() {
+        let out = transcode_cdx(cdx_16(), CdxVersion::V1_7, &FidelityReport::new()).unwrap();
+        let mut expected = cdx_16();
+        expected["specVersion"] = json!("1.7");
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_cdx_rejects_non_cdx() {
+        assert!(
+            transcode_cdx(
+                json!({ "spdxVersion": "SPDX-2.3" }),
+                CdxVersion::V1_6,
+                &FidelityReport::new()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_relationship_type_case() {
+        assert_eq!(to_screaming_snake("dependsOn"), "DEPENDS_ON");
+        assert_eq!(to_screaming_snake("affects"), "AFFECTS");
+        assert_eq!(to_camel("DEPENDS_ON"), "dependsOn");
+        assert_eq!(to_camel("AFFECTS"), "affects");
+    }
+
+    #[test]
+    fn test_spdx_simple_jsonld_round_trip() {
+        let simple = json!({
+            "spdxVersion": "SPDX-3.0",
+            "spdxId": "SPDXRef-DOCUMENT",
+            "name": "demo",
+            "documentNamespace": "https://example.com/demo",
+            "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": [] },
+            "elements": [
+                { "spdxId": "SPDXRef-app", "type": "SpdxPackage", "name": "app", "versionInfo": "1.0", "licenseConcluded": "MIT" },
+                { "spdxId": "SPDXRef-lib", "type": "SpdxPackage", "name": "lib", "purl": "pkg:npm/lib@2" }
+            ],
+            "relationships": [
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-lib" }
+            ]
+        });
+
+        let jsonld = transcode_spdx(
+            simple,
+            None,
+            SpdxSerialization::JsonLd,
+            &OutputIdentity::Random,
+            &FidelityReport::new(),
+        )
+        .unwrap();
+        let graph = jsonld["@graph"].as_array().unwrap();
+        let app = graph
+            .iter()
+            .find(|e| e["spdxId"] == "https://example.com/demo#SPDXRef-app")
+            .unwrap();
+        assert_eq!(app["type"], "software_Package");
+        assert_eq!(app["software_packageVersion"], "1.0");
+        assert!(graph.iter().any(|e| e["relationshipType"] == "dependsOn"));
+        assert!(
+            graph
+                .iter()
+                .any(|e| e["relationshipType"] == "hasConcludedLicense")
+        );
+
+        let back = transcode_spdx(
+            jsonld,
+            None,
+            SpdxSerialization::Simple,
+            &OutputIdentity::Random,
+            &FidelityReport::new(),
+        )
+        .unwrap();
+        assert_eq!(back["name"], "demo");
+        let app = &back["elements"][0];
+        assert_eq!(app["spdxId"], "SPDXRef-app");
+        assert_eq!(app["licenseConcluded"], "MIT");
+        assert_eq!(back["elements"][1]["purl"], "pkg:npm/lib@2");
+        assert_eq!(
+            back["relationships"],
+            json!([{ "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-lib" }])
+        );
+    }
+
+    #[test]
+    fn test_spdx2_passes_through() {
+        let doc = json!({ "spdxVersion": "SPDX-2.3", "SPDXID": "SPDXRef-DOCUMENT" });
+        let transcode = |version, serialization| {
+            transcode_spdx(
+                doc.clone(),
+                version,
+                serialization,
+                &OutputIdentity::Random,
+                &FidelityReport::new(),
+            )
+        };
+        assert_eq!(transcode(None, SpdxSerialization::Simple).unwrap(), doc);
+        assert_eq!(
+            transcode(Some(SpdxVersion::V2_3), SpdxSerialization::Simple).unwrap(),
+            doc
+        );
+        assert!(matches!(
+            transcode(None, SpdxSerialization::JsonLd),
+            Err(ConverterError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            transcode(Some(SpdxVersion::V3_0_1), SpdxSerialization::Simple),
+            Err(ConverterError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_jsonld_groups_relationships_by_source_and_type() {
+        let simple = json!({
+            "spdxVersion": "SPDX-3.0",
+            "name": "demo",
+            "documentNamespace": "https://example.com/demo",
+            "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": [] },
+            "elements": [],
+            "relationships": [
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-a" },
+                { "spdxElementId": "SPDXRef-lib", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-b" },
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "CONTAINS", "relatedSpdxElement": "SPDXRef-c" },
+                { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-d" }
+            ]
+        });
+
+        let jsonld = transcode_spdx(
+            simple,
+            None,
+            SpdxSerialization::JsonLd,
+            &OutputIdentity::Random,
+            &FidelityReport::new(),
+        )
+        .unwrap();
+        let relationships: Vec<_> = jsonld["@graph"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["type"] == "Relationship")
+            .map(|e| {
+                (
+                    e["from"].clone(),
+                    e["relationshipType"].clone(),
+                    e["to"].clone(),
+                )
+            })
+            .collect();
+        let iri = |id: &str| json!(format!("https://example.com/demo#{}", id));
+        assert_eq!(
+            relationships,
+            vec![
+                (
+                    iri("SPDXRef-app"),
+                    json!("dependsOn"),
+                    json!([iri("SPDXRef-a"), iri("SPDXRef-d")])
+                ),
+                (
+                    iri("SPDXRef-lib"),
+                    json!("dependsOn"),
+                    json!([iri("SPDXRef-b")])
+                ),
+                (
+                    iri("SPDXRef-app"),
+                    json!("contains"),
+                    json!([iri("SPDXRef-c")])
+                ),
+            ]
+        );
+    }
+}
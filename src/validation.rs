@@ -2,6 +2,7 @@
 //!
 //! Provides detailed validation with helpful error messages and suggestions.
 
+use crate::license::{Expression, LicenseIssue};
 use colored::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -494,6 +495,27 @@ fn validate_cdx_component(comp: &Value, idx: usize, report: &mut ValidationRepor
         );
     }
 
+    // Licenses
+    if let Some(licenses) = obj.get("licenses").and_then(|l| l.as_array()) {
+        for (i, choice) in licenses.iter().enumerate() {
+            let choice_location = format!("{}.licenses[{}]", location, i);
+            if let Some(expression) = choice.get("expression").and_then(|e| e.as_str()) {
+                validate_license_expression(
+                    expression,
+                    &format!("{}.expression", choice_location),
+                    report,
+                );
+            } else if let Some(id) = choice
+                .get("license")
+                .and_then(|l| l.get("id"))
+                .and_then(|id| id.as_str())
+            {
+                // A license id is a single-term expression
+                validate_license_expression(id, &format!("{}.license.id", choice_location), report);
+            }
+        }
+    }
+
     // Recommended: purl
     if !obj.contains_key("purl") {
         report.add_issue(
@@ -554,9 +576,66 @@ pub fn validate_spdx(value: &Value) -> ValidationReport {
         );
     }
 
+    // License expressions: SPDX 2.3 packages and files, SPDX 3 elements
+    for (array, keys) in [
+        ("packages", &["licenseConcluded", "licenseDeclared"][..]),
+        ("files", &["licenseConcluded"][..]),
+        ("elements", &["licenseConcluded", "licenseDeclared"][..]),
+        ("@graph", &["simplelicensing_licenseExpression"][..]),
+    ] {
+        let items = obj.get(array).and_then(|a| a.as_array());
+        for (idx, item) in items.into_iter().flatten().enumerate() {
+            for key in keys {
+                if let Some(expression) = item.get(*key).and_then(|e| e.as_str())
+                    && expression != "NOASSERTION"
+                    && expression != "NONE"
+                {
+                    let location = format!("{}[{}].{}", array, idx, key);
+                    validate_license_expression(expression, &location, &mut report);
+                }
+            }
+        }
+    }
+
     report
 }
 
+/// Reports unparsable license expressions as errors and unknown or
+/// deprecated ids as warnings
+fn validate_license_expression(expression: &str, location: &str, report: &mut ValidationReport) {
+    let parsed = match expression.parse::<Expression>() {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            report.add_issue(
+                ValidationIssue::error(e.to_string())
+                    .with_location(location)
+                    .with_suggestion(
+                        "Combine SPDX license ids with AND, OR and WITH, e.g. \"MIT OR Apache-2.0\"",
+                    ),
+            );
+            return;
+        }
+    };
+
+    for issue in parsed.issues() {
+        let suggestion = match &issue {
+            LicenseIssue::Deprecated { replacement, .. } => format!("Use '{}'", replacement),
+            LicenseIssue::UnknownLicense(_) => {
+                "Use an id from https://spdx.org/licenses/ or a LicenseRef- id".to_string()
+            }
+            LicenseIssue::UnknownException(_) => {
+                "Use an exception id from https://spdx.org/licenses/exceptions-index.html"
+                    .to_string()
+            }
+        };
+        report.add_issue(
+            ValidationIssue::warning(issue.to_string())
+                .with_location(location)
+                .with_suggestion(suggestion),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -623,4 +702,49 @@ mod tests {
         let report = validate_cdx(&cdx);
         assert!(report.has_errors());
     }
+
+    #[test]
+    fn test_validate_license_expressions() {
+        let cdx = json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "components": [
+                {
+                    "type": "library",
+                    "name": "test-lib",
+                    "version": "1.0.0",
+                    "licenses": [
+                        { "expression": "MIT OR" },
+                        { "license": { "id": "GPL-2.0" } }
+                    ]
+                }
+            ]
+        });
+
+        let report = validate_cdx(&cdx);
+        assert_eq!(report.error_count(), 1);
+        let deprecated = report
+            .issues
+            .iter()
+            .find(|i| i.location.as_deref() == Some("components[0].licenses[1].license.id"))
+            .unwrap();
+        assert_eq!(deprecated.severity, Severity::Warning);
+        assert_eq!(deprecated.suggestion.as_deref(), Some("Use 'GPL-2.0-only'"));
+
+        let spdx = json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "packages": [
+                { "licenseConcluded": "NOASSERTION", "licenseDeclared": "Apache-2.0 AND Foo" }
+            ]
+        });
+        let report = validate_spdx(&spdx);
+        assert_eq!(report.error_count(), 0);
+        assert_eq!(report.warning_count(), 1);
+        assert_eq!(
+            report.issues[0].location.as_deref(),
+            Some("packages[0].licenseDeclared")
+        );
+    }
 }
@@ -0,0 +1,1079 @@
+//! CSAF 2.0 VEX documents.
+//!
+//! Writes the `csaf_vex` profile: SBOM components become
+//! `product_tree.full_product_names` identified by purl and CPE, and the
+//! CycloneDX vulnerabilities of one id are grouped into one CSAF
+//! vulnerability whose `product_status` lists the affected products by
+//! analysis state. Justifications become `flags`, analysis details
+//! `threats` of category `impact`, responses `remediations` and CVSS
+//! ratings `scores`.
+
+use super::{
+    VexComponent, VexOptions, cdx_components, cdx_justification, cdx_vulnerabilities,
+    vex_justification,
+};
+use crate::bom_link::local_ref;
+use crate::cdx_version::CdxVersion;
+use crate::errors::ConverterError;
+use crate::models_cdx::{CdxAffects, CdxAnalysis, CdxRating, CdxVulnSource, CdxVulnerability};
+use crate::validation::{ValidationIssue, ValidationReport};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// CSAF version written
+pub const CSAF_VERSION: &str = "2.0";
+
+/// Remediation categories and details written for CycloneDX responses.
+/// The details are only boilerplate, so they are not read back as
+/// analysis details.
+const RESPONSE_REMEDIATIONS: &[(&str, &str, &str)] = &[
+    ("update", "vendor_fix", "Update to a fixed version"),
+    (
+        "rollback",
+        "vendor_fix",
+        "Roll back to an unaffected version",
+    ),
+    (
+        "workaround_available",
+        "workaround",
+        "A workaround is available",
+    ),
+    ("will_not_fix", "no_fix_planned", "No fix is planned"),
+    ("can_not_fix", "none_available", "No fix is available"),
+];
+
+/// Remediation written for affected products without a response, as the
+/// `csaf_vex` profile requires one
+const NO_REMEDIATION: (&str, &str) = ("none_available", "No remediation information provided");
+
+/// A CSAF 2.0 document
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafDocument {
+    pub document: CsafDocumentMeta,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_tree: Option<CsafProductTree>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vulnerabilities: Vec<CsafVulnerability>,
+}
+
+/// The `document` metadata of a CSAF document
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafDocumentMeta {
+    pub category: String, // "csaf_vex", "csaf_security_advisory", ...
+    pub csaf_version: String,
+    pub publisher: CsafPublisher,
+    pub title: String,
+    pub tracking: CsafTracking,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<CsafNote>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafPublisher {
+    pub category: String, // "vendor", "coordinator", ...
+    pub name: String,
+    pub namespace: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafTracking {
+    pub id: String,
+    pub status: String, // "draft", "interim", "final"
+    pub version: String,
+    pub initial_release_date: String,
+    pub current_release_date: String,
+    #[serde(default)]
+    pub revision_history: Vec<CsafRevision>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafRevision {
+    pub date: String,
+    pub number: String,
+    pub summary: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafNote {
+    pub category: String, // "description", "summary", "general", ...
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CsafProductTree {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<CsafBranch>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub full_product_names: Vec<CsafFullProductName>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_groups: Vec<CsafProductGroup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relationships: Vec<CsafRelationship>,
+}
+
+/// A vendor/product/version branch of the product tree
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafBranch {
+    pub category: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<CsafBranch>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<CsafFullProductName>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafFullProductName {
+    pub name: String,
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_identification_helper: Option<CsafProductHelper>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CsafProductHelper {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpe: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafProductGroup {
+    pub group_id: String,
+    pub product_ids: Vec<String>,
+}
+
+/// A product formed by relating two products, e.g. a component
+/// `default_component_of` a platform
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafRelationship {
+    pub category: String,
+    pub full_product_name: CsafFullProductName,
+    pub product_reference: String,
+    pub relates_to_product_reference: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CsafVulnerability {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cve: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<CsafId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<CsafNote>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<CsafReference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_status: Option<CsafProductStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<CsafFlag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub threats: Vec<CsafThreat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remediations: Vec<CsafRemediation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scores: Vec<CsafScore>,
+}
+
+/// A vulnerability id in a system other than CVE
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafId {
+    pub system_name: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafReference {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>, // "external", "self"
+    pub summary: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CsafProductStatus {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub first_affected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub first_fixed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_affected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_not_affected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub last_affected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recommended: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub under_investigation: Vec<String>,
+}
+
+impl CsafProductStatus {
+    /// The product ids of each status, with the CycloneDX analysis state
+    /// they map to. `recommended` says nothing about the vulnerability.
+    fn by_state(&self) -> Vec<(&'static str, &[String])> {
+        vec![
+            ("exploitable", &self.first_affected),
+            ("exploitable", &self.known_affected),
+            ("exploitable", &self.last_affected),
+            ("not_affected", &self.known_not_affected),
+            ("resolved", &self.first_fixed),
+            ("resolved", &self.fixed),
+            ("in_triage", &self.under_investigation),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafFlag {
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafThreat {
+    pub category: String, // "impact", "exploit_status", "target_set"
+    pub details: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafRemediation {
+    pub category: String, // "vendor_fix", "workaround", "mitigation", ...
+    pub details: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub product_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsafScore {
+    pub products: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cvss_v2: Option<CsafCvss>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cvss_v3: Option<CsafCvss>,
+}
+
+/// The CVSS fields of a score that map to a CycloneDX rating; the others
+/// are kept as they are
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsafCvss {
+    pub version: String,
+    pub vector_string: String,
+    pub base_score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_severity: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// What a product of a CSAF document identifies
+struct CsafProduct {
+    product_id: String,
+    purl: Option<String>,
+    cpe: Option<String>,
+}
+
+impl CsafDocument {
+    /// Parses a CSAF document
+    pub fn parse(value: Value) -> Result<Self, ConverterError> {
+        serde_json::from_value(value)
+            .map_err(|e| ConverterError::ParseError(format!("Invalid CSAF: {}", e)))
+    }
+
+    /// Builds a CSAF VEX document from the vulnerabilities of a CycloneDX
+    /// BOM. `affects` refs to components of the BOM become products
+    /// identified by purl and CPE; other refs become products named by
+    /// the ref.
+    pub fn from_cdx(bom: &Value, options: &VexOptions) -> Result<Self, ConverterError> {
+        let components: HashMap<String, VexComponent> = cdx_components(bom)
+            .into_iter()
+            .map(|c| (c.bom_ref.clone(), c))
+            .collect();
+        let namespace = options
+            .namespace
+            .clone()
+            .or_else(|| {
+                ["/metadata/supplier/url/0", "/metadata/manufacturer/url/0"]
+                    .iter()
+                    .find_map(|pointer| bom.pointer(pointer).and_then(|url| url.as_str()))
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| {
+                ConverterError::InvalidInput(
+                    "CSAF needs a publisher namespace: the BOM has no supplier or manufacturer URL, pass --publisher-namespace".to_string(),
+                )
+            })?;
+        let text = |pointer: &str| {
+            bom.pointer(pointer)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let timestamp =
+            text("/metadata/timestamp").unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        let title = match text("/metadata/component/name") {
+            Some(name) => format!("VEX for {}", name),
+            None => "VEX document".to_string(),
+        };
+
+        let mut products: Vec<CsafFullProductName> = Vec::new();
+        let mut vulnerabilities: Vec<CsafVulnerability> = Vec::new();
+        for vuln in cdx_vulnerabilities(bom)? {
+            let product_ids: Vec<String> = vuln
+                .affects
+                .iter()
+                .flatten()
+                .map(|affects| {
+                    let product = product_from_ref(&affects.bom_ref, &components);
+                    let product_id = product.product_id.clone();
+                    if !products.iter().any(|p| p.product_id == product_id) {
+                        products.push(product);
+                    }
+                    product_id
+                })
+                .collect();
+            if product_ids.is_empty() {
+                warn!(
+                    "Skipping vulnerability {} without affected components",
+                    vuln.id
+                );
+                continue;
+            }
+
+            let index = match vulnerabilities
+                .iter()
+                .position(|v| csaf_vulnerability_id(v) == Some(vuln.id.as_str()))
+            {
+                Some(index) => index,
+                None => {
+                    vulnerabilities.push(new_vulnerability(&vuln));
+                    vulnerabilities.len() - 1
+                }
+            };
+            add_statement(&mut vulnerabilities[index], vuln, &product_ids);
+        }
+
+        let tracking_id =
+            text("/serialNumber").unwrap_or_else(|| format!("urn:uuid:{}", uuid::Uuid::new_v4()));
+        Ok(CsafDocument {
+            document: CsafDocumentMeta {
+                category: "csaf_vex".to_string(),
+                csaf_version: CSAF_VERSION.to_string(),
+                publisher: CsafPublisher {
+                    category: "vendor".to_string(),
+                    name: super::document_author(bom, options),
+                    namespace,
+                },
+                title,
+                tracking: CsafTracking {
+                    id: tracking_id,
+                    status: "final".to_string(),
+                    version: "1".to_string(),
+                    initial_release_date: timestamp.clone(),
+                    current_release_date: timestamp.clone(),
+                    revision_history: vec![CsafRevision {
+                        date: timestamp,
+                        number: "1".to_string(),
+                        summary: "Initial version".to_string(),
+                    }],
+                    generator: Some(serde_json::json!({
+                        "engine": {
+                            "name": "sbom-converter",
+                            "version": env!("CARGO_PKG_VERSION")
+                        }
+                    })),
+                },
+                notes: Vec::new(),
+            },
+            product_tree: Some(CsafProductTree {
+                full_product_names: products,
+                ..Default::default()
+            }),
+            vulnerabilities,
+        })
+    }
+
+    /// Converts the vulnerabilities to CycloneDX vulnerabilities, one per
+    /// group of products with the same analysis and scores.
+    ///
+    /// With `components`, products are matched against them by bom-ref,
+    /// purl or CPE, and products matching nothing are reported as
+    /// warnings. Without, the product purl (or id) is the `affects` ref.
+    pub fn to_cdx_vulnerabilities(
+        &self,
+        components: Option<&[VexComponent]>,
+        report: &mut ValidationReport,
+    ) -> Vec<CdxVulnerability> {
+        let products = self.products();
+        let groups: HashMap<&str, &[String]> = self
+            .product_tree
+            .iter()
+            .flat_map(|tree| &tree.product_groups)
+            .map(|g| (g.group_id.as_str(), g.product_ids.as_slice()))
+            .collect();
+        let covers = |product_ids: &[String], group_ids: &[String], product: &str| {
+            product_ids.iter().any(|p| p == product)
+                || group_ids
+                    .iter()
+                    .filter_map(|g| groups.get(g.as_str()))
+                    .any(|members| members.iter().any(|p| p == product))
+        };
+
+        let mut vulnerabilities = Vec::new();
+        for (i, csaf_vuln) in self.vulnerabilities.iter().enumerate() {
+            let location = format!("vulnerabilities[{}]", i);
+            let Some(id) = csaf_vulnerability_id(csaf_vuln) else {
+                report.add_issue(
+                    ValidationIssue::warning("Vulnerability without cve or ids")
+                        .with_location(&location),
+                );
+                continue;
+            };
+            let source = csaf_vuln
+                .references
+                .iter()
+                .find(|r| r.category.as_deref() != Some("self"))
+                .map(|r| CdxVulnSource {
+                    name: r.summary.clone(),
+                    url: Some(r.url.clone()),
+                })
+                .or_else(|| {
+                    csaf_vuln.cve.is_none().then(|| CdxVulnSource {
+                        name: csaf_vuln.ids[0].system_name.clone(),
+                        url: None,
+                    })
+                });
+            let description = csaf_vuln
+                .notes
+                .iter()
+                .find(|n| n.category == "description")
+                .map(|n| n.text.clone());
+
+            // Products with the same analysis and scores share an entry
+            let mut entries: Vec<(AnalysisKey, Vec<usize>, Vec<String>)> = Vec::new();
+            let status = csaf_vuln.product_status.clone().unwrap_or_default();
+            for (state, product_ids) in status.by_state() {
+                for product in product_ids {
+                    let mut key = AnalysisKey {
+                        state,
+                        ..Default::default()
+                    };
+                    if state == "not_affected" {
+                        key.justification = csaf_vuln
+                            .flags
+                            .iter()
+                            .find(|f| covers(&f.product_ids, &f.group_ids, product))
+                            .and_then(|f| cdx_justification(&f.label));
+                    }
+                    let mut details: Vec<String> = csaf_vuln
+                        .threats
+                        .iter()
+                        .filter(|t| t.category == "impact")
+                        .filter(|t| covers(&t.product_ids, &t.group_ids, product))
+                        .map(|t| t.details.clone())
+                        .collect();
+                    for remediation in csaf_vuln
+                        .remediations
+                        .iter()
+                        .filter(|r| covers(&r.product_ids, &r.group_ids, product))
+                    {
+                        let (response, boilerplate) =
+                            remediation_response(&remediation.category, &remediation.details);
+                        if let Some(response) = response
+                            && !key.response.contains(&response)
+                        {
+                            key.response.push(response);
+                        }
+                        if !boilerplate {
+                            details.push(remediation.details.clone());
+                        }
+                    }
+                    key.detail = (!details.is_empty()).then(|| details.join("\n"));
+                    let scores: Vec<usize> = csaf_vuln
+                        .scores
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, s)| s.products.iter().any(|p| p == product))
+                        .map(|(n, _)| n)
+                        .collect();
+
+                    let refs = product_refs(product, products.get(product.as_str()), components);
+                    if refs.is_empty() {
+                        report.add_issue(
+                            ValidationIssue::warning(format!(
+                                "No component matches product {} of {}",
+                                product, id
+                            ))
+                            .with_location(&location),
+                        );
+                        continue;
+                    }
+                    match entries
+                        .iter_mut()
+                        .find(|(k, s, _)| *k == key && *s == scores)
+                    {
+                        Some((_, _, entry_refs)) => {
+                            for r in refs {
+                                if !entry_refs.contains(&r) {
+                                    entry_refs.push(r);
+                                }
+                            }
+                        }
+                        None => entries.push((key, scores, refs)),
+                    }
+                }
+            }
+
+            for (key, scores, refs) in entries {
+                let ratings: Vec<CdxRating> = scores
+                    .iter()
+                    .flat_map(|&n| cdx_ratings(&csaf_vuln.scores[n]))
+                    .collect();
+                vulnerabilities.push(CdxVulnerability {
+                    id: id.to_string(),
+                    source: source.clone(),
+                    description: description.clone(),
+                    ratings: (!ratings.is_empty()).then_some(ratings),
+                    cwes: None,
+                    analysis: Some(CdxAnalysis {
+                        state: Some(key.state.to_string()),
+                        justification: key.justification.map(str::to_string),
+                        response: key.response,
+                        detail: key.detail,
+                        first_issued: Some(self.document.tracking.initial_release_date.clone()),
+                        last_updated: Some(self.document.tracking.current_release_date.clone()),
+                    }),
+                    affects: Some(
+                        refs.into_iter()
+                            .map(|bom_ref| CdxAffects {
+                                bom_ref,
+                                versions: None,
+                            })
+                            .collect(),
+                    ),
+                    properties: None,
+                    extra: HashMap::new(),
+                });
+            }
+        }
+        vulnerabilities
+    }
+
+    /// Converts the document to a standalone CycloneDX VEX document
+    pub fn to_cdx(&self, version: CdxVersion) -> Result<Value, ConverterError> {
+        let mut report = ValidationReport::new();
+        let vulnerabilities = self.to_cdx_vulnerabilities(None, &mut report);
+        for issue in &report.issues {
+            warn!("{}", issue.message);
+        }
+        let tracking = &self.document.tracking;
+        let serial_number = tracking
+            .id
+            .starts_with("urn:uuid:")
+            .then_some(tracking.id.as_str());
+        let mut document = super::cdx_vex_document(
+            &vulnerabilities,
+            serial_number,
+            Some(&tracking.current_release_date),
+            version,
+        )?;
+        document["metadata"]["authors"] =
+            serde_json::json!([{ "name": self.document.publisher.name }]);
+        Ok(document)
+    }
+
+    /// Every product of the product tree by id, with the purl and CPE
+    /// identifying it. Relationship products without their own helper are
+    /// identified by the component they refer to.
+    fn products(&self) -> HashMap<&str, CsafProduct> {
+        fn insert<'a>(product: &'a CsafFullProductName, out: &mut HashMap<&'a str, CsafProduct>) {
+            let helper = product
+                .product_identification_helper
+                .clone()
+                .unwrap_or_default();
+            out.insert(
+                &product.product_id,
+                CsafProduct {
+                    product_id: product.product_id.clone(),
+                    purl: helper.purl,
+                    cpe: helper.cpe,
+                },
+            );
+        }
+        fn walk<'a>(branches: &'a [CsafBranch], out: &mut HashMap<&'a str, CsafProduct>) {
+            for branch in branches {
+                if let Some(product) = &branch.product {
+                    insert(product, out);
+                }
+                walk(&branch.branches, out);
+            }
+        }
+
+        let mut products = HashMap::new();
+        let Some(tree) = &self.product_tree else {
+            return products;
+        };
+        walk(&tree.branches, &mut products);
+        for product in &tree.full_product_names {
+            insert(product, &mut products);
+        }
+        for relationship in &tree.relationships {
+            let product = &relationship.full_product_name;
+            let (purl, cpe) = match &product.product_identification_helper {
+                Some(helper) => (helper.purl.clone(), helper.cpe.clone()),
+                None => products
+                    .get(relationship.product_reference.as_str())
+                    .map(|p| (p.purl.clone(), p.cpe.clone()))
+                    .unwrap_or_default(),
+            };
+            products.insert(
+                &product.product_id,
+                CsafProduct {
+                    product_id: product.product_id.clone(),
+                    purl,
+                    cpe,
+                },
+            );
+        }
+        products
+    }
+}
+
+/// The parts of a CycloneDX analysis a product's CSAF statements map to
+#[derive(Debug, Default, PartialEq)]
+struct AnalysisKey {
+    state: &'static str,
+    justification: Option<&'static str>,
+    response: Vec<String>,
+    detail: Option<String>,
+}
+
+/// The CVE, or else the first other id, of a CSAF vulnerability
+fn csaf_vulnerability_id(vuln: &CsafVulnerability) -> Option<&str> {
+    vuln.cve
+        .as_deref()
+        .or_else(|| vuln.ids.first().map(|id| id.text.as_str()))
+}
+
+/// The product for an `affects` ref
+fn product_from_ref(
+    reference: &str,
+    components: &HashMap<String, VexComponent>,
+) -> CsafFullProductName {
+    match components.get(local_ref(reference).as_ref()) {
+        Some(component) => CsafFullProductName {
+            name: component.display_name(),
+            product_id: component.bom_ref.clone(),
+            product_identification_helper: (component.purl.is_some() || component.cpe.is_some())
+                .then(|| CsafProductHelper {
+                    purl: component.purl.clone(),
+                    cpe: component.cpe.clone(),
+                }),
+        },
+        None => CsafFullProductName {
+            name: reference.to_string(),
+            product_id: reference.to_string(),
+            product_identification_helper: None,
+        },
+    }
+}
+
+/// The bom-refs a CSAF product resolves to
+fn product_refs(
+    product_id: &str,
+    product: Option<&CsafProduct>,
+    components: Option<&[VexComponent]>,
+) -> Vec<String> {
+    let purl = product.and_then(|p| p.purl.as_deref());
+    let cpe = product.and_then(|p| p.cpe.as_deref());
+    match components {
+        Some(components) => components
+            .iter()
+            .filter(|c| c.matches(Some(product_id), purl, cpe))
+            .map(|c| c.bom_ref.clone())
+            .collect(),
+        None => vec![
+            purl.map(str::to_string)
+                .or_else(|| product.map(|p| p.product_id.clone()))
+                .unwrap_or_else(|| product_id.to_string()),
+        ],
+    }
+}
+
+/// A CSAF vulnerability for the id, source and description of `vuln`
+fn new_vulnerability(vuln: &CdxVulnerability) -> CsafVulnerability {
+    let mut csaf_vuln = CsafVulnerability::default();
+    if vuln.id.starts_with("CVE-") {
+        csaf_vuln.cve = Some(vuln.id.clone());
+    } else {
+        csaf_vuln.ids.push(CsafId {
+            system_name: vuln
+                .source
+                .as_ref()
+                .map_or_else(|| "unknown".to_string(), |s| s.name.clone()),
+            text: vuln.id.clone(),
+        });
+    }
+    if let Some(source) = &vuln.source
+        && let Some(url) = &source.url
+    {
+        csaf_vuln.references.push(CsafReference {
+            category: Some("external".to_string()),
+            summary: source.name.clone(),
+            url: url.clone(),
+        });
+    }
+    // The csaf_vex profile requires notes
+    csaf_vuln.notes.push(match &vuln.description {
+        Some(description) => CsafNote {
+            category: "description".to_string(),
+            text: description.clone(),
+            title: Some("Vulnerability description".to_string()),
+        },
+        None => CsafNote {
+            category: "general".to_string(),
+            text: format!("VEX statements for {}", vuln.id),
+            title: None,
+        },
+    });
+    csaf_vuln
+}
+
+/// Adds the analysis and ratings of `vuln` for `product_ids` to a CSAF
+/// vulnerability
+fn add_statement(
+    csaf_vuln: &mut CsafVulnerability,
+    vuln: CdxVulnerability,
+    product_ids: &[String],
+) {
+    let analysis = vuln.analysis.unwrap_or_default();
+    let state = analysis.state.as_deref().unwrap_or("in_triage");
+    let status = csaf_vuln
+        .product_status
+        .get_or_insert_with(Default::default);
+    let list = match state {
+        "exploitable" => &mut status.known_affected,
+        "not_affected" | "false_positive" => &mut status.known_not_affected,
+        "resolved" | "resolved_with_pedigree" => &mut status.fixed,
+        _ => &mut status.under_investigation,
+    };
+    extend_unique(list, product_ids);
+
+    if matches!(state, "not_affected" | "false_positive") {
+        let label = analysis
+            .justification
+            .as_deref()
+            .and_then(vex_justification);
+        match label {
+            Some(label) => match csaf_vuln.flags.iter_mut().find(|f| f.label == label) {
+                Some(flag) => extend_unique(&mut flag.product_ids, product_ids),
+                None => csaf_vuln.flags.push(CsafFlag {
+                    label: label.to_string(),
+                    product_ids: product_ids.to_vec(),
+                    group_ids: Vec::new(),
+                }),
+            },
+            // Not affected products need a flag or an impact statement
+            None if analysis.detail.is_none() => push_threat(
+                csaf_vuln,
+                format!("CycloneDX analysis state: {}", state),
+                product_ids,
+            ),
+            None => {}
+        }
+    }
+    if let Some(detail) = analysis.detail {
+        push_threat(csaf_vuln, detail, product_ids);
+    }
+
+    let mut remediations: Vec<(&str, &str)> = analysis
+        .response
+        .iter()
+        .filter_map(|response| {
+            RESPONSE_REMEDIATIONS
+                .iter()
+                .find(|(r, _, _)| r == response)
+                .map(|(_, category, details)| (*category, *details))
+        })
+        .collect();
+    // Affected products need a remediation
+    if remediations.is_empty() && state == "exploitable" {
+        remediations.push(NO_REMEDIATION);
+    }
+    for (category, details) in remediations {
+        match csaf_vuln
+            .remediations
+            .iter_mut()
+            .find(|r| r.category == category && r.details == details)
+        {
+            Some(remediation) => extend_unique(&mut remediation.product_ids, product_ids),
+            None => csaf_vuln.remediations.push(CsafRemediation {
+                category: category.to_string(),
+                details: details.to_string(),
+                product_ids: product_ids.to_vec(),
+                group_ids: Vec::new(),
+            }),
+        }
+    }
+
+    for rating in vuln.ratings.iter().flatten() {
+        match csaf_score(rating, product_ids) {
+            Some(score) => csaf_vuln.scores.push(score),
+            None => warn!(
+                "Skipping {} rating of {}: CSAF only has CVSS v2 and v3 scores with a vector",
+                rating.method.as_deref().unwrap_or("unknown"),
+                vuln.id
+            ),
+        }
+    }
+}
+
+fn push_threat(csaf_vuln: &mut CsafVulnerability, details: String, product_ids: &[String]) {
+    match csaf_vuln
+        .threats
+        .iter_mut()
+        .find(|t| t.category == "impact" && t.details == details)
+    {
+        Some(threat) => extend_unique(&mut threat.product_ids, product_ids),
+        None => csaf_vuln.threats.push(CsafThreat {
+            category: "impact".to_string(),
+            details,
+            product_ids: product_ids.to_vec(),
+            group_ids: Vec::new(),
+        }),
+    }
+}
+
+fn extend_unique(list: &mut Vec<String>, items: &[String]) {
+    for item in items {
+        if !list.contains(item) {
+            list.push(item.clone());
+        }
+    }
+}
+
+/// The CycloneDX response for a remediation, and whether its details are
+/// boilerplate written for that response
+fn remediation_response(category: &str, details: &str) -> (Option<String>, bool) {
+    if let Some((response, _, _)) = RESPONSE_REMEDIATIONS
+        .iter()
+        .find(|(_, c, d)| *c == category && *d == details)
+    {
+        return (Some(response.to_string()), true);
+    }
+    if (category, details) == NO_REMEDIATION {
+        return (None, true);
+    }
+    let response = match category {
+        "vendor_fix" | "optional_patch" => Some("update"),
+        "workaround" | "mitigation" => Some("workaround_available"),
+        "no_fix_planned" => Some("will_not_fix"),
+        "none_available" => Some("can_not_fix"),
+        _ => None,
+    };
+    (response.map(str::to_string), false)
+}
+
+/// Maps a CycloneDX CVSS rating to a CSAF score
+fn csaf_score(rating: &CdxRating, product_ids: &[String]) -> Option<CsafScore> {
+    let score = rating.score?;
+    let vector = rating.vector.as_deref()?;
+    let severity = rating
+        .severity
+        .as_deref()
+        .filter(|s| *s != "unknown" && *s != "info")
+        .map(str::to_string)
+        .unwrap_or_else(|| cvss_severity(score).to_string())
+        .to_uppercase();
+    let cvss = |version: &str, vector_string: String, base_severity: Option<String>| CsafCvss {
+        version: version.to_string(),
+        vector_string,
+        base_score: score,
+        base_severity,
+        extra: HashMap::new(),
+    };
+    let (cvss_v2, cvss_v3) = match rating.method.as_deref()? {
+        "CVSSv2" => (Some(cvss("2.0", vector.to_string(), None)), None),
+        method @ ("CVSSv3" | "CVSSv31") => {
+            let version = if method == "CVSSv3" { "3.0" } else { "3.1" };
+            let vector_string = if vector.starts_with("CVSS:") {
+                vector.to_string()
+            } else {
+                format!("CVSS:{}/{}", version, vector)
+            };
+            (None, Some(cvss(version, vector_string, Some(severity))))
+        }
+        _ => return None,
+    };
+    Some(CsafScore {
+        products: product_ids.to_vec(),
+        cvss_v2,
+        cvss_v3,
+    })
+}
+
+/// The CVSS v3 qualitative severity of a score
+fn cvss_severity(score: f64) -> &'static str {
+    match score {
+        0.0 => "none",
+        s if s < 4.0 => "low",
+        s if s < 7.0 => "medium",
+        s if s < 9.0 => "high",
+        _ => "critical",
+    }
+}
+
+/// Maps a CSAF score to CycloneDX ratings
+fn cdx_ratings(score: &CsafScore) -> Vec<CdxRating> {
+    let mut ratings = Vec::new();
+    if let Some(cvss) = &score.cvss_v3 {
+        ratings.push(CdxRating {
+            score: Some(cvss.base_score),
+            severity: cvss.base_severity.as_deref().map(str::to_lowercase),
+            method: Some(
+                if cvss.version == "3.0" {
+                    "CVSSv3"
+                } else {
+                    "CVSSv31"
+                }
+                .to_string(),
+            ),
+            vector: Some(cvss.vector_string.clone()),
+            ..Default::default()
+        });
+    }
+    if let Some(cvss) = &score.cvss_v2 {
+        ratings.push(CdxRating {
+            score: Some(cvss.base_score),
+            method: Some("CVSSv2".to_string()),
+            vector: Some(cvss.vector_string.clone()),
+            ..Default::default()
+        });
+    }
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_remediation_response() {
+        assert_eq!(
+            remediation_response("vendor_fix", "Roll back to an unaffected version"),
+            (Some("rollback".to_string()), true)
+        );
+        assert_eq!(
+            remediation_response("vendor_fix", "Upgrade to 2.17.1"),
+            (Some("update".to_string()), false)
+        );
+        assert_eq!(
+            remediation_response(NO_REMEDIATION.0, NO_REMEDIATION.1),
+            (None, true)
+        );
+    }
+
+    #[test]
+    fn test_csaf_score() {
+        let rating = CdxRating {
+            score: Some(7.5),
+            method: Some("CVSSv31".to_string()),
+            vector: Some("AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N".to_string()),
+            ..Default::default()
+        };
+        let score = csaf_score(&rating, &["a".to_string()]).unwrap();
+        let cvss = score.cvss_v3.as_ref().unwrap();
+        assert_eq!(cvss.version, "3.1");
+        assert_eq!(
+            cvss.vector_string,
+            "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N"
+        );
+        assert_eq!(cvss.base_severity.as_deref(), Some("HIGH"));
+        assert_eq!(cdx_ratings(&score)[0].severity.as_deref(), Some("high"));
+
+        let rating = CdxRating {
+            score: Some(7.5),
+            method: Some("OWASP".to_string()),
+            vector: Some("SL:1".to_string()),
+            ..Default::default()
+        };
+        assert!(csaf_score(&rating, &["a".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_relationship_products_use_referenced_helper() {
+        let document = CsafDocument::parse(json!({
+            "document": {
+                "category": "csaf_vex",
+                "csaf_version": "2.0",
+                "publisher": { "category": "vendor", "name": "ACME", "namespace": "https://acme.example" },
+                "title": "Test",
+                "tracking": {
+                    "id": "ACME-2024-1",
+                    "status": "final",
+                    "version": "1",
+                    "initial_release_date": "2024-01-01T00:00:00Z",
+                    "current_release_date": "2024-01-02T00:00:00Z",
+                    "revision_history": []
+                }
+            },
+            "product_tree": {
+                "branches": [{
+                    "category": "vendor",
+                    "name": "ACME",
+                    "branches": [{
+                        "category": "product_version",
+                        "name": "1.0",
+                        "product": {
+                            "name": "libfoo 1.0",
+                            "product_id": "CSAFPID-1",
+                            "product_identification_helper": { "purl": "pkg:generic/libfoo@1.0" }
+                        }
+                    }]
+                }],
+                "relationships": [{
+                    "category": "default_component_of",
+                    "full_product_name": { "name": "libfoo in app", "product_id": "CSAFPID-2" },
+                    "product_reference": "CSAFPID-1",
+                    "relates_to_product_reference": "CSAFPID-3"
+                }]
+            },
+            "vulnerabilities": [{
+                "cve": "CVE-2024-1",
+                "product_status": { "known_not_affected": ["CSAFPID-2"] },
+                "flags": [{ "label": "vulnerable_code_not_present", "product_ids": ["CSAFPID-2"] }]
+            }]
+        }))
+        .unwrap();
+
+        let mut report = ValidationReport::new();
+        let vulns = document.to_cdx_vulnerabilities(None, &mut report);
+        assert_eq!(vulns.len(), 1);
+        let analysis = vulns[0].analysis.as_ref().unwrap();
+        assert_eq!(analysis.state.as_deref(), Some("not_affected"));
+        assert_eq!(analysis.justification.as_deref(), Some("code_not_present"));
+        assert_eq!(
+            vulns[0].affects.as_ref().unwrap()[0].bom_ref,
+            "pkg:generic/libfoo@1.0"
+        );
+    }
+}
@@ -0,0 +1,832 @@
+//! Standalone VEX (Vulnerability Exploitability eXchange) documents.
+//!
+//! Every VEX format maps to and from the CycloneDX vulnerability models
+//! (`CdxVulnerability`, `CdxAnalysis`, `CdxAffects`), so documents convert
+//! through CycloneDX and are applied onto CycloneDX SBOMs the same way.
+
+pub mod csaf;
+pub mod openvex;
+pub mod spdx;
+
+use crate::bom_link::{BomLink, local_ref};
+use crate::cdx_version::CdxVersion;
+use crate::errors::ConverterError;
+use crate::formats::read_json_value;
+use crate::id_map::IdMap;
+use crate::models_cdx::{self as cdx, CdxVulnerability};
+use crate::validation::{ValidationIssue, ValidationReport};
+use crate::version_detection::{SbomFormat, detect_format};
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// A supported VEX document format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VexFormat {
+    /// A CycloneDX BOM carrying `vulnerabilities`
+    CycloneDx,
+    OpenVex,
+    /// A CSAF 2.0 document, normally of the `csaf_vex` profile
+    Csaf,
+}
+
+impl VexFormat {
+    /// Detects the format of a parsed VEX document
+    pub fn detect(value: &Value) -> Option<Self> {
+        if value.get("bomFormat").and_then(|v| v.as_str()) == Some("CycloneDX") {
+            return Some(VexFormat::CycloneDx);
+        }
+        if value.pointer("/document/csaf_version").is_some() {
+            return Some(VexFormat::Csaf);
+        }
+        let context = value.get("@context").and_then(|v| v.as_str())?;
+        context
+            .starts_with(openvex::OPENVEX_CONTEXT_PREFIX)
+            .then_some(VexFormat::OpenVex)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VexFormat::CycloneDx => "CycloneDX",
+            VexFormat::OpenVex => "OpenVEX",
+            VexFormat::Csaf => "CSAF",
+        }
+    }
+}
+
+/// Options for writing VEX documents
+#[derive(Debug, Clone, Default)]
+pub struct VexOptions {
+    /// Author of OpenVEX output (default: the BOM authors)
+    pub author: Option<String>,
+    /// Publisher namespace of CSAF output (default: the BOM supplier or
+    /// manufacturer URL)
+    pub namespace: Option<String>,
+    /// Version of CycloneDX output
+    pub cdx_version: CdxVersion,
+}
+
+/// What VEX products are matched against in a CycloneDX SBOM
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VexComponent {
+    pub bom_ref: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub purl: Option<String>,
+    pub cpe: Option<String>,
+}
+
+impl VexComponent {
+    /// Whether a VEX product identified by `id`, `purl` and/or `cpe` is
+    /// this component
+    pub fn matches(&self, id: Option<&str>, purl: Option<&str>, cpe: Option<&str>) -> bool {
+        let by_id = id == Some(self.bom_ref.as_str());
+        let by_purl = purl
+            .zip(self.purl.as_deref())
+            .is_some_and(|(product, purl)| purl_matches(product, purl));
+        let by_cpe = cpe.is_some() && cpe == self.cpe.as_deref();
+        by_id || by_purl || by_cpe
+    }
+
+    /// A human-readable product name
+    pub fn display_name(&self) -> String {
+        match (&self.name, &self.version) {
+            (Some(name), Some(version)) => format!("{} {}", name, version),
+            (Some(name), None) => name.clone(),
+            _ => self.bom_ref.clone(),
+        }
+    }
+}
+
+/// The subject and all (nested) components of a CycloneDX BOM that have
+/// a bom-ref
+pub fn cdx_components(bom: &Value) -> Vec<VexComponent> {
+    fn collect(component: &Value, out: &mut Vec<VexComponent>) {
+        let text = |key: &str| component.get(key).and_then(|v| v.as_str());
+        if let Some(bom_ref) = text("bom-ref") {
+            out.push(VexComponent {
+                bom_ref: bom_ref.to_string(),
+                name: text("name").map(str::to_string),
+                version: text("version").map(str::to_string),
+                purl: text("purl").map(str::to_string),
+                cpe: text("cpe").map(str::to_string),
+            });
+        }
+        for child in component
+            .get("components")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+        {
+            collect(child, out);
+        }
+    }
+
+    let mut components = Vec::new();
+    if let Some(subject) = bom.get("metadata").and_then(|m| m.get("component")) {
+        collect(subject, &mut components);
+    }
+    collect(bom, &mut components);
+    components
+}
+
+/// Parses the `vulnerabilities` of a CycloneDX BOM
+pub fn cdx_vulnerabilities(bom: &Value) -> Result<Vec<CdxVulnerability>, ConverterError> {
+    match bom.get("vulnerabilities") {
+        Some(vulnerabilities) => serde_json::from_value(vulnerabilities.clone()).map_err(|e| {
+            ConverterError::ParseError(format!("Invalid CycloneDX vulnerabilities: {}", e))
+        }),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Whether the purl of a VEX product identifies the component `purl`.
+/// Qualifiers and subpath are ignored; a product without a version
+/// matches every version.
+pub fn purl_matches(product: &str, purl: &str) -> bool {
+    fn base(purl: &str) -> &str {
+        purl.split(['?', '#']).next().unwrap_or(purl)
+    }
+    fn without_version(purl: &str) -> &str {
+        let name_start = purl.rfind('/').map_or(0, |i| i + 1);
+        match purl[name_start..].find('@') {
+            Some(at) => &purl[..name_start + at],
+            None => purl,
+        }
+    }
+
+    let (product, purl) = (base(product), base(purl));
+    if product == purl {
+        return true;
+    }
+    let has_version = without_version(product) != product;
+    !has_version && without_version(purl) == product
+}
+
+/// Builds a standalone CycloneDX VEX document
+pub fn cdx_vex_document(
+    vulnerabilities: &[CdxVulnerability],
+    serial_number: Option<&str>,
+    timestamp: Option<&str>,
+    version: CdxVersion,
+) -> Result<Value, ConverterError> {
+    let metadata = cdx::CdxMetadata {
+        timestamp: timestamp
+            .map(str::to_string)
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+        tools: Some(cdx::CdxTools {
+            components: vec![cdx::CdxToolComponent {
+                component_type: "application".to_string(),
+                name: "sbom-converter".to_string(),
+                bom_ref: format!("sbom-converter-{}", env!("CARGO_PKG_VERSION")),
+            }],
+        }),
+    };
+    let serial_number = serial_number
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("urn:uuid:{}", uuid::Uuid::new_v4()));
+    Ok(serde_json::json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": version.as_str(),
+        "serialNumber": serial_number,
+        "version": 1,
+        "metadata": serde_json::to_value(metadata)?,
+        "vulnerabilities": serde_json::to_value(vulnerabilities)?,
+    }))
+}
+
+/// Converts the VEX document at `input` to `to`
+pub fn convert_file(
+    input: &Path,
+    output: &Path,
+    to: VexFormat,
+    options: &VexOptions,
+) -> Result<(), ConverterError> {
+    let value = read_json_value(input)?;
+    let from = VexFormat::detect(&value).ok_or_else(|| {
+        ConverterError::UnsupportedFormat(format!(
+            "Could not detect the VEX format of {}",
+            input.display()
+        ))
+    })?;
+
+    if from == to {
+        return Err(ConverterError::InvalidInput(format!(
+            "{} is already {}",
+            input.display(),
+            from.as_str()
+        )));
+    }
+
+    // Every format converts through CycloneDX
+    let bom = match from {
+        VexFormat::CycloneDx => value,
+        VexFormat::OpenVex => {
+            openvex::OpenVexDocument::parse(value)?.to_cdx(options.cdx_version)?
+        }
+        VexFormat::Csaf => csaf::CsafDocument::parse(value)?.to_cdx(options.cdx_version)?,
+    };
+    let converted = match to {
+        VexFormat::CycloneDx => bom,
+        VexFormat::OpenVex => {
+            serde_json::to_value(openvex::OpenVexDocument::from_cdx(&bom, options)?)?
+        }
+        VexFormat::Csaf => serde_json::to_value(csaf::CsafDocument::from_cdx(&bom, options)?)?,
+    };
+    write_json(output, &converted)
+}
+
+/// Applies VEX documents onto the CycloneDX SBOM at `sbom` and writes the
+/// result to `output`.
+///
+/// Documents may be CycloneDX (such as the `--split-vex` output), SPDX 3
+/// security elements, OpenVEX or CSAF. Their statements are resolved to
+/// SBOM components; where statements about a vulnerability in a component
+/// disagree, the newest wins, and the later document on a tie. The report
+/// lists conflicting statements and statements about components that are
+/// not in the SBOM. SPDX IDs in SPDX 3 documents become bom-refs through
+/// `ids`.
+pub fn apply_files(
+    sbom: &Path,
+    vex: &[PathBuf],
+    output: &Path,
+    ids: &IdMap,
+) -> Result<ValidationReport, ConverterError> {
+    let mut bom = read_json_value(sbom)?;
+    if VexFormat::detect(&bom) != Some(VexFormat::CycloneDx) {
+        return Err(ConverterError::UnsupportedFormat(format!(
+            "VEX statements can only be applied to CycloneDX SBOMs: {}",
+            sbom.display()
+        )));
+    }
+
+    let mut report = ValidationReport::new().with_file(sbom);
+    let target = VexTarget::of_bom(&bom);
+    let mut statements = Vec::new();
+    for path in vex {
+        statements.extend(read_statements(path, &target, ids, &mut report)?);
+    }
+    let statements = resolve_conflicts(statements, &bom, &mut report);
+
+    apply_vulnerabilities(&mut bom, statements)?;
+    write_json(output, &bom)?;
+    Ok(report)
+}
+
+/// The BOM VEX statements refer to, or are applied to
+struct VexTarget {
+    serial_number: Option<String>,
+    components: Vec<VexComponent>,
+}
+
+impl VexTarget {
+    fn of_bom(bom: &Value) -> Self {
+        VexTarget {
+            serial_number: bom
+                .get("serialNumber")
+                .and_then(|s| s.as_str())
+                .map(str::to_string),
+            components: cdx_components(bom),
+        }
+    }
+
+    /// Resolves an `affects` ref of a statement in `document` to the
+    /// bom-refs of this BOM: directly, through a BOM-Link, or through the
+    /// purl and CPE of the component the document itself describes. `Err`
+    /// explains why it does not resolve.
+    fn resolve(&self, reference: &str, document: &VexTarget) -> Result<Vec<String>, String> {
+        let local = match BomLink::parse(reference) {
+            Some(BomLink {
+                serial_number,
+                bom_ref: Some(local),
+                ..
+            }) if Some(serial_number.as_str()) == self.serial_number.as_deref() => {
+                return if self.components.iter().any(|c| c.bom_ref == local) {
+                    Ok(vec![local])
+                } else {
+                    Err(format!("{} is not a component of the SBOM", reference))
+                };
+            }
+            Some(link)
+                if Some(link.serial_number.as_str()) != document.serial_number.as_deref() =>
+            {
+                return Err(format!("{} refers to another BOM", reference));
+            }
+            _ => local_ref(reference),
+        };
+        let local = local.as_ref();
+
+        if self.components.iter().any(|c| c.bom_ref == local) {
+            return Ok(vec![local.to_string()]);
+        }
+        let (purl, cpe) = match document.components.iter().find(|c| c.bom_ref == local) {
+            Some(component) => (component.purl.as_deref(), component.cpe.as_deref()),
+            // Refs converted from OpenVEX products are purls
+            None => (Some(local).filter(|r| r.starts_with("pkg:")), None),
+        };
+        let matched: Vec<String> = self
+            .components
+            .iter()
+            .filter(|c| c.matches(None, purl, cpe))
+            .map(|c| c.bom_ref.clone())
+            .collect();
+        if matched.is_empty() {
+            Err(format!("{} matches no component of the SBOM", reference))
+        } else {
+            Ok(matched)
+        }
+    }
+}
+
+/// A statement about one vulnerability in one SBOM component
+struct Statement {
+    /// The statement, affecting only the component
+    vulnerability: CdxVulnerability,
+    timestamp: Option<DateTime<FixedOffset>>,
+    /// The document it was read from
+    source: String,
+}
+
+impl Statement {
+    /// The state and justification of the analysis
+    fn verdict(&self) -> String {
+        let analysis = self.vulnerability.analysis.as_ref();
+        let state = analysis
+            .and_then(|a| a.state.as_deref())
+            .unwrap_or("no analysis");
+        match analysis.and_then(|a| a.justification.as_deref()) {
+            Some(justification) => format!("{} ({})", state, justification),
+            None => state.to_string(),
+        }
+    }
+
+    fn affected_ref(&self) -> &str {
+        self.vulnerability
+            .affects
+            .as_ref()
+            .and_then(|a| a.first())
+            .map_or("", |a| a.bom_ref.as_str())
+    }
+}
+
+/// Reads the VEX document at `path` into statements about the components
+/// of `target`
+fn read_statements(
+    path: &Path,
+    target: &VexTarget,
+    ids: &IdMap,
+    report: &mut ValidationReport,
+) -> Result<Vec<Statement>, ConverterError> {
+    let value = read_json_value(path)?;
+    let source = path.display().to_string();
+    let mut document_report = ValidationReport::new();
+    let text = |pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+
+    // OpenVEX and CSAF products are matched by identity, CycloneDX and SPDX
+    // refs need resolving
+    let (vulnerabilities, timestamp, document) = match VexFormat::detect(&value) {
+        Some(VexFormat::OpenVex) => {
+            let document = openvex::OpenVexDocument::parse(value)?;
+            let vulns =
+                document.to_cdx_vulnerabilities(Some(&target.components), &mut document_report);
+            (vulns, Some(document.timestamp), None)
+        }
+        Some(VexFormat::Csaf) => {
+            let document = csaf::CsafDocument::parse(value)?;
+            let vulns =
+                document.to_cdx_vulnerabilities(Some(&target.components), &mut document_report);
+            (
+                vulns,
+                Some(document.document.tracking.current_release_date),
+                None,
+            )
+        }
+        Some(VexFormat::CycloneDx) => (
+            cdx_vulnerabilities(&value)?,
+            text("/metadata/timestamp"),
+            Some(VexTarget::of_bom(&value)),
+        ),
+        None if is_spdx(&value) => {
+            let spdx = spdx::SpdxVex::read(&value, ids)?;
+            let document = VexTarget {
+                serial_number: Some(spdx.serial_number),
+                components: spdx.components,
+            };
+            (spdx.vulnerabilities, spdx.timestamp, Some(document))
+        }
+        None => {
+            return Err(ConverterError::UnsupportedFormat(format!(
+                "Could not detect the VEX format of {}",
+                path.display()
+            )));
+        }
+    };
+
+    let mut statements = Vec::new();
+    for (i, vuln) in vulnerabilities.into_iter().enumerate() {
+        let mut refs: Vec<String> = Vec::new();
+        for affects in vuln.affects.iter().flatten() {
+            let resolved = match &document {
+                Some(document) => target.resolve(&affects.bom_ref, document),
+                None => Ok(vec![affects.bom_ref.clone()]),
+            };
+            match resolved {
+                Ok(resolved) => refs.extend(resolved),
+                Err(reason) => document_report.add_issue(
+                    ValidationIssue::warning(format!(
+                        "Dangling statement for {}: {}",
+                        vuln.id, reason
+                    ))
+                    .with_location(format!("vulnerabilities[{}]", i)),
+                ),
+            }
+        }
+        // Refs that resolve to the same component need not be adjacent
+        let mut seen = HashSet::new();
+        refs.retain(|bom_ref| seen.insert(bom_ref.clone()));
+
+        let analysis = vuln.analysis.as_ref();
+        let timestamp = analysis
+            .and_then(|a| a.last_updated.as_deref().or(a.first_issued.as_deref()))
+            .or(timestamp.as_deref())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+        for bom_ref in refs {
+            statements.push(Statement {
+                vulnerability: CdxVulnerability {
+                    affects: Some(vec![cdx::CdxAffects {
+                        bom_ref,
+                        versions: None,
+                    }]),
+                    ..vuln.clone()
+                },
+                timestamp,
+                source: source.clone(),
+            });
+        }
+    }
+
+    for mut issue in document_report.issues {
+        issue.location = Some(match issue.location {
+            Some(location) => format!("{}: {}", source, location),
+            None => source.clone(),
+        });
+        report.add_issue(issue);
+    }
+    Ok(statements)
+}
+
+/// Picks the statement that applies to each vulnerability in each
+/// component, and groups the picked statements with the same analysis.
+///
+/// The newest statement wins; statements without a timestamp are older
+/// than any other, and later documents win ties. Statements older than
+/// the analysis the SBOM already has are dropped.
+fn resolve_conflicts(
+    statements: Vec<Statement>,
+    bom: &Value,
+    report: &mut ValidationReport,
+) -> Vec<CdxVulnerability> {
+    let mut groups: Vec<Vec<Statement>> = Vec::new();
+    let mut positions: HashMap<(String, String), usize> = HashMap::new();
+    for statement in statements {
+        let key = (
+            statement.vulnerability.id.clone(),
+            statement.affected_ref().to_string(),
+        );
+        match positions.get(&key) {
+            Some(&position) => groups[position].push(statement),
+            None => {
+                positions.insert(key, groups.len());
+                groups.push(vec![statement]);
+            }
+        }
+    }
+
+    let mut picked: Vec<CdxVulnerability> = Vec::new();
+    for mut group in groups {
+        let newest = group
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, s)| (s.timestamp, *i))
+            .map_or(0, |(i, _)| i);
+        let winner = group.swap_remove(newest);
+        let (id, bom_ref) = (
+            winner.vulnerability.id.clone(),
+            winner.affected_ref().to_string(),
+        );
+
+        let conflicting: Vec<String> = group
+            .iter()
+            .filter(|s| s.verdict() != winner.verdict())
+            .map(|s| format!("{} in {}", s.verdict(), s.source))
+            .collect();
+        if !conflicting.is_empty() {
+            report.add_issue(
+                ValidationIssue::warning(format!(
+                    "Conflicting statements for {}: {} in {} overrides {}",
+                    id,
+                    winner.verdict(),
+                    winner.source,
+                    conflicting.join(", ")
+                ))
+                .with_location(&bom_ref),
+            );
+        }
+
+        if let (Some(timestamp), Some(current)) =
+            (winner.timestamp, sbom_analysis_time(bom, &id, &bom_ref))
+            && current > timestamp
+        {
+            report.add_issue(
+                ValidationIssue::info(format!(
+                    "Kept the SBOM analysis of {}, which is newer than the statement in {}",
+                    id, winner.source
+                ))
+                .with_location(&bom_ref),
+            );
+            continue;
+        }
+
+        let analysis = serde_json::to_value(&winner.vulnerability.analysis).ok();
+        match picked
+            .iter_mut()
+            .find(|v| v.id == id && serde_json::to_value(&v.analysis).ok() == analysis)
+        {
+            Some(statement) => statement
+                .affects
+                .get_or_insert_with(Vec::new)
+                .extend(winner.vulnerability.affects.unwrap_or_default()),
+            None => picked.push(winner.vulnerability),
+        }
+    }
+    picked
+}
+
+/// Whether a document is SPDX, including SPDX 3 JSON-LD with only
+/// security elements and no `SpdxDocument`
+fn is_spdx(value: &Value) -> bool {
+    matches!(detect_format(value), SbomFormat::Spdx(_))
+        || value
+            .get("@context")
+            .and_then(|c| c.as_str())
+            .is_some_and(|c| c.starts_with("https://spdx.org/rdf/"))
+}
+
+/// When the SBOM's analysis of vulnerability `id` in `bom_ref` was last
+/// updated
+fn sbom_analysis_time(bom: &Value, id: &str, bom_ref: &str) -> Option<DateTime<FixedOffset>> {
+    bom.get("vulnerabilities")?
+        .as_array()?
+        .iter()
+        .filter(|v| v.get("id").and_then(|i| i.as_str()) == Some(id))
+        .find(|v| affects_refs(v).iter().any(|r| local_ref(r) == bom_ref))
+        .and_then(|v| v.get("analysis"))
+        .and_then(|a| a.get("lastUpdated").or_else(|| a.get("firstIssued")))
+        .and_then(|t| t.as_str())
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+}
+
+/// Applies VEX statements, whose `affects` are bom-refs of `bom`, onto the
+/// BOM's vulnerabilities.
+///
+/// A statement replaces the analysis of the entries for the same
+/// vulnerability it fully covers. Entries it covers in part are split, so
+/// the other affected components keep their analysis; components without
+/// an entry get a new one.
+pub fn apply_vulnerabilities(
+    bom: &mut Value,
+    statements: Vec<CdxVulnerability>,
+) -> Result<(), ConverterError> {
+    let mut vulnerabilities: Vec<Value> = bom
+        .get_mut("vulnerabilities")
+        .and_then(|v| v.as_array_mut())
+        .map(std::mem::take)
+        .unwrap_or_default();
+
+    for statement in statements {
+        let analysis = serde_json::to_value(&statement.analysis)?;
+        let mut remaining: Vec<String> = statement
+            .affects
+            .iter()
+            .flatten()
+            .map(|a| a.bom_ref.clone())
+            .collect();
+        if remaining.is_empty() {
+            continue;
+        }
+
+        let mut additions = Vec::new();
+        let mut template: Option<Value> = None;
+        for vuln in vulnerabilities
+            .iter_mut()
+            .filter(|v| v.get("id").and_then(|id| id.as_str()) == Some(&statement.id))
+        {
+            template.get_or_insert_with(|| vuln.clone());
+            let refs: Vec<String> = affects_refs(vuln);
+            let hit: HashSet<String> = refs
+                .iter()
+                .filter(|r| remaining.iter().any(|m| *m == local_ref(r)))
+                .cloned()
+                .collect();
+            if hit.is_empty() {
+                continue;
+            }
+            remaining.retain(|m| !refs.iter().any(|r| local_ref(r) == *m));
+
+            if hit.len() == refs.len() {
+                vuln["analysis"] = analysis.clone();
+            } else {
+                let mut split = vuln.clone();
+                retain_affects(vuln, |r| !hit.contains(r));
+                retain_affects(&mut split, |r| hit.contains(r));
+                split["analysis"] = analysis.clone();
+                additions.push(split);
+            }
+        }
+
+        if !remaining.is_empty() {
+            let mut entry = match template {
+                Some(mut template) => {
+                    template["affects"] = Value::Array(Vec::new());
+                    template
+                }
+                None => serde_json::to_value(CdxVulnerability {
+                    affects: None,
+                    analysis: None,
+                    ..statement
+                })?,
+            };
+            entry["analysis"] = analysis.clone();
+            entry["affects"] = remaining
+                .iter()
+                .map(|r| serde_json::json!({ "ref": r }))
+                .collect();
+            additions.push(entry);
+        }
+        vulnerabilities.extend(additions);
+    }
+
+    bom["vulnerabilities"] = Value::Array(vulnerabilities);
+    Ok(())
+}
+
+/// The `affects[].ref` values of a vulnerability
+fn affects_refs(vuln: &Value) -> Vec<String> {
+    vuln.get("affects")
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.get("ref").and_then(|r| r.as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn retain_affects(vuln: &mut Value, keep: impl Fn(&str) -> bool) {
+    if let Some(affects) = vuln.get_mut("affects").and_then(|a| a.as_array_mut()) {
+        affects.retain(|a| a.get("ref").and_then(|r| r.as_str()).is_some_and(&keep));
+    }
+}
+
+/// The author of a VEX document written from a CycloneDX BOM: the
+/// `--author` option, else the first BOM author
+fn document_author(bom: &Value, options: &VexOptions) -> String {
+    options
+        .author
+        .clone()
+        .or_else(|| {
+            bom.pointer("/metadata/authors/0/name")
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "Unknown Author".to_string())
+}
+
+/// Whether `response` is a CycloneDX `analysis.response` value
+fn is_cdx_response(response: &str) -> bool {
+    matches!(
+        response,
+        "can_not_fix" | "will_not_fix" | "update" | "rollback" | "workaround_available"
+    )
+}
+
+/// Maps a CycloneDX impact analysis justification to the OpenVEX
+/// justification, which is also the CSAF flag label
+fn vex_justification(justification: &str) -> Option<&'static str> {
+    match justification {
+        "code_not_present" => Some("vulnerable_code_not_present"),
+        "code_not_reachable" => Some("vulnerable_code_not_in_execute_path"),
+        "requires_configuration" | "requires_environment" => {
+            Some("vulnerable_code_cannot_be_controlled_by_adversary")
+        }
+        "requires_dependency" => Some("component_not_present"),
+        "protected_by_compiler"
+        | "protected_at_runtime"
+        | "protected_at_perimeter"
+        | "protected_by_mitigating_control" => Some("inline_mitigations_already_exist"),
+        _ => None,
+    }
+}
+
+/// Maps an OpenVEX justification or CSAF flag label to a CycloneDX
+/// justification
+fn cdx_justification(justification: &str) -> Option<&'static str> {
+    match justification {
+        "vulnerable_code_not_present" => Some("code_not_present"),
+        "vulnerable_code_not_in_execute_path" => Some("code_not_reachable"),
+        "vulnerable_code_cannot_be_controlled_by_adversary" => Some("requires_configuration"),
+        "component_not_present" => Some("requires_dependency"),
+        "inline_mitigations_already_exist" => Some("protected_by_mitigating_control"),
+        _ => None,
+    }
+}
+
+fn write_json(output: &Path, value: &Value) -> Result<(), ConverterError> {
+    let file = File::create(output)
+        .map_err(|e| ConverterError::Io(e, format!("Failed to create {}", output.display())))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), value)
+        .map_err(|e| ConverterError::SerializationError(format!("Failed to write JSON: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_purl_matches() {
+        let purl = "pkg:npm/lodash@4.17.21?arch=x64";
+        assert!(purl_matches("pkg:npm/lodash@4.17.21", purl));
+        assert!(purl_matches("pkg:npm/lodash", purl));
+        assert!(!purl_matches("pkg:npm/lodash@4.17.20", purl));
+        assert!(!purl_matches("pkg:npm/lodash-es", purl));
+    }
+
+    #[test]
+    fn test_statements_name_each_component_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vex.cdx.json");
+        let sbom = json!({
+            "bomFormat": "CycloneDX",
+            "components": [
+                { "bom-ref": "a", "purl": "pkg:npm/a@1" },
+                { "bom-ref": "b", "purl": "pkg:npm/b@1" }
+            ]
+        });
+        // "x" is the VEX document's own name for "a"
+        let vex = json!({
+            "bomFormat": "CycloneDX",
+            "components": [{ "bom-ref": "x", "purl": "pkg:npm/a@1" }],
+            "vulnerabilities": [{
+                "id": "CVE-1",
+                "analysis": { "state": "not_affected", "justification": "code_not_present" },
+                "affects": [{ "ref": "x" }, { "ref": "b" }, { "ref": "a" }]
+            }]
+        });
+        std::fs::write(&path, vex.to_string()).unwrap();
+
+        let mut report = ValidationReport::new();
+        let statements =
+            read_statements(&path, &VexTarget::of_bom(&sbom), &IdMap::new(), &mut report).unwrap();
+        let refs: Vec<&str> = statements.iter().map(Statement::affected_ref).collect();
+        assert_eq!(refs, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_apply_splits_partially_covered_entries() {
+        let mut bom = json!({
+            "bomFormat": "CycloneDX",
+            "vulnerabilities": [{
+                "id": "CVE-1",
+                "ratings": [{ "score": 9.8 }],
+                "affects": [{ "ref": "a" }, { "ref": "b" }]
+            }]
+        });
+        let statement: CdxVulnerability = serde_json::from_value(json!({
+            "id": "CVE-1",
+            "analysis": { "state": "not_affected", "justification": "code_not_present" },
+            "affects": [{ "ref": "b" }, { "ref": "c" }]
+        }))
+        .unwrap();
+
+        apply_vulnerabilities(&mut bom, vec![statement]).unwrap();
+
+        let vulns = bom["vulnerabilities"].as_array().unwrap();
+        assert_eq!(vulns.len(), 3);
+        assert_eq!(vulns[0]["affects"], json!([{ "ref": "a" }]));
+        assert!(vulns[0].get("analysis").is_none());
+        // Split entries keep the other fields of the original entry
+        assert_eq!(vulns[1]["affects"], json!([{ "ref": "b" }]));
+        assert_eq!(vulns[1]["ratings"][0]["score"], 9.8);
+        assert_eq!(vulns[1]["analysis"]["state"], "not_affected");
+        assert_eq!(vulns[2]["affects"], json!([{ "ref": "c" }]));
+        assert_eq!(vulns[2]["analysis"]["justification"], "code_not_present");
+    }
+}
@@ -0,0 +1,548 @@
+//! OpenVEX documents.
+//!
+//! Maps OpenVEX statements to and from CycloneDX vulnerabilities: each
+//! statement becomes one vulnerability entry whose analysis carries the
+//! status, and whose `affects` are the statement's products (or their
+//! subcomponents, which name the affected component within the product).
+
+use super::{
+    VexComponent, VexOptions, cdx_components, cdx_justification, cdx_vulnerabilities,
+    vex_justification,
+};
+use crate::bom_link::local_ref;
+use crate::cdx_version::CdxVersion;
+use crate::errors::ConverterError;
+use crate::models_cdx::{CdxAffects, CdxAnalysis, CdxVulnSource, CdxVulnerability};
+use crate::validation::{ValidationIssue, ValidationReport};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// `@context` of the OpenVEX version written
+pub const OPENVEX_CONTEXT: &str = "https://openvex.dev/ns/v0.2.0";
+
+/// Prefix shared by the `@context` of all OpenVEX versions
+pub const OPENVEX_CONTEXT_PREFIX: &str = "https://openvex.dev/ns";
+
+/// An OpenVEX document
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenVexDocument {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub author: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooling: Option<String>,
+    #[serde(default)]
+    pub statements: Vec<OpenVexStatement>,
+}
+
+/// A statement about one vulnerability in a set of products
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenVexStatement {
+    #[serde(rename = "@id", default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub vulnerability: OpenVexVulnerability,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    #[serde(default)]
+    pub products: Vec<OpenVexProduct>,
+    pub status: String, // "not_affected", "affected", "fixed", "under_investigation"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impact_statement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_statement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_statement_timestamp: Option<String>,
+}
+
+/// The vulnerability of a statement. OpenVEX 0.0.x used a plain string.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum OpenVexVulnerability {
+    Name(String),
+    Object {
+        #[serde(rename = "@id", default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        aliases: Vec<String>,
+    },
+}
+
+impl OpenVexVulnerability {
+    pub fn name(&self) -> &str {
+        match self {
+            OpenVexVulnerability::Name(name) => name,
+            OpenVexVulnerability::Object { name, .. } => name,
+        }
+    }
+}
+
+/// A product or subcomponent, identified by its `@id` IRI and/or
+/// `identifiers` (`purl`, `cpe23`, `cpe22`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OpenVexProduct {
+    #[serde(rename = "@id", default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub identifiers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subcomponents: Vec<OpenVexProduct>,
+}
+
+impl OpenVexProduct {
+    fn purl(&self) -> Option<&str> {
+        self.identifiers
+            .get("purl")
+            .map(String::as_str)
+            .or(self.id.as_deref().filter(|id| id.starts_with("pkg:")))
+    }
+
+    fn cpe(&self) -> Option<&str> {
+        self.identifiers
+            .get("cpe23")
+            .or_else(|| self.identifiers.get("cpe22"))
+            .map(String::as_str)
+            .or(self.id.as_deref().filter(|id| id.starts_with("cpe:")))
+    }
+
+    /// A label for reports
+    fn label(&self) -> String {
+        self.id
+            .clone()
+            .or_else(|| self.purl().map(str::to_string))
+            .or_else(|| self.cpe().map(str::to_string))
+            .unwrap_or_else(|| "<unidentified product>".to_string())
+    }
+
+    /// The bom-refs of the components this product identifies
+    fn matching_refs(&self, components: &[VexComponent]) -> Vec<String> {
+        components
+            .iter()
+            .filter(|c| c.matches(self.id.as_deref(), self.purl(), self.cpe()))
+            .map(|c| c.bom_ref.clone())
+            .collect()
+    }
+
+    /// Identifies a CycloneDX component, by purl where it has one
+    fn from_component(component: &VexComponent) -> Self {
+        let mut identifiers = BTreeMap::new();
+        if let Some(purl) = &component.purl {
+            identifiers.insert("purl".to_string(), purl.clone());
+        }
+        if let Some(cpe) = &component.cpe {
+            identifiers.insert("cpe23".to_string(), cpe.clone());
+        }
+        OpenVexProduct {
+            id: Some(
+                component
+                    .purl
+                    .clone()
+                    .unwrap_or_else(|| component.bom_ref.clone()),
+            ),
+            identifiers,
+            subcomponents: Vec::new(),
+        }
+    }
+}
+
+impl OpenVexDocument {
+    /// Parses an OpenVEX document
+    pub fn parse(value: Value) -> Result<Self, ConverterError> {
+        serde_json::from_value(value)
+            .map_err(|e| ConverterError::ParseError(format!("Invalid OpenVEX: {}", e)))
+    }
+
+    /// Builds an OpenVEX document from the vulnerabilities of a CycloneDX
+    /// BOM. `affects` refs are identified by the purl of the component
+    /// they refer to; refs to components outside the BOM (e.g. in a
+    /// `--split-vex` document) are kept as product `@id`s.
+    pub fn from_cdx(bom: &Value, options: &VexOptions) -> Result<Self, ConverterError> {
+        let components: HashMap<String, VexComponent> = cdx_components(bom)
+            .into_iter()
+            .map(|c| (c.bom_ref.clone(), c))
+            .collect();
+        let metadata = bom.get("metadata");
+        let text = |value: Option<&Value>, key: &str| {
+            value
+                .and_then(|v| v.get(key))
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let author = super::document_author(bom, options);
+
+        let mut statements = Vec::new();
+        for vuln in cdx_vulnerabilities(bom)? {
+            let products: Vec<OpenVexProduct> = vuln
+                .affects
+                .iter()
+                .flatten()
+                .map(
+                    |affects| match components.get(local_ref(&affects.bom_ref).as_ref()) {
+                        Some(component) => OpenVexProduct::from_component(component),
+                        None => OpenVexProduct {
+                            id: Some(affects.bom_ref.clone()),
+                            ..Default::default()
+                        },
+                    },
+                )
+                .collect();
+            if products.is_empty() {
+                warn!(
+                    "Skipping vulnerability {} without affected components",
+                    vuln.id
+                );
+                continue;
+            }
+            statements.push(statement_from_cdx(vuln, products));
+        }
+
+        Ok(OpenVexDocument {
+            context: OPENVEX_CONTEXT.to_string(),
+            id: text(Some(bom), "serialNumber")
+                .unwrap_or_else(|| format!("urn:uuid:{}", uuid::Uuid::new_v4())),
+            author,
+            role: None,
+            timestamp: text(metadata, "timestamp")
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            last_updated: None,
+            version: 1,
+            tooling: Some(format!("sbom-converter-{}", env!("CARGO_PKG_VERSION"))),
+            statements,
+        })
+    }
+
+    /// Converts the statements to CycloneDX vulnerabilities.
+    ///
+    /// With `components`, products are matched against them by bom-ref,
+    /// purl or CPE, and products matching nothing are reported as
+    /// warnings. Without, the product `@id` (or purl) is the `affects` ref.
+    pub fn to_cdx_vulnerabilities(
+        &self,
+        components: Option<&[VexComponent]>,
+        report: &mut ValidationReport,
+    ) -> Vec<CdxVulnerability> {
+        let mut vulnerabilities = Vec::new();
+        for (i, statement) in self.statements.iter().enumerate() {
+            let location = format!("statements[{}]", i);
+            let mut refs = Vec::new();
+            for product in &statement.products {
+                // Subcomponents name the affected parts of the product
+                let targets: Vec<&OpenVexProduct> = if product.subcomponents.is_empty() {
+                    vec![product]
+                } else {
+                    product.subcomponents.iter().collect()
+                };
+                for target in targets {
+                    match components {
+                        Some(components) => {
+                            let matched = target.matching_refs(components);
+                            if matched.is_empty() {
+                                report.add_issue(
+                                    ValidationIssue::warning(format!(
+                                        "No component matches product {} of {}",
+                                        target.label(),
+                                        statement.vulnerability.name()
+                                    ))
+                                    .with_location(&location),
+                                );
+                            }
+                            refs.extend(matched);
+                        }
+                        None => refs.push(target.label()),
+                    }
+                }
+            }
+            refs.dedup();
+
+            match statement_to_cdx(statement, &self.timestamp) {
+                Some(mut vuln) => {
+                    vuln.affects = Some(
+                        refs.into_iter()
+                            .map(|bom_ref| CdxAffects {
+                                bom_ref,
+                                versions: None,
+                            })
+                            .collect(),
+                    );
+                    vulnerabilities.push(vuln);
+                }
+                None => report.add_issue(
+                    ValidationIssue::warning(format!(
+                        "Unknown OpenVEX status '{}'",
+                        statement.status
+                    ))
+                    .with_location(&location),
+                ),
+            }
+        }
+        vulnerabilities
+    }
+
+    /// Converts the document to a standalone CycloneDX VEX document
+    pub fn to_cdx(&self, version: CdxVersion) -> Result<Value, ConverterError> {
+        let mut report = ValidationReport::new();
+        let vulnerabilities = self.to_cdx_vulnerabilities(None, &mut report);
+        for issue in &report.issues {
+            warn!("{}", issue.message);
+        }
+        let serial_number = self.id.starts_with("urn:uuid:").then_some(self.id.as_str());
+        let mut document = super::cdx_vex_document(
+            &vulnerabilities,
+            serial_number,
+            Some(&self.timestamp),
+            version,
+        )?;
+        document["metadata"]["authors"] = serde_json::json!([{ "name": self.author }]);
+        Ok(document)
+    }
+}
+
+/// Maps a CycloneDX vulnerability and its analysis to an OpenVEX statement
+fn statement_from_cdx(vuln: CdxVulnerability, products: Vec<OpenVexProduct>) -> OpenVexStatement {
+    let analysis = vuln.analysis.unwrap_or_default();
+    let state = analysis.state.as_deref().unwrap_or("in_triage");
+    let status = match state {
+        "exploitable" => "affected",
+        "not_affected" | "false_positive" => "not_affected",
+        "resolved" | "resolved_with_pedigree" => "fixed",
+        _ => "under_investigation",
+    };
+
+    let mut statement = OpenVexStatement {
+        id: None,
+        vulnerability: OpenVexVulnerability::Object {
+            id: vuln.source.and_then(|source| source.url),
+            name: vuln.id,
+            description: vuln.description,
+            aliases: Vec::new(),
+        },
+        timestamp: analysis.first_issued,
+        last_updated: analysis.last_updated,
+        products,
+        status: status.to_string(),
+        status_notes: None,
+        justification: None,
+        impact_statement: None,
+        action_statement: None,
+        action_statement_timestamp: None,
+    };
+    match status {
+        "affected" => {
+            // An action statement is required for affected products
+            let responses = analysis.response.join(", ");
+            statement.action_statement = Some(
+                analysis
+                    .detail
+                    .or_else(|| (!responses.is_empty()).then_some(responses))
+                    .unwrap_or_else(|| "No action statement provided".to_string()),
+            );
+        }
+        "not_affected" => {
+            statement.justification = analysis
+                .justification
+                .as_deref()
+                .and_then(vex_justification)
+                .map(str::to_string);
+            // Without a justification an impact statement is required
+            statement.impact_statement = analysis.detail.or_else(|| {
+                statement
+                    .justification
+                    .is_none()
+                    .then(|| format!("CycloneDX analysis state: {}", state))
+            });
+        }
+        _ => statement.status_notes = analysis.detail,
+    }
+    statement
+}
+
+/// Maps an OpenVEX statement to a CycloneDX vulnerability without
+/// `affects`. `None` for unknown statuses.
+fn statement_to_cdx(statement: &OpenVexStatement, timestamp: &str) -> Option<CdxVulnerability> {
+    let mut analysis = CdxAnalysis {
+        first_issued: statement
+            .timestamp
+            .clone()
+            .or_else(|| Some(timestamp.to_string())),
+        last_updated: statement.last_updated.clone(),
+        ..Default::default()
+    };
+    match statement.status.as_str() {
+        "affected" => {
+            analysis.state = Some("exploitable".to_string());
+            // An action statement made of response values is the response
+            let responses: Vec<String> = statement
+                .action_statement
+                .iter()
+                .flat_map(|s| s.split(',').map(str::trim))
+                .map(str::to_string)
+                .collect();
+            if !responses.is_empty() && responses.iter().all(|r| super::is_cdx_response(r)) {
+                analysis.response = responses;
+            } else {
+                analysis.detail = statement.action_statement.clone();
+            }
+        }
+        "not_affected" => {
+            analysis.state = Some("not_affected".to_string());
+            analysis.justification = statement
+                .justification
+                .as_deref()
+                .and_then(cdx_justification)
+                .map(str::to_string);
+            analysis.detail = statement.impact_statement.clone();
+        }
+        "fixed" => analysis.state = Some("resolved".to_string()),
+        "under_investigation" => analysis.state = Some("in_triage".to_string()),
+        _ => return None,
+    }
+    if analysis.detail.is_none() {
+        analysis.detail = statement.status_notes.clone();
+    }
+
+    let (source, description) = match &statement.vulnerability {
+        OpenVexVulnerability::Object {
+            id, description, ..
+        } => (
+            id.as_ref().map(|url| CdxVulnSource {
+                name: url_host(url).unwrap_or(url).to_string(),
+                url: Some(url.clone()),
+            }),
+            description.clone(),
+        ),
+        OpenVexVulnerability::Name(_) => (None, None),
+    };
+    Some(CdxVulnerability {
+        id: statement.vulnerability.name().to_string(),
+        source,
+        description,
+        ratings: None,
+        cwes: None,
+        analysis: Some(analysis),
+        affects: None,
+        properties: None,
+        extra: HashMap::new(),
+    })
+}
+
+/// The host of an http(s) URL
+fn url_host(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    rest.split('/').next().filter(|host| !host.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn statement(value: Value) -> OpenVexStatement {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_statement_status_mapping() {
+        let not_affected = statement(json!({
+            "vulnerability": { "name": "CVE-2023-1" },
+            "products": [{ "@id": "pkg:npm/a@1" }],
+            "status": "not_affected",
+            "justification": "vulnerable_code_not_in_execute_path",
+            "impact_statement": "Only the CLI uses it"
+        }));
+        let vuln = statement_to_cdx(&not_affected, "2024-01-01T00:00:00Z").unwrap();
+        let analysis = vuln.analysis.unwrap();
+        assert_eq!(analysis.state.as_deref(), Some("not_affected"));
+        assert_eq!(
+            analysis.justification.as_deref(),
+            Some("code_not_reachable")
+        );
+        assert_eq!(analysis.detail.as_deref(), Some("Only the CLI uses it"));
+        assert_eq!(
+            analysis.first_issued.as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+
+        let affected = statement(json!({
+            "vulnerability": "CVE-2023-2",
+            "status": "affected",
+            "action_statement": "update, workaround_available"
+        }));
+        let vuln = statement_to_cdx(&affected, "").unwrap();
+        assert_eq!(vuln.id, "CVE-2023-2");
+        let analysis = vuln.analysis.unwrap();
+        assert_eq!(analysis.state.as_deref(), Some("exploitable"));
+        assert_eq!(analysis.response, vec!["update", "workaround_available"]);
+
+        let unknown = statement(json!({ "vulnerability": "CVE-2023-3", "status": "maybe" }));
+        assert!(statement_to_cdx(&unknown, "").is_none());
+    }
+
+    #[test]
+    fn test_statement_from_cdx_requires_statements() {
+        let vuln: CdxVulnerability = serde_json::from_value(json!({
+            "id": "CVE-2023-4",
+            "analysis": { "state": "false_positive" }
+        }))
+        .unwrap();
+        let statement = statement_from_cdx(vuln, Vec::new());
+        assert_eq!(statement.status, "not_affected");
+        // Not affected without a justification needs an impact statement
+        assert_eq!(
+            statement.impact_statement.as_deref(),
+            Some("CycloneDX analysis state: false_positive")
+        );
+
+        let vuln: CdxVulnerability = serde_json::from_value(json!({
+            "id": "CVE-2023-5",
+            "analysis": { "state": "exploitable" }
+        }))
+        .unwrap();
+        let statement = statement_from_cdx(vuln, Vec::new());
+        assert_eq!(statement.status, "affected");
+        assert!(statement.action_statement.is_some());
+    }
+
+    #[test]
+    fn test_products_match_by_purl() {
+        let components = vec![VexComponent {
+            bom_ref: "lodash".to_string(),
+            purl: Some("pkg:npm/lodash@4.17.21".to_string()),
+            ..Default::default()
+        }];
+        let product = OpenVexProduct {
+            id: Some("pkg:npm/lodash".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(product.matching_refs(&components), vec!["lodash"]);
+
+        let product = OpenVexProduct {
+            id: Some("https://example.com/product".to_string()),
+            identifiers: BTreeMap::from([(
+                "purl".to_string(),
+                "pkg:npm/lodash@4.17.20".to_string(),
+            )]),
+            ..Default::default()
+        };
+        assert!(product.matching_refs(&components).is_empty());
+    }
+}
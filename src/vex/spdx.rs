@@ -0,0 +1,120 @@
+//! SPDX 3 security elements as VEX statements.
+//!
+//! Reads the vulnerabilities, VEX assessments and `affects` relationships
+//! of an SPDX 3 document (simple JSON or JSON-LD) the same way the SPDX →
+//! CycloneDX converter does, so the statements carry the bom-refs the
+//! converter gives the assessed packages (or those of an ID map).
+
+use super::VexComponent;
+use crate::converter_spdx_to_cdx::{SpdxRelationshipIndex, SpdxVulnerabilityIndex};
+use crate::errors::ConverterError;
+use crate::fidelity::FidelityReport;
+use crate::id_map::IdMap;
+use crate::models_cdx::CdxVulnerability;
+use crate::models_spdx::{
+    JsonLdElement, JsonLdRelationship, SpdxRelationshipMinimal, SpdxVulnAssessment,
+};
+use serde_json::Value;
+
+/// The VEX statements of an SPDX 3 document
+#[derive(Debug)]
+pub struct SpdxVex {
+    /// Serial number the `affects` refs of the statements are linked to
+    pub serial_number: String,
+    /// The packages of the document, by the bom-refs the statements use
+    pub components: Vec<VexComponent>,
+    pub vulnerabilities: Vec<CdxVulnerability>,
+    /// When the document was created
+    pub timestamp: Option<String>,
+}
+
+impl SpdxVex {
+    /// Reads the security elements of a parsed SPDX 3 document, mapping
+    /// SPDX IDs to bom-refs through `ids`
+    pub fn read(document: &Value, ids: &IdMap) -> Result<Self, ConverterError> {
+        let elements = ["@graph", "elements"]
+            .iter()
+            .filter_map(|key| document.get(key).and_then(|e| e.as_array()))
+            .flatten();
+
+        let mut vulnerabilities = SpdxVulnerabilityIndex::default();
+        let mut index = SpdxRelationshipIndex::new();
+        let mut components = Vec::new();
+        let mut timestamp = document
+            .pointer("/creationInfo/created")
+            .and_then(|c| c.as_str())
+            .map(str::to_string);
+        for element in elements {
+            let type_name = element.get("type").and_then(|t| t.as_str()).unwrap_or("");
+            match type_name {
+                "Relationship" | "LifecycleScopedRelationship" => {
+                    if let Ok(rel) = serde_json::from_value::<JsonLdRelationship>(element.clone()) {
+                        for to in rel.to {
+                            index.insert(SpdxRelationshipMinimal {
+                                spdx_element_id: rel.from.clone(),
+                                relationship_type: rel.relationship_type.clone(),
+                                related_spdx_element: to,
+                            })?;
+                        }
+                    }
+                    continue;
+                }
+                "CreationInfo" => {
+                    if timestamp.is_none() {
+                        timestamp = element
+                            .get("created")
+                            .and_then(|c| c.as_str())
+                            .map(str::to_string);
+                    }
+                    continue;
+                }
+                "software_Package" | "SpdxPackage" => {
+                    if let Ok(package) = serde_json::from_value::<JsonLdElement>(element.clone()) {
+                        components.push(VexComponent {
+                            bom_ref: ids.bom_ref(&package.spdx_id),
+                            name: package.name.clone(),
+                            version: package.software_package_version.clone().or_else(|| {
+                                element
+                                    .get("versionInfo")
+                                    .and_then(|v| v.as_str())
+                                    .map(str::to_string)
+                            }),
+                            purl: package.extract_purl(),
+                            cpe: package.extract_cpe(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+            vulnerabilities.add_element(element.clone());
+        }
+
+        // The simple layout keeps relationships, assessments included, in
+        // their own array
+        for relationship in document
+            .get("relationships")
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let type_name = relationship.get("type").and_then(|t| t.as_str());
+            if type_name.is_some_and(SpdxVulnAssessment::is_assessment_type) {
+                vulnerabilities.add_element(relationship.clone());
+            } else if let Ok(rel) =
+                serde_json::from_value::<SpdxRelationshipMinimal>(relationship.clone())
+            {
+                index.insert(rel)?;
+            }
+        }
+
+        let serial_number = format!("urn:uuid:{}", uuid::Uuid::new_v4());
+        let vulnerabilities =
+            vulnerabilities.to_cdx(&index, &serial_number, ids, &FidelityReport::new())?;
+        Ok(SpdxVex {
+            serial_number,
+            components,
+            vulnerabilities,
+            timestamp,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+//! Integration tests for BOM-Link handling.
+//!
+//! Tests that `validate` checks BOM-Links against the linked BOMs, that
+//! `merge` rewrites links between its inputs into local refs, and that
+//! `diff` treats links into a BOM itself like plain bom-refs.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::tempdir;
+
+const APP_SERIAL: &str = "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79";
+const LIB_SERIAL: &str = "urn:uuid:8f2b0c4e-7a6d-4a53-9d1e-2b6c3f5a7e10";
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A BOM of an application depending on a library described in another BOM
+fn get_app_bom() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "serialNumber": APP_SERIAL,
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "app",
+                "version": "1.0.0"
+            }
+        ],
+        "dependencies": [
+            {
+                "ref": "app",
+                "dependsOn": [format!("urn:cdx:{}/1#lib", &LIB_SERIAL[9..])]
+            }
+        ]
+    })
+}
+
+/// The BOM of the library the application links to
+fn get_lib_bom() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "serialNumber": LIB_SERIAL,
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "lib",
+                "type": "library",
+                "name": "lib",
+                "version": "2.0.0",
+                "purl": "pkg:npm/lib@2.0.0"
+            }
+        ]
+    })
+}
+
+/// Runs `validate` with a JSON report
+fn validate(input: &Path, linked_boms: &[&Path]) -> (Output, Value) {
+    let mut cmd = get_cmd();
+    cmd.arg("validate")
+        .arg("--input")
+        .arg(input)
+        .arg("--report-format")
+        .arg("json");
+    if !linked_boms.is_empty() {
+        cmd.arg("--linked-bom").args(linked_boms);
+    }
+    let output = cmd.output().unwrap();
+    let report = serde_json::from_slice(&output.stdout).unwrap();
+    (output, report)
+}
+
+// --- Tests ---
+
+#[test]
+fn test_validate_reports_unloaded_bom() {
+    let dir = tempdir().unwrap();
+    let app_path = dir.path().join("app.cdx.json");
+    fs::write(&app_path, get_app_bom().to_string()).unwrap();
+
+    let (output, report) = validate(&app_path, &[]);
+    assert!(output.status.success());
+    assert_eq!(report["summary"]["errors"], 0);
+    let issue = report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["message"].as_str().unwrap().contains("not loaded"))
+        .unwrap();
+    assert_eq!(issue["severity"], "info");
+    assert!(issue["message"].as_str().unwrap().contains(LIB_SERIAL));
+}
+
+#[test]
+fn test_validate_resolves_linked_bom() {
+    let dir = tempdir().unwrap();
+    let app_path = dir.path().join("app.cdx.json");
+    let lib_path = dir.path().join("lib.cdx.json");
+    fs::write(&app_path, get_app_bom().to_string()).unwrap();
+    fs::write(&lib_path, get_lib_bom().to_string()).unwrap();
+
+    let (output, report) = validate(&app_path, &[&lib_path]);
+    assert!(output.status.success());
+    assert_eq!(report["summary"]["errors"], 0);
+    assert!(
+        !report["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|i| i["message"].as_str().unwrap().contains("BOM-Link"))
+    );
+
+    // A link to a bom-ref the linked BOM does not have is an error
+    let mut app = get_app_bom();
+    app["dependencies"][0]["dependsOn"][0] = json!(format!("urn:cdx:{}/1#libx", &LIB_SERIAL[9..]));
+    fs::write(&app_path, app.to_string()).unwrap();
+
+    let (_, report) = validate(&app_path, &[&lib_path]);
+    assert_eq!(report["summary"]["errors"], 1);
+    let issue = report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["severity"] == "error")
+        .unwrap();
+    assert!(
+        issue["message"]
+            .as_str()
+            .unwrap()
+            .contains("points at nothing")
+    );
+    assert_eq!(issue["location"], "dependencies[0].dependsOn[0]");
+}
+
+#[test]
+fn test_merge_rewrites_links_into_local_refs() {
+    let dir = tempdir().unwrap();
+    let app_path = dir.path().join("app.cdx.json");
+    let lib_path = dir.path().join("lib.cdx.json");
+    let output_path = dir.path().join("merged.cdx.json");
+    fs::write(&app_path, get_app_bom().to_string()).unwrap();
+    fs::write(&lib_path, get_lib_bom().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("merge")
+        .arg("--inputs")
+        .arg(&app_path)
+        .arg(&lib_path)
+        .arg("--output")
+        .arg(&output_path);
+    cmd.assert().success();
+
+    let merged: Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    let dependency = merged["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["ref"] == "app")
+        .unwrap();
+    assert_eq!(dependency["dependsOn"], json!(["lib"]));
+}
+
+#[test]
+fn test_diff_treats_self_links_as_local_refs() {
+    let dir = tempdir().unwrap();
+    let old_path = dir.path().join("old.cdx.json");
+    let new_path = dir.path().join("new.cdx.json");
+
+    let mut old = get_lib_bom();
+    old["components"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "bom-ref": "core", "type": "library", "name": "core" }));
+    old["dependencies"] = json!([{ "ref": "lib", "dependsOn": ["core"] }]);
+    let mut new = old.clone();
+    new["dependencies"] = json!([{
+        "ref": format!("urn:cdx:{}/1#lib", &LIB_SERIAL[9..]),
+        "dependsOn": [format!("urn:cdx:{}/1#core", &LIB_SERIAL[9..])]
+    }]);
+    fs::write(&old_path, old.to_string()).unwrap();
+    fs::write(&new_path, new.to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("diff")
+        .arg("--file1")
+        .arg(&old_path)
+        .arg("--file2")
+        .arg(&new_path)
+        .arg("--report-format")
+        .arg("json");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report: Value = serde_json::from_str(&stdout[stdout.find('{').unwrap()..]).unwrap();
+    assert_eq!(report["summary"]["dependencies_added"], 0);
+    assert_eq!(report["summary"]["dependencies_removed"], 0);
+}
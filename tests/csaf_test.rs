@@ -0,0 +1,340 @@
+//! Integration tests for the `vex` subcommand with CSAF documents.
+//!
+//! Tests that CycloneDX VEX documents convert to the CSAF 2.0 VEX profile
+//! and back, and that CSAF advisories are applied onto a CycloneDX SBOM.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX VEX document with an affected, a not affected and a fixed
+/// component
+fn get_test_cdx_vex() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "serialNumber": "urn:uuid:0b6a1c7e-3f44-4d39-9f0e-6d7f3c0c2a11",
+        "version": 1,
+        "metadata": {
+            "timestamp": "2024-05-01T00:00:00Z",
+            "authors": [{ "name": "ACME PSIRT" }],
+            "component": { "bom-ref": "app", "type": "application", "name": "acme-app" }
+        },
+        "components": [
+            {
+                "bom-ref": "log4j",
+                "type": "library",
+                "name": "log4j-core",
+                "version": "2.14.1",
+                "purl": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21",
+                "cpe": "cpe:2.3:a:lodash:lodash:4.17.21:*:*:*:*:*:*:*"
+            },
+            {
+                "bom-ref": "openssl",
+                "type": "library",
+                "name": "openssl",
+                "version": "3.0.7",
+                "purl": "pkg:generic/openssl@3.0.7"
+            }
+        ],
+        "vulnerabilities": [
+            {
+                "id": "CVE-2021-44228",
+                "source": { "name": "NVD", "url": "https://nvd.nist.gov/vuln/detail/CVE-2021-44228" },
+                "description": "Log4Shell",
+                "ratings": [{
+                    "method": "CVSSv31",
+                    "score": 10.0,
+                    "severity": "critical",
+                    "vector": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"
+                }],
+                "analysis": { "state": "exploitable", "response": ["update"] },
+                "affects": [{ "ref": "log4j" }]
+            },
+            {
+                "id": "CVE-2021-23337",
+                "analysis": {
+                    "state": "not_affected",
+                    "justification": "code_not_reachable",
+                    "detail": "template() is never called"
+                },
+                "affects": [{ "ref": "lodash" }]
+            },
+            {
+                "id": "CVE-2021-23337",
+                "analysis": { "state": "resolved" },
+                "affects": [{ "ref": "openssl" }]
+            }
+        ]
+    })
+}
+
+/// Runs `vex convert` with the given extra arguments
+fn convert(input: &Path, output: &Path, to: &str, args: &[&str]) -> Output {
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--to")
+        .arg(to)
+        .args(args);
+    cmd.output().unwrap()
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_cdx_vex_to_csaf_and_back() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("vex.cdx.json");
+    let csaf_path = dir.path().join("vex.csaf.json");
+    let output_path = dir.path().join("roundtrip.cdx.json");
+    fs::write(&input_path, get_test_cdx_vex().to_string()).unwrap();
+
+    let output = convert(
+        &input_path,
+        &csaf_path,
+        "csaf",
+        &["--publisher-namespace", "https://acme.example"],
+    );
+    assert!(output.status.success());
+    let csaf = read_json(&csaf_path);
+    assert_eq!(csaf["document"]["category"], "csaf_vex");
+    assert_eq!(csaf["document"]["csaf_version"], "2.0");
+    assert_eq!(csaf["document"]["publisher"]["name"], "ACME PSIRT");
+    assert_eq!(
+        csaf["document"]["publisher"]["namespace"],
+        "https://acme.example"
+    );
+    assert_eq!(csaf["document"]["title"], "VEX for acme-app");
+    assert_eq!(
+        csaf["document"]["tracking"]["id"],
+        "urn:uuid:0b6a1c7e-3f44-4d39-9f0e-6d7f3c0c2a11"
+    );
+
+    let products = csaf["product_tree"]["full_product_names"]
+        .as_array()
+        .unwrap();
+    assert_eq!(products.len(), 3);
+    assert_eq!(products[1]["name"], "lodash 4.17.21");
+    assert_eq!(products[1]["product_id"], "lodash");
+    assert_eq!(
+        products[1]["product_identification_helper"]["cpe"],
+        "cpe:2.3:a:lodash:lodash:4.17.21:*:*:*:*:*:*:*"
+    );
+
+    // Entries for the same vulnerability are grouped
+    let vulnerabilities = csaf["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 2);
+    let log4shell = &vulnerabilities[0];
+    assert_eq!(log4shell["cve"], "CVE-2021-44228");
+    assert_eq!(log4shell["notes"][0]["text"], "Log4Shell");
+    assert_eq!(
+        log4shell["references"][0]["url"],
+        "https://nvd.nist.gov/vuln/detail/CVE-2021-44228"
+    );
+    assert_eq!(
+        log4shell["product_status"]["known_affected"],
+        json!(["log4j"])
+    );
+    assert_eq!(log4shell["remediations"][0]["category"], "vendor_fix");
+    assert_eq!(
+        log4shell["scores"][0]["cvss_v3"]["baseSeverity"],
+        "CRITICAL"
+    );
+    assert_eq!(log4shell["scores"][0]["products"], json!(["log4j"]));
+
+    let lodash = &vulnerabilities[1];
+    assert_eq!(
+        lodash["product_status"]["known_not_affected"],
+        json!(["lodash"])
+    );
+    assert_eq!(lodash["product_status"]["fixed"], json!(["openssl"]));
+    assert_eq!(
+        lodash["flags"],
+        json!([{ "label": "vulnerable_code_not_in_execute_path", "product_ids": ["lodash"] }])
+    );
+    assert_eq!(
+        lodash["threats"],
+        json!([{ "category": "impact", "details": "template() is never called", "product_ids": ["lodash"] }])
+    );
+
+    let output = convert(&csaf_path, &output_path, "cyclonedx", &[]);
+    assert!(output.status.success());
+    let cdx = read_json(&output_path);
+    assert_eq!(cdx["serialNumber"], csaf["document"]["tracking"]["id"]);
+    assert_eq!(cdx["metadata"]["authors"][0]["name"], "ACME PSIRT");
+    let vulnerabilities = cdx["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 3);
+    assert_eq!(vulnerabilities[0]["id"], "CVE-2021-44228");
+    assert_eq!(vulnerabilities[0]["source"]["name"], "NVD");
+    assert_eq!(vulnerabilities[0]["description"], "Log4Shell");
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "exploitable");
+    assert_eq!(
+        vulnerabilities[0]["analysis"]["response"],
+        json!(["update"])
+    );
+    assert!(vulnerabilities[0]["analysis"].get("detail").is_none());
+    assert_eq!(vulnerabilities[0]["ratings"][0]["method"], "CVSSv31");
+    assert_eq!(vulnerabilities[0]["ratings"][0]["severity"], "critical");
+    assert_eq!(
+        vulnerabilities[0]["affects"][0]["ref"],
+        "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+    );
+    assert_eq!(vulnerabilities[1]["analysis"]["state"], "not_affected");
+    assert_eq!(
+        vulnerabilities[1]["analysis"]["justification"],
+        "code_not_reachable"
+    );
+    assert_eq!(
+        vulnerabilities[1]["analysis"]["detail"],
+        "template() is never called"
+    );
+    assert_eq!(vulnerabilities[2]["analysis"]["state"], "resolved");
+}
+
+#[test]
+fn test_csaf_needs_publisher_namespace() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("vex.cdx.json");
+    fs::write(&input_path, get_test_cdx_vex().to_string()).unwrap();
+
+    let output = convert(&input_path, &dir.path().join("out.json"), "csaf", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--publisher-namespace"));
+
+    // The supplier URL is used when there is one
+    let mut vex = get_test_cdx_vex();
+    vex["metadata"]["supplier"] = json!({ "name": "ACME", "url": ["https://acme.example"] });
+    fs::write(&input_path, vex.to_string()).unwrap();
+    let output_path = dir.path().join("out.json");
+    assert!(
+        convert(&input_path, &output_path, "csaf", &[])
+            .status
+            .success()
+    );
+    let csaf = read_json(&output_path);
+    assert_eq!(
+        csaf["document"]["publisher"]["namespace"],
+        "https://acme.example"
+    );
+}
+
+#[test]
+fn test_apply_csaf_advisory() {
+    let dir = tempdir().unwrap();
+    let sbom_path = dir.path().join("sbom.cdx.json");
+    let csaf_path = dir.path().join("advisory.csaf.json");
+    let output_path = dir.path().join("output.cdx.json");
+
+    let mut sbom = get_test_cdx_vex();
+    sbom.as_object_mut().unwrap().remove("vulnerabilities");
+    fs::write(&sbom_path, sbom.to_string()).unwrap();
+    let csaf = json!({
+        "document": {
+            "category": "csaf_vex",
+            "csaf_version": "2.0",
+            "publisher": { "category": "vendor", "name": "Upstream", "namespace": "https://upstream.example" },
+            "title": "Upstream advisory",
+            "tracking": {
+                "id": "UPSTREAM-2024-0001",
+                "status": "final",
+                "version": "2",
+                "initial_release_date": "2024-04-01T00:00:00Z",
+                "current_release_date": "2024-04-15T00:00:00Z",
+                "revision_history": []
+            }
+        },
+        "product_tree": {
+            "full_product_names": [
+                {
+                    "name": "openssl 3.0.7",
+                    "product_id": "CSAFPID-0001",
+                    "product_identification_helper": { "purl": "pkg:generic/openssl@3.0.7" }
+                },
+                {
+                    "name": "openssl 1.1.1",
+                    "product_id": "CSAFPID-0002",
+                    "product_identification_helper": { "purl": "pkg:generic/openssl@1.1.1" }
+                }
+            ],
+            "product_groups": [
+                { "group_id": "CSAFGID-0001", "product_ids": ["CSAFPID-0001", "CSAFPID-0002"] }
+            ]
+        },
+        "vulnerabilities": [{
+            "cve": "CVE-2023-0286",
+            "notes": [{ "category": "description", "text": "X.400 type confusion" }],
+            "product_status": { "known_affected": ["CSAFPID-0001", "CSAFPID-0002"] },
+            "remediations": [{
+                "category": "vendor_fix",
+                "details": "Upgrade to 3.0.8",
+                "group_ids": ["CSAFGID-0001"]
+            }]
+        }]
+    });
+    fs::write(&csaf_path, csaf.to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("apply")
+        .arg("--sbom")
+        .arg(&sbom_path)
+        .arg("--vex")
+        .arg(&csaf_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--report-format")
+        .arg("json");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["warnings"], 1);
+    assert!(
+        report["issues"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("CSAFPID-0002")
+    );
+
+    let result = read_json(&output_path);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 1);
+    assert_eq!(vulnerabilities[0]["id"], "CVE-2023-0286");
+    assert_eq!(vulnerabilities[0]["description"], "X.400 type confusion");
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "exploitable");
+    assert_eq!(
+        vulnerabilities[0]["analysis"]["response"],
+        json!(["update"])
+    );
+    assert_eq!(vulnerabilities[0]["analysis"]["detail"], "Upgrade to 3.0.8");
+    assert_eq!(
+        vulnerabilities[0]["analysis"]["lastUpdated"],
+        "2024-04-15T00:00:00Z"
+    );
+    assert_eq!(vulnerabilities[0]["affects"], json!([{ "ref": "openssl" }]));
+}
@@ -0,0 +1,174 @@
+//! Integration tests for the conversion fidelity report.
+//!
+//! Tests that both converters list the source data they could not map,
+//! and that the report is written next to the output by default.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX BOM using fields SPDX has no property for
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "services": [{ "bom-ref": "api", "name": "api" }],
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "app",
+                "version": "1.0.0",
+                "pedigree": { "notes": "forked from upstream" },
+                "properties": [{ "name": "build", "value": "release" }]
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "licenses": [
+                    { "expression": "MIT" },
+                    { "license": { "id": "Apache-2.0" } }
+                ]
+            }
+        ],
+        "vulnerabilities": [
+            { "id": "CVE-2021-23337", "affects": [{ "ref": "lodash" }] }
+        ]
+    })
+}
+
+/// Finds the entry for `path` in a fidelity report
+fn dropped<'a>(report: &'a Value, path: &str) -> Option<&'a Value> {
+    report["dropped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["path"] == path)
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_cdx_to_spdx23_fidelity_report() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--spdx-version")
+        .arg("2.3")
+        .arg("--fidelity-report");
+    cmd.assert().success();
+
+    // A bare flag writes the report next to the output
+    let report = read_json(&dir.path().join("output.spdx.fidelity.json"));
+    assert_eq!(report["direction"], "cdx-to-spdx");
+
+    let pedigree = dropped(&report, "component.pedigree").unwrap();
+    assert_eq!(pedigree["count"], 1);
+    assert_eq!(pedigree["examples"], json!(["app"]));
+    assert!(dropped(&report, "component.properties").is_some());
+    assert!(dropped(&report, "bom.services").is_some());
+
+    // Both licenses are combined into one expression
+    assert!(dropped(&report, "component.licenses").is_none());
+    // SPDX 2.3 has no vulnerability elements
+    assert_eq!(
+        dropped(&report, "vulnerability").unwrap()["examples"],
+        json!(["CVE-2021-23337"])
+    );
+}
+
+#[test]
+fn test_spdx23_to_cdx_fidelity_report() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+    let report_path = dir.path().join("report.json");
+    let spdx = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "demo",
+        "documentNamespace": "https://example.com/demo",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "packages": [
+            {
+                "SPDXID": "SPDXRef-lodash",
+                "name": "lodash",
+                "versionInfo": "4.17.21",
+                "downloadLocation": "NOASSERTION",
+                "attributionTexts": ["Lodash contributors"]
+            }
+        ],
+        "relationships": [
+            { "spdxElementId": "SPDXRef-lodash", "relationshipType": "GENERATED_FROM", "relatedSpdxElement": "NOASSERTION" }
+        ],
+        "snippets": []
+    });
+    fs::write(&input_path, spdx.to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx")
+        .arg("--fidelity-report")
+        .arg(&report_path);
+    cmd.assert().success();
+
+    let report = read_json(&report_path);
+    assert_eq!(report["direction"], "spdx-to-cdx");
+    assert_eq!(
+        dropped(&report, "package.attributionTexts").unwrap()["examples"],
+        json!(["SPDXRef-lodash"])
+    );
+    assert!(dropped(&report, "relationship.GENERATED_FROM").is_some());
+    assert!(dropped(&report, "document.snippets").is_some());
+    assert!(report["totalDropped"].as_u64().unwrap() >= 3);
+}
+
+#[test]
+fn test_no_report_without_flag() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx");
+    cmd.assert().success();
+
+    assert!(!dir.path().join("output.spdx.fidelity.json").exists());
+}
@@ -0,0 +1,284 @@
+//! Integration tests for `--id-map`.
+//!
+//! Tests that conversions write the SPDX ID / bom-ref map, that JSON-LD
+//! IRIs get the same bom-ref in every run, that a reverse conversion with
+//! the map restores the original bom-refs, and that `vex apply` resolves
+//! SPDX IDs through the map.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX SBOM whose bom-refs are not SPDX IDs
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            { "bom-ref": "app", "type": "application", "name": "app", "version": "1.0.0" },
+            { "bom-ref": "lib", "type": "library", "name": "lib", "version": "2.0.0" }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["lib"] }
+        ]
+    })
+}
+
+/// An SPDX 3 JSON-LD document with IRI element IDs
+fn get_test_jsonld() -> Value {
+    json!({
+        "@context": "https://spdx.org/rdf/3.0.1/spdx-context.jsonld",
+        "@graph": [
+            {
+                "type": "CreationInfo",
+                "@id": "_:creationinfo",
+                "created": "2024-03-01T00:00:00Z",
+                "specVersion": "3.0.1"
+            },
+            {
+                "type": "software_Package",
+                "spdxId": "https://example.com/spdx/pkg-app",
+                "name": "app",
+                "software_packageVersion": "1.0.0"
+            },
+            {
+                "type": "software_Package",
+                "spdxId": "https://example.com/spdx/pkg-lib",
+                "name": "lib",
+                "software_packageVersion": "2.0.0"
+            },
+            {
+                "type": "Relationship",
+                "spdxId": "https://example.com/spdx/rel-1",
+                "from": "https://example.com/spdx/pkg-app",
+                "relationshipType": "dependsOn",
+                "to": ["https://example.com/spdx/pkg-lib"]
+            }
+        ]
+    })
+}
+
+/// Runs `convert` with `--id-map` and returns the parsed output
+fn convert(input: &Path, output: &Path, direction: &str, id_map: &Path, args: &[&str]) -> Value {
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--direction")
+        .arg(direction)
+        .arg("--id-map")
+        .arg(id_map)
+        .args(args);
+    cmd.assert().success();
+    read_json(output)
+}
+
+fn write_json(dir: &Path, name: &str, value: &Value) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, value.to_string()).unwrap();
+    path
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// The bom-refs of the components of a CycloneDX BOM
+fn bom_refs(bom: &Value) -> Vec<String> {
+    let mut refs: Vec<String> = bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["bom-ref"].as_str().unwrap().to_string())
+        .collect();
+    refs.sort();
+    refs
+}
+
+// --- Tests ---
+
+#[test]
+fn test_jsonld_bom_refs_are_stable() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_jsonld());
+
+    let first_map = dir.path().join("first.ids.json");
+    let second_map = dir.path().join("second.ids.json");
+    let first = convert(
+        &input,
+        &dir.path().join("first.cdx.json"),
+        "spdx-to-cdx",
+        &first_map,
+        &[],
+    );
+    let second = convert(
+        &input,
+        &dir.path().join("second.cdx.json"),
+        "spdx-to-cdx",
+        &second_map,
+        &[],
+    );
+    assert_eq!(bom_refs(&first), bom_refs(&second));
+    assert!(bom_refs(&first)[0].starts_with("pkg-app-"));
+
+    // The map records every IRI and its bom-ref
+    let map = read_json(&first_map);
+    assert_eq!(map["idMapVersion"], 1);
+    let ids = map["ids"].as_array().unwrap();
+    let lib = ids
+        .iter()
+        .find(|e| e["spdxId"] == "https://example.com/spdx/pkg-lib")
+        .unwrap();
+    assert_eq!(first["dependencies"][0]["dependsOn"][0], lib["bom-ref"]);
+    assert_eq!(map, read_json(&second_map));
+}
+
+#[test]
+fn test_reverse_conversion_restores_bom_refs() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+    let id_map = dir.path().join("ids.json");
+    let spdx_path = dir.path().join("output.spdx.json");
+
+    let spdx = convert(
+        &input,
+        &spdx_path,
+        "cdx-to-spdx",
+        &id_map,
+        &["--spdx-serialization", "json-ld"],
+    );
+    assert!(spdx["@graph"].as_array().unwrap().iter().any(|e| {
+        e["spdxId"]
+            .as_str()
+            .is_some_and(|id| id.ends_with("#SPDXRef-lib"))
+    }));
+
+    // Without the map, JSON-LD IRIs become hash-based bom-refs...
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(&spdx_path)
+        .arg("--output")
+        .arg(dir.path().join("plain.cdx.json"))
+        .arg("--direction")
+        .arg("spdx-to-cdx");
+    cmd.assert().success();
+    let plain = read_json(&dir.path().join("plain.cdx.json"));
+    assert_ne!(bom_refs(&plain), ["app", "lib"]);
+
+    // ...with it, the original bom-refs come back
+    let bom = convert(
+        &spdx_path,
+        &dir.path().join("back.cdx.json"),
+        "spdx-to-cdx",
+        &id_map,
+        &[],
+    );
+    assert_eq!(bom_refs(&bom), ["app", "lib"]);
+    let dependency = bom["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["ref"] == "app")
+        .unwrap();
+    assert_eq!(dependency["dependsOn"], json!(["lib"]));
+}
+
+#[test]
+fn test_invalid_id_map_is_rejected() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+    let id_map = write_json(
+        dir.path(),
+        "ids.json",
+        &json!({ "idMapVersion": 99, "ids": [] }),
+    );
+
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(dir.path().join("output.spdx.json"))
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--id-map")
+        .arg(&id_map);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unsupported ID map version"));
+}
+
+#[test]
+fn test_vex_apply_resolves_spdx_ids_through_map() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+    let id_map = dir.path().join("ids.json");
+    convert(
+        &input,
+        &dir.path().join("output.spdx.json"),
+        "cdx-to-spdx",
+        &id_map,
+        &["--spdx-serialization", "json-ld"],
+    );
+
+    // A VEX document about the converted SPDX document; the package has
+    // no purl, so only the map ties it to the SBOM component
+    let vex = json!({
+        "@context": "https://spdx.org/rdf/3.0.1/spdx-context.jsonld",
+        "@graph": [
+            {
+                "type": "software_Package",
+                "spdxId": "https://vendor.example/spdx#SPDXRef-lib",
+                "name": "lib"
+            },
+            {
+                "type": "security_Vulnerability",
+                "spdxId": "https://vendor.example/spdx#vuln-1",
+                "name": "CVE-2024-0001"
+            },
+            {
+                "type": "security_VexNotAffectedVulnAssessmentRelationship",
+                "spdxId": "https://vendor.example/spdx#vex-1",
+                "from": "https://vendor.example/spdx#vuln-1",
+                "relationshipType": "doesNotAffect",
+                "to": ["https://vendor.example/spdx#SPDXRef-lib"],
+                "security_justificationType": "vulnerableCodeNotPresent"
+            }
+        ]
+    });
+    let vex_path = write_json(dir.path(), "vendor.spdx.json", &vex);
+    let output_path = dir.path().join("applied.cdx.json");
+
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("apply")
+        .arg("--sbom")
+        .arg(&input)
+        .arg("--vex")
+        .arg(&vex_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--id-map")
+        .arg(&id_map);
+    cmd.assert().success();
+
+    let result = read_json(&output_path);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 1);
+    assert_eq!(vulnerabilities[0]["affects"], json!([{ "ref": "lib" }]));
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "not_affected");
+}
@@ -183,6 +183,127 @@ fn test_cdx_to_spdx_streaming() {
     assert_eq!(affect_rel["relatedSpdxElement"], "SPDXRef-pkg-b");
 }
 
+#[test]
+fn test_cdx_to_spdx_jsonld_output() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("test.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_cdx()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--spdx-serialization")
+        .arg("json-ld");
+    cmd.assert().success();
+
+    let output_content = fs::read_to_string(&output_path).unwrap();
+    let output_json: Value = serde_json::from_str(&output_content).unwrap();
+
+    // JSON-LD output passes the JSON-LD structural validation...
+    sbom_converter::schema::validate_json_schema(
+        include_str!("../schemas/spdx_3.0.1.schema.json"),
+        &output_path,
+        false,
+    )
+    .unwrap();
+
+    // ...and the SPDX 3.0.1 model constraints: every element is typed,
+    // identified and created; creation info is UTC to the second
+    let is_date_time = |s: &str| {
+        s.len() == 20
+            && s.ends_with('Z')
+            && chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SZ").is_ok()
+    };
+    for element in output_json["@graph"].as_array().unwrap() {
+        assert!(element["type"].is_string(), "untyped element {}", element);
+        if element["type"] == "CreationInfo" {
+            assert!(element["@id"].as_str().unwrap().starts_with("_:"));
+            assert_eq!(element["specVersion"], "3.0.1");
+            assert!(is_date_time(element["created"].as_str().unwrap()));
+            assert!(!element["createdBy"].as_array().unwrap().is_empty());
+            continue;
+        }
+        assert!(element["spdxId"].is_string(), "no spdxId: {}", element);
+        assert_eq!(element["creationInfo"], "_:creationinfo");
+        if element.get("from").is_some() {
+            assert!(element["relationshipType"].is_string());
+            assert!(!element["to"].as_array().unwrap().is_empty());
+        }
+    }
+
+    assert_eq!(
+        output_json["@context"],
+        "https://spdx.org/rdf/3.0.1/spdx-context.jsonld"
+    );
+    let graph = output_json["@graph"].as_array().unwrap();
+    assert_eq!(graph[0]["type"], "CreationInfo");
+    assert_eq!(graph[0]["specVersion"], "3.0.1");
+    assert_eq!(graph.last().unwrap()["type"], "SpdxDocument");
+
+    let of_type = |t: &str| graph.iter().filter(|e| e["type"] == t).collect::<Vec<_>>();
+    let packages = of_type("software_Package");
+    assert_eq!(packages.len(), 2);
+    let pkg_a = packages.iter().find(|e| e["name"] == "Package A").unwrap();
+    assert_eq!(pkg_a["software_packageVersion"], "1.0.0");
+    assert!(
+        pkg_a["spdxId"]
+            .as_str()
+            .unwrap()
+            .ends_with("#SPDXRef-pkg-a")
+    );
+    assert_eq!(of_type("security_Vulnerability").len(), 1);
+    assert_eq!(of_type("simplelicensing_LicenseExpression").len(), 1);
+
+    // dependsOn, affects and hasConcludedLicense, all with from/to
+    let relationships = of_type("Relationship");
+    assert_eq!(relationships.len(), 3);
+    let dep_rel = relationships
+        .iter()
+        .find(|r| r["relationshipType"] == "dependsOn")
+        .unwrap();
+    assert_eq!(dep_rel["from"], pkg_a["spdxId"]);
+    assert!(
+        dep_rel["to"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("#SPDXRef-pkg-b")
+    );
+    assert!(
+        relationships
+            .iter()
+            .any(|r| r["relationshipType"] == "affects")
+    );
+
+    // The JSON-LD output reads back through the SPDX -> CDX converter
+    let roundtrip_path = dir.path().join("roundtrip.cdx.json");
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&output_path)
+        .arg("--output")
+        .arg(&roundtrip_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx");
+    cmd.assert().success();
+
+    let roundtrip: Value =
+        serde_json::from_str(&fs::read_to_string(roundtrip_path).unwrap()).unwrap();
+    let components = roundtrip["components"].as_array().unwrap();
+    assert_eq!(components.len(), 2);
+    let comp_a = components
+        .iter()
+        .find(|c| c["name"] == "Package A")
+        .unwrap();
+    assert_eq!(comp_a["version"], "1.0.0");
+    assert_eq!(roundtrip["dependencies"].as_array().unwrap().len(), 1);
+}
+
 #[test]
 fn test_spdx_to_cdx_streaming() {
     let dir = tempdir().unwrap();
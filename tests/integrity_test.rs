@@ -0,0 +1,285 @@
+//! Integration tests for `--dangling-refs`.
+//!
+//! Tests that refs to elements missing from the output (files dropped by
+//! `--packages-only`, unknown bom-refs) are pruned, rewritten to their
+//! containing package, or fail the conversion, and that every change is
+//! listed in the fidelity report.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// An SPDX 2.3 document whose package contains a file with a dependency
+/// of its own
+fn get_test_spdx23() -> Value {
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "app",
+        "documentNamespace": "https://example.com/spdxdocs/app-1.0",
+        "creationInfo": {
+            "created": "2025-01-01T00:00:00Z",
+            "creators": ["Tool: example-generator-1.0"]
+        },
+        "packages": [
+            { "SPDXID": "SPDXRef-app", "name": "app", "versionInfo": "1.0.0", "downloadLocation": "NOASSERTION" },
+            { "SPDXID": "SPDXRef-lib", "name": "lib", "versionInfo": "2.0.0", "downloadLocation": "NOASSERTION" }
+        ],
+        "files": [
+            { "SPDXID": "SPDXRef-main", "fileName": "./main.c" }
+        ],
+        "relationships": [
+            { "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-app" },
+            { "spdxElementId": "SPDXRef-app", "relationshipType": "CONTAINS", "relatedSpdxElement": "SPDXRef-main" },
+            { "spdxElementId": "SPDXRef-main", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-lib" }
+        ]
+    })
+}
+
+/// A CycloneDX BOM whose dependencies and vulnerability name a bom-ref no
+/// component has
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            { "bom-ref": "app", "type": "application", "name": "app" },
+            { "bom-ref": "lib", "type": "library", "name": "lib" }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["lib", "ghost"] }
+        ],
+        "vulnerabilities": [
+            { "id": "CVE-2024-0001", "affects": [{ "ref": "ghost" }, { "ref": "lib" }] }
+        ]
+    })
+}
+
+fn write_json(dir: &Path, name: &str, value: &Value) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, value.to_string()).unwrap();
+    path
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Builds a `convert` command for `input`
+fn convert_cmd(input: &Path, output: &Path, direction: &str, args: &[&str]) -> Command {
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--direction")
+        .arg(direction)
+        .args(args);
+    cmd
+}
+
+/// The `dependsOn` of the dependency entry for `dep_ref`
+fn depends_on<'a>(bom: &'a Value, dep_ref: &str) -> Option<&'a Value> {
+    bom["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["ref"] == dep_ref)
+        .map(|d| &d["dependsOn"])
+}
+
+// --- Tests ---
+
+#[test]
+fn test_packages_only_keeps_dangling_refs_without_policy() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx23());
+    let output = dir.path().join("output.cdx.json");
+
+    convert_cmd(&input, &output, "spdx-to-cdx", &["--packages-only"])
+        .assert()
+        .success();
+    let bom = read_json(&output);
+    assert_eq!(depends_on(&bom, "app"), Some(&json!(["main"])));
+    assert!(depends_on(&bom, "main").is_some());
+}
+
+#[test]
+fn test_prune_removes_refs_to_dropped_files() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx23());
+    let output = dir.path().join("output.cdx.json");
+    let report = dir.path().join("report.json");
+
+    convert_cmd(
+        &input,
+        &output,
+        "spdx-to-cdx",
+        &["--packages-only", "--dangling-refs", "prune"],
+    )
+    .arg("--fidelity-report")
+    .arg(&report)
+    .assert()
+    .success();
+
+    let bom = read_json(&output);
+    assert_eq!(bom["dependencies"], json!([]));
+
+    let report = read_json(&report);
+    let pruned = report["dropped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["path"] == "dangling_ref.dependencies.dependsOn")
+        .unwrap();
+    assert_eq!(pruned["examples"], json!(["main"]));
+}
+
+#[test]
+fn test_rewrite_points_refs_at_containing_package() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx23());
+    let output = dir.path().join("output.cdx.json");
+
+    convert_cmd(
+        &input,
+        &output,
+        "spdx-to-cdx",
+        &["--packages-only", "--dangling-refs", "rewrite"],
+    )
+    .assert()
+    .success();
+
+    // The file's dependency moves to its package, which does not depend
+    // on itself
+    let bom = read_json(&output);
+    assert_eq!(
+        bom["dependencies"],
+        json!([{ "ref": "app", "dependsOn": ["lib"] }])
+    );
+}
+
+#[test]
+fn test_fail_policy_rejects_dangling_refs() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx23());
+    let output_path = dir.path().join("output.cdx.json");
+
+    let output = convert_cmd(
+        &input,
+        &output_path,
+        "spdx-to-cdx",
+        &["--packages-only", "--dangling-refs", "fail"],
+    )
+    .output()
+    .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("dangling ref(s)"));
+    assert!(stderr.contains("dependencies.dependsOn: main"));
+    // No output with the dangling refs is left behind
+    assert!(!output_path.exists());
+
+    // Without --packages-only every ref resolves
+    convert_cmd(
+        &input,
+        &dir.path().join("full.cdx.json"),
+        "spdx-to-cdx",
+        &["--dangling-refs", "fail"],
+    )
+    .assert()
+    .success();
+}
+
+#[test]
+fn test_prune_cdx_to_spdx_relationships() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+
+    for (name, args) in [
+        ("output.spdx23.json", vec!["--spdx-version", "2.3"]),
+        ("output.spdx.json", vec![]),
+    ] {
+        let output = dir.path().join(name);
+        let mut args = args;
+        args.extend(["--dangling-refs", "prune"]);
+        convert_cmd(&input, &output, "cdx-to-spdx", &args)
+            .assert()
+            .success();
+
+        let doc = read_json(&output);
+        let relationships = doc["relationships"].as_array().unwrap();
+        assert!(relationships.iter().all(|r| {
+            r["spdxElementId"] != "SPDXRef-ghost" && r["relatedSpdxElement"] != "SPDXRef-ghost"
+        }));
+        assert!(
+            relationships
+                .iter()
+                .any(|r| r["relatedSpdxElement"] == "SPDXRef-lib")
+        );
+    }
+}
+
+#[test]
+fn test_prune_affects_in_split_vex() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+    let spdx = dir.path().join("output.spdx.json");
+    convert_cmd(&input, &spdx, "cdx-to-spdx", &[])
+        .assert()
+        .success();
+
+    // The SPDX document still has the ghost; its CycloneDX conversion
+    // splits the vulnerabilities off into a VEX document
+    let output = dir.path().join("output.cdx.json");
+    let vex = dir.path().join("output.vex.json");
+    convert_cmd(
+        &spdx,
+        &output,
+        "spdx-to-cdx",
+        &["--split-vex", "--dangling-refs", "prune"],
+    )
+    .assert()
+    .success();
+
+    let vex = read_json(&vex);
+    let affects = vex["vulnerabilities"][0]["affects"].as_array().unwrap();
+    assert_eq!(affects.len(), 1);
+    assert!(affects[0]["ref"].as_str().unwrap().ends_with("#lib"));
+}
+
+#[test]
+fn test_fail_policy_removes_split_vex() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+    let spdx = dir.path().join("output.spdx.json");
+    convert_cmd(&input, &spdx, "cdx-to-spdx", &[])
+        .assert()
+        .success();
+
+    let output = dir.path().join("output.cdx.json");
+    let vex = dir.path().join("output.vex.json");
+    convert_cmd(
+        &spdx,
+        &output,
+        "spdx-to-cdx",
+        &["--split-vex", "--dangling-refs", "fail"],
+    )
+    .assert()
+    .failure();
+    assert!(!output.exists());
+    assert!(!vex.exists());
+}
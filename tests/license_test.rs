@@ -0,0 +1,257 @@
+//! Integration tests for license mapping between CycloneDX and SPDX.
+//!
+//! Tests that every license choice is carried across, that declared and
+//! concluded licenses stay apart, that custom license text survives and
+//! that expressions are normalized.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX BOM with combined, acknowledged and custom licenses
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "multi",
+                "type": "library",
+                "name": "multi",
+                "licenses": [
+                    { "license": { "id": "MIT" } },
+                    { "expression": "Apache-2.0 OR BSD-3-Clause" }
+                ]
+            },
+            {
+                "bom-ref": "acknowledged",
+                "type": "library",
+                "name": "acknowledged",
+                "licenses": [
+                    { "license": { "id": "GPL-2.0-only", "acknowledgement": "declared" } },
+                    { "license": { "id": "MIT", "acknowledgement": "concluded" } }
+                ]
+            },
+            {
+                "bom-ref": "custom",
+                "type": "library",
+                "name": "custom",
+                "licenses": [{
+                    "license": {
+                        "name": "Acme Proprietary",
+                        "text": { "content": "All rights reserved." }
+                    }
+                }]
+            }
+        ]
+    })
+}
+
+/// Runs one conversion and returns the parsed output
+fn convert(input: &Path, output: &Path, direction: &str, args: &[&str]) -> Value {
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--direction")
+        .arg(direction)
+        .args(args);
+    cmd.assert().success();
+    serde_json::from_str(&fs::read_to_string(output).unwrap()).unwrap()
+}
+
+/// Converts the test BOM to SPDX with `args` and back to CycloneDX
+fn round_trip(dir: &Path, args: &[&str]) -> (Value, Value) {
+    let input_path = dir.join("input.cdx.json");
+    let spdx_path = dir.join("output.spdx.json");
+    let cdx_path = dir.join("back.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    let spdx = convert(&input_path, &spdx_path, "cdx-to-spdx", args);
+    let cdx = convert(&spdx_path, &cdx_path, "spdx-to-cdx", &[]);
+    (spdx, cdx)
+}
+
+fn component_licenses<'a>(bom: &'a Value, name: &str) -> &'a Value {
+    &bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == name)
+        .unwrap()["licenses"]
+}
+
+/// The licenses every layout must bring back to CycloneDX
+fn assert_cdx_licenses(cdx: &Value) {
+    assert_eq!(
+        component_licenses(cdx, "multi"),
+        &json!([{ "expression": "MIT AND (Apache-2.0 OR BSD-3-Clause)" }])
+    );
+    assert_eq!(
+        component_licenses(cdx, "acknowledged"),
+        &json!([
+            { "expression": "MIT", "acknowledgement": "concluded" },
+            { "expression": "GPL-2.0-only", "acknowledgement": "declared" }
+        ])
+    );
+    let custom = &component_licenses(cdx, "custom")[0]["license"];
+    assert_eq!(custom["name"], "Acme Proprietary");
+    assert_eq!(custom["text"]["content"], "All rights reserved.");
+}
+
+// --- Tests ---
+
+#[test]
+fn test_spdx23_license_round_trip() {
+    let dir = tempdir().unwrap();
+    let (spdx, cdx) = round_trip(dir.path(), &["--spdx-version", "2.3"]);
+
+    let package = |name: &str| {
+        spdx["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["name"] == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(
+        package("multi")["licenseConcluded"],
+        "MIT AND (Apache-2.0 OR BSD-3-Clause)"
+    );
+    assert_eq!(package("acknowledged")["licenseConcluded"], "MIT");
+    assert_eq!(package("acknowledged")["licenseDeclared"], "GPL-2.0-only");
+    assert_eq!(
+        package("custom")["licenseConcluded"],
+        "LicenseRef-Acme-Proprietary"
+    );
+    assert_eq!(
+        spdx["hasExtractedLicensingInfos"],
+        json!([{
+            "licenseId": "LicenseRef-Acme-Proprietary",
+            "extractedText": "All rights reserved.",
+            "name": "Acme Proprietary"
+        }])
+    );
+
+    assert_cdx_licenses(&cdx);
+}
+
+#[test]
+fn test_jsonld_license_round_trip() {
+    let dir = tempdir().unwrap();
+    let (spdx, cdx) = round_trip(dir.path(), &["--spdx-serialization", "json-ld"]);
+
+    let graph = spdx["@graph"].as_array().unwrap();
+    let text = graph
+        .iter()
+        .find(|e| e["type"] == "simplelicensing_SimpleLicensingText")
+        .unwrap();
+    assert_eq!(text["simplelicensing_licenseText"], "All rights reserved.");
+
+    // The LicenseRef- id points at the licensing text element
+    let custom = graph
+        .iter()
+        .find(|e| e["simplelicensing_licenseExpression"] == "LicenseRef-Acme-Proprietary")
+        .unwrap();
+    assert_eq!(
+        custom["simplelicensing_customIdToUri"][0]["value"],
+        text["spdxId"]
+    );
+    assert_eq!(
+        graph
+            .iter()
+            .filter(|e| e["relationshipType"] == "hasDeclaredLicense")
+            .count(),
+        1
+    );
+
+    assert_cdx_licenses(&cdx);
+}
+
+#[test]
+fn test_simple_license_round_trip() {
+    let dir = tempdir().unwrap();
+    let (_, cdx) = round_trip(dir.path(), &["--spdx-serialization", "simple"]);
+    assert_cdx_licenses(&cdx);
+}
+
+#[test]
+fn test_spdx23_licenses_for_cdx_1_5() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+    let spdx = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "demo",
+        "documentNamespace": "https://example.com/demo",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "packages": [{
+            "SPDXID": "SPDXRef-lib",
+            "name": "lib",
+            "downloadLocation": "NOASSERTION",
+            "licenseConcluded": "MIT",
+            "licenseDeclared": "GPL-2.0-only"
+        }]
+    });
+    fs::write(&input_path, spdx.to_string()).unwrap();
+
+    // CycloneDX 1.5 has no acknowledgement, so only the concluded license fits
+    let cdx = convert(
+        &input_path,
+        &output_path,
+        "spdx-to-cdx",
+        &["--output-version", "1.5"],
+    );
+    assert_eq!(
+        component_licenses(&cdx, "lib"),
+        &json!([{ "expression": "MIT" }])
+    );
+}
+
+#[test]
+fn test_license_expressions_normalized() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+    let cdx = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [{
+            "bom-ref": "legacy",
+            "type": "library",
+            "name": "legacy",
+            "licenses": [
+                { "expression": "gpl-2.0+ or mit" },
+                { "license": { "id": "GPL-2.0-with-classpath-exception" } }
+            ]
+        }]
+    });
+    fs::write(&input_path, cdx.to_string()).unwrap();
+
+    let spdx = convert(
+        &input_path,
+        &output_path,
+        "cdx-to-spdx",
+        &["--spdx-version", "2.3"],
+    );
+    assert_eq!(
+        spdx["packages"][0]["licenseConcluded"],
+        "(GPL-2.0-or-later OR MIT) AND GPL-2.0-only WITH Classpath-exception-2.0"
+    );
+}
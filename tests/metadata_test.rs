@@ -0,0 +1,274 @@
+//! Integration tests for mapping CycloneDX `metadata` to the SPDX document.
+//!
+//! Tests that the BOM subject becomes the document's root element and that
+//! the document name and creation info come from the source metadata.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX BOM describing `my-app` 2.0
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "metadata": {
+            "timestamp": "2024-05-01T10:00:00+02:00",
+            "tools": {
+                "components": [{ "type": "application", "name": "syft", "version": "1.0" }]
+            },
+            "authors": [{ "name": "Jane Doe", "email": "jane@example.com" }],
+            "supplier": { "name": "Acme" },
+            "lifecycles": [{ "phase": "build" }],
+            "component": {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "my-app",
+                "version": "2.0"
+            }
+        },
+        "components": [
+            { "bom-ref": "lib", "type": "library", "name": "lib", "version": "1.0" }
+        ],
+        "dependencies": [{ "ref": "app", "dependsOn": ["lib"] }]
+    })
+}
+
+/// Converts `cdx` to SPDX with the given extra arguments
+fn convert(dir: &Path, cdx: &Value, args: &[&str]) -> Value {
+    let input_path = dir.join("input.cdx.json");
+    let output_path = dir.join("output.spdx.json");
+    fs::write(&input_path, cdx.to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .args(args);
+    cmd.assert().success();
+
+    serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_spdx23_document_from_metadata() {
+    let dir = tempdir().unwrap();
+    let spdx = convert(dir.path(), &get_test_cdx(), &["--spdx-version", "2.3"]);
+
+    assert_eq!(spdx["name"], "my-app-2.0");
+    assert_eq!(spdx["creationInfo"]["created"], "2024-05-01T08:00:00Z");
+    assert_eq!(
+        spdx["creationInfo"]["creators"],
+        json!([
+            "Person: Jane Doe (jane@example.com)",
+            "Tool: syft-1.0",
+            format!("Tool: sbom-converter-{}", env!("CARGO_PKG_VERSION"))
+        ])
+    );
+
+    // Only the subject is described; the BOM supplier becomes its supplier
+    assert_eq!(spdx["documentDescribes"], json!(["SPDXRef-app"]));
+    let root = spdx["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["SPDXID"] == "SPDXRef-app")
+        .unwrap();
+    assert_eq!(root["supplier"], "Organization: Acme");
+
+    let relationships = spdx["relationships"].as_array().unwrap();
+    assert!(relationships.iter().any(|r| {
+        r["spdxElementId"] == "SPDXRef-DOCUMENT"
+            && r["relationshipType"] == "DESCRIBES"
+            && r["relatedSpdxElement"] == "SPDXRef-app"
+    }));
+    assert!(relationships.iter().any(|r| {
+        r["spdxElementId"] == "SPDXRef-app" && r["relatedSpdxElement"] == "SPDXRef-lib"
+    }));
+}
+
+#[test]
+fn test_jsonld_root_element_from_metadata() {
+    let dir = tempdir().unwrap();
+    let spdx = convert(
+        dir.path(),
+        &get_test_cdx(),
+        &["--spdx-serialization", "json-ld"],
+    );
+
+    let graph = spdx["@graph"].as_array().unwrap();
+    let of_type = |t: &'static str| graph.iter().filter(move |e| e["type"] == t);
+
+    let document = of_type("SpdxDocument").next().unwrap();
+    assert_eq!(document["name"], "my-app-2.0");
+    let root_element = document["rootElement"].as_array().unwrap();
+    assert_eq!(root_element.len(), 1);
+    assert!(root_element[0].as_str().unwrap().ends_with("#SPDXRef-app"));
+
+    let describes = of_type("Relationship")
+        .find(|r| r["relationshipType"] == "describes")
+        .unwrap();
+    assert_eq!(describes["from"], document["spdxId"]);
+    assert_eq!(describes["to"], document["rootElement"]);
+
+    let creation_info = of_type("CreationInfo").next().unwrap();
+    assert_eq!(creation_info["created"], "2024-05-01T08:00:00Z");
+    assert_eq!(creation_info["createdBy"].as_array().unwrap().len(), 2);
+    assert_eq!(of_type("Person").next().unwrap()["name"], "Jane Doe");
+    assert!(of_type("Tool").any(|t| t["name"] == "syft-1.0"));
+}
+
+#[test]
+fn test_simple_document_from_metadata() {
+    let dir = tempdir().unwrap();
+    let spdx = convert(
+        dir.path(),
+        &get_test_cdx(),
+        &["--spdx-serialization", "simple"],
+    );
+
+    assert_eq!(spdx["name"], "my-app-2.0");
+    assert_eq!(spdx["creationInfo"]["created"], "2024-05-01T08:00:00+00:00");
+    assert!(spdx["relationships"].as_array().unwrap().iter().any(|r| {
+        r["spdxElementId"] == "SPDXRef-DOCUMENT"
+            && r["relationshipType"] == "DESCRIBES"
+            && r["relatedSpdxElement"] == "SPDXRef-app"
+    }));
+}
+
+#[test]
+fn test_without_metadata_component() {
+    let dir = tempdir().unwrap();
+    let mut cdx = get_test_cdx();
+    cdx["metadata"].as_object_mut().unwrap().remove("component");
+    let spdx = convert(dir.path(), &cdx, &["--spdx-version", "2.3"]);
+
+    // Falls back to the generic name and describes every top-level package
+    assert_eq!(spdx["name"], "Converted SBOM");
+    assert_eq!(spdx["documentDescribes"], json!(["SPDXRef-lib"]));
+    assert_eq!(spdx["creationInfo"]["created"], "2024-05-01T08:00:00Z");
+}
+
+#[test]
+fn test_metadata_fidelity_report() {
+    let dir = tempdir().unwrap();
+    let mut cdx = get_test_cdx();
+    cdx["metadata"]["timestamp"] = json!("last tuesday");
+    convert(
+        dir.path(),
+        &cdx,
+        &["--spdx-version", "2.3", "--fidelity-report"],
+    );
+
+    let report: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("output.spdx.fidelity.json")).unwrap(),
+    )
+    .unwrap();
+    let paths: Vec<&str> = report["dropped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["metadata.lifecycles", "metadata.timestamp"]);
+}
+
+#[test]
+fn test_metadata_read_in_either_order() {
+    let dir = tempdir().unwrap();
+    let cdx = get_test_cdx();
+    let args = ["--spdx-version", "2.3", "--reproducible"];
+    // `json!` sorts the keys, so `components` comes before `metadata`
+    let late = convert(dir.path(), &cdx, &args);
+
+    // Written by hand with `metadata` first, as generators do
+    let mut body = cdx.as_object().unwrap().clone();
+    let metadata = body.remove("metadata").unwrap();
+    let early = format!(
+        "{{\"metadata\":{},{}",
+        metadata,
+        &Value::Object(body).to_string()[1..]
+    );
+    let input_path = dir.path().join("early.cdx.json");
+    let output_path = dir.path().join("early.spdx.json");
+    fs::write(&input_path, early).unwrap();
+    get_cmd()
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .args(args)
+        .assert()
+        .success();
+
+    // Only the namespace, derived from the input bytes, differs
+    let mut early: Value =
+        serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    early["documentNamespace"] = late["documentNamespace"].clone();
+    assert_eq!(early, late);
+    assert_eq!(early["name"], "my-app-2.0");
+}
+
+#[test]
+fn test_metadata_component_repeated_in_components() {
+    let dir = tempdir().unwrap();
+    let mut cdx = get_test_cdx();
+    cdx["components"].as_array_mut().unwrap().push(json!({
+        "bom-ref": "app",
+        "type": "application",
+        "name": "my-app",
+        "version": "2.0",
+        "components": [{ "bom-ref": "plugin", "type": "library", "name": "plugin" }]
+    }));
+
+    let spdx = convert(dir.path(), &cdx, &["--spdx-version", "2.3"]);
+    let packages = spdx["packages"].as_array().unwrap();
+    let ids: Vec<&str> = packages
+        .iter()
+        .map(|p| p["SPDXID"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["SPDXRef-app", "SPDXRef-lib", "SPDXRef-plugin"]);
+    assert_eq!(spdx["documentDescribes"], json!(["SPDXRef-app"]));
+    // The copy's nested components go under the root element
+    assert!(spdx["relationships"].as_array().unwrap().iter().any(|r| {
+        r["spdxElementId"] == "SPDXRef-app"
+            && r["relationshipType"] == "CONTAINS"
+            && r["relatedSpdxElement"] == "SPDXRef-plugin"
+    }));
+
+    for serialization in ["simple", "json-ld"] {
+        let spdx = convert(dir.path(), &cdx, &["--spdx-serialization", serialization]);
+        let elements = spdx
+            .get("elements")
+            .unwrap_or(&spdx["@graph"])
+            .as_array()
+            .unwrap();
+        let roots = elements
+            .iter()
+            .filter(|e| {
+                e["spdxId"]
+                    .as_str()
+                    .is_some_and(|id| id.ends_with("SPDXRef-app"))
+            })
+            .count();
+        assert_eq!(roots, 1, "{} layout", serialization);
+    }
+}
@@ -232,7 +232,7 @@ fn test_output_version_with_vex_split() {
     let dir = tempdir().unwrap();
     let input_path = dir.path().join("input.spdx.json");
     let output_path = dir.path().join("output.cdx.json");
-    let vex_path = dir.path().join("input.spdx.vex.json");
+    let vex_path = dir.path().join("output.vex.json");
 
     // Create SPDX input with vulnerability data
     let spdx_with_vuln = json!({
@@ -301,11 +301,9 @@ fn test_output_version_with_vex_split() {
     assert_eq!(output_json["specVersion"], "1.5");
 
     // Verify VEX file also has correct version
-    if vex_path.exists() {
-        let vex_str = fs::read_to_string(&vex_path).unwrap();
-        let vex_json: Value = serde_json::from_str(&vex_str).unwrap();
-        assert_eq!(vex_json["specVersion"], "1.5");
-    }
+    let vex_str = fs::read_to_string(&vex_path).unwrap();
+    let vex_json: Value = serde_json::from_str(&vex_str).unwrap();
+    assert_eq!(vex_json["specVersion"], "1.5");
 }
 
 #[test]
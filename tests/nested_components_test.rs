@@ -0,0 +1,250 @@
+//! Integration tests for nested CycloneDX components (assemblies).
+//!
+//! Tests that nested components become SPDX elements linked by `CONTAINS`
+//! relationships, and that `--nest-components` rebuilds the nesting.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A container image with two layers, one of which nests a library
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "image",
+                "type": "container",
+                "name": "image",
+                "version": "1.0.0",
+                "components": [
+                    {
+                        "bom-ref": "layer-1",
+                        "type": "library",
+                        "name": "layer-1",
+                        "components": [
+                            { "bom-ref": "openssl", "type": "library", "name": "openssl", "version": "3.0.13" }
+                        ]
+                    },
+                    { "bom-ref": "layer-2", "type": "library", "name": "layer-2" }
+                ]
+            },
+            { "bom-ref": "lodash", "type": "library", "name": "lodash", "version": "4.17.21" }
+        ],
+        "dependencies": [
+            { "ref": "image", "dependsOn": ["lodash"] }
+        ]
+    })
+}
+
+/// Runs a conversion in the given direction with extra arguments
+fn convert(input_path: &Path, output_path: &Path, direction: &str, extra: &[&str]) {
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input_path)
+        .arg("--output")
+        .arg(output_path)
+        .arg("--direction")
+        .arg(direction)
+        .args(extra);
+    cmd.assert().success();
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Relationships of the given type as (from, to) pairs
+fn relationships(spdx: &Value, relationship_type: &str) -> Vec<(String, String)> {
+    spdx["relationships"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["relationshipType"] == relationship_type)
+        .map(|r| {
+            (
+                r["spdxElementId"].as_str().unwrap().to_string(),
+                r["relatedSpdxElement"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_nested_components_to_spdx23() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &output_path,
+        "cdx-to-spdx",
+        &["--spdx-version", "2.3"],
+    );
+    let output = read_json(&output_path);
+
+    let names: Vec<&str> = output["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["image", "layer-1", "openssl", "layer-2", "lodash"]);
+
+    let contains = relationships(&output, "CONTAINS");
+    assert_eq!(contains.len(), 3);
+    assert!(contains.contains(&("SPDXRef-image".to_string(), "SPDXRef-layer-1".to_string())));
+    assert!(contains.contains(&("SPDXRef-layer-1".to_string(), "SPDXRef-openssl".to_string())));
+    assert_eq!(relationships(&output, "DEPENDS_ON").len(), 1);
+
+    // Only top-level components describe the document
+    assert_eq!(
+        output["documentDescribes"],
+        json!(["SPDXRef-image", "SPDXRef-lodash"])
+    );
+}
+
+#[test]
+fn test_nested_components_to_jsonld() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &output_path,
+        "cdx-to-spdx",
+        &["--spdx-serialization", "json-ld"],
+    );
+    let output = read_json(&output_path);
+    let graph = output["@graph"].as_array().unwrap();
+
+    let packages = graph
+        .iter()
+        .filter(|e| e["type"] == "software_Package")
+        .count();
+    assert_eq!(packages, 5);
+
+    let contains: Vec<&Value> = graph
+        .iter()
+        .filter(|e| e["type"] == "Relationship" && e["relationshipType"] == "contains")
+        .collect();
+    assert_eq!(contains.len(), 3);
+    assert!(
+        contains[0]["from"]
+            .as_str()
+            .unwrap()
+            .ends_with("#SPDXRef-image")
+    );
+}
+
+#[test]
+fn test_nesting_rebuilt_from_contains() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let spdx_path = dir.path().join("middle.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &spdx_path,
+        "cdx-to-spdx",
+        &["--spdx-version", "2.3"],
+    );
+    convert(
+        &spdx_path,
+        &output_path,
+        "spdx-to-cdx",
+        &["--nest-components"],
+    );
+    let output = read_json(&output_path);
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 2);
+    let image = components.iter().find(|c| c["name"] == "image").unwrap();
+    let layers = image["components"].as_array().unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0]["bom-ref"], "layer-1");
+    assert_eq!(layers[0]["components"][0]["name"], "openssl");
+
+    // Containment is not duplicated as a dependency
+    assert_eq!(output["dependencies"], get_test_cdx()["dependencies"]);
+}
+
+#[test]
+fn test_contains_stays_flat_without_flag() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let spdx_path = dir.path().join("middle.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &spdx_path,
+        "cdx-to-spdx",
+        &["--spdx-version", "2.3"],
+    );
+    convert(&spdx_path, &output_path, "spdx-to-cdx", &[]);
+    let output = read_json(&output_path);
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 5);
+    assert!(components.iter().all(|c| c.get("components").is_none()));
+}
+
+#[test]
+fn test_nesting_survives_contains_cycle() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+    let spdx = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "cycle",
+        "documentNamespace": "https://example.com/cycle",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "packages": [
+            { "SPDXID": "SPDXRef-a", "name": "a", "downloadLocation": "NOASSERTION" },
+            { "SPDXID": "SPDXRef-b", "name": "b", "downloadLocation": "NOASSERTION" }
+        ],
+        "relationships": [
+            { "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-a" },
+            { "spdxElementId": "SPDXRef-a", "relationshipType": "CONTAINS", "relatedSpdxElement": "SPDXRef-b" },
+            { "spdxElementId": "SPDXRef-b", "relationshipType": "CONTAINS", "relatedSpdxElement": "SPDXRef-a" }
+        ]
+    });
+    fs::write(&input_path, spdx.to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &output_path,
+        "spdx-to-cdx",
+        &["--nest-components"],
+    );
+    let output = read_json(&output_path);
+
+    // Both packages are written exactly once
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 1);
+    assert_eq!(components[0]["components"].as_array().unwrap().len(), 1);
+}
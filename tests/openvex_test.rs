@@ -0,0 +1,280 @@
+//! Integration tests for the `vex` subcommand with OpenVEX documents.
+//!
+//! Tests that CycloneDX VEX documents convert to OpenVEX and back, and that
+//! OpenVEX statements are applied onto a CycloneDX SBOM by purl.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX SBOM with two versions of lodash and log4j
+fn get_test_sbom() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "serialNumber": "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79",
+        "version": 1,
+        "metadata": {
+            "timestamp": "2024-05-01T00:00:00Z",
+            "authors": [{ "name": "ACME Security" }]
+        },
+        "components": [
+            {
+                "bom-ref": "lodash-old",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.15",
+                "purl": "pkg:npm/lodash@4.17.15"
+            },
+            {
+                "bom-ref": "lodash-new",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21"
+            },
+            {
+                "bom-ref": "log4j",
+                "type": "library",
+                "name": "log4j-core",
+                "version": "2.14.1",
+                "purl": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1?type=jar"
+            }
+        ]
+    })
+}
+
+/// A CycloneDX VEX document for the test SBOM
+fn get_test_cdx_vex() -> Value {
+    let mut vex = get_test_sbom();
+    vex["vulnerabilities"] = json!([
+        {
+            "id": "CVE-2021-44228",
+            "source": { "name": "NVD", "url": "https://nvd.nist.gov/vuln/detail/CVE-2021-44228" },
+            "analysis": {
+                "state": "exploitable",
+                "response": ["update"],
+                "firstIssued": "2021-12-10T00:00:00Z"
+            },
+            "affects": [{ "ref": "log4j" }]
+        },
+        {
+            "id": "CVE-2021-23337",
+            "analysis": {
+                "state": "not_affected",
+                "justification": "code_not_reachable",
+                "detail": "template() is never called"
+            },
+            "affects": [{ "ref": "lodash-old" }, { "ref": "lodash-new" }]
+        }
+    ]);
+    vex
+}
+
+/// Runs `vex convert` and returns the parsed output
+fn convert(input: &Path, output: &Path, to: &str) -> Value {
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--to")
+        .arg(to);
+    cmd.assert().success();
+    serde_json::from_str(&fs::read_to_string(output).unwrap()).unwrap()
+}
+
+/// Runs `vex apply` with a JSON report
+fn apply(sbom: &Path, vex: &Path, output: &Path) -> (Output, Value) {
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("apply")
+        .arg("--sbom")
+        .arg(sbom)
+        .arg("--vex")
+        .arg(vex)
+        .arg("--output")
+        .arg(output)
+        .arg("--report-format")
+        .arg("json");
+    let output = cmd.output().unwrap();
+    let report = serde_json::from_slice(&output.stdout).unwrap();
+    (output, report)
+}
+
+// --- Tests ---
+
+#[test]
+fn test_cdx_vex_to_openvex_and_back() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("vex.cdx.json");
+    let openvex_path = dir.path().join("vex.openvex.json");
+    let output_path = dir.path().join("roundtrip.cdx.json");
+    fs::write(&input_path, get_test_cdx_vex().to_string()).unwrap();
+
+    let openvex = convert(&input_path, &openvex_path, "openvex");
+    assert_eq!(openvex["@context"], "https://openvex.dev/ns/v0.2.0");
+    assert_eq!(
+        openvex["@id"],
+        "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79"
+    );
+    assert_eq!(openvex["author"], "ACME Security");
+    assert_eq!(openvex["timestamp"], "2024-05-01T00:00:00Z");
+
+    let statements = openvex["statements"].as_array().unwrap();
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0]["vulnerability"]["name"], "CVE-2021-44228");
+    assert_eq!(
+        statements[0]["vulnerability"]["@id"],
+        "https://nvd.nist.gov/vuln/detail/CVE-2021-44228"
+    );
+    assert_eq!(statements[0]["status"], "affected");
+    assert_eq!(statements[0]["action_statement"], "update");
+    assert_eq!(
+        statements[0]["products"][0]["@id"],
+        "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1?type=jar"
+    );
+    assert_eq!(statements[1]["status"], "not_affected");
+    assert_eq!(
+        statements[1]["justification"],
+        "vulnerable_code_not_in_execute_path"
+    );
+    assert_eq!(
+        statements[1]["impact_statement"],
+        "template() is never called"
+    );
+    assert_eq!(statements[1]["products"].as_array().unwrap().len(), 2);
+
+    let cdx = convert(&openvex_path, &output_path, "cyclonedx");
+    assert_eq!(cdx["bomFormat"], "CycloneDX");
+    assert_eq!(cdx["serialNumber"], openvex["@id"]);
+    let vulnerabilities = cdx["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 2);
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "exploitable");
+    assert_eq!(
+        vulnerabilities[0]["analysis"]["response"],
+        json!(["update"])
+    );
+    assert_eq!(
+        vulnerabilities[0]["analysis"]["firstIssued"],
+        "2021-12-10T00:00:00Z"
+    );
+    assert_eq!(vulnerabilities[0]["source"]["name"], "nvd.nist.gov");
+    assert_eq!(vulnerabilities[1]["analysis"]["state"], "not_affected");
+    assert_eq!(
+        vulnerabilities[1]["analysis"]["justification"],
+        "code_not_reachable"
+    );
+    assert_eq!(
+        vulnerabilities[1]["affects"][1]["ref"],
+        "pkg:npm/lodash@4.17.21"
+    );
+}
+
+#[test]
+fn test_apply_openvex_by_purl() {
+    let dir = tempdir().unwrap();
+    let sbom_path = dir.path().join("sbom.cdx.json");
+    let vex_path = dir.path().join("vex.openvex.json");
+    let output_path = dir.path().join("output.cdx.json");
+
+    let mut sbom = get_test_sbom();
+    sbom["vulnerabilities"] = json!([{
+        "id": "CVE-2021-23337",
+        "analysis": { "state": "in_triage" },
+        "affects": [{ "ref": "lodash-old" }, { "ref": "lodash-new" }]
+    }]);
+    fs::write(&sbom_path, sbom.to_string()).unwrap();
+    let openvex = json!({
+        "@context": "https://openvex.dev/ns/v0.2.0",
+        "@id": "https://example.com/vex/2024-0001",
+        "author": "ACME Security",
+        "timestamp": "2024-06-01T00:00:00Z",
+        "version": 1,
+        "statements": [
+            {
+                "vulnerability": { "name": "CVE-2021-23337" },
+                "products": [{ "@id": "pkg:npm/lodash@4.17.21" }],
+                "status": "fixed"
+            },
+            {
+                "vulnerability": { "name": "CVE-2021-44228" },
+                "products": [
+                    { "@id": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1" },
+                    { "@id": "pkg:pypi/requests@2.31.0" }
+                ],
+                "status": "affected",
+                "action_statement": "Upgrade to 2.17.1"
+            }
+        ]
+    });
+    fs::write(&vex_path, openvex.to_string()).unwrap();
+
+    let (output, report) = apply(&sbom_path, &vex_path, &output_path);
+    assert!(output.status.success());
+    assert_eq!(report["summary"]["warnings"], 1);
+    assert_eq!(
+        report["issues"][0]["location"],
+        format!("{}: statements[1]", vex_path.display())
+    );
+
+    let result: Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    assert_eq!(result["components"], sbom["components"]);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 3);
+
+    // The fixed version is split off, the old one stays in triage
+    assert_eq!(vulnerabilities[0]["id"], "CVE-2021-23337");
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "in_triage");
+    assert_eq!(
+        vulnerabilities[0]["affects"],
+        json!([{ "ref": "lodash-old" }])
+    );
+    assert_eq!(vulnerabilities[1]["id"], "CVE-2021-23337");
+    assert_eq!(vulnerabilities[1]["analysis"]["state"], "resolved");
+    assert_eq!(
+        vulnerabilities[1]["affects"],
+        json!([{ "ref": "lodash-new" }])
+    );
+
+    // Qualifiers are ignored when matching purls
+    assert_eq!(vulnerabilities[2]["id"], "CVE-2021-44228");
+    assert_eq!(vulnerabilities[2]["analysis"]["state"], "exploitable");
+    assert_eq!(
+        vulnerabilities[2]["analysis"]["detail"],
+        "Upgrade to 2.17.1"
+    );
+    assert_eq!(vulnerabilities[2]["affects"], json!([{ "ref": "log4j" }]));
+}
+
+#[test]
+fn test_convert_rejects_same_format() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("vex.cdx.json");
+    fs::write(&input_path, get_test_cdx_vex().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("convert")
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(dir.path().join("out.json"))
+        .arg("--to")
+        .arg("cyclonedx");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is already CycloneDX"));
+}
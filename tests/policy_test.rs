@@ -0,0 +1,159 @@
+//! Integration tests for the `policy` subcommand.
+//!
+//! Tests that JSON and TOML policies are applied to CycloneDX and SPDX
+//! input, and that the exit code reflects denied and needs-review licenses.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX BOM with a permissive, a copyleft and an internal component
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21",
+                "licenses": [{ "expression": "MIT OR GPL-3.0-only" }]
+            },
+            {
+                "type": "library",
+                "name": "readline",
+                "version": "8.2",
+                "purl": "pkg:generic/readline@8.2",
+                "licenses": [{ "license": { "id": "GPL-3.0" } }]
+            },
+            {
+                "type": "library",
+                "name": "internal-ui",
+                "version": "0.1.0",
+                "purl": "pkg:npm/%40acme/internal-ui@0.1.0"
+            }
+        ]
+    })
+}
+
+const TOML_POLICY: &str = r#"
+allowed = ["MIT", "Apache-2.0"]
+denied = ["GPL-3.0-only"]
+needs_review = ["LGPL-2.1-only"]
+
+[[exceptions]]
+purl = "pkg:npm/%40acme/*"
+reason = "First-party code"
+"#;
+
+/// Runs `policy` with a JSON report
+fn run_policy(input: &Path, policy: &Path, args: &[&str]) -> (Output, Value) {
+    let mut cmd = get_cmd();
+    cmd.arg("policy")
+        .arg("--input")
+        .arg(input)
+        .arg("--policy")
+        .arg(policy)
+        .arg("--report-format")
+        .arg("json")
+        .args(args);
+    let output = cmd.output().unwrap();
+    let report = serde_json::from_slice(&output.stdout).unwrap();
+    (output, report)
+}
+
+// --- Tests ---
+
+#[test]
+fn test_policy_denies_license() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let policy_path = dir.path().join("policy.toml");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+    fs::write(&policy_path, TOML_POLICY).unwrap();
+
+    let (output, report) = run_policy(&input_path, &policy_path, &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 denied"));
+
+    // lodash may be used under MIT; the deprecated GPL-3.0 id is upgraded
+    // before matching; the internal component is exempt
+    assert_eq!(report["summary"]["errors"], 1);
+    assert_eq!(report["summary"]["infos"], 1);
+    let issues = report["issues"].as_array().unwrap();
+    assert_eq!(issues[0]["location"], "pkg:generic/readline@8.2");
+    assert_eq!(issues[0]["message"], "Denied license: GPL-3.0-only");
+    assert_eq!(issues[1]["suggestion"], "First-party code");
+}
+
+#[test]
+fn test_json_policy_on_spdx() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let policy_path = dir.path().join("policy.json");
+    let spdx = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "demo",
+        "documentNamespace": "https://example.com/demo",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "packages": [{
+            "SPDXID": "SPDXRef-lib",
+            "name": "lib",
+            "versionInfo": "1.0",
+            "downloadLocation": "NOASSERTION",
+            "licenseConcluded": "LGPL-2.1-only AND MIT"
+        }]
+    });
+    fs::write(&input_path, spdx.to_string()).unwrap();
+    let policy = json!({
+        "allowed": ["MIT"],
+        "needs_review": ["LGPL-2.1-only"]
+    });
+    fs::write(&policy_path, policy.to_string()).unwrap();
+
+    // Licenses needing review are warnings and only fail on request
+    let (output, report) = run_policy(&input_path, &policy_path, &[]);
+    assert!(output.status.success());
+    assert_eq!(report["summary"]["warnings"], 1);
+    assert_eq!(report["issues"][0]["location"], "lib@1.0");
+
+    let (output, _) = run_policy(&input_path, &policy_path, &["--fail-on-review"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_invalid_policy() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let policy_path = dir.path().join("policy.toml");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+    fs::write(
+        &policy_path,
+        "allowed = [\"MIT\"]\nblocked = [\"GPL-3.0-only\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("policy")
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--policy")
+        .arg(&policy_path);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown field `blocked`"));
+}
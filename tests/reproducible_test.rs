@@ -0,0 +1,258 @@
+//! Integration tests for `--reproducible` conversions.
+//!
+//! Tests that converting the same input twice gives byte-identical output,
+//! that serial numbers and namespaces still change with the input, and
+//! that timestamps come from the source document or `SOURCE_DATE_EPOCH`.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// An SPDX 2.3 document with enough relationships to fill several
+/// dependency entries
+fn get_test_spdx() -> Value {
+    let packages: Vec<Value> = ["app", "a", "b", "c", "d", "e"]
+        .iter()
+        .map(|name| {
+            json!({
+                "SPDXID": format!("SPDXRef-{}", name),
+                "name": name,
+                "versionInfo": "1.0.0"
+            })
+        })
+        .collect();
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "app",
+        "documentNamespace": "https://example.com/app",
+        "creationInfo": {
+            "created": "2024-03-01T00:00:00Z",
+            "creators": ["Tool: test"]
+        },
+        "packages": packages,
+        "relationships": [
+            { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-e" },
+            { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-a" },
+            { "spdxElementId": "SPDXRef-e", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-d" },
+            { "spdxElementId": "SPDXRef-d", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-c" },
+            { "spdxElementId": "SPDXRef-c", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-b" },
+            { "spdxElementId": "SPDXRef-b", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-a" }
+        ]
+    })
+}
+
+/// A CycloneDX BOM without a timestamp
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "metadata": {
+            "component": { "bom-ref": "app", "type": "application", "name": "app", "version": "1.0.0" }
+        },
+        "components": [
+            { "bom-ref": "b", "type": "library", "name": "b", "version": "1.0.0" },
+            { "bom-ref": "a", "type": "library", "name": "a", "version": "1.0.0" }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["b", "a"] },
+            { "ref": "b", "dependsOn": ["a"] }
+        ]
+    })
+}
+
+/// Runs a reproducible conversion and returns the output bytes
+fn convert(input: &Path, output: &Path, direction: &str, args: &[&str]) -> Vec<u8> {
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--direction")
+        .arg(direction)
+        .arg("--reproducible")
+        .args(args)
+        .env_remove("SOURCE_DATE_EPOCH");
+    cmd.assert().success();
+    fs::read(output).unwrap()
+}
+
+fn write_json(dir: &Path, name: &str, value: &Value) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, value.to_string()).unwrap();
+    path
+}
+
+// --- Tests ---
+
+#[test]
+fn test_spdx_to_cdx_is_reproducible() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx());
+    let first_path = dir.path().join("first.cdx.json");
+    let second_path = dir.path().join("second.cdx.json");
+
+    let first = convert(&input, &first_path, "spdx-to-cdx", &[]);
+    let second = convert(&input, &second_path, "spdx-to-cdx", &[]);
+    assert_eq!(first, second);
+
+    let bom: Value = serde_json::from_slice(&first).unwrap();
+    assert_eq!(bom["metadata"]["timestamp"], "2024-03-01T00:00:00+00:00");
+    let refs: Vec<&str> = bom["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["ref"].as_str().unwrap())
+        .collect();
+    assert_eq!(refs, ["app", "b", "c", "d", "e"]);
+    assert_eq!(bom["dependencies"][0]["dependsOn"], json!(["a", "e"]));
+
+    // Another input gets another serial number
+    let mut changed = get_test_spdx();
+    changed["packages"][0]["versionInfo"] = json!("1.0.1");
+    let changed_input = write_json(dir.path(), "changed.spdx.json", &changed);
+    let changed_bom: Value = serde_json::from_slice(&convert(
+        &changed_input,
+        &dir.path().join("changed.cdx.json"),
+        "spdx-to-cdx",
+        &[],
+    ))
+    .unwrap();
+    assert_ne!(changed_bom["serialNumber"], bom["serialNumber"]);
+}
+
+#[test]
+fn test_split_vex_is_reproducible() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx());
+
+    let mut outputs = Vec::new();
+    for name in ["first", "second"] {
+        let output = dir.path().join(format!("{}.cdx.json", name));
+        let bom = convert(&input, &output, "spdx-to-cdx", &["--split-vex"]);
+        let vex = fs::read(dir.path().join(format!("{}.vex.json", name))).unwrap();
+        outputs.push((bom, vex));
+    }
+    assert_eq!(outputs[0], outputs[1]);
+
+    let bom: Value = serde_json::from_slice(&outputs[0].0).unwrap();
+    let vex: Value = serde_json::from_slice(&outputs[0].1).unwrap();
+    assert_ne!(bom["serialNumber"], vex["serialNumber"]);
+}
+
+#[test]
+fn test_cdx_to_spdx_is_reproducible() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+
+    for args in [
+        &["--spdx-version", "2.3"][..],
+        &["--spdx-serialization", "json-ld"][..],
+    ] {
+        let first = convert(
+            &input,
+            &dir.path().join("first.spdx.json"),
+            "cdx-to-spdx",
+            args,
+        );
+        let second = convert(
+            &input,
+            &dir.path().join("second.spdx.json"),
+            "cdx-to-spdx",
+            args,
+        );
+        assert_eq!(first, second, "{:?}", args);
+    }
+
+    // Relationships are sorted, not in input order
+    let output = convert(
+        &input,
+        &dir.path().join("output.spdx.json"),
+        "cdx-to-spdx",
+        &["--spdx-version", "2.3"],
+    );
+    let doc: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(doc["creationInfo"]["created"], "1970-01-01T00:00:00Z");
+    let targets: Vec<&str> = doc["relationships"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["relationshipType"] == "DEPENDS_ON")
+        .map(|r| r["relatedSpdxElement"].as_str().unwrap())
+        .collect();
+    assert_eq!(targets, ["SPDXRef-a", "SPDXRef-b", "SPDXRef-a"]);
+}
+
+#[test]
+fn test_source_date_epoch() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.cdx.json", &get_test_cdx());
+    let output = dir.path().join("output.spdx.json");
+
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--spdx-version")
+        .arg("2.3")
+        .arg("--reproducible")
+        .env("SOURCE_DATE_EPOCH", "1700000000");
+    cmd.assert().success();
+    let doc: Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(doc["creationInfo"]["created"], "2023-11-14T22:13:20Z");
+
+    // A malformed value is a configuration error
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--reproducible")
+        .env("SOURCE_DATE_EPOCH", "yesterday");
+    let result = cmd.output().unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("SOURCE_DATE_EPOCH"));
+}
+
+#[test]
+fn test_default_output_is_not_reproducible() {
+    let dir = tempdir().unwrap();
+    let input = write_json(dir.path(), "input.spdx.json", &get_test_spdx());
+
+    let mut serial_numbers = Vec::new();
+    for name in ["first", "second"] {
+        let output = dir.path().join(format!("{}.cdx.json", name));
+        let mut cmd = get_cmd();
+        cmd.arg("convert")
+            .arg("--input")
+            .arg(&input)
+            .arg("--output")
+            .arg(&output)
+            .arg("--direction")
+            .arg("spdx-to-cdx");
+        cmd.assert().success();
+        let bom: Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        serial_numbers.push(bom["serialNumber"].clone());
+    }
+    assert_ne!(serial_numbers[0], serial_numbers[1]);
+}
@@ -0,0 +1,187 @@
+//! Integration tests for the `roundtrip` subcommand.
+//!
+//! Tests that losses from converting to the other format family and back
+//! are reported, and that the exit code follows the `--max-loss` threshold.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX BOM with hashes, licenses, dependencies and a vulnerability
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "app",
+                "version": "1.0.0"
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21",
+                "hashes": [{ "alg": "SHA-256", "content": "abc123" }],
+                "licenses": [{ "license": { "id": "MIT" } }]
+            }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["lodash"] }
+        ],
+        "vulnerabilities": [
+            { "id": "CVE-2021-23337", "affects": [{ "ref": "lodash" }] }
+        ]
+    })
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_roundtrip_reports_losses_and_fails() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    // SPDX 2.3 has no vulnerability elements, so the round trip loses one
+    let mut cmd = get_cmd();
+    cmd.arg("roundtrip")
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--spdx-version")
+        .arg("2.3");
+    let output = cmd.output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stdout.contains("Round trip: cdx-to-spdx → spdx-to-cdx"));
+    assert!(stdout.contains("CVE-2021-23337"));
+    assert!(stderr.contains("above the 0% threshold"));
+}
+
+#[test]
+fn test_roundtrip_json_report_under_threshold() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let report_path = dir.path().join("report.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("roundtrip")
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--spdx-version")
+        .arg("2.3")
+        .arg("--max-loss")
+        .arg("50")
+        .arg("--report-format")
+        .arg("json")
+        .arg("--output")
+        .arg(&report_path);
+    cmd.assert().success();
+
+    let report = read_json(&report_path);
+    assert_eq!(report["forward"], "cdx-to-spdx");
+    assert_eq!(report["backward"], "spdx-to-cdx");
+    assert_eq!(report["source_items"], 4);
+    assert_eq!(report["lost_items"], 1);
+    assert_eq!(report["diff"]["summary"]["vulnerabilities_removed"], 1);
+    assert_eq!(report["diff"]["summary"]["dependencies_removed"], 0);
+
+    // Hashes and licenses survive SPDX 2.3
+    let common = report["diff"]["components"]["common"].as_array().unwrap();
+    let lodash = common.iter().find(|c| c["name"] == "lodash").unwrap();
+    assert_eq!(lodash["hashes"], json!(["SHA256:abc123"]));
+    assert_eq!(lodash["licenses"], json!(["MIT"]));
+}
+
+#[test]
+fn test_roundtrip_reports_changed_components() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let report_path = dir.path().join("report.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    // The legacy SPDX 3 simple layout carries no component type
+    let mut cmd = get_cmd();
+    cmd.arg("roundtrip")
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--max-loss")
+        .arg("100")
+        .arg("--report-format")
+        .arg("json")
+        .arg("--output")
+        .arg(&report_path);
+    cmd.assert().success();
+
+    let report = read_json(&report_path);
+    let modified = report["diff"]["components"]["modified"].as_array().unwrap();
+    let app = modified.iter().find(|c| c["name"] == "app").unwrap();
+    assert!(
+        app["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c.as_str().unwrap().starts_with("type:"))
+    );
+    // Licenses survive every layout
+    assert!(!modified.iter().any(|c| c["name"] == "lodash"));
+}
+
+#[test]
+fn test_roundtrip_lossless_spdx23() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let spdx = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "demo",
+        "documentNamespace": "https://example.com/demo",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "packages": [
+            { "SPDXID": "SPDXRef-app", "name": "app", "versionInfo": "1.0.0", "downloadLocation": "NOASSERTION" },
+            {
+                "SPDXID": "SPDXRef-lodash",
+                "name": "lodash",
+                "versionInfo": "4.17.21",
+                "downloadLocation": "NOASSERTION",
+                "licenseConcluded": "MIT",
+                "checksums": [{ "algorithm": "SHA256", "checksumValue": "abc123" }]
+            }
+        ],
+        "relationships": [
+            { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-lodash" }
+        ]
+    });
+    fs::write(&input_path, spdx.to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("roundtrip").arg("--input").arg(&input_path);
+    let output = cmd.output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("Round trip: spdx-to-cdx → cdx-to-spdx"));
+    assert!(stdout.contains("Lost or changed: 0 of 3 items"));
+}
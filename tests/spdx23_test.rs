@@ -0,0 +1,464 @@
+//! Integration tests for SPDX 2.3 JSON support.
+//!
+//! Tests that SPDX 2.3 documents (`packages`, `files`, `relationships`)
+//! are converted to CycloneDX components and dependencies, and that
+//! CycloneDX input can be written out as SPDX 2.3.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A small SPDX 2.3 JSON document as produced by common SBOM generators
+fn get_test_spdx23() -> Value {
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "vendor-app",
+        "documentNamespace": "https://example.com/spdxdocs/vendor-app-1.0",
+        "creationInfo": {
+            "created": "2025-01-01T00:00:00Z",
+            "creators": ["Tool: example-generator-1.0"]
+        },
+        "documentDescribes": ["SPDXRef-Package-app"],
+        "packages": [
+            {
+                "SPDXID": "SPDXRef-Package-app",
+                "name": "vendor-app",
+                "versionInfo": "1.0.0",
+                "downloadLocation": "NOASSERTION",
+                "primaryPackagePurpose": "APPLICATION",
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": "Apache-2.0",
+                "filesAnalyzed": false
+            },
+            {
+                "SPDXID": "SPDXRef-Package-lodash",
+                "name": "lodash",
+                "versionInfo": "4.17.21",
+                "downloadLocation": "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+                "description": "Lodash modular utilities.",
+                "licenseConcluded": "MIT",
+                "licenseDeclared": "MIT",
+                "checksums": [
+                    { "algorithm": "SHA1", "checksumValue": "679591c564c3bffaae8454cf0b3df370c3d6911c" },
+                    { "algorithm": "SHA256", "checksumValue": "6a2bb7a1cd2e8d1e6e0d7b0c0a1b8e8b8e6c0b5a1c2d3e4f5a6b7c8d9e0f1a2b" }
+                ],
+                "externalRefs": [
+                    {
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": "pkg:npm/lodash@4.17.21"
+                    },
+                    {
+                        "referenceCategory": "SECURITY",
+                        "referenceType": "cpe23Type",
+                        "referenceLocator": "cpe:2.3:a:lodash:lodash:4.17.21:*:*:*:*:*:*:*"
+                    }
+                ]
+            },
+            {
+                "SPDXID": "SPDXRef-Package-minimist",
+                "name": "minimist",
+                "versionInfo": "1.2.8",
+                "downloadLocation": "NOASSERTION"
+            }
+        ],
+        "files": [
+            {
+                "SPDXID": "SPDXRef-File-index",
+                "fileName": "./index.js",
+                "checksums": [
+                    { "algorithm": "SHA1", "checksumValue": "d6a770ba38583ed4bb4525bd96e50461655d2758" }
+                ],
+                "licenseConcluded": "NOASSERTION"
+            }
+        ],
+        "relationships": [
+            {
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": "SPDXRef-Package-app"
+            },
+            {
+                "spdxElementId": "SPDXRef-Package-app",
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": "SPDXRef-Package-lodash"
+            },
+            {
+                "spdxElementId": "SPDXRef-Package-minimist",
+                "relationshipType": "DEV_DEPENDENCY_OF",
+                "relatedSpdxElement": "SPDXRef-Package-app"
+            },
+            {
+                "spdxElementId": "SPDXRef-File-index",
+                "relationshipType": "CONTAINED_BY",
+                "relatedSpdxElement": "SPDXRef-Package-app"
+            },
+            {
+                "spdxElementId": "SPDXRef-Package-lodash",
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": "NOASSERTION"
+            }
+        ]
+    })
+}
+
+/// A CycloneDX document with a package, a file and a vulnerability
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "vendor-app",
+                "version": "1.0.0",
+                "licenses": [{ "expression": "Apache-2.0" }]
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21",
+                "cpe": "cpe:2.3:a:lodash:lodash:4.17.21:*:*:*:*:*:*:*",
+                "hashes": [
+                    { "alg": "SHA-256", "content": "6a2bb7a1cd2e8d1e6e0d7b0c0a1b8e8b8e6c0b5a1c2d3e4f5a6b7c8d9e0f1a2b" }
+                ],
+                "licenses": [{ "license": { "id": "MIT" } }]
+            },
+            {
+                "bom-ref": "index",
+                "type": "file",
+                "name": "./index.js",
+                "hashes": [
+                    { "alg": "SHA-1", "content": "d6a770ba38583ed4bb4525bd96e50461655d2758" }
+                ]
+            }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["lodash", "index"] }
+        ],
+        "vulnerabilities": [
+            { "id": "CVE-2021-23337", "affects": [{ "ref": "lodash" }] }
+        ]
+    })
+}
+
+/// Runs a CDX -> SPDX 2.3 conversion and returns the parsed output
+fn convert_to_spdx23(input_path: &Path, output_path: &Path) -> Value {
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input_path)
+        .arg("--output")
+        .arg(output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--spdx-version")
+        .arg("2.3");
+    cmd.assert().success();
+
+    serde_json::from_str(&fs::read_to_string(output_path).unwrap()).unwrap()
+}
+
+/// Runs an SPDX -> CDX conversion and returns the parsed output
+fn convert(input_path: &Path, output_path: &Path, extra_args: &[&str]) -> Value {
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input_path)
+        .arg("--output")
+        .arg(output_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx")
+        .args(extra_args);
+    cmd.assert().success();
+
+    serde_json::from_str(&fs::read_to_string(output_path).unwrap()).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_spdx23_packages_to_components() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_spdx23()).unwrap();
+
+    let output = convert(&input_path, &output_path, &[]);
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 4); // 3 packages + 1 file
+
+    let app = components
+        .iter()
+        .find(|c| c["bom-ref"] == "Package-app")
+        .unwrap();
+    assert_eq!(app["type"], "application");
+    assert_eq!(app["version"], "1.0.0");
+    // licenseConcluded is NOASSERTION, so the declared license is used
+    assert_eq!(app["licenses"][0]["expression"], "Apache-2.0");
+
+    let lodash = components
+        .iter()
+        .find(|c| c["bom-ref"] == "Package-lodash")
+        .unwrap();
+    assert_eq!(lodash["type"], "library");
+    assert_eq!(lodash["purl"], "pkg:npm/lodash@4.17.21");
+    assert_eq!(
+        lodash["cpe"],
+        "cpe:2.3:a:lodash:lodash:4.17.21:*:*:*:*:*:*:*"
+    );
+    assert_eq!(lodash["description"], "Lodash modular utilities.");
+    assert_eq!(lodash["licenses"][0]["expression"], "MIT");
+    let hashes = lodash["hashes"].as_array().unwrap();
+    assert_eq!(hashes.len(), 2);
+    assert_eq!(hashes[0]["alg"], "SHA-1");
+    assert_eq!(hashes[1]["alg"], "SHA-256");
+
+    let file = components
+        .iter()
+        .find(|c| c["bom-ref"] == "File-index")
+        .unwrap();
+    assert_eq!(file["type"], "file");
+    assert_eq!(file["name"], "./index.js");
+    assert!(file.get("licenses").is_none());
+}
+
+#[test]
+fn test_spdx23_relationships_to_dependencies() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_spdx23()).unwrap();
+
+    let output = convert(&input_path, &output_path, &[]);
+
+    let dependencies = output["dependencies"].as_array().unwrap();
+    // DESCRIBES is not a dependency and NOASSERTION targets are dropped
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0]["ref"], "Package-app");
+
+    // DEPENDS_ON, inverted DEV_DEPENDENCY_OF and inverted CONTAINED_BY
+    let mut depends_on: Vec<&str> = dependencies[0]["dependsOn"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d.as_str().unwrap())
+        .collect();
+    depends_on.sort();
+    assert_eq!(
+        depends_on,
+        vec!["File-index", "Package-lodash", "Package-minimist"]
+    );
+}
+
+#[test]
+fn test_spdx23_packages_only() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_spdx23()).unwrap();
+
+    let output = convert(&input_path, &output_path, &["--packages-only"]);
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3);
+    assert!(components.iter().all(|c| c["type"] != "file"));
+}
+
+#[test]
+fn test_cdx_to_spdx23_output() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_cdx()).unwrap();
+
+    let output = convert_to_spdx23(&input_path, &output_path);
+
+    // The output must conform to the bundled SPDX 2.3 schema
+    let schema: Value =
+        serde_json::from_str(include_str!("../schemas/spdx_2.3.schema.json")).unwrap();
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(&output)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect();
+    assert!(errors.is_empty(), "schema errors: {:?}", errors);
+
+    assert_eq!(output["spdxVersion"], "SPDX-2.3");
+    assert_eq!(output["SPDXID"], "SPDXRef-DOCUMENT");
+    assert!(
+        output["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://spdx.org/spdxdocs/")
+    );
+
+    let packages = output["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 2);
+    let lodash = packages
+        .iter()
+        .find(|p| p["SPDXID"] == "SPDXRef-lodash")
+        .unwrap();
+    assert_eq!(lodash["versionInfo"], "4.17.21");
+    assert_eq!(lodash["downloadLocation"], "NOASSERTION");
+    assert_eq!(lodash["primaryPackagePurpose"], "LIBRARY");
+    assert_eq!(lodash["licenseConcluded"], "MIT");
+    assert_eq!(lodash["checksums"][0]["algorithm"], "SHA256");
+    assert_eq!(lodash["externalRefs"][0]["referenceType"], "purl");
+    assert_eq!(lodash["externalRefs"][1]["referenceType"], "cpe23Type");
+
+    let files = output["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["SPDXID"], "SPDXRef-index");
+    assert_eq!(files[0]["fileName"], "./index.js");
+    assert_eq!(files[0]["checksums"][0]["algorithm"], "SHA1");
+
+    assert_eq!(
+        output["documentDescribes"],
+        json!(["SPDXRef-app", "SPDXRef-lodash"])
+    );
+
+    // Vulnerabilities have no SPDX 2.3 representation
+    let relationships = output["relationships"].as_array().unwrap();
+    assert_eq!(relationships.len(), 2);
+    assert!(
+        relationships
+            .iter()
+            .all(|r| r["relationshipType"] == "DEPENDS_ON" && r["spdxElementId"] == "SPDXRef-app")
+    );
+}
+
+#[test]
+fn test_cdx_to_spdx23_round_trip() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let spdx_path = dir.path().join("output.spdx.json");
+    let cdx_path = dir.path().join("roundtrip.cdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_cdx()).unwrap();
+
+    convert_to_spdx23(&input_path, &spdx_path);
+    let output = convert(&spdx_path, &cdx_path, &[]);
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3);
+    let lodash = components
+        .iter()
+        .find(|c| c["bom-ref"] == "lodash")
+        .unwrap();
+    assert_eq!(lodash["purl"], "pkg:npm/lodash@4.17.21");
+    assert_eq!(lodash["hashes"][0]["alg"], "SHA-256");
+    assert_eq!(lodash["licenses"][0]["expression"], "MIT");
+
+    let dependencies = output["dependencies"].as_array().unwrap();
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0]["ref"], "app");
+}
+
+/// A CDX component carrying provenance fields SPDX can represent
+fn get_provenance_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "openssl",
+                "type": "library",
+                "name": "openssl",
+                "version": "3.0.13",
+                "supplier": { "name": "OpenSSL Foundation" },
+                "authors": [{ "name": "Jane Doe" }],
+                "copyright": "Copyright (c) 1998-2024 The OpenSSL Project",
+                "swid": { "tagId": "openssl-3.0.13", "name": "openssl" },
+                "externalReferences": [
+                    { "type": "website", "url": "https://www.openssl.org" },
+                    { "type": "vcs", "url": "https://github.com/openssl/openssl" },
+                    { "type": "advisories", "url": "https://www.openssl.org/news/vulnerabilities.html" }
+                ]
+            }
+        ]
+    })
+}
+
+#[test]
+fn test_cdx_to_spdx23_round_trip_keeps_provenance() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let spdx_path = dir.path().join("output.spdx.json");
+    let cdx_path = dir.path().join("roundtrip.cdx.json");
+    fs::write(&input_path, get_provenance_cdx().to_string()).unwrap();
+
+    let spdx = convert_to_spdx23(&input_path, &spdx_path);
+    let package = &spdx["packages"][0];
+    assert_eq!(package["supplier"], "Organization: OpenSSL Foundation");
+    assert_eq!(package["originator"], "Person: Jane Doe");
+    assert_eq!(package["homepage"], "https://www.openssl.org");
+
+    let output = convert(&spdx_path, &cdx_path, &[]);
+    let openssl = &output["components"][0];
+    assert_eq!(openssl["supplier"]["name"], "OpenSSL Foundation");
+    assert_eq!(openssl["author"], "Jane Doe");
+    assert_eq!(
+        openssl["copyright"],
+        "Copyright (c) 1998-2024 The OpenSSL Project"
+    );
+    assert_eq!(openssl["swid"]["tagId"], "openssl-3.0.13");
+
+    let refs = openssl["externalReferences"].as_array().unwrap();
+    for ref_type in ["website", "vcs", "advisories"] {
+        assert!(
+            refs.iter().any(|r| r["type"] == ref_type),
+            "missing {} reference",
+            ref_type
+        );
+    }
+}
+
+#[test]
+fn test_spdx23_rejects_jsonld_serialization() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+
+    let mut input_file = File::create(&input_path).unwrap();
+    writeln!(input_file, "{}", get_test_cdx()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--spdx-version")
+        .arg("2.3")
+        .arg("--spdx-serialization")
+        .arg("json-ld");
+    cmd.assert().failure();
+}
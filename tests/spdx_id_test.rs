@@ -0,0 +1,169 @@
+//! Integration tests for the SPDX IDs written by CDX -> SPDX conversions.
+//!
+//! Tests that purl bom-refs become valid SPDX idstrings, that bom-refs and
+//! vulnerability ids that collide after cleaning get distinct IDs, and that
+//! relationships and assessments refer to the rewritten IDs in every SPDX
+//! layout.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A BOM whose bom-refs and vulnerability ids are not valid SPDX IDs
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            { "bom-ref": "pkg:npm/%40scope/a@1.0.0?arch=x64", "type": "library", "name": "a" },
+            { "bom-ref": "pkg:npm/%40scope/a/1.0.0?arch=x64", "type": "library", "name": "a-fork" },
+            { "bom-ref": "DOCUMENT", "type": "application", "name": "app" }
+        ],
+        "dependencies": [
+            {
+                "ref": "DOCUMENT",
+                "dependsOn": ["pkg:npm/%40scope/a@1.0.0?arch=x64", "pkg:npm/%40scope/a/1.0.0?arch=x64"]
+            }
+        ],
+        "vulnerabilities": [
+            {
+                "id": "GHSA:xxxx",
+                "affects": [{ "ref": "pkg:npm/%40scope/a@1.0.0?arch=x64" }]
+            },
+            {
+                "id": "GHSA:xxxx",
+                "affects": [{ "ref": "pkg:npm/%40scope/a/1.0.0?arch=x64" }],
+                "analysis": { "state": "not_affected", "justification": "code_not_present" }
+            }
+        ]
+    })
+}
+
+/// Converts `input` to SPDX with the given options
+fn convert(input: &Path, output: &Path, args: &[&str]) -> Value {
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .args(args);
+    cmd.assert().success();
+    serde_json::from_str(&fs::read_to_string(output).unwrap()).unwrap()
+}
+
+/// The local part of an SPDX ID or JSON-LD IRI
+fn local(id: &str) -> &str {
+    id.rsplit_once('#').map_or(id, |(_, fragment)| fragment)
+}
+
+fn is_valid_spdx_id(id: &str) -> bool {
+    id.strip_prefix("SPDXRef-").is_some_and(|rest| {
+        !rest.is_empty()
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    })
+}
+
+/// Asserts that the element IDs are valid and distinct, and returns them
+fn element_ids<'a>(elements: impl Iterator<Item = &'a str>) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    for id in elements {
+        assert!(is_valid_spdx_id(local(id)), "invalid SPDX ID {}", id);
+        assert!(
+            ids.insert(local(id).to_string()),
+            "duplicate SPDX ID {}",
+            id
+        );
+    }
+    ids
+}
+
+// --- Tests ---
+
+#[test]
+fn test_spdx23_ids_are_valid_and_consistent() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("input.cdx.json");
+    fs::write(&input, get_test_cdx().to_string()).unwrap();
+
+    let doc = convert(
+        &input,
+        &dir.path().join("output.spdx.json"),
+        &["--spdx-version", "2.3"],
+    );
+    let ids = element_ids(
+        doc["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["SPDXID"].as_str().unwrap()),
+    );
+    assert!(ids.contains("SPDXRef-pkg-npm--40scope-a-1.0.0-arch-x64"));
+    assert!(ids.contains("SPDXRef-pkg-npm--40scope-a-1.0.0-arch-x64-2"));
+    // The component must not take the document's own ID
+    assert!(ids.contains("SPDXRef-Component-DOCUMENT"));
+
+    for rel in doc["relationships"].as_array().unwrap() {
+        assert!(ids.contains(rel["spdxElementId"].as_str().unwrap()));
+        assert!(ids.contains(rel["relatedSpdxElement"].as_str().unwrap()));
+    }
+}
+
+#[test]
+fn test_spdx3_ids_are_valid_and_consistent() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("input.cdx.json");
+    fs::write(&input, get_test_cdx().to_string()).unwrap();
+
+    // Simple layout: elements, SPDX 2 style relationships and assessments
+    let doc = convert(&input, &dir.path().join("simple.spdx.json"), &[]);
+    let elements = doc["elements"].as_array().unwrap();
+    let ids = element_ids(elements.iter().map(|e| e["spdxId"].as_str().unwrap()));
+    assert!(ids.contains("SPDXRef-Vulnerability-GHSA-xxxx"));
+    assert!(ids.contains("SPDXRef-Vulnerability-GHSA-xxxx-2"));
+    let relationships = doc["relationships"].as_array().unwrap();
+    for rel in relationships.iter().filter(|r| r.get("type").is_none()) {
+        assert!(ids.contains(rel["spdxElementId"].as_str().unwrap()));
+        assert!(ids.contains(rel["relatedSpdxElement"].as_str().unwrap()));
+    }
+    let assessment = relationships
+        .iter()
+        .find(|r| r["type"] == "security_VexNotAffectedVulnAssessmentRelationship")
+        .unwrap();
+    assert_eq!(assessment["from"], "SPDXRef-Vulnerability-GHSA-xxxx-2");
+    assert_eq!(
+        assessment["to"],
+        json!(["SPDXRef-pkg-npm--40scope-a-1.0.0-arch-x64-2"])
+    );
+
+    // JSON-LD: IRIs whose fragments are SPDX IDs, relationships as elements
+    let doc = convert(
+        &input,
+        &dir.path().join("jsonld.spdx.json"),
+        &["--spdx-serialization", "json-ld"],
+    );
+    let graph = doc["@graph"].as_array().unwrap();
+    let ids = element_ids(graph.iter().filter_map(|e| e["spdxId"].as_str()));
+    for element in graph.iter().filter(|e| e.get("from").is_some()) {
+        assert!(ids.contains(local(element["from"].as_str().unwrap())));
+        for to in element["to"].as_array().unwrap() {
+            assert!(ids.contains(local(to.as_str().unwrap())));
+        }
+    }
+}
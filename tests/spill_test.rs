@@ -0,0 +1,343 @@
+//! Integration tests for `--spill-threshold`.
+//!
+//! Tests that SPDX -> CDX conversions whose relationship index is spilled
+//! to disk write the same bytes as conversions that keep it in memory, for
+//! every SPDX layout, with nested components and split-off VEX documents,
+//! and that they keep no per-relationship state in memory.
+
+use assert_cmd::prelude::*;
+use sbom_converter::cdx_version::CdxVersion;
+use sbom_converter::converter_spdx_to_cdx::{DependencyOutput, convert_spdx_to_cdx};
+use sbom_converter::fidelity::FidelityReport;
+use sbom_converter::id_map::IdMap;
+use sbom_converter::integrity::Containers;
+use sbom_converter::models_cdx::CdxDependency;
+use sbom_converter::progress::ProgressTracker;
+use sbom_converter::reproducible::OutputIdentity;
+use serde_json::{Value, json};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A BOM with nesting, licenses, dependencies and vulnerabilities, so its
+/// SPDX conversion has relationships of every kind the index serves
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "metadata": {
+            "component": { "bom-ref": "root", "type": "application", "name": "root" }
+        },
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "app",
+                "licenses": [{ "license": { "id": "Apache-2.0" } }],
+                "components": [
+                    { "bom-ref": "main.c", "type": "file", "name": "main.c" },
+                    { "bom-ref": "util.c", "type": "file", "name": "util.c" }
+                ]
+            },
+            { "bom-ref": "lib", "type": "library", "name": "lib", "licenses": [{ "license": { "id": "MIT" } }] },
+            { "bom-ref": "zlib", "type": "library", "name": "zlib" },
+            { "bom-ref": "a0", "type": "library", "name": "a0" }
+        ],
+        "dependencies": [
+            { "ref": "root", "dependsOn": ["app", "zlib"] },
+            { "ref": "app", "dependsOn": ["lib", "zlib", "a0"] },
+            { "ref": "lib", "dependsOn": ["zlib"] },
+            { "ref": "a0", "dependsOn": ["zlib", "lib"] }
+        ],
+        "vulnerabilities": [
+            {
+                "id": "CVE-2024-0001",
+                "affects": [{ "ref": "lib" }, { "ref": "zlib" }],
+                "analysis": { "state": "not_affected", "justification": "code_not_present" }
+            },
+            { "id": "CVE-2024-0002", "affects": [{ "ref": "a0" }] }
+        ]
+    })
+}
+
+/// An SPDX 2.3 document with `count` packages that each contain five files
+/// and depend on two other packages
+fn get_large_spdx23(count: usize) -> Value {
+    let mut packages = Vec::new();
+    let mut files = Vec::new();
+    let mut relationships = Vec::new();
+    for i in 0..count {
+        let package = format!("SPDXRef-pkg-{}", i);
+        packages.push(json!({
+            "SPDXID": package,
+            "name": format!("pkg-{}", i),
+            "versionInfo": "1.0.0",
+            "downloadLocation": "NOASSERTION"
+        }));
+        for j in 0..5 {
+            let file = format!("SPDXRef-file-{}-{}", i, j);
+            files.push(json!({ "SPDXID": file, "fileName": format!("./{}/{}.c", i, j) }));
+            relationships.push(json!({
+                "spdxElementId": package,
+                "relationshipType": "CONTAINS",
+                "relatedSpdxElement": file
+            }));
+        }
+        for other in [(i + 1) % count, (i * 7) % count] {
+            relationships.push(json!({
+                "spdxElementId": package,
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": format!("SPDXRef-pkg-{}", other)
+            }));
+        }
+    }
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "large",
+        "documentNamespace": "https://example.com/spdxdocs/large-1.0",
+        "creationInfo": {
+            "created": "2025-01-01T00:00:00Z",
+            "creators": ["Tool: example-generator-1.0"]
+        },
+        "packages": packages,
+        "files": files,
+        "relationships": relationships
+    })
+}
+
+/// A converted BOM with its serial number, which depends on the options,
+/// replaced wherever it appears (the VEX BOM-links use it too)
+fn without_serial_number(bom: &[u8]) -> String {
+    let text = String::from_utf8(bom.to_vec()).unwrap();
+    let serial_number = serde_json::from_str::<Value>(&text).unwrap()["serialNumber"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    text.replace(&serial_number, "urn:uuid:serial-number")
+}
+
+/// Runs a reproducible `convert` and returns the bytes written to `output`
+fn convert(input: &Path, output: &Path, direction: &str, args: &[&str]) -> Vec<u8> {
+    let mut cmd = get_cmd();
+    cmd.arg("--reproducible")
+        .arg("convert")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--direction")
+        .arg(direction)
+        .args(args);
+    cmd.assert().success();
+    fs::read(output).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_spilled_index_gives_identical_output() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("input.cdx.json");
+    fs::write(&input, get_test_cdx().to_string()).unwrap();
+
+    for layout in [
+        vec!["--spdx-version", "2.3"],
+        vec![],
+        vec!["--spdx-serialization", "json-ld"],
+    ] {
+        let spdx = dir.path().join("input.spdx.json");
+        convert(&input, &spdx, "cdx-to-spdx", &layout);
+
+        let expected = convert(
+            &spdx,
+            &dir.path().join("memory.cdx.json"),
+            "spdx-to-cdx",
+            &[],
+        );
+        for threshold in ["1", "3"] {
+            let actual = convert(
+                &spdx,
+                &dir.path().join("spilled.cdx.json"),
+                "spdx-to-cdx",
+                &["--spill-threshold", threshold],
+            );
+            assert_eq!(actual, expected, "{:?} {}", layout, threshold);
+
+            // A spilled index writes components flat even when asked to nest them
+            let actual = convert(
+                &spdx,
+                &dir.path().join("spilled.cdx.json"),
+                "spdx-to-cdx",
+                &["--nest-components", "--spill-threshold", threshold],
+            );
+            assert_eq!(
+                without_serial_number(&actual),
+                without_serial_number(&expected),
+                "{:?} {}",
+                layout,
+                threshold
+            );
+        }
+    }
+}
+
+#[test]
+fn test_spilled_index_gives_identical_vex() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("input.cdx.json");
+    fs::write(&input, get_test_cdx().to_string()).unwrap();
+    let spdx = dir.path().join("input.spdx.json");
+    convert(
+        &input,
+        &spdx,
+        "cdx-to-spdx",
+        &["--spdx-serialization", "json-ld"],
+    );
+
+    let memory = dir.path().join("memory.cdx.json");
+    let spilled = dir.path().join("spilled.cdx.json");
+    let expected = convert(&spdx, &memory, "spdx-to-cdx", &["--split-vex"]);
+    let actual = convert(
+        &spdx,
+        &spilled,
+        "spdx-to-cdx",
+        &["--split-vex", "--spill-threshold", "1"],
+    );
+    assert_eq!(actual, expected);
+
+    let expected_vex = fs::read(dir.path().join("memory.vex.json")).unwrap();
+    let actual_vex = fs::read(dir.path().join("spilled.vex.json")).unwrap();
+    assert_eq!(actual_vex, expected_vex);
+    let vex: Value = serde_json::from_slice(&actual_vex).unwrap();
+    assert_eq!(vex["vulnerabilities"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_far_past_threshold_gives_identical_output() {
+    let dir = tempdir().unwrap();
+    let spdx = dir.path().join("input.spdx.json");
+    fs::write(&spdx, get_large_spdx23(300).to_string()).unwrap();
+
+    // Hundreds of runs: more than are merged at once, for the index and
+    // for the dependencies
+    let expected = convert(
+        &spdx,
+        &dir.path().join("memory.cdx.json"),
+        "spdx-to-cdx",
+        &[],
+    );
+    let actual = convert(
+        &spdx,
+        &dir.path().join("spilled.cdx.json"),
+        "spdx-to-cdx",
+        &["--spill-threshold", "10"],
+    );
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_spilled_index_writes_components_flat() {
+    let dir = tempdir().unwrap();
+    let spdx = dir.path().join("input.spdx.json");
+    let report = dir.path().join("report.json");
+    fs::write(&spdx, get_large_spdx23(300).to_string()).unwrap();
+
+    // Nesting would keep every component tree in memory
+    let flat = convert(&spdx, &dir.path().join("flat.cdx.json"), "spdx-to-cdx", &[]);
+    let spilled = convert(
+        &spdx,
+        &dir.path().join("spilled.cdx.json"),
+        "spdx-to-cdx",
+        &[
+            "--nest-components",
+            "--spill-threshold",
+            "10",
+            "--fidelity-report",
+            report.to_str().unwrap(),
+        ],
+    );
+    assert_eq!(
+        without_serial_number(&spilled),
+        without_serial_number(&flat)
+    );
+
+    let report: Value = serde_json::from_slice(&fs::read(&report).unwrap()).unwrap();
+    assert!(
+        report["dropped"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|d| d["path"] == "option.nestComponents")
+    );
+}
+
+#[test]
+fn test_spilled_conversion_keeps_no_per_edge_buffer() {
+    let dir = tempdir().unwrap();
+    let spdx = dir.path().join("input.spdx.json");
+    fs::write(&spdx, get_large_spdx23(300).to_string()).unwrap();
+
+    let convert_with = |spill_threshold: Option<usize>| {
+        let containers = Containers::new();
+        let input = BufReader::new(File::open(&spdx).unwrap());
+        let output = BufWriter::new(File::create(dir.path().join("output.cdx.json")).unwrap());
+        convert_spdx_to_cdx(
+            input,
+            output,
+            &spdx,
+            ProgressTracker::new(0),
+            false,
+            false,
+            None,
+            CdxVersion::V1_6,
+            FidelityReport::new(),
+            &OutputIdentity::default(),
+            &IdMap::new(),
+            &containers,
+            spill_threshold,
+        )
+        .unwrap();
+        containers
+    };
+
+    // One container per file in memory, none once spilled
+    let containers = convert_with(None);
+    assert_eq!(containers.len(), 300 * 5);
+    let containers = convert_with(Some(10));
+    assert!(containers.is_skipped());
+    assert!(containers.is_empty());
+
+    // Spilled dependencies are sorted on disk into the in-memory order
+    let mut memory = DependencyOutput::memory();
+    let mut spilled = DependencyOutput::spilled(10).unwrap();
+    for i in (0..1000).rev() {
+        for output in [&mut memory, &mut spilled] {
+            output
+                .add(CdxDependency {
+                    dep_ref: format!("pkg-{}", i),
+                    depends_on: vec![format!("pkg-{}", i + 1)],
+                })
+                .unwrap();
+        }
+    }
+    assert_eq!(memory.buffered(), 1000);
+    assert_eq!(spilled.buffered(), 0);
+
+    let mut expected = BufWriter::new(Vec::new());
+    memory.finish(&mut expected).unwrap();
+    let mut actual = BufWriter::new(Vec::new());
+    spilled.finish(&mut actual).unwrap();
+    assert_eq!(actual.into_inner().unwrap(), expected.into_inner().unwrap());
+}
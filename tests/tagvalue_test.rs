@@ -0,0 +1,310 @@
+//! Integration tests for SPDX tag-value (`.spdx`) support.
+//!
+//! Tests that tag-value documents convert to CycloneDX, that CycloneDX
+//! can be written out as tag-value, and that tag-value files are accepted
+//! by `merge` and `diff`.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A tag-value document in the shape Yocto's `create-spdx` produces
+fn get_test_tag_value(image: &str, busybox_version: &str) -> String {
+    format!(
+        "SPDXVersion: SPDX-2.3
+DataLicense: CC0-1.0
+SPDXID: SPDXRef-DOCUMENT
+DocumentName: {image}
+DocumentNamespace: http://spdx.org/spdxdocs/{image}-1234
+Creator: Tool: yocto
+Created: 2025-01-01T00:00:00Z
+
+##### Package: {image}
+
+PackageName: {image}
+SPDXID: SPDXRef-Image
+PackageVersion: 1.0
+PackageDownloadLocation: NOASSERTION
+PrimaryPackagePurpose: OPERATING-SYSTEM
+PackageLicenseDeclared: MIT
+
+##### Package: busybox
+
+PackageName: busybox
+SPDXID: SPDXRef-Package-busybox
+PackageVersion: {busybox_version}
+PackageDownloadLocation: https://busybox.net/downloads/busybox-{busybox_version}.tar.bz2
+FilesAnalyzed: true
+PackageChecksum: SHA256: 6a2bb7a1cd2e8d1e6e0d7b0c0a1b8e8b8e6c0b5a1c2d3e4f5a6b7c8d9e0f1a2b
+PackageLicenseConcluded: GPL-2.0-only
+PackageCopyrightText: <text>Copyright (C) Busybox authors
+</text>
+ExternalRef: PACKAGE-MANAGER purl pkg:generic/busybox@{busybox_version}
+ExternalRef: SECURITY cpe23Type cpe:2.3:a:busybox:busybox:{busybox_version}:*:*:*:*:*:*:*
+
+FileName: ./bin/busybox
+SPDXID: SPDXRef-File-busybox
+FileChecksum: SHA1: d6a770ba38583ed4bb4525bd96e50461655d2758
+LicenseConcluded: GPL-2.0-only
+
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Image
+Relationship: SPDXRef-Image DEPENDS_ON SPDXRef-Package-busybox
+Relationship: SPDXRef-Package-busybox CONTAINS SPDXRef-File-busybox
+"
+    )
+}
+
+/// A CycloneDX document with a package and a file
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "vendor-app",
+                "version": "1.0.0",
+                "licenses": [{ "expression": "Apache-2.0" }]
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21",
+                "hashes": [
+                    { "alg": "SHA-256", "content": "6a2bb7a1cd2e8d1e6e0d7b0c0a1b8e8b8e6c0b5a1c2d3e4f5a6b7c8d9e0f1a2b" }
+                ]
+            },
+            {
+                "bom-ref": "index",
+                "type": "file",
+                "name": "./index.js"
+            }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["lodash"] }
+        ]
+    })
+}
+
+/// Runs a conversion in the given direction
+fn convert(input_path: &Path, output_path: &Path, direction: &str) {
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input_path)
+        .arg("--output")
+        .arg(output_path)
+        .arg("--direction")
+        .arg(direction);
+    cmd.assert().success();
+}
+
+// --- Tests ---
+
+#[test]
+fn test_tag_value_to_cdx() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("image.spdx");
+    let output_path = dir.path().join("image.cdx.json");
+    fs::write(&input_path, get_test_tag_value("core-image", "1.36.1")).unwrap();
+
+    convert(&input_path, &output_path, "spdx-to-cdx");
+    let output: Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3); // 2 packages + 1 file
+
+    let image = components.iter().find(|c| c["bom-ref"] == "Image").unwrap();
+    assert_eq!(image["type"], "operating-system");
+    assert_eq!(image["licenses"][0]["expression"], "MIT");
+
+    let busybox = components
+        .iter()
+        .find(|c| c["bom-ref"] == "Package-busybox")
+        .unwrap();
+    assert_eq!(busybox["version"], "1.36.1");
+    assert_eq!(busybox["purl"], "pkg:generic/busybox@1.36.1");
+    assert_eq!(
+        busybox["cpe"],
+        "cpe:2.3:a:busybox:busybox:1.36.1:*:*:*:*:*:*:*"
+    );
+    assert_eq!(busybox["hashes"][0]["alg"], "SHA-256");
+    assert_eq!(busybox["licenses"][0]["expression"], "GPL-2.0-only");
+
+    let mut deps: Vec<(String, Vec<String>)> = output["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            let targets = d["dependsOn"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t.as_str().unwrap().to_string())
+                .collect();
+            (d["ref"].as_str().unwrap().to_string(), targets)
+        })
+        .collect();
+    deps.sort();
+    assert_eq!(
+        deps,
+        vec![
+            ("Image".to_string(), vec!["Package-busybox".to_string()]),
+            (
+                "Package-busybox".to_string(),
+                vec!["File-busybox".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_cdx_to_tag_value() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    // Tag-value implies SPDX 2.3 even without --spdx-version
+    convert(&input_path, &output_path, "cdx-to-spdx");
+    let output = fs::read_to_string(&output_path).unwrap();
+
+    assert!(output.contains("SPDXVersion: SPDX-2.3\n"));
+    assert!(output.contains("SPDXID: SPDXRef-DOCUMENT\n"));
+    assert!(output.contains("PackageName: lodash\n"));
+    assert!(output.contains("PackageVersion: 4.17.21\n"));
+    assert!(output.contains("PackageChecksum: SHA256: 6a2bb7a1"));
+    assert!(output.contains("ExternalRef: PACKAGE-MANAGER purl pkg:npm/lodash@4.17.21\n"));
+    assert!(output.contains("FileName: ./index.js\n"));
+    assert!(output.contains("Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-app\n"));
+    assert!(output.contains("Relationship: SPDXRef-app DEPENDS_ON SPDXRef-lodash\n"));
+}
+
+#[test]
+fn test_cdx_tag_value_round_trip() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let spdx_path = dir.path().join("output.spdx");
+    let cdx_path = dir.path().join("roundtrip.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(&input_path, &spdx_path, "cdx-to-spdx");
+    convert(&spdx_path, &cdx_path, "spdx-to-cdx");
+    let output: Value = serde_json::from_str(&fs::read_to_string(&cdx_path).unwrap()).unwrap();
+
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3);
+    let lodash = components
+        .iter()
+        .find(|c| c["bom-ref"] == "lodash")
+        .unwrap();
+    assert_eq!(lodash["purl"], "pkg:npm/lodash@4.17.21");
+    assert_eq!(lodash["hashes"][0]["alg"], "SHA-256");
+
+    let dependencies = output["dependencies"].as_array().unwrap();
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0]["ref"], "app");
+    assert_eq!(dependencies[0]["dependsOn"], json!(["lodash"]));
+}
+
+#[test]
+fn test_tag_value_rejects_cdx_direction() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("image.spdx");
+    let output_path = dir.path().join("image.json");
+    fs::write(&input_path, get_test_tag_value("core-image", "1.36.1")).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx");
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_merge_tag_value_inputs() {
+    let dir = tempdir().unwrap();
+    let first = dir.path().join("first.spdx");
+    let second = dir.path().join("second.spdx");
+    let merged_json = dir.path().join("merged.spdx.json");
+    let merged_tv = dir.path().join("merged.spdx");
+    fs::write(&first, get_test_tag_value("core-image", "1.36.1")).unwrap();
+    fs::write(
+        &second,
+        get_test_tag_value("core-image", "1.36.1")
+            .replace("SPDXRef-Package-busybox", "SPDXRef-Package-busybox-native"),
+    )
+    .unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("merge")
+        .arg("--inputs")
+        .arg(&first)
+        .arg(&second)
+        .arg("--output")
+        .arg(&merged_json);
+    cmd.assert().success();
+
+    let merged: Value = serde_json::from_str(&fs::read_to_string(&merged_json).unwrap()).unwrap();
+    assert_eq!(merged["spdxVersion"], "SPDX-2.3");
+    let packages = merged["packages"].as_array().unwrap();
+    // SPDXRef-Image is shared, the two busybox packages are distinct
+    assert_eq!(packages.len(), 3);
+
+    let mut cmd = get_cmd();
+    cmd.arg("merge")
+        .arg("--inputs")
+        .arg(&first)
+        .arg(&second)
+        .arg("--output")
+        .arg(&merged_tv);
+    cmd.assert().success();
+
+    let output = fs::read_to_string(&merged_tv).unwrap();
+    assert_eq!(output.matches("PackageName: ").count(), 3);
+    assert!(output.contains("SPDXID: SPDXRef-Package-busybox-native\n"));
+}
+
+#[test]
+fn test_diff_tag_value_inputs() {
+    let dir = tempdir().unwrap();
+    let old = dir.path().join("old.spdx");
+    let new = dir.path().join("new.spdx");
+    let report_path = dir.path().join("diff.json");
+    fs::write(&old, get_test_tag_value("core-image", "1.36.1")).unwrap();
+    fs::write(&new, get_test_tag_value("core-image", "1.37.0")).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("diff")
+        .arg("--file1")
+        .arg(&old)
+        .arg("--file2")
+        .arg(&new)
+        .arg("--report-format")
+        .arg("json")
+        .arg("--output")
+        .arg(&report_path);
+    cmd.assert().success();
+
+    let report: Value = serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    // The purl carries the version, so busybox shows up as removed + added
+    assert_eq!(report["summary"]["components_added"], 1);
+    assert_eq!(report["summary"]["components_removed"], 1);
+    assert_eq!(report["components"]["added"][0]["version"], "1.37.0");
+}
@@ -0,0 +1,358 @@
+//! Integration tests for same-family conversion (`cdx-to-cdx`, `spdx-to-spdx`).
+//!
+//! Tests CycloneDX JSON <-> XML and version downgrades, and SPDX simple JSON
+//! <-> JSON-LD and JSON <-> tag-value re-encoding.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX 1.6 BOM using fields that older versions do not define
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "metadata": {
+            "lifecycles": [{ "phase": "build" }]
+        },
+        "components": [
+            {
+                "bom-ref": "app",
+                "type": "application",
+                "name": "app",
+                "version": "1.0.0",
+                "tags": ["web"]
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21"
+            }
+        ],
+        "dependencies": [
+            { "ref": "app", "dependsOn": ["lodash"] }
+        ],
+        "vulnerabilities": [
+            { "id": "CVE-2021-23337", "affects": [{ "ref": "lodash" }] }
+        ],
+        "formulation": [{ "bom-ref": "build" }]
+    })
+}
+
+/// A legacy SPDX 3 simple JSON document
+fn get_test_spdx_simple() -> Value {
+    json!({
+        "spdxVersion": "SPDX-3.0",
+        "dataLicense": "CC0-1.0",
+        "spdxId": "SPDXRef-DOCUMENT",
+        "name": "demo",
+        "documentNamespace": "https://example.com/demo",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "elements": [
+            { "spdxId": "SPDXRef-app", "type": "SpdxPackage", "name": "app", "versionInfo": "1.0.0" },
+            { "spdxId": "SPDXRef-lodash", "type": "SpdxPackage", "name": "lodash", "versionInfo": "4.17.21", "licenseConcluded": "MIT" }
+        ],
+        "relationships": [
+            { "spdxElementId": "SPDXRef-app", "relationshipType": "DEPENDS_ON", "relatedSpdxElement": "SPDXRef-lodash" }
+        ]
+    })
+}
+
+/// Runs a conversion in the given direction with extra arguments
+fn convert(input_path: &Path, output_path: &Path, direction: &str, extra: &[&str]) {
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input_path)
+        .arg("--output")
+        .arg(output_path)
+        .arg("--direction")
+        .arg(direction)
+        .args(extra);
+    cmd.assert().success();
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_cdx_to_cdx_downgrade() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &output_path,
+        "cdx-to-cdx",
+        &["--output-version", "1.3"],
+    );
+    let output = read_json(&output_path);
+
+    assert_eq!(output["bomFormat"], "CycloneDX");
+    assert_eq!(output["specVersion"], "1.3");
+    assert!(output.get("vulnerabilities").is_none());
+    assert!(output.get("formulation").is_none());
+    assert!(output["metadata"].get("lifecycles").is_none());
+    assert!(output["components"][0].get("tags").is_none());
+    assert_eq!(output["components"][1]["purl"], "pkg:npm/lodash@4.17.21");
+    assert_eq!(output["dependencies"], get_test_cdx()["dependencies"]);
+}
+
+#[test]
+fn test_cdx_to_cdx_json_xml_round_trip() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let xml_path = dir.path().join("output.cdx.xml");
+    let json_path = dir.path().join("roundtrip.cdx.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &xml_path,
+        "cdx-to-cdx",
+        &["--output-version", "1.5"],
+    );
+    let xml = fs::read_to_string(&xml_path).unwrap();
+    assert!(xml.contains("http://cyclonedx.org/schema/bom/1.5"));
+    assert!(xml.contains("lodash"));
+
+    convert(&xml_path, &json_path, "cdx-to-cdx", &[]);
+    let output = read_json(&json_path);
+    assert_eq!(output["specVersion"], "1.6");
+    let components = output["components"].as_array().unwrap();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[1]["version"], "4.17.21");
+}
+
+#[test]
+fn test_cdx_to_cdx_xml_keeps_component_details() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let xml_path = dir.path().join("output.cdx.xml");
+    let json_path = dir.path().join("roundtrip.cdx.json");
+    let component = json!({
+        "bom-ref": "openssl",
+        "type": "library",
+        "supplier": { "name": "OpenSSL Foundation" },
+        "group": "org.openssl",
+        "name": "openssl",
+        "version": "3.0.13",
+        "scope": "required",
+        "hashes": [{ "alg": "SHA-256", "content": "abc123" }],
+        "licenses": [{ "expression": "Apache-2.0" }],
+        "copyright": "Copyright (c) The OpenSSL Project",
+        "externalReferences": [
+            { "type": "vcs", "url": "https://github.com/openssl/openssl" }
+        ],
+        "properties": [{ "name": "build", "value": "release" }],
+        "components": [
+            { "bom-ref": "libcrypto", "type": "library", "name": "libcrypto" }
+        ]
+    });
+    let bom = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [component],
+        "dependencies": [{ "ref": "openssl", "dependsOn": ["libcrypto"] }]
+    });
+    fs::write(&input_path, bom.to_string()).unwrap();
+
+    convert(&input_path, &xml_path, "cdx-to-cdx", &[]);
+    convert(&xml_path, &json_path, "cdx-to-cdx", &[]);
+
+    let output = read_json(&json_path);
+    assert_eq!(output["components"][0], component);
+    assert_eq!(output["dependencies"], bom["dependencies"]);
+}
+
+#[test]
+fn test_cdx_to_cdx_does_not_emit_spdx() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.json");
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    convert(&input_path, &output_path, "cdx-to-cdx", &[]);
+    let output = read_json(&output_path);
+    assert!(output.get("spdxVersion").is_none());
+    assert_eq!(output, get_test_cdx());
+}
+
+#[test]
+fn test_spdx_to_spdx_simple_to_jsonld() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let jsonld_path = dir.path().join("output.jsonld");
+    let cdx_path = dir.path().join("output.cdx.json");
+    fs::write(&input_path, get_test_spdx_simple().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &jsonld_path,
+        "spdx-to-spdx",
+        &["--output-format", "json", "--spdx-serialization", "json-ld"],
+    );
+    let output = read_json(&jsonld_path);
+    assert_eq!(
+        output["@context"],
+        "https://spdx.org/rdf/3.0.1/spdx-context.jsonld"
+    );
+    let graph = output["@graph"].as_array().unwrap();
+    let lodash = graph
+        .iter()
+        .find(|e| e["spdxId"] == "https://example.com/demo#SPDXRef-lodash")
+        .unwrap();
+    assert_eq!(lodash["type"], "software_Package");
+    assert_eq!(lodash["software_packageVersion"], "4.17.21");
+
+    // The JSON-LD output is readable by the SPDX -> CDX converter
+    convert(
+        &jsonld_path,
+        &cdx_path,
+        "spdx-to-cdx",
+        &["--input-format", "json"],
+    );
+    let cdx = read_json(&cdx_path);
+    assert_eq!(cdx["components"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        cdx["dependencies"][0]["dependsOn"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_spdx_to_spdx_jsonld_to_simple() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let jsonld_path = dir.path().join("middle.spdx.json");
+    let simple_path = dir.path().join("output.spdx.json");
+    fs::write(&input_path, get_test_spdx_simple().to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &jsonld_path,
+        "spdx-to-spdx",
+        &["--spdx-serialization", "json-ld"],
+    );
+    convert(&jsonld_path, &simple_path, "spdx-to-spdx", &[]);
+
+    let output = read_json(&simple_path);
+    let expected = get_test_spdx_simple();
+    assert_eq!(output["name"], "demo");
+    assert_eq!(output["elements"], expected["elements"]);
+    assert_eq!(output["relationships"], expected["relationships"]);
+}
+
+#[test]
+fn test_spdx_to_spdx_json_to_tag_value() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.spdx");
+    let spdx23 = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "demo",
+        "documentNamespace": "https://example.com/demo",
+        "creationInfo": { "created": "2025-01-01T00:00:00Z", "creators": ["Tool: test"] },
+        "packages": [
+            { "SPDXID": "SPDXRef-lodash", "name": "lodash", "versionInfo": "4.17.21", "downloadLocation": "NOASSERTION" }
+        ]
+    });
+    fs::write(&input_path, spdx23.to_string()).unwrap();
+
+    convert(&input_path, &output_path, "spdx-to-spdx", &[]);
+    let output = fs::read_to_string(&output_path).unwrap();
+    assert!(output.contains("SPDXVersion: SPDX-2.3\n"));
+    assert!(output.contains("PackageName: lodash\n"));
+}
+
+#[test]
+fn test_spdx3_to_tag_value_is_rejected() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let output_path = dir.path().join("output.spdx");
+    fs::write(&input_path, get_test_spdx_simple().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("spdx-to-spdx");
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_spdx_to_spdx_jsonld_reports_dropped_elements() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.jsonld");
+    let output_path = dir.path().join("output.spdx.json");
+    let report_path = dir.path().join("report.json");
+    let jsonld = json!({
+        "@context": "https://spdx.org/rdf/3.0.1/spdx-context.jsonld",
+        "@graph": [
+            {
+                "type": "SpdxDocument",
+                "spdxId": "https://example.com/demo#SPDXRef-DOCUMENT",
+                "name": "demo",
+                "rootElement": ["https://example.com/demo#SPDXRef-app"]
+            },
+            { "type": "software_Package", "spdxId": "https://example.com/demo#SPDXRef-app", "name": "app" },
+            { "type": "Organization", "spdxId": "https://example.com/demo#SPDXRef-acme", "name": "Acme" },
+            {
+                "type": "simplelicensing_SimpleLicensingText",
+                "spdxId": "https://example.com/demo#LicenseRef-custom",
+                "simplelicensing_licenseText": "All rights reserved."
+            }
+        ]
+    });
+    fs::write(&input_path, jsonld.to_string()).unwrap();
+
+    convert(
+        &input_path,
+        &output_path,
+        "spdx-to-spdx",
+        &["--fidelity-report", report_path.to_str().unwrap()],
+    );
+
+    let report = read_json(&report_path);
+    let dropped = report["dropped"].as_array().unwrap();
+    let count = |path: &str| {
+        dropped
+            .iter()
+            .find(|d| d["path"] == path)
+            .map(|d| d["count"].clone())
+    };
+    assert_eq!(count("element.Organization"), Some(json!(1)));
+    assert_eq!(
+        count("element.simplelicensing_SimpleLicensingText"),
+        Some(json!(1))
+    );
+    assert!(count("element.software_Package").is_none());
+
+    let output = read_json(&output_path);
+    assert_eq!(output["elements"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,408 @@
+//! Integration tests for `vex apply`.
+//!
+//! Tests that CycloneDX VEX (as written by `--split-vex`), SPDX 3 security
+//! elements and several documents at once are applied onto a CycloneDX
+//! SBOM, and that conflicting and dangling statements are reported. Also
+//! tests where `--split-vex` writes the VEX document and how it links to
+//! the BOM.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+const SERIAL: &str = "urn:uuid:6c1f5a3e-8b2d-4e7f-9a10-2b3c4d5e6f70";
+
+/// A CycloneDX SBOM without vulnerabilities
+fn get_test_sbom() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "serialNumber": SERIAL,
+        "version": 1,
+        "metadata": { "timestamp": "2024-01-01T00:00:00Z" },
+        "components": [
+            {
+                "bom-ref": "log4j",
+                "type": "library",
+                "name": "log4j-core",
+                "version": "2.14.1",
+                "purl": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+            },
+            {
+                "bom-ref": "lodash",
+                "type": "library",
+                "name": "lodash",
+                "version": "4.17.21",
+                "purl": "pkg:npm/lodash@4.17.21"
+            }
+        ]
+    })
+}
+
+/// A CycloneDX VEX document with the given vulnerabilities
+fn cdx_vex(timestamp: &str, vulnerabilities: Value) -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "metadata": { "timestamp": timestamp },
+        "vulnerabilities": vulnerabilities
+    })
+}
+
+/// An SPDX 3 document with a VEX assessment of log4j
+fn get_test_spdx() -> Value {
+    json!({
+        "spdxVersion": "SPDX-3.0",
+        "creationInfo": { "created": "2024-03-01T00:00:00Z" },
+        "elements": [
+            {
+                "type": "SpdxPackage",
+                "spdxId": "SPDXRef-log4j",
+                "name": "log4j-core",
+                "software_packageVersion": "2.14.1",
+                "software_packageUrl": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+            },
+            {
+                "type": "SpdxVulnerability",
+                "spdxId": "SPDXRef-vuln",
+                "name": "CVE-2021-44228"
+            }
+        ],
+        "relationships": [
+            {
+                "type": "security_VexNotAffectedVulnAssessmentRelationship",
+                "spdxId": "SPDXRef-vex",
+                "from": "SPDXRef-vuln",
+                "relationshipType": "doesNotAffect",
+                "to": ["SPDXRef-log4j"],
+                "security_justificationType": "vulnerableCodeNotPresent"
+            }
+        ]
+    })
+}
+
+/// The BOM-Link to the document with the given serial number
+fn bom_link(serial_number: &Value) -> String {
+    let serial_number = serial_number.as_str().unwrap();
+    format!(
+        "urn:cdx:{}/1",
+        serial_number.strip_prefix("urn:uuid:").unwrap()
+    )
+}
+
+fn write_json(dir: &Path, name: &str, value: &Value) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, value.to_string()).unwrap();
+    path
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Runs `vex apply` with a JSON report and returns the report and result
+fn apply(sbom: &Path, vex: &[&Path], output: &Path) -> (Value, Value) {
+    let mut cmd = get_cmd();
+    cmd.arg("vex")
+        .arg("apply")
+        .arg("--sbom")
+        .arg(sbom)
+        .arg("--vex")
+        .args(vex)
+        .arg("--output")
+        .arg(output)
+        .arg("--report-format")
+        .arg("json");
+    let output_result = cmd.output().unwrap();
+    assert!(
+        output_result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output_result.stderr)
+    );
+    let report = serde_json::from_slice(&output_result.stdout).unwrap();
+    (report, read_json(output))
+}
+
+fn messages(report: &Value) -> Vec<String> {
+    report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|issue| issue["message"].as_str().unwrap().to_string())
+        .collect()
+}
+
+// --- Tests ---
+
+#[test]
+fn test_apply_bom_link_refs() {
+    let dir = tempdir().unwrap();
+    let sbom_path = write_json(dir.path(), "sbom.cdx.json", &get_test_sbom());
+    let vex = cdx_vex(
+        "2024-02-01T00:00:00Z",
+        json!([
+            {
+                "id": "CVE-2021-44228",
+                "analysis": { "state": "exploitable", "response": ["update"] },
+                "affects": [
+                    { "ref": format!("{}#log4j", SERIAL) },
+                    { "ref": format!("{}#missing", SERIAL) },
+                    { "ref": "urn:uuid:00000000-0000-0000-0000-000000000000#log4j" }
+                ]
+            }
+        ]),
+    );
+    let vex_path = write_json(dir.path(), "sbom.vex.json", &vex);
+    let output_path = dir.path().join("output.cdx.json");
+
+    let (report, result) = apply(&sbom_path, &[&vex_path], &output_path);
+    assert_eq!(result["components"], get_test_sbom()["components"]);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 1);
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "exploitable");
+    assert_eq!(vulnerabilities[0]["affects"], json!([{ "ref": "log4j" }]));
+
+    // Refs to unknown components and to other BOMs are dangling
+    assert_eq!(report["summary"]["warnings"], 2);
+    let messages = messages(&report);
+    assert!(messages[0].contains("#missing is not a component of the SBOM"));
+    assert!(messages[1].contains("refers to another BOM"));
+    assert_eq!(
+        report["issues"][0]["location"],
+        format!("{}: vulnerabilities[0]", vex_path.display())
+    );
+}
+
+#[test]
+fn test_apply_split_vex_output() {
+    let dir = tempdir().unwrap();
+    let spdx_path = write_json(dir.path(), "app.spdx.json", &get_test_spdx());
+    let bom_path = dir.path().join("app.cdx.json");
+
+    let mut cmd = get_cmd();
+    cmd.arg("--split-vex")
+        .arg("convert")
+        .arg("--input")
+        .arg(&spdx_path)
+        .arg("--output")
+        .arg(&bom_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx");
+    cmd.assert().success();
+    let vex_path = dir.path().join("app.vex.json");
+    let bom = read_json(&bom_path);
+    let vex = read_json(&vex_path);
+    assert!(bom.get("vulnerabilities").is_none());
+
+    // The documents link to each other by serial number
+    assert_ne!(bom["serialNumber"], vex["serialNumber"]);
+    assert_eq!(
+        bom["externalReferences"][0]["type"],
+        "exploitability-statement"
+    );
+    assert_eq!(
+        bom["externalReferences"][0]["url"],
+        bom_link(&vex["serialNumber"])
+    );
+    assert_eq!(vex["externalReferences"][0]["type"], "bom");
+    assert_eq!(
+        vex["externalReferences"][0]["url"],
+        bom_link(&bom["serialNumber"])
+    );
+
+    // Applying the split-off VEX document restores the vulnerabilities
+    let output_path = dir.path().join("output.cdx.json");
+    let (report, result) = apply(&bom_path, &[&vex_path], &output_path);
+    assert_eq!(report["summary"]["total"], 0);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 1);
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "not_affected");
+    assert_eq!(vulnerabilities[0]["affects"], json!([{ "ref": "log4j" }]));
+}
+
+#[test]
+fn test_split_vex_explicit_xml_output() {
+    let dir = tempdir().unwrap();
+    let spdx_path = write_json(dir.path(), "app.spdx.json", &get_test_spdx());
+    let bom_path = dir.path().join("app.cdx.json");
+    let vex_path = dir.path().join("statements.xml");
+
+    let mut cmd = get_cmd();
+    cmd.arg("convert")
+        .arg("--input")
+        .arg(&spdx_path)
+        .arg("--output")
+        .arg(&bom_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx")
+        .arg("--split-vex")
+        .arg("--vex-output")
+        .arg(&vex_path);
+    cmd.assert().success();
+    assert!(!dir.path().join("app.vex.json").exists());
+
+    let bom = read_json(&bom_path);
+    let xml = fs::read_to_string(&vex_path).unwrap();
+    let vex_serial = bom["externalReferences"][0]["url"]
+        .as_str()
+        .unwrap()
+        .strip_prefix("urn:cdx:")
+        .and_then(|link| link.strip_suffix("/1"))
+        .unwrap()
+        .to_string();
+    assert!(xml.contains(&format!("serialNumber=\"urn:uuid:{}\"", vex_serial)));
+    assert!(xml.contains(&format!(
+        "<reference type=\"bom\"><url>{}</url>",
+        bom_link(&bom["serialNumber"])
+    )));
+    assert!(xml.contains("<state>not_affected</state>"));
+    assert!(xml.contains("<justification>code_not_present</justification>"));
+    assert!(xml.contains(&format!("<ref>{}#", bom["serialNumber"].as_str().unwrap())));
+}
+
+#[test]
+fn test_split_vex_refuses_to_overwrite_output() {
+    let dir = tempdir().unwrap();
+    let spdx_path = write_json(dir.path(), "app.spdx.json", &get_test_spdx());
+    let bom_path = dir.path().join("app.vex.json");
+
+    let mut cmd = get_cmd();
+    cmd.arg("--split-vex")
+        .arg("convert")
+        .arg("--input")
+        .arg(&spdx_path)
+        .arg("--output")
+        .arg(&bom_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would overwrite the output file"));
+}
+
+#[test]
+fn test_apply_spdx_security_elements() {
+    let dir = tempdir().unwrap();
+    let sbom_path = write_json(dir.path(), "sbom.cdx.json", &get_test_sbom());
+    let spdx = json!({
+        "@context": "https://spdx.org/rdf/3.0.1/spdx-context.jsonld",
+        "@graph": [
+            {
+                "type": "CreationInfo",
+                "@id": "_:creationinfo",
+                "created": "2024-03-01T00:00:00Z",
+                "specVersion": "3.0.1"
+            },
+            {
+                "type": "software_Package",
+                "spdxId": "https://vendor.example/spdx/pkg-lodash",
+                "name": "lodash",
+                "software_packageVersion": "4.17.21",
+                "software_packageUrl": "pkg:npm/lodash@4.17.21"
+            },
+            {
+                "type": "security_Vulnerability",
+                "spdxId": "https://vendor.example/spdx/vuln-1",
+                "name": "CVE-2021-23337"
+            },
+            {
+                "type": "security_VexAffectedVulnAssessmentRelationship",
+                "spdxId": "https://vendor.example/spdx/vex-1",
+                "from": "https://vendor.example/spdx/vuln-1",
+                "relationshipType": "affects",
+                "to": ["https://vendor.example/spdx/pkg-lodash"],
+                "security_actionStatement": "update"
+            }
+        ]
+    });
+    let spdx_path = write_json(dir.path(), "vendor.spdx.json", &spdx);
+    let output_path = dir.path().join("output.cdx.json");
+
+    // The SPDX package is matched to the SBOM component by purl
+    let (report, result) = apply(&sbom_path, &[&spdx_path], &output_path);
+    assert_eq!(report["summary"]["total"], 0);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 1);
+    assert_eq!(vulnerabilities[0]["id"], "CVE-2021-23337");
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "exploitable");
+    assert_eq!(
+        vulnerabilities[0]["analysis"]["response"],
+        json!(["update"])
+    );
+    assert_eq!(vulnerabilities[0]["affects"], json!([{ "ref": "lodash" }]));
+}
+
+#[test]
+fn test_apply_newest_statement_wins() {
+    let dir = tempdir().unwrap();
+    let mut sbom = get_test_sbom();
+    sbom["vulnerabilities"] = json!([{
+        "id": "CVE-2021-23337",
+        "analysis": { "state": "in_triage", "lastUpdated": "2024-06-01T00:00:00Z" },
+        "affects": [{ "ref": "lodash" }]
+    }]);
+    let sbom_path = write_json(dir.path(), "sbom.cdx.json", &sbom);
+
+    let newer = cdx_vex(
+        "2024-05-01T00:00:00Z",
+        json!([{
+            "id": "CVE-2021-44228",
+            "analysis": { "state": "exploitable" },
+            "affects": [{ "ref": "log4j" }]
+        }]),
+    );
+    let older = json!({
+        "@context": "https://openvex.dev/ns/v0.2.0",
+        "@id": "https://vendor.example/vex/1",
+        "author": "Vendor",
+        "timestamp": "2024-04-01T00:00:00Z",
+        "version": 1,
+        "statements": [
+            {
+                "vulnerability": { "name": "CVE-2021-44228" },
+                "products": [{ "@id": "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1" }],
+                "status": "not_affected",
+                "justification": "vulnerable_code_not_present"
+            },
+            {
+                "vulnerability": { "name": "CVE-2021-23337" },
+                "products": [{ "@id": "pkg:npm/lodash@4.17.21" }],
+                "status": "fixed"
+            }
+        ]
+    });
+    let newer_path = write_json(dir.path(), "newer.cdx.json", &newer);
+    let older_path = write_json(dir.path(), "older.openvex.json", &older);
+    let output_path = dir.path().join("output.cdx.json");
+
+    // Document order does not matter, timestamps do
+    let (report, result) = apply(&sbom_path, &[&newer_path, &older_path], &output_path);
+    let vulnerabilities = result["vulnerabilities"].as_array().unwrap();
+    assert_eq!(vulnerabilities.len(), 2);
+    // The SBOM's own analysis is newer than the OpenVEX statement
+    assert_eq!(vulnerabilities[0]["analysis"]["state"], "in_triage");
+    assert_eq!(vulnerabilities[1]["id"], "CVE-2021-44228");
+    assert_eq!(vulnerabilities[1]["analysis"]["state"], "exploitable");
+
+    assert_eq!(report["summary"]["warnings"], 1);
+    assert_eq!(report["summary"]["infos"], 1);
+    let messages = messages(&report);
+    assert!(messages[0].starts_with("Conflicting statements for CVE-2021-44228: exploitable in"));
+    assert!(messages[0].ends_with(&format!(
+        "overrides not_affected (code_not_present) in {}",
+        older_path.display()
+    )));
+    assert_eq!(report["issues"][0]["location"], "log4j");
+    assert!(messages[1].starts_with("Kept the SBOM analysis of CVE-2021-23337"));
+}
@@ -0,0 +1,499 @@
+//! Integration tests for VEX conversion.
+//!
+//! Tests that CycloneDX vulnerability analyses and ratings become SPDX 3
+//! security profile assessment relationships, and that both SPDX 3 shapes
+//! convert back into CycloneDX analyses, ratings and CWEs.
+
+use assert_cmd::prelude::*;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+// --- Helper Functions ---
+
+/// Helper to get the binary command for testing.
+fn get_cmd() -> Command {
+    Command::cargo_bin("sbom-converter").unwrap()
+}
+
+/// A CycloneDX BOM with one vulnerability per VEX state
+fn get_test_cdx() -> Value {
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [
+            { "bom-ref": "log4j", "type": "library", "name": "log4j-core", "version": "2.14.1" },
+            { "bom-ref": "lodash", "type": "library", "name": "lodash", "version": "4.17.21" }
+        ],
+        "vulnerabilities": [
+            {
+                "id": "CVE-2021-44228",
+                "source": { "name": "NVD", "url": "https://nvd.nist.gov/vuln/detail/CVE-2021-44228" },
+                "cwes": [502, 917],
+                "ratings": [{
+                    "method": "CVSSv31",
+                    "score": 10.0,
+                    "severity": "critical",
+                    "vector": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"
+                }],
+                "analysis": {
+                    "state": "exploitable",
+                    "response": ["update"],
+                    "firstIssued": "2021-12-10T00:00:00Z"
+                },
+                "affects": [{ "ref": "log4j" }]
+            },
+            {
+                "id": "CVE-2021-23337",
+                "analysis": {
+                    "state": "not_affected",
+                    "justification": "code_not_reachable",
+                    "detail": "template() is never called"
+                },
+                "affects": [{ "ref": "lodash" }]
+            },
+            {
+                "id": "CVE-2020-8203",
+                "analysis": { "state": "resolved", "detail": "Fixed in 4.17.19" },
+                "affects": [{ "ref": "lodash" }]
+            },
+            {
+                "id": "GHSA-p6mc-m468-83gw",
+                "ratings": [{ "method": "OWASP", "score": 5.0 }],
+                "analysis": { "state": "in_triage" },
+                "affects": [{ "ref": "lodash" }]
+            }
+        ]
+    })
+}
+
+/// Converts the test BOM to SPDX 3 with the given serialization
+fn convert(dir: &Path, serialization: &str) -> (Value, Value) {
+    let input_path = dir.join("input.cdx.json");
+    let output_path = dir.join(format!("output.{}.json", serialization));
+    let report_path = dir.join(format!("report.{}.json", serialization));
+    fs::write(&input_path, get_test_cdx().to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--spdx-serialization")
+        .arg(serialization)
+        .arg("--fidelity-report")
+        .arg(&report_path);
+    cmd.assert().success();
+
+    let output = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    let report = serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    (output, report)
+}
+
+/// Finds the assessment of `type_name` from the vulnerability `vuln_id`
+fn assessment<'a>(elements: &'a [Value], type_name: &str, vuln_id: &str) -> &'a Value {
+    elements
+        .iter()
+        .find(|e| {
+            e["type"] == type_name
+                && e["from"]
+                    .as_str()
+                    .is_some_and(|from| from.ends_with(&format!("Vulnerability-{}", vuln_id)))
+        })
+        .unwrap_or_else(|| panic!("no {} for {}", type_name, vuln_id))
+}
+
+/// Converts an SPDX file to CycloneDX and returns the vulnerabilities
+fn convert_to_cdx(dir: &Path, input_path: &Path) -> Vec<Value> {
+    let output_path = dir.join("roundtrip.cdx.json");
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("spdx-to-cdx");
+    cmd.assert().success();
+
+    let output: Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    output["vulnerabilities"].as_array().unwrap().clone()
+}
+
+/// Finds the converted vulnerability `id`
+fn vulnerability<'a>(vulnerabilities: &'a [Value], id: &str) -> &'a Value {
+    vulnerabilities
+        .iter()
+        .find(|v| v["id"] == id)
+        .unwrap_or_else(|| panic!("no vulnerability {}", id))
+}
+
+// --- Tests ---
+
+#[test]
+fn test_vex_to_jsonld() {
+    let dir = tempdir().unwrap();
+    let (output, report) = convert(dir.path(), "json-ld");
+    let graph = output["@graph"].as_array().unwrap();
+
+    let affected = assessment(
+        graph,
+        "security_VexAffectedVulnAssessmentRelationship",
+        "CVE-2021-44228",
+    );
+    assert_eq!(affected["relationshipType"], "affects");
+    assert_eq!(affected["security_actionStatement"], "update");
+    assert_eq!(affected["security_publishedTime"], "2021-12-10T00:00:00Z");
+    assert!(
+        affected["to"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("#SPDXRef-log4j")
+    );
+    assert_eq!(affected["creationInfo"], "_:creationinfo");
+
+    let not_affected = assessment(
+        graph,
+        "security_VexNotAffectedVulnAssessmentRelationship",
+        "CVE-2021-23337",
+    );
+    assert_eq!(not_affected["relationshipType"], "doesNotAffect");
+    assert_eq!(
+        not_affected["security_justificationType"],
+        "vulnerableCodeNotInExecutePath"
+    );
+    assert_eq!(
+        not_affected["security_impactStatement"],
+        "template() is never called"
+    );
+
+    let fixed = assessment(
+        graph,
+        "security_VexFixedVulnAssessmentRelationship",
+        "CVE-2020-8203",
+    );
+    assert_eq!(fixed["relationshipType"], "fixedIn");
+    assert_eq!(fixed["security_statusNotes"], "Fixed in 4.17.19");
+
+    let triage = assessment(
+        graph,
+        "security_VexUnderInvestigationVulnAssessmentRelationship",
+        "GHSA-p6mc-m468-83gw",
+    );
+    assert_eq!(triage["relationshipType"], "underInvestigationFor");
+
+    let cvss = assessment(
+        graph,
+        "security_CvssV3VulnAssessmentRelationship",
+        "CVE-2021-44228",
+    );
+    assert_eq!(cvss["relationshipType"], "hasAssessmentFor");
+    assert_eq!(cvss["security_score"], 10.0);
+    assert_eq!(cvss["security_severity"], "critical");
+    assert_eq!(
+        cvss["security_vectorString"],
+        "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"
+    );
+
+    // Assessed vulnerabilities have no plain "affects" relationship
+    assert!(
+        !graph
+            .iter()
+            .any(|e| e["type"] == "Relationship" && e["relationshipType"] == "affects")
+    );
+
+    // The source issues the CVE id; CWEs are extra identifiers
+    let vuln = graph
+        .iter()
+        .find(|e| e["type"] == "security_Vulnerability" && e["name"] == "CVE-2021-44228")
+        .unwrap();
+    let ids = vuln["externalIdentifier"].as_array().unwrap();
+    assert_eq!(ids[0]["issuingAuthority"], "NVD");
+    assert_eq!(
+        ids[0]["identifierLocator"],
+        json!(["https://nvd.nist.gov/vuln/detail/CVE-2021-44228"])
+    );
+    assert_eq!(ids[1]["identifier"], "CWE-502");
+    assert_eq!(ids[2]["identifier"], "CWE-917");
+
+    // Only the OWASP rating has no SPDX counterpart
+    let dropped = report["dropped"].as_array().unwrap();
+    let ratings = dropped
+        .iter()
+        .find(|d| d["path"] == "vulnerability.ratings")
+        .unwrap();
+    assert_eq!(ratings["examples"], json!(["GHSA-p6mc-m468-83gw"]));
+    assert!(
+        !dropped
+            .iter()
+            .any(|d| d["path"] == "vulnerability.analysis")
+    );
+}
+
+#[test]
+fn test_vex_to_simple_json() {
+    let dir = tempdir().unwrap();
+    let (output, _) = convert(dir.path(), "simple");
+
+    // Assessments are relationships, not elements
+    let elements = output["elements"].as_array().unwrap();
+    assert!(elements.iter().all(|e| e.get("from").is_none()));
+    let relationships = output["relationships"].as_array().unwrap();
+    let not_affected = assessment(
+        relationships,
+        "security_VexNotAffectedVulnAssessmentRelationship",
+        "CVE-2021-23337",
+    );
+    assert_eq!(not_affected["to"], json!(["SPDXRef-lodash"]));
+    assert!(not_affected.get("creationInfo").is_none());
+    assessment(
+        relationships,
+        "security_CvssV3VulnAssessmentRelationship",
+        "CVE-2021-44228",
+    );
+
+    // Every vulnerability has an analysis, so none plainly affects
+    assert!(
+        !relationships
+            .iter()
+            .any(|r| r["relationshipType"] == "AFFECTS")
+    );
+}
+
+#[test]
+fn test_vex_roundtrip() {
+    for serialization in ["json-ld", "simple"] {
+        let dir = tempdir().unwrap();
+        convert(dir.path(), serialization);
+        let spdx_path = dir.path().join(format!("output.{}.json", serialization));
+        let vulnerabilities = convert_to_cdx(dir.path(), &spdx_path);
+        assert_eq!(vulnerabilities.len(), 4, "{}", serialization);
+
+        let log4shell = vulnerability(&vulnerabilities, "CVE-2021-44228");
+        assert_eq!(log4shell["analysis"]["state"], "exploitable");
+        assert_eq!(log4shell["analysis"]["response"], json!(["update"]));
+        assert_eq!(log4shell["analysis"]["firstIssued"], "2021-12-10T00:00:00Z");
+        assert_eq!(log4shell["ratings"][0]["method"], "CVSSv31");
+        assert_eq!(log4shell["ratings"][0]["score"], 10.0);
+        assert_eq!(log4shell["ratings"][0]["severity"], "critical");
+        let versions = &log4shell["affects"][0]["versions"];
+        assert_eq!(
+            versions,
+            &json!([{ "version": "2.14.1", "status": "affected" }])
+        );
+
+        let lodash = vulnerability(&vulnerabilities, "CVE-2021-23337");
+        assert_eq!(lodash["analysis"]["state"], "not_affected");
+        assert_eq!(lodash["analysis"]["justification"], "code_not_reachable");
+        assert_eq!(lodash["analysis"]["detail"], "template() is never called");
+
+        let fixed = vulnerability(&vulnerabilities, "CVE-2020-8203");
+        assert_eq!(fixed["analysis"]["state"], "resolved");
+        assert_eq!(fixed["analysis"]["detail"], "Fixed in 4.17.19");
+
+        let triage = vulnerability(&vulnerabilities, "GHSA-p6mc-m468-83gw");
+        assert_eq!(triage["analysis"]["state"], "in_triage");
+
+        // Only JSON-LD carries the source and CWEs
+        if serialization == "json-ld" {
+            assert_eq!(log4shell["source"]["name"], "NVD");
+            assert_eq!(log4shell["cwes"], json!([502, 917]));
+        }
+    }
+}
+
+#[test]
+fn test_vex_roundtrip_across_layouts() {
+    // Analyses, ratings and affected versions, without the source and CWEs
+    // only JSON-LD has (the refs depend on the serial number)
+    let assessed = |vulnerabilities: Vec<Value>| -> Vec<Value> {
+        let mut assessed: Vec<Value> = vulnerabilities
+            .iter()
+            .map(|v| {
+                let versions: Vec<&Value> = v["affects"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|a| &a["versions"])
+                    .collect();
+                json!([v["id"], v["analysis"], v["ratings"], versions])
+            })
+            .collect();
+        assessed.sort_by_key(|v| v.to_string());
+        assessed
+    };
+
+    for (from, to) in [("simple", "json-ld"), ("json-ld", "simple")] {
+        let dir = tempdir().unwrap();
+        convert(dir.path(), from);
+        let direct = assessed(convert_to_cdx(
+            dir.path(),
+            &dir.path().join(format!("output.{}.json", from)),
+        ));
+
+        // Switch the layout, then convert back to CycloneDX
+        let transcoded = dir.path().join(format!("transcoded.{}.json", to));
+        get_cmd()
+            .arg("--input")
+            .arg(dir.path().join(format!("output.{}.json", from)))
+            .arg("--output")
+            .arg(&transcoded)
+            .arg("--direction")
+            .arg("spdx-to-spdx")
+            .arg("--spdx-serialization")
+            .arg(to)
+            .assert()
+            .success();
+        let spdx: Value = serde_json::from_str(&fs::read_to_string(&transcoded).unwrap()).unwrap();
+        let assessments = spdx
+            .get("relationships")
+            .unwrap_or(&spdx["@graph"])
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|r| {
+                r["type"]
+                    .as_str()
+                    .is_some_and(|t| t.ends_with("VulnAssessmentRelationship"))
+            })
+            .count();
+        assert_eq!(assessments, 5, "{} -> {}", from, to);
+
+        let roundtrip = assessed(convert_to_cdx(dir.path(), &transcoded));
+        assert_eq!(roundtrip, direct, "{} -> {}", from, to);
+    }
+}
+
+#[test]
+fn test_spdx_scores_to_cdx() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.spdx.json");
+    let ns = "https://example.com/doc#";
+    let spdx = json!({
+        "@context": "https://spdx.org/rdf/3.0.1/spdx-context.jsonld",
+        "@graph": [
+            {
+                "type": "software_Package",
+                "spdxId": format!("{}app", ns),
+                "creationInfo": "_:creationinfo",
+                "name": "app",
+                "software_packageVersion": "1.0"
+            },
+            {
+                "type": "security_Vulnerability",
+                "spdxId": format!("{}vuln", ns),
+                "creationInfo": "_:creationinfo",
+                "externalIdentifier": [{
+                    "type": "ExternalIdentifier",
+                    "externalIdentifierType": "cve",
+                    "identifier": "CVE-2024-3094"
+                }]
+            },
+            {
+                "type": "security_EpssVulnAssessmentRelationship",
+                "spdxId": format!("{}epss", ns),
+                "creationInfo": "_:creationinfo",
+                "from": format!("{}vuln", ns),
+                "relationshipType": "hasAssessmentFor",
+                "to": [format!("{}app", ns)],
+                "security_probability": 0.85,
+                "security_percentile": 0.99
+            },
+            {
+                "type": "security_ExploitCatalogVulnAssessmentRelationship",
+                "spdxId": format!("{}kev", ns),
+                "creationInfo": "_:creationinfo",
+                "from": format!("{}vuln", ns),
+                "relationshipType": "hasAssessmentFor",
+                "to": [format!("{}app", ns)],
+                "security_catalogType": "kev",
+                "security_exploited": true,
+                "security_locator": "https://www.cisa.gov/known-exploited-vulnerabilities-catalog"
+            },
+            {
+                "type": "Relationship",
+                "spdxId": format!("{}rel", ns),
+                "creationInfo": "_:creationinfo",
+                "from": format!("{}vuln", ns),
+                "relationshipType": "affects",
+                "to": [format!("{}app", ns)]
+            }
+        ]
+    });
+    fs::write(&input_path, spdx.to_string()).unwrap();
+
+    let vulnerabilities = convert_to_cdx(dir.path(), &input_path);
+    assert_eq!(vulnerabilities.len(), 1);
+    let vuln = &vulnerabilities[0];
+    assert_eq!(vuln["id"], "CVE-2024-3094");
+
+    // Without a VEX assessment nothing is claimed about exploitability,
+    // and the source is not guessed
+    assert!(vuln.get("analysis").is_none());
+    assert!(vuln.get("source").is_none());
+    assert_eq!(vuln["affects"].as_array().unwrap().len(), 1);
+    assert_eq!(vuln["affects"][0]["versions"][0]["version"], "1.0");
+
+    assert_eq!(vuln["ratings"][0]["source"]["name"], "EPSS");
+    assert_eq!(vuln["ratings"][0]["score"], 0.85);
+    assert_eq!(vuln["properties"][0]["name"], "spdx:exploitCatalog:kev");
+    assert!(
+        vuln["properties"][0]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("exploited https://www.cisa.gov/")
+    );
+}
+
+#[test]
+fn test_cvss_rating_without_vector_is_dropped() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.cdx.json");
+    let output_path = dir.path().join("output.spdx.json");
+    let report_path = dir.path().join("report.json");
+    let cdx = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.6",
+        "version": 1,
+        "components": [{ "bom-ref": "log4j", "type": "library", "name": "log4j-core" }],
+        "vulnerabilities": [{
+            "id": "CVE-2021-44228",
+            "ratings": [{ "method": "CVSSv31", "score": 10.0, "severity": "critical" }],
+            "affects": [{ "ref": "log4j" }]
+        }]
+    });
+    fs::write(&input_path, cdx.to_string()).unwrap();
+
+    let mut cmd = get_cmd();
+    cmd.arg("--input")
+        .arg(&input_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--direction")
+        .arg("cdx-to-spdx")
+        .arg("--fidelity-report")
+        .arg(&report_path);
+    cmd.assert().success();
+
+    // SPDX requires a vector string, so the rating has no assessment
+    let output: Value = serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+    let relationships = output["relationships"].as_array().unwrap();
+    assert!(
+        !relationships
+            .iter()
+            .any(|r| r["type"] == "security_CvssV3VulnAssessmentRelationship")
+    );
+
+    let report: Value = serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let ratings = report["dropped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["path"] == "vulnerability.ratings")
+        .unwrap();
+    assert_eq!(ratings["examples"], json!(["CVE-2021-44228"]));
+}